@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+#[derive(Clone, Debug)]
+pub struct TelegramClient {
+    pub token: String,
+    pub chat_id: String,
+}
+
+impl TelegramClient {
+    pub fn new(token: &str, chat_id: &str) -> Option<Self> {
+        let token = token.trim();
+        let chat_id = chat_id.trim();
+        if token.is_empty() || chat_id.is_empty() {
+            return None;
+        }
+        Some(TelegramClient {
+            token: token.to_string(),
+            chat_id: chat_id.to_string(),
+        })
+    }
+
+    pub fn send(&self, text: &str) {
+        let client = self.clone();
+        let text = text.to_string();
+        thread::spawn(move || {
+            if let Err(e) = client.send_blocking(&text) {
+                eprintln!("telegram alert error: {}", e);
+            }
+        });
+    }
+
+    pub fn send_blocking(&self, text: &str) -> Result<()> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.token);
+        let body = serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": text,
+        });
+        let response = ureq::post(&url)
+            .timeout(Duration::from_secs(10))
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())
+            .map_err(|e| anyhow!("{}", e))?;
+        if response.status() != 200 {
+            return Err(anyhow!("Telegram returned HTTP {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+pub struct XrunStormDetector {
+    window: Duration,
+    threshold: u32,
+    samples: VecDeque<(Instant, u32)>,
+    alarmed: bool,
+}
+
+impl XrunStormDetector {
+    pub fn new(threshold: u32, window_secs: u64) -> Self {
+        XrunStormDetector {
+            window: Duration::from_secs(window_secs),
+            threshold: threshold.max(1),
+            samples: VecDeque::new(),
+            alarmed: false,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.alarmed = false;
+    }
+
+    // Returns the xrun count inside the window when a storm starts.
+    pub fn update(&mut self, xruns: u32, now: Instant) -> Option<u32> {
+        self.samples.push_back((now, xruns));
+        while let Some(&(t, _)) = self.samples.front() {
+            if now.duration_since(t) > self.window {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+        let oldest = self.samples.front().map(|&(_, v)| v).unwrap_or(xruns);
+        let recent = xruns.saturating_sub(oldest);
+        if recent >= self.threshold {
+            if !self.alarmed {
+                self.alarmed = true;
+                return Some(recent);
+            }
+        } else if recent == 0 {
+            self.alarmed = false;
+        }
+        None
+    }
+}
@@ -0,0 +1,121 @@
+use std::f32::consts::PI;
+use std::fmt;
+
+// FFT length for the MPX analyzer. Bin width at the 192 kHz output rate runs
+// from 187.5 Hz (1024) down to 23.4 Hz (8192), enough to separate the pilot
+// and the RDS sidebands from nearby program energy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FftSize {
+    #[default]
+    N1024,
+    N2048,
+    N4096,
+    N8192,
+}
+
+impl FftSize {
+    pub const ALL: [FftSize; 4] = [FftSize::N1024, FftSize::N2048, FftSize::N4096, FftSize::N8192];
+
+    pub fn points(self) -> usize {
+        match self {
+            FftSize::N1024 => 1024,
+            FftSize::N2048 => 2048,
+            FftSize::N4096 => 4096,
+            FftSize::N8192 => 8192,
+        }
+    }
+
+    pub fn from_u32(v: u32) -> Self {
+        match v {
+            1 => FftSize::N2048,
+            2 => FftSize::N4096,
+            3 => FftSize::N8192,
+            _ => FftSize::N1024,
+        }
+    }
+
+    pub fn to_u32(self) -> u32 {
+        match self {
+            FftSize::N1024 => 0,
+            FftSize::N2048 => 1,
+            FftSize::N4096 => 2,
+            FftSize::N8192 => 3,
+        }
+    }
+}
+
+impl fmt::Display for FftSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({:.0} Hz)", self.points(), 192_000.0 / self.points() as f32)
+    }
+}
+
+// Hann is the default; Blackman-Harris trades a wider main lobe for far lower
+// leakage next to the pilot, and the flat-top window reads sine amplitudes
+// to a few hundredths of a dB wherever they fall between bins.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FftWindow {
+    #[default]
+    Hann,
+    BlackmanHarris,
+    FlatTop,
+}
+
+impl FftWindow {
+    pub const ALL: [FftWindow; 3] = [FftWindow::Hann, FftWindow::BlackmanHarris, FftWindow::FlatTop];
+
+    fn coefficients(self) -> &'static [f32] {
+        match self {
+            FftWindow::Hann => &[0.5, 0.5],
+            FftWindow::BlackmanHarris => &[0.35875, 0.48829, 0.14128, 0.01168],
+            FftWindow::FlatTop => &[0.215_578_95, 0.416_631_58, 0.277_263_16, 0.083_578_95, 0.006_947_37],
+        }
+    }
+
+    pub fn table(self, len: usize) -> Vec<f32> {
+        let coefficients = self.coefficients();
+        (0..len)
+            .map(|i| {
+                let x = 2.0 * PI * i as f32 / len as f32;
+                coefficients
+                    .iter()
+                    .enumerate()
+                    .map(|(k, &a)| (if k % 2 == 0 { a } else { -a }) * (k as f32 * x).cos())
+                    .sum::<f32>()
+            })
+            .collect()
+    }
+
+    // How far a full-scale sine reads below 0 dB in a 1/N-scaled one-sided
+    // spectrum taken with this window.
+    pub fn sine_ref_db(self) -> f32 {
+        -20.0 * (self.coefficients()[0] / 2.0).log10()
+    }
+
+    pub fn from_u32(v: u32) -> Self {
+        match v {
+            1 => FftWindow::BlackmanHarris,
+            2 => FftWindow::FlatTop,
+            _ => FftWindow::Hann,
+        }
+    }
+
+    pub fn to_u32(self) -> u32 {
+        match self {
+            FftWindow::Hann => 0,
+            FftWindow::BlackmanHarris => 1,
+            FftWindow::FlatTop => 2,
+        }
+    }
+}
+
+impl fmt::Display for FftWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            FftWindow::Hann => "Hann",
+            FftWindow::BlackmanHarris => "Blackman-Harris",
+            FftWindow::FlatTop => "Flat top",
+        };
+        write!(f, "{}", label)
+    }
+}
@@ -1,4 +1,4 @@
-use iced::widget::{button, checkbox, column, container, pick_list, progress_bar, row, scrollable, slider, text, text_input, Column};
+use iced::widget::{button, checkbox, column, container, pick_list, progress_bar, row, scrollable, slider, text, text_input, tooltip, Column, Row};
 use iced::widget::button as button_widget;
 use iced::widget::container as container_widget;
 use iced::widget::slider as slider_widget;
@@ -13,12 +13,65 @@ use serde::{Deserialize, Serialize};
 use rand::Rng;
 use std::fs;
 use std::path::PathBuf;
-use iced::widget::canvas::{Canvas, Frame, Geometry, Path, Program, Stroke, Text};
+use iced::widget::canvas::{Cache, Canvas, Frame, Geometry, Path, Program, Stroke, Text};
 use iced::{Color, Renderer};
-use std::time::Duration;
-
-use pulse_fm_rds_encoder::audio_io::{list_input_devices, list_output_devices, start_engine, AudioEngine, AudioEngineConfig};
-use pulse_fm_rds_encoder::wav_writer::{generate_mpx_wav, GenerateConfig};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime};
+
+use pulse_fm_rds_encoder::autostart::{AutostartSettings, LaunchOptions, AUTOSTART_RETRY};
+use pulse_fm_rds_encoder::delay::{MAX_DELAY_SECS, MAX_DIVERSITY_DELAY_MS};
+use pulse_fm_rds_encoder::diagnostics::{self, DiagnosticsInput, MeterRecord, METER_RECORDS};
+use pulse_fm_rds_encoder::chain::{Stage, StageLevels};
+use pulse_fm_rds_encoder::processing_preset::ProcessingPreset;
+use pulse_fm_rds_encoder::program_feed::ProgramFeed;
+use pulse_fm_rds_encoder::levels::{db_to_linear, linear_to_db, MAX_GAIN_DB, MAX_THRESHOLD_DB, MIN_GAIN_DB, MIN_THRESHOLD_DB};
+use pulse_fm_rds_encoder::calibration::{correction_factor, level_for_target, CalibrationStep, DeviationCalibration, PILOT_TARGET_KHZ, RDS_TARGET_KHZ};
+use pulse_fm_rds_encoder::mod_history::{ModulationHistory, ModulationPoint, ModulationStats, FULL_SCALE_KHZ, HISTORY_MINUTES};
+use pulse_fm_rds_encoder::mod_limits::{LimitState, ModulationLimits, ModulationWindow};
+use pulse_fm_rds_encoder::fm_mpx::{DEFAULT_LPF_CUTOFF_HZ, MAX_LPF_CUTOFF_HZ, MIN_LPF_CUTOFF_HZ};
+use pulse_fm_rds_encoder::mask::MaskResult;
+use pulse_fm_rds_encoder::analyzer::{FftSize, FftWindow};
+use pulse_fm_rds_encoder::impairment::{Impairments, DEFAULT_RIPPLE_DELAY_US};
+use pulse_fm_rds_encoder::injection::Injection;
+use pulse_fm_rds_encoder::inserts;
+use pulse_fm_rds_encoder::iq_export::{IqSettings, DEFAULT_DEVIATION_KHZ, DEFAULT_IQ_SAMPLE_RATE};
+use pulse_fm_rds_encoder::failover::{BackupInput, FailoverConfig, FailoverStatus};
+use pulse_fm_rds_encoder::traffic::TrafficRouting;
+use pulse_fm_rds_encoder::rds_feed::{RdsFeedConfig, RdsFeedFormat, RdsFeedTransport};
+use pulse_fm_rds_encoder::compliance::{self, ComplianceSettings, RdsOptions};
+use pulse_fm_rds_encoder::contacts::{list_serial_ports, ContactAction, ContactEvent, ContactLine, ContactMapping, ContactMonitor};
+use pulse_fm_rds_encoder::alerts::{TelegramClient, XrunStormDetector};
+use pulse_fm_rds_encoder::automation::{scheduled_offset, AutomationParam, AutomationRule, RampCurve};
+use pulse_fm_rds_encoder::preset_sync::{self, PresetRemote, RemoteKind};
+use pulse_fm_rds_encoder::help::{HelpArea, HelpTopic};
+use pulse_fm_rds_encoder::ps_receiver::{predicted_changes, PsChange, ReceiverTimeline};
+use pulse_fm_rds_encoder::pty_schedule::{scheduled_pty, PtyDays, PtyRule};
+use pulse_fm_rds_encoder::rds_network::{batch_file, plan_network, SitePlan, Transmitter};
+use pulse_fm_rds_encoder::audio_io::{list_input_devices, list_output_devices, play_alert_tone, start_engine, AudioEngine, AudioEngineConfig, EngineSettings, DSP_LOAD_WARN};
+use pulse_fm_rds_encoder::rds::{af_code, af_code_stream, af_tuned_missing, group_cycle_for_mix, ps_segments_per_sec, rate_limits_for_cycle, FastTuning, GroupStats, PsScrollMode, RtOverflow, AF_MAX, AF_METHOD_B_PAIRS, DEFAULT_RT_ELLIPSIS, DEFAULT_RT_PART_SECS};
+use pulse_fm_rds_encoder::rds_checks::{station_warnings, StationCheck};
+use pulse_fm_rds_encoder::rds_countries::{RdsCountry, RDS_COUNTRIES};
+use pulse_fm_rds_encoder::rds_history::{write_history_csv, RdsChangeSource, RdsField, RdsHistoryEntry};
+use pulse_fm_rds_encoder::rds_macros::{expand, Clock, MacroValues, ValueProvider};
+use pulse_fm_rds_encoder::rds_strings::{unmappable_chars, RdsCodeTable};
+use pulse_fm_rds_encoder::rds_translit::TranslitTable;
+use pulse_fm_rds_encoder::status_server::{EncoderStatus, RemoteCommand, StatusServer};
+use pulse_fm_rds_encoder::rtp::{RtpConfig, RtpStatus};
+use pulse_fm_rds_encoder::session::{write_session_report, SessionStats};
+use pulse_fm_rds_encoder::silence::{post_webhook, SilenceDetector, SilenceEvent};
+use pulse_fm_rds_encoder::data_providers::{DataProvider, ProviderFeeds};
+use pulse_fm_rds_encoder::metadata_filter::{Filtered, MetadataFilter};
+use pulse_fm_rds_encoder::supervisor::{log_recovery, Supervisor, SupervisorSettings};
+use pulse_fm_rds_encoder::song_history::{SongHistory, DEFAULT_PREVIOUSLY_RT, DEFAULT_SONG_HISTORY_LEN};
+use pulse_fm_rds_encoder::sync::{start_message, start_time, SyncLink, SyncMessage, SyncRole, START_LEAD};
+use pulse_fm_rds_encoder::test_sequence::{TestSequence, TestStep};
+use pulse_fm_rds_encoder::phase_align::{PhaseReport, MAX_PILOT_PHASE_DEG, PHASE_TEST_SECS};
+use pulse_fm_rds_encoder::pilot_pll::PilotLockStatus;
+use pulse_fm_rds_encoder::loopback::{input_hint, LOOPBACK_PREFIX};
+use pulse_fm_rds_encoder::refresh::{GraphRate, RefreshSettings, TextRate};
+use pulse_fm_rds_encoder::storage::{self, config_dir, config_path, load_config, save_config, ALERTS_FILE, AUTOMATION_FILE, AUTOSTART_FILE, CALIBRATION_FILE, COMPLIANCE_FILE, CONFIG_DIR_ENV, CONTACTS_FILE, INSERTS_FILE, LIMITS_FILE, METADATA_FILTER_FILE, NETWORK_FILE, PRESETS_FILE, PRESET_REMOTE_FILE, PROVIDERS_FILE, PTY_SCHEDULE_FILE, REFRESH_FILE, SUPERVISOR_FILE};
+use pulse_fm_rds_encoder::stereo_test::{SeparationReport, SEPARATION_TEST_SECS};
+use pulse_fm_rds_encoder::wav_writer::{generate_iq_wav, generate_mpx_wav, generate_phase_test_wav, generate_test_sequence_wav, run_separation_test, GenerateConfig, RenderReport};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct PtyItem {
@@ -28,9 +81,14 @@ pub(crate) struct PtyItem {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct CountryItem {
-    label: &'static str,
-    country_hex: Option<&'static str>,
-    ecc_hex: Option<&'static str>,
+    label: String,
+    country: Option<RdsCountry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AfRow {
+    freq: String,
+    regional: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -124,25 +182,76 @@ fn pty_items() -> Vec<PtyItem> {
     ]
 }
 
+fn code_table_items() -> Vec<RdsCodeTable> {
+    RdsCodeTable::SELECTABLE.to_vec()
+}
+
+fn contact_line_items() -> Vec<ContactLine> {
+    vec![ContactLine::Cts, ContactLine::Dsr, ContactLine::Dcd, ContactLine::Ri]
+}
+
+fn contact_action_items() -> Vec<ContactAction> {
+    vec![ContactAction::Ta, ContactAction::TxMute, ContactAction::Preset]
+}
+
+fn ps_scroll_mode_items() -> Vec<PsScrollMode> {
+    vec![PsScrollMode::Character, PsScrollMode::Paging]
+}
+
+fn fast_tuning_items() -> Vec<FastTuning> {
+    vec![FastTuning::Off, FastTuning::WhileTa, FastTuning::Always]
+}
+
+fn rt_overflow_items() -> Vec<RtOverflow> {
+    vec![RtOverflow::Cut, RtOverflow::Ellipsis, RtOverflow::Rotate]
+}
+
 fn preemph_items() -> Vec<Preemphasis> {
     vec![Preemphasis::Off, Preemphasis::Us50, Preemphasis::Us75]
 }
 
+fn country_item(country: RdsCountry) -> CountryItem {
+    CountryItem {
+        label: format!("{} ({:X} / {:02X})", country.name, country.country_code, country.ecc),
+        country: Some(country),
+    }
+}
+
 fn country_items() -> Vec<CountryItem> {
-    vec![
-        CountryItem {
-            label: "Tunisia (7 / E2)",
-            country_hex: Some("7"),
-            ecc_hex: Some("E2"),
-        },
-        CountryItem {
-            label: "Custom (enter manually)",
-            country_hex: None,
-            ecc_hex: None,
-        },
-    ]
+    let mut items: Vec<CountryItem> = RDS_COUNTRIES.iter().copied().map(country_item).collect();
+    items.push(CountryItem {
+        label: "Custom (enter manually)".to_string(),
+        country: None,
+    });
+    items
+}
+
+fn default_country_item() -> CountryItem {
+    RDS_COUNTRIES
+        .iter()
+        .copied()
+        .find(|c| c.name == "Tunisia")
+        .map(country_item)
+        .unwrap_or_else(|| CountryItem {
+            label: "Custom (enter manually)".to_string(),
+            country: None,
+        })
 }
 
+const RDS_HISTORY_LIMIT: usize = 20_000;
+const GROUP_STATS_WINDOW: Duration = Duration::from_secs(60);
+// How far back the receiver view looks.
+const RECEIVER_WINDOW_SECS: f32 = 60.0;
+// After compliance mode lowers the RDS level, the injection meter gets this
+// long to catch up before it is checked again.
+const COMPLIANCE_SETTLE: Duration = Duration::from_secs(2);
+const COMPLIANCE_LOG_LIMIT: usize = 20;
+const MONITOR_OFF: &str = "Off";
+const XRUN_STORM_COUNT: u32 = 50;
+const XRUN_STORM_WINDOW_SECS: u64 = 10;
+// Injection bars run to 15% modulation; pilot sits near 9%, RDS near 3-5%.
+const INJECTION_BAR_MAX: f32 = 0.15;
+
 fn color_bg() -> Color {
     Color::from_rgb8(5, 7, 15)
 }
@@ -196,6 +305,36 @@ pub enum Message {
     PsChanged(String),
     RtChanged(String),
     PiChanged(String),
+    CodeTableChanged(RdsCodeTable),
+    AlarmChanged(bool),
+    TxMuteChanged(bool),
+    FftSizeSelected(FftSize),
+    FftWindowSelected(FftWindow),
+    StatusServerEnabled(bool),
+    StatusServerPortChanged(String),
+    StatusServerLanChanged(bool),
+    SyncRoleSelected(SyncRole),
+    SyncPeerChanged(String),
+    SyncPortChanged(String),
+    SyncTimeoutChanged(String),
+    SyncAlignedStartToggled(bool),
+    SyncConnect,
+    SyncDisconnect,
+    SyncTakeOver,
+    SyncHandOver,
+    ContactRefreshPorts,
+    ContactPortSelected(String),
+    ContactConnect,
+    ContactDisconnect,
+    ContactAddMapping,
+    ContactRemoveMapping(usize),
+    ContactLineChanged(usize, ContactLine),
+    ContactActionChanged(usize, ContactAction),
+    ContactPresetChanged(usize, String),
+    ContactInvertChanged(usize, bool),
+    TransliterateChanged(bool),
+    TranslitPathChanged(String),
+    ReloadTranslitTable,
     TaChanged(bool),
     TpChanged(bool),
     MsChanged(bool),
@@ -208,17 +347,35 @@ pub enum Message {
     AbAutoChanged(bool),
     CtChanged(bool),
     FrequencyChanged(String),
-    AfListChanged(String),
+    AfAddRow,
+    AfRemoveRow(usize),
+    AfRowFreqChanged(usize, String),
+    AfRowRegionalChanged(usize, bool),
     AfBaseChanged(String),
     AfSpacingChanged(String),
     AfCountChanged(String),
     AfGenerate,
+    NetworkAddSite,
+    NetworkRemoveSite(usize),
+    NetworkNameChanged(usize, String),
+    NetworkFreqChanged(usize, String),
+    NetworkPiChanged(usize, String),
+    NetworkNeighboursChanged(usize, String),
+    NetworkUseSite(usize),
+    NetworkExport,
     PsScrollEnabled(bool),
     PsScrollTextChanged(String),
     PsScrollSpeedChanged(f32),
+    PsScrollModeChanged(PsScrollMode),
+    PsPageDwellChanged(f32),
+    PsPageCenterChanged(bool),
     RtScrollEnabled(bool),
     RtScrollTextChanged(String),
     RtScrollSpeedChanged(f32),
+    RtOverflowChanged(RtOverflow),
+    RtEllipsisChanged(String),
+    RtPartSecsChanged(f32),
+    RateGuardChanged(bool),
     GainChanged(f32),
     LimiterEnabled(bool),
     LimiterThresholdChanged(f32),
@@ -227,44 +384,177 @@ pub enum Message {
     RdsLevelChanged(f32),
     StereoSeparationChanged(f32),
     PreemphasisChanged(Preemphasis),
+    LpfCutoffChanged(f32),
+    PilotNotchToggled(bool),
+    StageBypassToggled(Stage, bool),
+    InsertToggled(&'static str, bool),
+    ProcessingPresetSelected(ProcessingPreset),
     CompressorEnabled(bool),
     CompThresholdChanged(f32),
     CompRatioChanged(f32),
     CompAttackChanged(f32),
     CompReleaseChanged(f32),
+    CompMakeupChanged(f32),
     Group0aChanged(String),
     Group2aChanged(String),
     Group4aChanged(String),
     CtIntervalGroupsChanged(String),
+    FastTuningChanged(FastTuning),
     ApplyGroupMix,
     PsAltListChanged(String),
     PsAltIntervalChanged(String),
     ApplyPsAlternates,
+    ReloadDataProviders,
+    PreviouslyRtToggled(bool),
+    PreviouslyRtTextChanged(String),
+    PreviouslyIntervalChanged(String),
+    SongHistoryLenChanged(String),
+    BannedWordsChanged(String),
+    DropBannedToggled(bool),
+    StripBracketsToggled(bool),
+    TitleCaseToggled(bool),
+    MetadataMaxLenChanged(String),
+    AddOpenWeatherExample,
+    RdsHistoryPathChanged(String),
+    ExportRdsHistory,
+    ClearRdsHistory,
+    ModHistoryPathChanged(String),
+    ModLimitChanged(usize, String),
+    SaveModLimits,
+    TestStepSecsChanged(usize, f32),
+    TestOutputChanged(String),
+    RunTestSequence,
+    StopTestSequence,
+    RenderTestSequence,
+    PilotPhaseChanged(f32),
+    PhaseTestPathChanged(String),
+    RenderPhaseTest,
+    PhaseTestRendered(Result<PhaseReport, String>),
+    RunSeparationTest,
+    SeparationTestDone(Result<SeparationReport, String>),
+    CalibrationStart,
+    CalibrationMeasuredChanged(String),
+    CalibrationNext,
+    CalibrationCancel,
+    CalibrationApply,
+    ExportModHistory,
     CountrySelected(CountryItem),
     GenerateRandomPi,
     PresetSelected(String),
+    SupervisorToggled(bool),
+    SupervisorMaxRestartsChanged(u32),
+    ComplianceToggled(bool),
+    AutostartToggled(bool),
+    AutostartPresetSelected(String),
+    PresetIncludeDevicesToggled(bool),
+    PresetIncludeFrequencyToggled(bool),
+    PresetRemoteKindSelected(RemoteKind),
+    PresetRemoteLocationChanged(String),
+    PresetRemoteTokenChanged(String),
+    StorageDirChanged(String),
+    ApplyStorageDir,
+    SettingsFileChanged(String),
+    ExportSettings,
+    ImportSettings,
+    HelpTopicSelected(HelpTopic),
+    PullPresets,
+    PushPresets,
+    PresetsPulled(Result<String, String>),
+    PresetsPushed(Result<(), String>),
+    ExportDiagnostics,
+    DiagnosticsExported(Result<String, String>),
     PresetNameChanged(String),
     SavePreset,
     LoadPreset,
     TabSelected(Tab),
     Tick,
+    GraphTick,
+    GraphRateSelected(GraphRate),
+    TextRateSelected(TextRate),
     CountryCodeChanged(String),
     AreaCodeChanged(String),
     ProgramRefChanged(String),
     EccChanged(String),
+    LicChanged(String),
     ApplyPiFromParts,
     DurationChanged(String),
     AudioChanged(String),
     OutputChanged(String),
+    ResumeExportChanged(bool),
+    ImpairmentChanged(usize, String),
+    IqExportToggled(bool),
+    IqRateChanged(String),
+    IqDeviationChanged(String),
+    ProgramFeedPathChanged(String),
+    ProgramFeedDelayChanged(String),
+    GroupSchedulePathChanged(String),
     Generate,
     Generated(Result<(), String>),
+    MpxRendered(Result<RenderReport, String>),
 
     CopyPi,
     WindowResized(u32, u32),
     NoOp,
     RefreshDevices,
     InputSelected(String),
+    ProgramDelayChanged(f32),
+    DumpProgramDelay,
+    FadeChanged(f32),
+    AutomationAddRule,
+    AutomationRemoveRule(usize),
+    AutomationParamChanged(usize, AutomationParam),
+    AutomationStartChanged(usize, String),
+    AutomationEndChanged(usize, String),
+    AutomationOffsetChanged(usize, f32),
+    AutomationRampChanged(usize, f32),
+    AutomationCurveChanged(usize, RampCurve),
+    PtyRuleAdd,
+    PtyRuleRemove(usize),
+    PtyRuleDaysChanged(usize, PtyDays),
+    PtyRuleStartChanged(usize, String),
+    PtyRuleEndChanged(usize, String),
+    PtyRuleEveryHourToggled(usize, bool),
+    PtyRulePtyChanged(usize, PtyItem),
+    PtyOverrideToggled(bool),
+    LockPinChanged(String),
+    LockOperator,
+    EngineerUnlock,
+    AlertsToggled(bool),
+    TelegramTokenChanged(String),
+    TelegramChatChanged(String),
+    AlertDeadAirToggled(bool),
+    AlertDeviceLostToggled(bool),
+    AlertXrunStormToggled(bool),
+    AlertTest,
+    SilenceAlarmToggled(bool),
+    SilenceThresholdChanged(f32),
+    SilenceHoldChanged(f32),
+    SilenceWebhookChanged(String),
+    DiversityDelayChanged(f32),
+    DiversityDelayNudge(f32),
+    RtpToggled(bool),
+    MpxInputToggled(bool),
+    ExclusiveOutputToggled(bool),
+    MpxInputPilotToggled(bool),
+    MpxFileChanged(String),
+    MpxFileLoopToggled(bool),
+    RtpAddressChanged(String),
+    RtpPortChanged(String),
+    FailoverToggled(bool),
+    BackupInputSelected(String),
+    BackupFileChanged(String),
+    FailoverSilenceChanged(String),
+    FailoverRestoreChanged(String),
+    TrafficToggled(bool),
+    TrafficInputSelected(String),
+    TrafficFileChanged(String),
+    TrafficCrossfadeChanged(String),
+    RdsFeedToggled(bool),
+    RdsFeedTransportSelected(RdsFeedTransport),
+    RdsFeedFormatSelected(RdsFeedFormat),
+    RdsFeedAddressChanged(String),
     OutputSelected(String),
+    MonitorSelected(String),
     StartStream,
     StopStream,
 }
@@ -273,6 +563,12 @@ pub struct App {
     ps: String,
     rt: String,
     pi_hex: String,
+    code_table_items: Vec<RdsCodeTable>,
+    code_table: RdsCodeTable,
+    transliterate: bool,
+    translit_table_path: String,
+    translit: Option<TranslitTable>,
+    alarm: bool,
     ta: bool,
     tp: bool,
     ms: bool,
@@ -288,59 +584,163 @@ pub struct App {
     ab_auto: bool,
     ct_enabled: bool,
     duration: String,
+    test_step_secs: [f32; 6],
+    test_output_path: String,
+    phase_test_path: String,
+    phase_report: Option<PhaseReport>,
+    separation_report: Option<SeparationReport>,
+    test_status: Option<(TestStep, f32)>,
     audio_path: String,
     output_path: String,
+    resume_export: bool,
+    impairment_inputs: [String; 4],
+    iq_export: bool,
+    iq_rate: String,
+    iq_deviation: String,
+    program_feed_path: String,
+    program_feed_delay: String,
+    group_schedule_path: String,
     frequency_mhz: String,
-    af_list_text: String,
-    af_warning: Option<String>,
+    af_rows: Vec<AfRow>,
     af_base: String,
     af_spacing: String,
     af_count: String,
+    network_sites: Vec<Transmitter>,
     ps_scroll_enabled: bool,
     ps_scroll_text: String,
     ps_scroll_cps: f32,
+    ps_scroll_mode_items: Vec<PsScrollMode>,
+    ps_scroll_mode: PsScrollMode,
+    ps_page_dwell: f32,
+    ps_page_center: bool,
     rt_scroll_enabled: bool,
     rt_scroll_text: String,
     rt_scroll_cps: f32,
-    output_gain: f32,
+    rt_overflow_items: Vec<RtOverflow>,
+    rt_overflow: RtOverflow,
+    rt_ellipsis: String,
+    rt_part_secs: f32,
+    rate_guard: bool,
+    output_gain_db: f32,
     limiter_enabled: bool,
-    limiter_threshold: f32,
+    limiter_threshold_db: f32,
     limiter_lookahead_ms: f32,
     pilot_level: f32,
+    pilot_phase_deg: f32,
     rds_level: f32,
     stereo_separation: f32,
     preemphasis_items: Vec<Preemphasis>,
     preemphasis_selected: Preemphasis,
+    lpf_cutoff_hz: f32,
+    pilot_notch: bool,
     compressor_enabled: bool,
     comp_threshold: f32,
     comp_ratio: f32,
     comp_attack: f32,
     comp_release: f32,
+    comp_makeup: f32,
     group_0a: String,
     group_2a: String,
     group_4a: String,
     ct_interval_groups: String,
+    fast_tuning_items: Vec<FastTuning>,
+    fast_tuning: FastTuning,
     ps_alt_list_text: String,
+    macro_values: MacroValues,
+    macro_sent: [String; 4],
+    data_providers: Vec<DataProvider>,
+    provider_feeds: Option<ProviderFeeds>,
+    song_history: SongHistory,
+    song_history_len: String,
+    previously_rt: bool,
+    previously_rt_text: String,
+    previously_interval: String,
+    metadata_filter: MetadataFilter,
+    banned_words_text: String,
+    metadata_max_len: String,
     ps_alt_interval: String,
+    rds_history: Vec<RdsHistoryEntry>,
+    group_stats_samples: VecDeque<(Instant, GroupStats)>,
+    rds_history_path: String,
+    mod_history: ModulationHistory,
+    mod_history_path: String,
+    last_modulation: ModulationStats,
+    mod_window: ModulationWindow,
+    mod_limits: ModulationLimits,
+    mod_limit_inputs: [String; 4],
+    calibration: DeviationCalibration,
+    calibration_step: Option<CalibrationStep>,
+    calibration_input: String,
+    calibration_pilot: Option<(f32, f32)>,
+    stage_bypass: [bool; 4],
+    enabled_inserts: Vec<String>,
+    stage_levels: [StageLevels; 4],
+    processing_preset: Option<ProcessingPreset>,
     meter_rms: f32,
     meter_peak: f32,
-    meter_pilot: f32,
-    meter_rds: f32,
+    injection: Injection,
     meter_bands_db: [f32; 48],
     scope_samples: Vec<f32>,
     scope_prev: Vec<f32>,
     spectrum_peak_db: Vec<f32>,
     spectrum_avg_db: Vec<f32>,
+    input_spectrum_db: Vec<f32>,
+    input_levels: [(f32, f32); 2],
     xrun_count: u32,
     buffer_fill: f32,
     latency_ms: f32,
+    dsp_load_avg: f32,
+    dsp_load_worst: f32,
+    drift_ppm: f32,
+    mask_result: Option<MaskResult>,
+    fft_size: FftSize,
+    fft_window: FftWindow,
+    refresh: RefreshSettings,
     window_width: f32,
     pi_country_hex: String,
     pi_area_hex: String,
     pi_program_hex: String,
     ecc_hex: String,
+    lic_hex: String,
     presets: Vec<Preset>,
+    tx_mute: bool,
+    contact_ports: Vec<String>,
+    contact_port: Option<String>,
+    contact_mappings: Vec<ContactMapping>,
+    contact_monitor: Option<ContactMonitor>,
+    status_server: Option<StatusServer>,
+    status_server_port: String,
+    status_server_lan: bool,
+    sync_role: SyncRole,
+    sync_peer: String,
+    sync_port: String,
+    sync_timeout_secs: String,
+    sync_link: Option<SyncLink>,
+    sync_on_air: bool,
+    sync_last_state: String,
+    sync_last_sent: Option<Instant>,
+    sync_aligned_start: bool,
+    sync_start_at: Option<SystemTime>,
     preset_selected: Option<String>,
+    preset_include_devices: bool,
+    preset_include_frequency: bool,
+    autostart: AutostartSettings,
+    // Devices the auto-start waits for; cleared once streaming or stopped.
+    autostart_pending: Option<(Option<String>, Option<String>)>,
+    autostart_last_try: Option<Instant>,
+    supervisor_settings: SupervisorSettings,
+    supervisor: Supervisor,
+    compliance: ComplianceSettings,
+    // What compliance mode changed, newest last.
+    compliance_log: Vec<(chrono::DateTime<chrono::Local>, String)>,
+    compliance_level_changed: Option<Instant>,
+    preset_remote: PresetRemote,
+    preset_syncing: bool,
+    storage_dir_text: String,
+    settings_file_text: String,
+    help_topic: HelpTopic,
+    meter_records: VecDeque<MeterRecord>,
+    diagnostics_exporting: bool,
     preset_name: String,
     tab_selected: Tab,
     status: String,
@@ -350,6 +750,55 @@ pub struct App {
     output_devices: Vec<String>,
     selected_input: Option<String>,
     selected_output: Option<String>,
+    selected_monitor: Option<String>,
+    exclusive_output: bool,
+    fade_secs: f32,
+    stopping: bool,
+    session: Option<SessionStats>,
+    automation_rules: Vec<AutomationRule>,
+    automation_applied: Option<[f32; 4]>,
+    pty_rules: Vec<PtyRule>,
+    pty_override: bool,
+    pty_scheduled: Option<u8>,
+    automation_offsets: [f32; 4],
+    locked: bool,
+    lock_pin: String,
+    lock_pin_input: String,
+    alerts: AlertSettings,
+    xrun_storm: XrunStormDetector,
+    silence_enabled: bool,
+    silence_threshold_db: f32,
+    silence_hold_secs: f32,
+    silence_webhook: String,
+    program_silence: SilenceDetector,
+    mpx_silence: SilenceDetector,
+    silence_flash: bool,
+    program_delay_secs: f32,
+    program_delay_current: f32,
+    diversity_delay_ms: f32,
+    rtp_enabled: bool,
+    rtp_address: String,
+    mpx_input: bool,
+    mpx_input_pilot: bool,
+    mpx_file: String,
+    mpx_file_loop: bool,
+    input_pilot: Option<PilotLockStatus>,
+    rtp_port: String,
+    rtp_status: Option<RtpStatus>,
+    failover_enabled: bool,
+    backup_input: Option<String>,
+    backup_file: String,
+    failover_silence_secs: String,
+    failover_restore_secs: String,
+    failover_status: Option<FailoverStatus>,
+    traffic_enabled: bool,
+    traffic_input: Option<String>,
+    traffic_file: String,
+    traffic_crossfade_secs: String,
+    rds_feed_enabled: bool,
+    rds_feed_transport: RdsFeedTransport,
+    rds_feed_format: RdsFeedFormat,
+    rds_feed_address: String,
     engine: Option<AudioEngine>,
 }
 
@@ -359,6 +808,12 @@ impl Default for App {
             ps: "BOUZIDFM".to_string(),
             rt: "BOUZIDFM Sidi Bouzid 98.0 MHz".to_string(),
             pi_hex: "7200".to_string(),
+            code_table_items: code_table_items(),
+            code_table: RdsCodeTable::E1,
+            transliterate: false,
+            translit_table_path: String::new(),
+            translit: None,
+            alarm: false,
             ta: false,
             tp: false,
             ms: true,
@@ -369,68 +824,166 @@ impl Default for App {
             pty_items: pty_items(),
             pty_selected: PtyItem { code: 10, label: "Pop music" },
             country_items: country_items(),
-            country_selected: CountryItem {
-                label: "Tunisia (7 / E2)",
-                country_hex: Some("7"),
-                ecc_hex: Some("E2"),
-            },
+            country_selected: default_country_item(),
             ab_flag: false,
             ab_auto: true,
             ct_enabled: true,
             duration: "10".to_string(),
+            test_step_secs: [10.0; 6],
+            test_output_path: "test_sequence.wav".to_string(),
+            phase_test_path: "phase_test.wav".to_string(),
+            phase_report: None,
+            separation_report: None,
+            test_status: None,
             audio_path: "".to_string(),
             output_path: "mpx.wav".to_string(),
+            resume_export: false,
+            impairment_inputs: [String::new(), "0".to_string(), DEFAULT_RIPPLE_DELAY_US.to_string(), "0".to_string()],
+            iq_export: false,
+            iq_rate: DEFAULT_IQ_SAMPLE_RATE.to_string(),
+            iq_deviation: DEFAULT_DEVIATION_KHZ.to_string(),
+            program_feed_path: String::new(),
+            program_feed_delay: "0".to_string(),
+            group_schedule_path: String::new(),
             frequency_mhz: "98.0".to_string(),
-            af_list_text: "98.0".to_string(),
-            af_warning: None,
+            af_rows: parse_af_rows("98.0"),
             af_base: "98.0".to_string(),
             af_spacing: "0.2".to_string(),
             af_count: "1".to_string(),
+            network_sites: Vec::new(),
             ps_scroll_enabled: false,
             ps_scroll_text: "BOUZIDFM".to_string(),
             ps_scroll_cps: 2.0,
+            ps_scroll_mode_items: ps_scroll_mode_items(),
+            ps_scroll_mode: PsScrollMode::Character,
+            ps_page_dwell: 2.0,
+            ps_page_center: false,
             rt_scroll_enabled: false,
             rt_scroll_text: "BOUZIDFM Sidi Bouzid 98.0 MHz".to_string(),
             rt_scroll_cps: 2.0,
-            output_gain: 1.0,
+            rt_overflow_items: rt_overflow_items(),
+            rt_overflow: RtOverflow::Ellipsis,
+            rt_ellipsis: DEFAULT_RT_ELLIPSIS.to_string(),
+            rt_part_secs: DEFAULT_RT_PART_SECS,
+            rate_guard: true,
+            output_gain_db: 0.0,
             limiter_enabled: true,
-            limiter_threshold: 0.95,
+            limiter_threshold_db: -0.5,
             limiter_lookahead_ms: 2.0,
             pilot_level: 0.9,
+            pilot_phase_deg: 0.0,
             rds_level: 1.0,
             stereo_separation: 1.0,
             preemphasis_items: preemph_items(),
             preemphasis_selected: Preemphasis::Us50,
+            lpf_cutoff_hz: DEFAULT_LPF_CUTOFF_HZ,
+            pilot_notch: true,
             compressor_enabled: false,
             comp_threshold: -18.0,
             comp_ratio: 3.0,
             comp_attack: 0.01,
             comp_release: 0.2,
+            comp_makeup: 0.0,
             group_0a: "4".to_string(),
             group_2a: "1".to_string(),
             group_4a: "0".to_string(),
             ct_interval_groups: "0".to_string(),
+            fast_tuning_items: fast_tuning_items(),
+            fast_tuning: FastTuning::Off,
             ps_alt_list_text: "".to_string(),
+            macro_values: MacroValues::default(),
+            macro_sent: Default::default(),
+            data_providers: Vec::new(),
+            provider_feeds: None,
+            song_history: SongHistory::new(DEFAULT_SONG_HISTORY_LEN),
+            song_history_len: DEFAULT_SONG_HISTORY_LEN.to_string(),
+            previously_rt: false,
+            previously_rt_text: DEFAULT_PREVIOUSLY_RT.to_string(),
+            previously_interval: "30".to_string(),
+            metadata_filter: MetadataFilter::default(),
+            banned_words_text: String::new(),
+            metadata_max_len: String::new(),
             ps_alt_interval: "0".to_string(),
+            rds_history: Vec::new(),
+            group_stats_samples: VecDeque::new(),
+            rds_history_path: "rds_history.csv".to_string(),
+            mod_history: ModulationHistory::default(),
+            mod_history_path: "modulation_history.csv".to_string(),
+            last_modulation: ModulationStats::default(),
+            mod_window: ModulationWindow::default(),
+            mod_limits: ModulationLimits::default(),
+            mod_limit_inputs: Default::default(),
+            calibration: DeviationCalibration::default(),
+            calibration_step: None,
+            calibration_input: String::new(),
+            stage_bypass: [false; 4],
+            enabled_inserts: Vec::new(),
+            stage_levels: [StageLevels::default(); 4],
+            processing_preset: None,
+            calibration_pilot: None,
             meter_rms: 0.0,
             meter_peak: 0.0,
-            meter_pilot: 0.0,
-            meter_rds: 0.0,
+            injection: Injection::default(),
             meter_bands_db: [-60.0; 48],
             scope_samples: Vec::new(),
             scope_prev: Vec::new(),
             spectrum_peak_db: Vec::new(),
             spectrum_avg_db: Vec::new(),
+            input_spectrum_db: Vec::new(),
+            input_levels: [(0.0, 0.0); 2],
             xrun_count: 0,
             buffer_fill: 0.0,
             latency_ms: 0.0,
+            dsp_load_avg: 0.0,
+            dsp_load_worst: 0.0,
+            drift_ppm: 0.0,
+            mask_result: None,
+            fft_size: FftSize::default(),
+            refresh: RefreshSettings::default(),
+            fft_window: FftWindow::default(),
             window_width: 1200.0,
             pi_country_hex: "7".to_string(),
             pi_area_hex: "2".to_string(),
             pi_program_hex: "00".to_string(),
             ecc_hex: "E2".to_string(),
+            lic_hex: String::new(),
             presets: Vec::new(),
+            tx_mute: false,
+            contact_ports: Vec::new(),
+            contact_port: None,
+            contact_mappings: Vec::new(),
+            contact_monitor: None,
+            status_server: None,
+            status_server_port: "8089".to_string(),
+            status_server_lan: false,
+            sync_role: SyncRole::Off,
+            sync_peer: String::new(),
+            sync_port: "8090".to_string(),
+            sync_timeout_secs: "5".to_string(),
+            sync_link: None,
+            sync_on_air: false,
+            sync_last_state: String::new(),
+            sync_last_sent: None,
+            sync_aligned_start: false,
+            sync_start_at: None,
             preset_selected: None,
+            preset_include_devices: true,
+            preset_include_frequency: true,
+            autostart: AutostartSettings::default(),
+            autostart_pending: None,
+            autostart_last_try: None,
+            supervisor_settings: SupervisorSettings::default(),
+            supervisor: Supervisor::default(),
+            compliance: ComplianceSettings::default(),
+            compliance_log: Vec::new(),
+            compliance_level_changed: None,
+            preset_remote: PresetRemote::default(),
+            preset_syncing: false,
+            storage_dir_text: String::new(),
+            settings_file_text: "pulsefm-settings.json".to_string(),
+            help_topic: HelpTopic::Ps,
+            meter_records: VecDeque::new(),
+            diagnostics_exporting: false,
             preset_name: "BOUZIDFM".to_string(),
             tab_selected: Tab::Dashboard,
             status: "Idle".to_string(),
@@ -440,6 +993,55 @@ impl Default for App {
             output_devices: Vec::new(),
             selected_input: None,
             selected_output: None,
+            selected_monitor: None,
+            exclusive_output: false,
+            fade_secs: 0.5,
+            stopping: false,
+            session: None,
+            automation_rules: Vec::new(),
+            automation_applied: None,
+            pty_rules: Vec::new(),
+            pty_override: false,
+            pty_scheduled: None,
+            automation_offsets: [0.0; 4],
+            locked: false,
+            lock_pin: String::new(),
+            lock_pin_input: String::new(),
+            alerts: AlertSettings::default(),
+            xrun_storm: XrunStormDetector::new(XRUN_STORM_COUNT, XRUN_STORM_WINDOW_SECS),
+            silence_enabled: false,
+            silence_threshold_db: -50.0,
+            silence_hold_secs: 10.0,
+            silence_webhook: String::new(),
+            program_silence: SilenceDetector::new(-50.0, 10.0),
+            mpx_silence: SilenceDetector::new(-50.0, 10.0),
+            silence_flash: false,
+            program_delay_secs: 0.0,
+            program_delay_current: 0.0,
+            diversity_delay_ms: 0.0,
+            rtp_enabled: false,
+            rtp_address: "239.69.0.1".to_string(),
+            mpx_input: false,
+            mpx_input_pilot: false,
+            mpx_file: String::new(),
+            mpx_file_loop: true,
+            input_pilot: None,
+            rtp_port: "5004".to_string(),
+            rtp_status: None,
+            failover_enabled: false,
+            backup_input: None,
+            backup_file: String::new(),
+            failover_silence_secs: "10".to_string(),
+            failover_restore_secs: "30".to_string(),
+            failover_status: None,
+            traffic_enabled: false,
+            traffic_input: None,
+            traffic_file: String::new(),
+            traffic_crossfade_secs: "0.5".to_string(),
+            rds_feed_enabled: false,
+            rds_feed_transport: RdsFeedTransport::Udp,
+            rds_feed_format: RdsFeedFormat::Groups,
+            rds_feed_address: "127.0.0.1:9100".to_string(),
             engine: None,
         }
     }
@@ -449,12 +1051,41 @@ impl iced::Application for App {
     type Executor = iced::executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = ();
+    type Flags = LaunchOptions;
 
-    fn new(_flags: ()) -> (Self, Command<Self::Message>) {
+    fn new(flags: LaunchOptions) -> (Self, Command<Self::Message>) {
         let mut app = Self::default();
-        app.presets = load_presets().unwrap_or_default();
+        app.storage_dir_text = storage::override_dir().map(|dir| dir.display().to_string()).unwrap_or_default();
+        app.presets = load_config(PRESETS_FILE).unwrap_or_default();
+        app.preset_remote = load_config(PRESET_REMOTE_FILE).unwrap_or_default();
+        app.data_providers = load_config(PROVIDERS_FILE).unwrap_or_default();
+        app.restart_provider_feeds();
+        let contacts = load_config::<ContactSettings>(CONTACTS_FILE).unwrap_or_default();
+        if !contacts.port.is_empty() {
+            app.contact_port = Some(contacts.port);
+        }
+        app.contact_mappings = contacts.mappings;
+        app.alerts = load_config(ALERTS_FILE).unwrap_or_default();
+        app.metadata_filter = load_config(METADATA_FILTER_FILE).unwrap_or_default();
+        app.banned_words_text = app.metadata_filter.banned_words.join(", ");
+        app.metadata_max_len = app.metadata_filter.max_len.map(|n| n.to_string()).unwrap_or_default();
+        app.automation_rules = load_config(AUTOMATION_FILE).unwrap_or_default();
+        app.pty_rules = load_config(PTY_SCHEDULE_FILE).unwrap_or_default();
+        app.refresh = load_config(REFRESH_FILE).unwrap_or_default();
+        app.enabled_inserts = load_config(INSERTS_FILE).unwrap_or_default();
+        app.network_sites = load_config(NETWORK_FILE).unwrap_or_default();
+        app.calibration = load_config(CALIBRATION_FILE).unwrap_or_default();
+        app.mod_limits = load_config(LIMITS_FILE).unwrap_or_default();
+        app.mod_limit_inputs = mod_limit_inputs(&app.mod_limits);
+        app.contact_ports = list_serial_ports().unwrap_or_default();
         app.refresh_devices();
+        app.supervisor_settings = load_config(SUPERVISOR_FILE).unwrap_or_default();
+        app.compliance = load_config(COMPLIANCE_FILE).unwrap_or_default();
+        app.enforce_compliance();
+        app.autostart = load_config(AUTOSTART_FILE).unwrap_or_default();
+        if flags.autostart || app.autostart.enabled {
+            app.begin_autostart(flags.preset.or(app.autostart.preset.clone()));
+        }
         (app, Command::none())
     }
 
@@ -463,8 +1094,16 @@ impl iced::Application for App {
     }
 
     fn subscription(&self) -> iced::Subscription<Self::Message> {
+        // The graphs only tick while they are on screen and something is
+        // running; everything else follows the slower text rate.
+        let graphs = if self.engine.is_some() && self.tab_selected == Tab::Meters {
+            iced::time::every(self.refresh.graphs.interval()).map(|_| Message::GraphTick)
+        } else {
+            iced::Subscription::none()
+        };
         iced::Subscription::batch(vec![
-            iced::time::every(Duration::from_millis(200)).map(|_| Message::Tick),
+            iced::time::every(self.refresh.text.interval()).map(|_| Message::Tick),
+            graphs,
             iced::subscription::events().map(|event| match event {
                 Event::Window(window::Event::Resized { width, height: _ }) => Message::WindowResized(width, 0),
                 _ => Message::NoOp,
@@ -473,18 +1112,161 @@ impl iced::Application for App {
     }
 
     fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
+        if self.locked && !allowed_when_locked(&message) {
+            self.status = "Operator mode: engineer unlock required".to_string();
+            return Command::none();
+        }
         match message {
+            Message::AutomationAddRule => {
+                self.automation_rules.push(AutomationRule {
+                    param: AutomationParam::OutputGain,
+                    start: "22:00".to_string(),
+                    end: "06:00".to_string(),
+                    offset_db: -3.0,
+                    ramp_secs: 60.0,
+                    curve: RampCurve::Log,
+                });
+                self.automation_changed();
+                Command::none()
+            }
+            Message::AutomationRemoveRule(index) => {
+                if index < self.automation_rules.len() {
+                    self.automation_rules.remove(index);
+                    self.automation_changed();
+                }
+                Command::none()
+            }
+            Message::AutomationParamChanged(index, v) => {
+                if let Some(rule) = self.automation_rules.get_mut(index) {
+                    rule.param = v;
+                    self.automation_changed();
+                }
+                Command::none()
+            }
+            Message::AutomationStartChanged(index, v) => {
+                if let Some(rule) = self.automation_rules.get_mut(index) {
+                    rule.start = v;
+                    self.automation_changed();
+                }
+                Command::none()
+            }
+            Message::AutomationEndChanged(index, v) => {
+                if let Some(rule) = self.automation_rules.get_mut(index) {
+                    rule.end = v;
+                    self.automation_changed();
+                }
+                Command::none()
+            }
+            Message::AutomationOffsetChanged(index, v) => {
+                if let Some(rule) = self.automation_rules.get_mut(index) {
+                    rule.offset_db = v;
+                    self.automation_changed();
+                }
+                Command::none()
+            }
+            Message::AutomationRampChanged(index, v) => {
+                if let Some(rule) = self.automation_rules.get_mut(index) {
+                    rule.ramp_secs = v;
+                    self.automation_changed();
+                }
+                Command::none()
+            }
+            Message::AutomationCurveChanged(index, v) => {
+                if let Some(rule) = self.automation_rules.get_mut(index) {
+                    rule.curve = v;
+                    self.automation_changed();
+                }
+                Command::none()
+            }
+            Message::PtyRuleAdd => {
+                self.pty_rules.push(PtyRule {
+                    days: PtyDays::Daily,
+                    start: "00:00".to_string(),
+                    end: "00:05".to_string(),
+                    every_hour: true,
+                    pty: 1,
+                });
+                self.pty_rules_changed();
+                Command::none()
+            }
+            Message::PtyRuleRemove(index) => {
+                if index < self.pty_rules.len() {
+                    self.pty_rules.remove(index);
+                    self.pty_rules_changed();
+                }
+                Command::none()
+            }
+            Message::PtyRuleDaysChanged(index, v) => {
+                if let Some(rule) = self.pty_rules.get_mut(index) {
+                    rule.days = v;
+                    self.pty_rules_changed();
+                }
+                Command::none()
+            }
+            Message::PtyRuleStartChanged(index, v) => {
+                if let Some(rule) = self.pty_rules.get_mut(index) {
+                    rule.start = v;
+                    self.pty_rules_changed();
+                }
+                Command::none()
+            }
+            Message::PtyRuleEndChanged(index, v) => {
+                if let Some(rule) = self.pty_rules.get_mut(index) {
+                    rule.end = v;
+                    self.pty_rules_changed();
+                }
+                Command::none()
+            }
+            Message::PtyRuleEveryHourToggled(index, v) => {
+                if let Some(rule) = self.pty_rules.get_mut(index) {
+                    rule.every_hour = v;
+                    self.pty_rules_changed();
+                }
+                Command::none()
+            }
+            Message::PtyRulePtyChanged(index, v) => {
+                if let Some(rule) = self.pty_rules.get_mut(index) {
+                    rule.pty = v.code;
+                    self.pty_rules_changed();
+                }
+                Command::none()
+            }
+            Message::PtyOverrideToggled(v) => {
+                self.pty_override = v;
+                self.pty_schedule_changed();
+                Command::none()
+            }
+            Message::LockPinChanged(v) => {
+                self.lock_pin_input = v;
+                Command::none()
+            }
+            Message::LockOperator => {
+                self.lock_pin = std::mem::take(&mut self.lock_pin_input);
+                self.locked = true;
+                self.status = "Operator mode: only TA, PS/RT and presets can be changed".to_string();
+                Command::none()
+            }
+            Message::EngineerUnlock => {
+                if self.lock_pin_input == self.lock_pin {
+                    self.locked = false;
+                    self.status = "Engineer mode unlocked".to_string();
+                } else {
+                    self.status = "Wrong engineer PIN".to_string();
+                }
+                self.lock_pin_input.clear();
+                Command::none()
+            }
             Message::PsChanged(v) => {
                 self.ps = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_ps(&self.ps);
+                    engine.update_ps(&self.expand_text(&self.ps), RdsChangeSource::Manual);
                 }
                 Command::none()
             }
             Message::RtChanged(v) => {
                 self.rt = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_rt(&self.rt);
+                    engine.update_rt(&self.expand_text(&self.rt), RdsChangeSource::Manual);
                 }
                 Command::none()
             }
@@ -497,6 +1279,231 @@ impl iced::Application for App {
                 }
                 Command::none()
             }
+            Message::CodeTableChanged(v) => {
+                self.code_table = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_code_table(self.code_table);
+                }
+                Command::none()
+            }
+            Message::TxMuteChanged(v) => {
+                self.tx_mute = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_tx_mute(self.tx_mute);
+                }
+                Command::none()
+            }
+            Message::FftSizeSelected(v) => {
+                self.fft_size = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_analyzer(self.fft_size, self.fft_window);
+                }
+                Command::none()
+            }
+            Message::FftWindowSelected(v) => {
+                self.fft_window = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_analyzer(self.fft_size, self.fft_window);
+                }
+                Command::none()
+            }
+            Message::StatusServerEnabled(v) => {
+                self.status_server = None;
+                if v {
+                    let host = if self.status_server_lan { "0.0.0.0" } else { "127.0.0.1" };
+                    let bind = format!("{}:{}", host, self.status_server_port.trim());
+                    match StatusServer::start(&bind) {
+                        Ok(server) => {
+                            self.status_server = Some(server);
+                            self.status = format!("Status endpoints on http://{}", bind);
+                        }
+                        Err(e) => self.status = format!("Status server error: {}", e),
+                    }
+                }
+                Command::none()
+            }
+            Message::RdsFeedToggled(v) => {
+                self.rds_feed_enabled = v;
+                Command::none()
+            }
+            Message::RdsFeedTransportSelected(v) => {
+                self.rds_feed_transport = v;
+                Command::none()
+            }
+            Message::RdsFeedFormatSelected(v) => {
+                self.rds_feed_format = v;
+                Command::none()
+            }
+            Message::RdsFeedAddressChanged(v) => {
+                self.rds_feed_address = v;
+                Command::none()
+            }
+            Message::StatusServerPortChanged(v) => {
+                self.status_server_port = v;
+                Command::none()
+            }
+            Message::StatusServerLanChanged(v) => {
+                self.status_server_lan = v;
+                Command::none()
+            }
+            Message::SyncRoleSelected(v) => {
+                self.sync_role = v;
+                self.sync_link = None;
+                Command::none()
+            }
+            Message::SyncPeerChanged(v) => {
+                self.sync_peer = v;
+                Command::none()
+            }
+            Message::SyncPortChanged(v) => {
+                self.sync_port = v;
+                Command::none()
+            }
+            Message::SyncTimeoutChanged(v) => {
+                self.sync_timeout_secs = v;
+                Command::none()
+            }
+            Message::SyncAlignedStartToggled(v) => {
+                self.sync_aligned_start = v;
+                Command::none()
+            }
+            Message::SyncConnect => {
+                if self.sync_role == SyncRole::Off {
+                    self.status = "Select main or standby role first".to_string();
+                    return Command::none();
+                }
+                let Ok(port) = self.sync_port.trim().parse::<u16>() else {
+                    self.status = "Invalid sync port".to_string();
+                    return Command::none();
+                };
+                match SyncLink::open(port, self.sync_peer.trim()) {
+                    Ok(link) => {
+                        self.sync_link = Some(link);
+                        self.sync_last_state.clear();
+                        self.sync_last_sent = None;
+                        if self.sync_role == SyncRole::Standby {
+                            self.set_standby_on_air(false);
+                        }
+                        self.status = format!("Sync active as {} with {}", self.sync_role, self.sync_peer.trim());
+                    }
+                    Err(e) => self.status = format!("Sync error: {}", e),
+                }
+                Command::none()
+            }
+            Message::SyncDisconnect => {
+                self.sync_link = None;
+                self.status = "Sync stopped".to_string();
+                Command::none()
+            }
+            Message::SyncTakeOver => {
+                if let Some(link) = &self.sync_link {
+                    let _ = link.send(&SyncMessage::Takeover);
+                }
+                self.set_standby_on_air(true);
+                self.status = "Standby took over output".to_string();
+                Command::none()
+            }
+            Message::SyncHandOver => {
+                if let Some(link) = &self.sync_link {
+                    let _ = link.send(&SyncMessage::Handover);
+                }
+                self.set_tx_mute(true);
+                self.status = "Output handed over to standby".to_string();
+                Command::none()
+            }
+            Message::ContactRefreshPorts => {
+                match list_serial_ports() {
+                    Ok(ports) => self.contact_ports = ports,
+                    Err(e) => self.status = format!("Serial port error: {}", e),
+                }
+                Command::none()
+            }
+            Message::ContactPortSelected(v) => {
+                self.contact_port = Some(v);
+                self.save_contacts();
+                Command::none()
+            }
+            Message::ContactConnect => {
+                let Some(port) = self.contact_port.clone() else {
+                    self.status = "Select a serial port".to_string();
+                    return Command::none();
+                };
+                match ContactMonitor::open(&port, self.contact_mappings.clone()) {
+                    Ok(monitor) => {
+                        self.contact_monitor = Some(monitor);
+                        self.status = format!("Contact closures active on {}", port);
+                    }
+                    Err(e) => self.status = format!("Contact closure error: {}", e),
+                }
+                Command::none()
+            }
+            Message::ContactDisconnect => {
+                self.contact_monitor = None;
+                self.status = "Contact closures stopped".to_string();
+                Command::none()
+            }
+            Message::ContactAddMapping => {
+                self.contact_mappings.push(ContactMapping {
+                    line: ContactLine::Cts,
+                    action: ContactAction::Ta,
+                    preset: String::new(),
+                    invert: false,
+                });
+                self.contact_mappings_changed();
+                Command::none()
+            }
+            Message::ContactRemoveMapping(index) => {
+                if index < self.contact_mappings.len() {
+                    self.contact_mappings.remove(index);
+                    self.contact_mappings_changed();
+                }
+                Command::none()
+            }
+            Message::ContactLineChanged(index, v) => {
+                if let Some(mapping) = self.contact_mappings.get_mut(index) {
+                    mapping.line = v;
+                    self.contact_mappings_changed();
+                }
+                Command::none()
+            }
+            Message::ContactActionChanged(index, v) => {
+                if let Some(mapping) = self.contact_mappings.get_mut(index) {
+                    mapping.action = v;
+                    self.contact_mappings_changed();
+                }
+                Command::none()
+            }
+            Message::ContactPresetChanged(index, v) => {
+                if let Some(mapping) = self.contact_mappings.get_mut(index) {
+                    mapping.preset = v;
+                    self.contact_mappings_changed();
+                }
+                Command::none()
+            }
+            Message::ContactInvertChanged(index, v) => {
+                if let Some(mapping) = self.contact_mappings.get_mut(index) {
+                    mapping.invert = v;
+                    self.contact_mappings_changed();
+                }
+                Command::none()
+            }
+            Message::AlarmChanged(v) => {
+                self.alarm = v;
+                Command::none()
+            }
+            Message::TransliterateChanged(v) => {
+                self.transliterate = v;
+                self.reload_translit();
+                Command::none()
+            }
+            Message::TranslitPathChanged(v) => {
+                self.translit_table_path = v;
+                Command::none()
+            }
+            Message::ReloadTranslitTable => {
+                self.reload_translit();
+                Command::none()
+            }
             Message::TaChanged(v) => {
                 self.ta = v;
                 if let Some(engine) = &self.engine {
@@ -549,7 +1556,7 @@ impl iced::Application for App {
             Message::PtyChanged(v) => {
                 self.pty_selected = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_pty(self.pty_selected.code);
+                    engine.update_pty(self.on_air_pty());
                 }
                 Command::none()
             }
@@ -576,14 +1583,33 @@ impl iced::Application for App {
             }
             Message::FrequencyChanged(v) => {
                 self.frequency_mhz = v;
+                self.apply_af();
                 Command::none()
             }
-            Message::AfListChanged(v) => {
-                self.af_list_text = v;
-                if let Some(engine) = &self.engine {
-                    let (list, warning) = parse_af_list(&self.af_list_text);
-                    self.af_warning = warning;
-                    engine.update_af_list(&list);
+            Message::AfAddRow => {
+                if self.af_rows.len() < AF_MAX {
+                    self.af_rows.push(AfRow { freq: String::new(), regional: false });
+                }
+                Command::none()
+            }
+            Message::AfRemoveRow(index) => {
+                if index < self.af_rows.len() {
+                    self.af_rows.remove(index);
+                    self.apply_af();
+                }
+                Command::none()
+            }
+            Message::AfRowFreqChanged(index, v) => {
+                if let Some(row) = self.af_rows.get_mut(index) {
+                    row.freq = v;
+                    self.apply_af();
+                }
+                Command::none()
+            }
+            Message::AfRowRegionalChanged(index, v) => {
+                if let Some(row) = self.af_rows.get_mut(index) {
+                    row.regional = v;
+                    self.apply_af();
                 }
                 Command::none()
             }
@@ -602,79 +1628,217 @@ impl iced::Application for App {
             Message::AfGenerate => {
                 let base = self.af_base.trim().parse::<f32>().unwrap_or(98.0);
                 let spacing = self.af_spacing.trim().parse::<f32>().unwrap_or(0.2);
-                let count = self.af_count.trim().parse::<usize>().unwrap_or(1).min(25);
-                let mut freqs = Vec::new();
-                for i in 0..count {
-                    freqs.push(base + spacing * i as f32);
+                let count = self.af_count.trim().parse::<usize>().unwrap_or(1).min(AF_MAX);
+                self.af_rows = (0..count)
+                    .map(|i| AfRow { freq: format!("{:.1}", base + spacing * i as f32), regional: false })
+                    .collect();
+                self.apply_af();
+                Command::none()
+            }
+            Message::NetworkAddSite => {
+                self.network_sites.push(Transmitter {
+                    name: format!("Site {}", self.network_sites.len() + 1),
+                    freq_mhz: String::new(),
+                    pi_hex: self.pi_hex.clone(),
+                    neighbours: String::new(),
+                });
+                self.network_changed();
+                Command::none()
+            }
+            Message::NetworkRemoveSite(index) => {
+                if index < self.network_sites.len() {
+                    self.network_sites.remove(index);
+                    self.network_changed();
                 }
-                self.af_list_text = freqs.iter().map(|f| format!("{:.1}", f)).collect::<Vec<_>>().join(", ");
-                let (list, warning) = parse_af_list(&self.af_list_text);
-                self.af_warning = warning;
-                if let Some(engine) = &self.engine {
-                    engine.update_af_list(&list);
+                Command::none()
+            }
+            Message::NetworkNameChanged(index, v) => {
+                if let Some(site) = self.network_sites.get_mut(index) {
+                    site.name = v;
+                    self.network_changed();
+                }
+                Command::none()
+            }
+            Message::NetworkFreqChanged(index, v) => {
+                if let Some(site) = self.network_sites.get_mut(index) {
+                    site.freq_mhz = v;
+                    self.network_changed();
+                }
+                Command::none()
+            }
+            Message::NetworkPiChanged(index, v) => {
+                if let Some(site) = self.network_sites.get_mut(index) {
+                    site.pi_hex = v;
+                    self.network_changed();
+                }
+                Command::none()
+            }
+            Message::NetworkNeighboursChanged(index, v) => {
+                if let Some(site) = self.network_sites.get_mut(index) {
+                    site.neighbours = v;
+                    self.network_changed();
+                }
+                Command::none()
+            }
+            Message::NetworkUseSite(index) => {
+                let name = self.network_sites.get(index).map(|site| site.name.trim().to_string());
+                let (plans, _) = plan_network(&self.network_sites);
+                match plans.into_iter().find(|plan| Some(&plan.name) == name.as_ref()) {
+                    Some(plan) => {
+                        self.pi_hex = format!("{:04X}", plan.pi);
+                        self.frequency_mhz = format!("{:.1}", plan.tuned_mhz);
+                        self.af_rows = parse_af_rows(&plan.af_text());
+                        if let Some(engine) = &self.engine {
+                            engine.update_pi(plan.pi);
+                        }
+                        self.apply_af();
+                        self.status = format!("Using the AF list and PI of {}", plan.name);
+                    }
+                    None => self.status = "Fix the site's PI and frequency first".to_string(),
+                }
+                Command::none()
+            }
+            Message::NetworkExport => {
+                let (plans, _) = plan_network(&self.network_sites);
+                if plans.is_empty() {
+                    self.status = "No valid sites to export".to_string();
+                    return Command::none();
+                }
+                if let Err(e) = fs::write(network_batch_path(), batch_file(&plans)) {
+                    self.status = format!("Network export error: {}", e);
+                    return Command::none();
+                }
+                let mut presets = self.presets.clone();
+                for plan in &plans {
+                    let preset = self.site_preset(plan);
+                    match presets.iter().position(|p| p.name == preset.name) {
+                        Some(pos) => presets[pos] = preset,
+                        None => presets.push(preset),
+                    }
+                }
+                match save_config(PRESETS_FILE, &presets) {
+                    Ok(()) => {
+                        self.presets = presets;
+                        self.status = format!(
+                            "Exported {} sites to {} and as presets",
+                            plans.len(),
+                            network_batch_path().display()
+                        );
+                    }
+                    Err(e) => self.status = format!("Preset save error: {}", e),
                 }
                 Command::none()
             }
             Message::PsScrollEnabled(v) => {
                 self.ps_scroll_enabled = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_ps_scroll(self.ps_scroll_enabled, &self.ps_scroll_text, self.ps_scroll_cps);
+                    engine.update_ps_scroll_mode(self.ps_scroll_mode, self.ps_page_dwell, self.ps_page_center);
+            engine.update_ps_scroll(self.ps_scroll_enabled, &self.expand_text(&self.ps_scroll_text), self.ps_scroll_cps);
                 }
                 Command::none()
             }
             Message::PsScrollTextChanged(v) => {
                 self.ps_scroll_text = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_ps_scroll(self.ps_scroll_enabled, &self.ps_scroll_text, self.ps_scroll_cps);
+                    engine.update_ps_scroll(self.ps_scroll_enabled, &self.expand_text(&self.ps_scroll_text), self.ps_scroll_cps);
                 }
                 Command::none()
             }
             Message::PsScrollSpeedChanged(v) => {
                 self.ps_scroll_cps = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_ps_scroll(self.ps_scroll_enabled, &self.ps_scroll_text, self.ps_scroll_cps);
+                    engine.update_ps_scroll(self.ps_scroll_enabled, &self.expand_text(&self.ps_scroll_text), self.ps_scroll_cps);
+                }
+                Command::none()
+            }
+            Message::PsScrollModeChanged(v) => {
+                self.ps_scroll_mode = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_ps_scroll_mode(self.ps_scroll_mode, self.ps_page_dwell, self.ps_page_center);
+                }
+                Command::none()
+            }
+            Message::PsPageDwellChanged(v) => {
+                self.ps_page_dwell = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_ps_scroll_mode(self.ps_scroll_mode, self.ps_page_dwell, self.ps_page_center);
+                }
+                Command::none()
+            }
+            Message::PsPageCenterChanged(v) => {
+                self.ps_page_center = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_ps_scroll_mode(self.ps_scroll_mode, self.ps_page_dwell, self.ps_page_center);
                 }
                 Command::none()
             }
             Message::RtScrollEnabled(v) => {
                 self.rt_scroll_enabled = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_rt_scroll(self.rt_scroll_enabled, &self.rt_scroll_text, self.rt_scroll_cps);
+                    engine.update_rt_scroll(self.rt_scroll_enabled, &self.expand_text(&self.rt_scroll_text), self.rt_scroll_cps);
                 }
                 Command::none()
             }
             Message::RtScrollTextChanged(v) => {
                 self.rt_scroll_text = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_rt_scroll(self.rt_scroll_enabled, &self.rt_scroll_text, self.rt_scroll_cps);
+                    engine.update_rt_scroll(self.rt_scroll_enabled, &self.expand_text(&self.rt_scroll_text), self.rt_scroll_cps);
                 }
                 Command::none()
             }
             Message::RtScrollSpeedChanged(v) => {
                 self.rt_scroll_cps = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_rt_scroll(self.rt_scroll_enabled, &self.rt_scroll_text, self.rt_scroll_cps);
+                    engine.update_rt_scroll(self.rt_scroll_enabled, &self.expand_text(&self.rt_scroll_text), self.rt_scroll_cps);
+                }
+                Command::none()
+            }
+            Message::RtOverflowChanged(v) => {
+                self.rt_overflow = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_rt_overflow(self.rt_overflow, &self.rt_ellipsis, self.rt_part_secs);
+                }
+                Command::none()
+            }
+            Message::RtEllipsisChanged(v) => {
+                self.rt_ellipsis = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_rt_overflow(self.rt_overflow, &self.rt_ellipsis, self.rt_part_secs);
+                }
+                Command::none()
+            }
+            Message::RtPartSecsChanged(v) => {
+                self.rt_part_secs = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_rt_overflow(self.rt_overflow, &self.rt_ellipsis, self.rt_part_secs);
+                }
+                Command::none()
+            }
+            Message::RateGuardChanged(v) => {
+                self.rate_guard = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_rate_guard(self.rate_guard);
                 }
                 Command::none()
             }
             Message::GainChanged(v) => {
-                self.output_gain = v;
+                self.output_gain_db = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_gain(self.output_gain);
+                    engine.update_gain(db_to_linear(self.output_gain_db));
                 }
                 Command::none()
             }
             Message::LimiterEnabled(v) => {
                 self.limiter_enabled = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_limiter(self.limiter_enabled, self.limiter_threshold);
+                    engine.update_limiter(self.limiter_enabled, db_to_linear(self.limiter_threshold_db));
                 }
                 Command::none()
             }
             Message::LimiterThresholdChanged(v) => {
-                self.limiter_threshold = v;
+                self.limiter_threshold_db = v;
                 if let Some(engine) = &self.engine {
-                    engine.update_limiter(self.limiter_enabled, self.limiter_threshold);
+                    engine.update_limiter(self.limiter_enabled, db_to_linear(self.limiter_threshold_db));
                 }
                 Command::none()
             }
@@ -693,8 +1857,15 @@ impl iced::Application for App {
                 }
                 Command::none()
             }
-            Message::RdsLevelChanged(v) => {
-                self.rds_level = v;
+            Message::PilotPhaseChanged(v) => {
+                self.pilot_phase_deg = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_pilot_phase(self.pilot_phase_deg);
+                }
+                Command::none()
+            }
+            Message::RdsLevelChanged(v) => {
+                self.rds_level = v;
                 if let Some(engine) = &self.engine {
                     engine.update_rds_level(self.rds_level);
                 }
@@ -707,6 +1878,72 @@ impl iced::Application for App {
                 }
                 Command::none()
             }
+            Message::PilotNotchToggled(v) => {
+                self.pilot_notch = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_pilot_notch(self.pilot_notch);
+                }
+                Command::none()
+            }
+            Message::ProcessingPresetSelected(preset) => {
+                let s = preset.settings();
+                self.processing_preset = Some(preset);
+                self.compressor_enabled = s.compressor_enabled;
+                self.comp_threshold = s.comp_threshold_db;
+                self.comp_ratio = s.comp_ratio;
+                self.comp_attack = s.comp_attack;
+                self.comp_release = s.comp_release;
+                self.comp_makeup = s.comp_makeup_db;
+                self.limiter_enabled = s.limiter_enabled;
+                self.limiter_threshold_db = s.limiter_threshold_db;
+                self.limiter_lookahead_ms = s.limiter_lookahead_ms;
+                if !s.preemphasis {
+                    self.preemphasis_selected = Preemphasis::Off;
+                } else if self.preemphasis_selected == Preemphasis::Off {
+                    self.preemphasis_selected = Preemphasis::Us50;
+                }
+                if let Some(engine) = &self.engine {
+                    engine.update_compressor(
+                        self.compressor_enabled,
+                        self.comp_threshold,
+                        self.comp_ratio,
+                        self.comp_attack,
+                        self.comp_release,
+                        self.comp_makeup,
+                    );
+                    engine.update_limiter(self.limiter_enabled, db_to_linear(self.limiter_threshold_db));
+                    engine.update_limiter_lookahead(((self.limiter_lookahead_ms / 1000.0) * 228000.0) as usize);
+                    engine.update_preemphasis(preemph_to_tau(self.preemphasis_selected.clone()));
+                }
+                self.status = format!("Processing preset: {}", preset);
+                Command::none()
+            }
+            Message::StageBypassToggled(stage, v) => {
+                self.stage_bypass[stage.index()] = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_bypass(stage, v);
+                }
+                Command::none()
+            }
+            Message::InsertToggled(name, v) => {
+                self.enabled_inserts.retain(|n| n != name);
+                if v {
+                    self.enabled_inserts.push(name.to_string());
+                }
+                if let Err(e) = save_config(INSERTS_FILE, &self.enabled_inserts) {
+                    self.status = format!("Insert settings save error: {}", e);
+                } else if self.engine.is_some() {
+                    self.status = "Restart the stream to change the inserts".to_string();
+                }
+                Command::none()
+            }
+            Message::LpfCutoffChanged(v) => {
+                self.lpf_cutoff_hz = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_lpf_cutoff(self.lpf_cutoff_hz);
+                }
+                Command::none()
+            }
             Message::PreemphasisChanged(v) => {
                 self.preemphasis_selected = v;
                 if let Some(engine) = &self.engine {
@@ -723,6 +1960,7 @@ impl iced::Application for App {
                         self.comp_ratio,
                         self.comp_attack,
                         self.comp_release,
+                        self.comp_makeup,
                     );
                 }
                 Command::none()
@@ -736,6 +1974,7 @@ impl iced::Application for App {
                         self.comp_ratio,
                         self.comp_attack,
                         self.comp_release,
+                        self.comp_makeup,
                     );
                 }
                 Command::none()
@@ -749,6 +1988,7 @@ impl iced::Application for App {
                         self.comp_ratio,
                         self.comp_attack,
                         self.comp_release,
+                        self.comp_makeup,
                     );
                 }
                 Command::none()
@@ -762,6 +2002,7 @@ impl iced::Application for App {
                         self.comp_ratio,
                         self.comp_attack,
                         self.comp_release,
+                        self.comp_makeup,
                     );
                 }
                 Command::none()
@@ -775,6 +2016,21 @@ impl iced::Application for App {
                         self.comp_ratio,
                         self.comp_attack,
                         self.comp_release,
+                        self.comp_makeup,
+                    );
+                }
+                Command::none()
+            }
+            Message::CompMakeupChanged(v) => {
+                self.comp_makeup = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_compressor(
+                        self.compressor_enabled,
+                        self.comp_threshold,
+                        self.comp_ratio,
+                        self.comp_attack,
+                        self.comp_release,
+                        self.comp_makeup,
                     );
                 }
                 Command::none()
@@ -795,6 +2051,13 @@ impl iced::Application for App {
                 self.ct_interval_groups = v;
                 Command::none()
             }
+            Message::FastTuningChanged(v) => {
+                self.fast_tuning = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_fast_tuning(self.fast_tuning);
+                }
+                Command::none()
+            }
             Message::ApplyGroupMix => {
                 if let Some(engine) = &self.engine {
                     let g0 = self.group_0a.trim().parse::<usize>().unwrap_or(4);
@@ -812,17 +2075,20 @@ impl iced::Application for App {
             }
             Message::CountrySelected(item) => {
                 self.country_selected = item.clone();
-                if let Some(country) = item.country_hex {
-                    self.pi_country_hex = country.to_string();
-                }
-                if let Some(ecc) = item.ecc_hex {
-                    self.ecc_hex = ecc.to_string();
+                if let Some(country) = item.country {
+                    self.pi_country_hex = format!("{:X}", country.country_code);
+                    self.ecc_hex = format!("{:02X}", country.ecc);
+                    if let Some(engine) = &self.engine {
+                        engine.update_ecc(parse_hex_byte(&self.ecc_hex));
+                    }
                 }
                 Command::none()
             }
             Message::GenerateRandomPi => {
-                let country_hex = self.country_selected.country_hex.unwrap_or(self.pi_country_hex.trim());
-                let country = u16::from_str_radix(country_hex.trim_start_matches("0x"), 16).unwrap_or(0x7);
+                let country = match self.country_selected.country {
+                    Some(c) => c.country_code as u16,
+                    None => u16::from_str_radix(self.pi_country_hex.trim().trim_start_matches("0x"), 16).unwrap_or(0x7),
+                };
                 let area = rand::thread_rng().gen_range(0u16..=0xF);
                 let program = rand::thread_rng().gen_range(0u16..=0xFF);
                 let pi = (country << 12) | (area << 8) | program;
@@ -840,123 +2106,173 @@ impl iced::Application for App {
                 self.ps_alt_interval = v;
                 Command::none()
             }
-            Message::ApplyPsAlternates => {
-                if let Some(engine) = &self.engine {
-                    let list = self.ps_alt_list_text
-                        .split('|')
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect::<Vec<_>>();
-                    let interval = self.ps_alt_interval.trim().parse::<usize>().unwrap_or(0);
-                    engine.update_ps_alternates(list, interval);
+            Message::ReloadDataProviders => {
+                match load_config::<Vec<DataProvider>>(PROVIDERS_FILE) {
+                    Ok(providers) => {
+                        self.data_providers = providers;
+                        self.restart_provider_feeds();
+                        self.status = format!("Loaded {} data providers", self.data_providers.len());
+                    }
+                    Err(e) => self.status = format!("Data providers error: {}", e),
                 }
                 Command::none()
             }
-            Message::PresetSelected(v) => {
-                self.preset_selected = Some(v);
+            Message::PreviouslyRtToggled(v) => {
+                self.previously_rt = v;
                 Command::none()
             }
-            Message::PresetNameChanged(v) => {
-                self.preset_name = v;
+            Message::PreviouslyRtTextChanged(v) => {
+                self.previously_rt_text = v;
                 Command::none()
             }
-            Message::SavePreset => {
-                let preset = self.to_preset();
-                let mut presets = self.presets.clone();
-                if let Some(pos) = presets.iter().position(|p| p.name == preset.name) {
-                    presets[pos] = preset;
-                } else {
-                    presets.push(preset);
-                }
-                if let Err(e) = save_presets(&presets) {
-                    self.status = format!("Preset save error: {}", e);
-                } else {
-                    self.presets = presets;
+            Message::PreviouslyIntervalChanged(v) => {
+                self.previously_interval = v;
+                Command::none()
+            }
+            Message::SongHistoryLenChanged(v) => {
+                self.song_history_len = v;
+                if let Ok(len) = self.song_history_len.trim().parse::<usize>() {
+                    self.song_history.set_capacity(len);
                 }
                 Command::none()
             }
-            Message::LoadPreset => {
-                if let Some(name) = &self.preset_selected {
-                    if let Some(p) = self.presets.iter().find(|p| &p.name == name).cloned() {
-                        self.apply_preset(p);
+            Message::BannedWordsChanged(v) => {
+                self.metadata_filter.banned_words = v
+                    .split(',')
+                    .map(|w| w.trim().to_string())
+                    .filter(|w| !w.is_empty())
+                    .collect();
+                self.banned_words_text = v;
+                self.save_metadata_filter();
+                Command::none()
+            }
+            Message::DropBannedToggled(v) => {
+                self.metadata_filter.drop_banned = v;
+                self.save_metadata_filter();
+                Command::none()
+            }
+            Message::StripBracketsToggled(v) => {
+                self.metadata_filter.strip_brackets = v;
+                self.save_metadata_filter();
+                Command::none()
+            }
+            Message::TitleCaseToggled(v) => {
+                self.metadata_filter.title_case = v;
+                self.save_metadata_filter();
+                Command::none()
+            }
+            Message::MetadataMaxLenChanged(v) => {
+                self.metadata_filter.max_len = v.trim().parse::<usize>().ok().filter(|n| *n > 0);
+                self.metadata_max_len = v;
+                self.save_metadata_filter();
+                Command::none()
+            }
+            Message::AddOpenWeatherExample => {
+                self.data_providers.push(DataProvider::openweather_example());
+                match save_config(PROVIDERS_FILE, &self.data_providers) {
+                    Ok(()) => {
+                        self.status = format!(
+                            "OpenWeather example added to {}, set your API key there and reload",
+                            config_path(PROVIDERS_FILE).display()
+                        );
                     }
+                    Err(e) => self.status = format!("Data providers save error: {}", e),
                 }
+                self.restart_provider_feeds();
                 Command::none()
             }
-            Message::TabSelected(tab) => {
-                self.tab_selected = tab;
+            Message::ApplyPsAlternates => {
+                if let Some(engine) = &self.engine {
+                    let list = self.ps_alternates();
+                    let interval = self.ps_alt_interval.trim().parse::<usize>().unwrap_or(0);
+                    engine.update_ps_alternates(list, interval);
+                }
                 Command::none()
             }
-            Message::Tick => {
-                if let Some(engine) = &self.engine {
-                    let snapshot = engine.meter_snapshot();
-                    self.meter_rms = snapshot.rms;
-                    self.meter_peak = snapshot.peak;
-                    self.meter_pilot = snapshot.pilot;
-                    self.meter_rds = snapshot.rds;
-                    for i in 0..self.meter_bands_db.len() {
-                        let incoming = snapshot.bands_db[i];
-                        let prev = self.meter_bands_db[i];
-                        let decayed = prev - 1.5;
-                        self.meter_bands_db[i] = incoming.max(decayed);
-                    }
-                    self.scope_prev = self.scope_samples.clone();
-                    self.scope_samples = snapshot.scope;
-                    self.spectrum_peak_db = snapshot.spectrum_peak_db;
-                    self.spectrum_avg_db = snapshot.spectrum_avg_db;
-                    self.xrun_count = snapshot.xrun_count;
-                    self.buffer_fill = snapshot.buffer_fill;
-                    self.latency_ms = snapshot.latency_ms;
+            Message::RdsHistoryPathChanged(v) => {
+                self.rds_history_path = v;
+                Command::none()
+            }
+            Message::ExportRdsHistory => {
+                self.collect_rds_history();
+                let path = self.rds_history_path.trim();
+                if path.is_empty() {
+                    self.status = "History path is required".to_string();
+                    return Command::none();
+                }
+                match write_history_csv(&self.rds_history, path) {
+                    Ok(()) => self.status = format!("Exported {} history entries", self.rds_history.len()),
+                    Err(e) => self.status = format!("History export error: {}", e),
                 }
                 Command::none()
             }
-            Message::CountryCodeChanged(v) => {
-                self.pi_country_hex = v;
+            Message::ClearRdsHistory => {
+                self.collect_rds_history();
+                self.rds_history.clear();
                 Command::none()
             }
-            Message::AreaCodeChanged(v) => {
-                self.pi_area_hex = v;
+            Message::ModHistoryPathChanged(v) => {
+                self.mod_history_path = v;
                 Command::none()
             }
-            Message::ProgramRefChanged(v) => {
-                self.pi_program_hex = v;
+            Message::ModLimitChanged(index, v) => {
+                if let Some(input) = self.mod_limit_inputs.get_mut(index) {
+                    *input = v;
+                }
                 Command::none()
             }
-            Message::EccChanged(v) => {
-                self.ecc_hex = v;
+            Message::SaveModLimits => {
+                let values = self.mod_limit_inputs.clone().map(|v| v.trim().parse::<f32>().ok());
+                let [Some(power_warn_dbr), Some(power_limit_dbr), Some(deviation_warn_khz), Some(deviation_limit_khz)] = values else {
+                    self.status = "Limits must be numbers".to_string();
+                    return Command::none();
+                };
+                if power_warn_dbr > power_limit_dbr || deviation_warn_khz > deviation_limit_khz {
+                    self.status = "Each amber threshold must not exceed its limit".to_string();
+                    return Command::none();
+                }
+                self.mod_limits = ModulationLimits {
+                    power_warn_dbr,
+                    power_limit_dbr,
+                    deviation_warn_khz,
+                    deviation_limit_khz,
+                };
+                self.status = match save_config(LIMITS_FILE, &self.mod_limits) {
+                    Ok(()) => "License limits saved".to_string(),
+                    Err(e) => format!("Limits save error: {}", e),
+                };
                 Command::none()
             }
-            Message::ApplyPiFromParts => {
-                match build_pi_from_parts(&self.pi_country_hex, &self.pi_area_hex, &self.pi_program_hex, &self.ecc_hex) {
-                    Ok(pi) => {
-                        self.pi_hex = format!("{:04X}", pi);
-                        if let Some(engine) = &self.engine {
-                            engine.update_pi(pi);
-                        }
-                    }
-                    Err(e) => {
-                        self.status = e;
-                    }
+            Message::TestStepSecsChanged(index, v) => {
+                if let Some(secs) = self.test_step_secs.get_mut(index) {
+                    *secs = v;
                 }
                 Command::none()
             }
-            Message::DurationChanged(v) => {
-                self.duration = v;
+            Message::TestOutputChanged(v) => {
+                self.test_output_path = v;
                 Command::none()
             }
-            Message::AudioChanged(v) => {
-                self.audio_path = v;
+            Message::RunTestSequence => {
+                let Some(engine) = &self.engine else {
+                    self.status = "Start the stream to run the test sequence live".to_string();
+                    return Command::none();
+                };
+                engine.start_test_sequence(&self.test_sequence());
+                self.test_status = engine.test_sequence_status();
                 Command::none()
             }
-            Message::OutputChanged(v) => {
-                self.output_path = v;
+            Message::StopTestSequence => {
+                if let Some(engine) = &self.engine {
+                    engine.stop_test_sequence();
+                }
+                self.test_status = None;
                 Command::none()
             }
-            Message::Generate => {
+            Message::RenderTestSequence => {
                 if self.generating {
                     return Command::none();
                 }
-
                 let pi = match parse_pi(&self.pi_hex) {
                     Ok(v) => v,
                     Err(e) => {
@@ -964,933 +2280,4461 @@ impl iced::Application for App {
                         return Command::none();
                     }
                 };
-
-                let duration = match self.duration.trim().parse::<f32>() {
-                    Ok(v) if v > 0.0 => v,
-                    _ => {
-                        self.status = "Duration must be a positive number".to_string();
-                        return Command::none();
-                    }
-                };
-
-                let audio_path = self.audio_path.trim();
-                let audio_path = if audio_path.is_empty() {
-                    None
-                } else {
-                    Some(audio_path.to_string())
-                };
-
-                let config = GenerateConfig {
-                    duration_secs: duration,
-                    audio_path,
-                    ps: self.ps.clone(),
-                    rt: self.rt.clone(),
-                    pi,
-                    tp: self.tp,
-                    ta: self.ta,
-                    pty: self.pty_selected.code,
-                    ms: self.ms,
-                    di: self.di_bits(),
-                    ab: self.ab_flag,
-                    ab_auto: self.ab_auto,
-                    ct_enabled: self.ct_enabled,
-                    af_list_mhz: parse_af_list(&self.af_list_text).0,
-                    ps_scroll_enabled: self.ps_scroll_enabled,
-                    ps_scroll_text: self.ps_scroll_text.clone(),
-                    ps_scroll_cps: self.ps_scroll_cps,
-                    rt_scroll_enabled: self.rt_scroll_enabled,
-                    rt_scroll_text: self.rt_scroll_text.clone(),
-                    rt_scroll_cps: self.rt_scroll_cps,
-                    output_gain: self.output_gain,
-                    limiter_enabled: self.limiter_enabled,
-                    limiter_threshold: self.limiter_threshold,
-                    limiter_lookahead: ((self.limiter_lookahead_ms / 1000.0) * 228000.0) as usize,
-                    pilot_level: self.pilot_level,
-                    rds_level: self.rds_level,
-                    stereo_separation: self.stereo_separation,
-                    preemphasis_tau: preemph_to_tau(self.preemphasis_selected.clone()),
-                    compressor_enabled: self.compressor_enabled,
-                    comp_threshold_db: self.comp_threshold,
-                    comp_ratio: self.comp_ratio,
-                    comp_attack: self.comp_attack,
-                    comp_release: self.comp_release,
-                    group_0a: self.group_0a.trim().parse::<usize>().unwrap_or(4),
-                    group_2a: self.group_2a.trim().parse::<usize>().unwrap_or(1),
-                    group_4a: self.group_4a.trim().parse::<usize>().unwrap_or(0),
-                    ct_interval_groups: self.ct_interval_groups.trim().parse::<usize>().unwrap_or(0),
-                    ps_alt_list: self.ps_alt_list_text
-                        .split('|')
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect(),
-                    ps_alt_interval: self.ps_alt_interval.trim().parse::<usize>().unwrap_or(0),
-                };
-
-                let output_path = self.output_path.trim().to_string();
+                let output_path = self.test_output_path.trim().to_string();
                 if output_path.is_empty() {
                     self.status = "Output path is required".to_string();
                     return Command::none();
                 }
-
-                self.status = "Generating...".to_string();
+                let sequence = self.test_sequence();
+                let config = self.generate_config(sequence.total_secs(), None, pi);
+                self.status = "Rendering test sequence...".to_string();
                 self.generating = true;
 
                 Command::perform(
                     async move {
-                        generate_mpx_wav(&config, &output_path, |_| {})
+                        generate_test_sequence_wav(&config, &sequence, &output_path, |_| {})
                             .map_err(|e: anyhow::Error| e.to_string())
                     },
                     Message::Generated,
                 )
             }
-            Message::Generated(result) => {
+            Message::PhaseTestPathChanged(v) => {
+                self.phase_test_path = v;
+                Command::none()
+            }
+            Message::RenderPhaseTest => {
+                if self.generating {
+                    return Command::none();
+                }
+                let output_path = self.phase_test_path.trim().to_string();
+                if output_path.is_empty() {
+                    self.status = "Output path is required".to_string();
+                    return Command::none();
+                }
+                let config = self.generate_config(PHASE_TEST_SECS, None, 0);
+                self.status = "Rendering phase test...".to_string();
+                self.generating = true;
+
+                Command::perform(
+                    async move { generate_phase_test_wav(&config, &output_path).map_err(|e: anyhow::Error| e.to_string()) },
+                    Message::PhaseTestRendered,
+                )
+            }
+            Message::PhaseTestRendered(result) => {
                 self.generating = false;
                 match result {
-                    Ok(()) => self.status = "Done".to_string(),
+                    Ok(report) => {
+                        self.status = format!("Phase test: {}", report);
+                        self.phase_report = Some(report);
+                    }
                     Err(e) => self.status = format!("Error: {}", e),
                 }
                 Command::none()
             }
-
-            Message::CopyPi => {
-                let pi = self.pi_hex.trim();
-                if pi.is_empty() {
-                    self.status = "PI is empty".to_string();
+            Message::RunSeparationTest => {
+                if self.generating {
                     return Command::none();
                 }
-                self.status = "PI copied".to_string();
-                Command::batch(vec![iced::clipboard::write(pi.to_string())])
-            }
-            Message::WindowResized(width, _height) => {
-                self.window_width = width as f32;
-                Command::none()
-            }
-            Message::NoOp => Command::none(),
+                let config = self.generate_config(SEPARATION_TEST_SECS, None, 0);
+                self.status = "Running stereo separation self-test...".to_string();
+                self.generating = true;
 
-            Message::RefreshDevices => {
-                self.refresh_devices();
+                Command::perform(
+                    async move { run_separation_test(&config).map_err(|e: anyhow::Error| e.to_string()) },
+                    Message::SeparationTestDone,
+                )
+            }
+            Message::SeparationTestDone(result) => {
+                self.generating = false;
+                match result {
+                    Ok(report) => {
+                        self.status = format!("Separation self-test: {}", report);
+                        self.separation_report = Some(report);
+                    }
+                    Err(e) => self.status = format!("Error: {}", e),
+                }
                 Command::none()
             }
-            Message::InputSelected(v) => {
-                self.selected_input = Some(v);
+            Message::CalibrationStart => {
+                let Some(engine) = &self.engine else {
+                    self.status = "Start the stream before calibrating".to_string();
+                    return Command::none();
+                };
+                engine.update_calibration(Some(CalibrationStep::PilotOnly));
+                self.calibration_step = Some(CalibrationStep::PilotOnly);
+                self.calibration_input.clear();
+                self.calibration_pilot = None;
                 Command::none()
             }
-            Message::OutputSelected(v) => {
-                self.selected_output = Some(v);
+            Message::CalibrationMeasuredChanged(v) => {
+                self.calibration_input = v;
                 Command::none()
             }
-            Message::StartStream => {
-                if self.engine.is_some() {
+            Message::CalibrationNext => {
+                let Some(step) = self.calibration_step else {
                     return Command::none();
-                }
-                let output = match self.selected_output.clone() {
-                    Some(v) => v,
-                    None => {
-                        self.status = "Select an output device".to_string();
-                        return Command::none();
+                };
+                let Ok(measured) = self.calibration_input.trim().parse::<f32>() else {
+                    self.status = "Enter the measured deviation in kHz".to_string();
+                    return Command::none();
+                };
+                match step {
+                    CalibrationStep::PilotOnly => {
+                        let nominal = self.last_modulation.pilot_peak * FULL_SCALE_KHZ;
+                        self.calibration_pilot = Some((nominal, measured));
+                        self.calibration_step = Some(CalibrationStep::RdsOnly);
+                        self.calibration_input.clear();
+                        if let Some(engine) = &self.engine {
+                            engine.update_calibration(self.calibration_step);
+                        }
                     }
+                    CalibrationStep::RdsOnly => {
+                        let nominal = self.last_modulation.rds_peak * FULL_SCALE_KHZ;
+                        self.calibration_step = None;
+                        if let Some(engine) = &self.engine {
+                            engine.update_calibration(None);
+                        }
+                        let pilot = self.calibration_pilot.and_then(|(n, m)| correction_factor(n, m));
+                        let rds = correction_factor(nominal, measured);
+                        let (Some(pilot_factor), Some(rds_factor)) = (pilot, rds) else {
+                            self.status = "Calibration failed: deviations must be above zero".to_string();
+                            return Command::none();
+                        };
+                        self.calibration = DeviationCalibration { pilot_factor, rds_factor };
+                        self.status = match save_config(CALIBRATION_FILE, &self.calibration) {
+                            Ok(()) => format!("Calibration saved: pilot x{:.3}, RDS x{:.3}", pilot_factor, rds_factor),
+                            Err(e) => format!("Calibration save error: {}", e),
+                        };
+                    }
+                }
+                Command::none()
+            }
+            Message::CalibrationCancel => {
+                self.calibration_step = None;
+                if let Some(engine) = &self.engine {
+                    engine.update_calibration(None);
+                }
+                Command::none()
+            }
+            Message::CalibrationApply => {
+                let pilot_khz = self.calibration.pilot_khz(self.last_modulation.pilot_peak * FULL_SCALE_KHZ);
+                let rds_khz = self.calibration.rds_khz(self.last_modulation.rds_peak * FULL_SCALE_KHZ);
+                if self.engine.is_none() || pilot_khz <= 0.0 || rds_khz <= 0.0 {
+                    self.status = "Start the stream with pilot and RDS enabled to apply calibration".to_string();
+                    return Command::none();
+                }
+                let pilot = level_for_target(self.pilot_level, pilot_khz, PILOT_TARGET_KHZ).clamp(0.2, 1.5);
+                let rds = level_for_target(self.rds_level, rds_khz, RDS_TARGET_KHZ).clamp(0.2, 1.5);
+                let _ = self.dispatch_internal(Message::PilotLevelChanged(pilot));
+                let _ = self.dispatch_internal(Message::RdsLevelChanged(rds));
+                self.status = format!("Levels set for {:.2} kHz pilot, {:.2} kHz RDS", PILOT_TARGET_KHZ, RDS_TARGET_KHZ);
+                Command::none()
+            }
+            Message::ExportModHistory => {
+                let path = self.mod_history_path.trim();
+                if path.is_empty() {
+                    self.status = "History path is required".to_string();
+                    return Command::none();
+                }
+                match self.mod_history.write_csv(path) {
+                    Ok(()) => self.status = format!("Exported {} minutes of modulation history", self.mod_history.len()),
+                    Err(e) => self.status = format!("History export error: {}", e),
+                }
+                Command::none()
+            }
+            Message::PresetSelected(v) => {
+                self.preset_selected = Some(v);
+                Command::none()
+            }
+            Message::PresetNameChanged(v) => {
+                self.preset_name = v;
+                Command::none()
+            }
+            Message::SavePreset => {
+                let preset = self.to_preset();
+                let mut presets = self.presets.clone();
+                if let Some(pos) = presets.iter().position(|p| p.name == preset.name) {
+                    presets[pos] = preset;
+                } else {
+                    presets.push(preset);
+                }
+                if let Err(e) = save_config(PRESETS_FILE, &presets) {
+                    self.status = format!("Preset save error: {}", e);
+                } else {
+                    self.presets = presets;
+                }
+                Command::none()
+            }
+            Message::SupervisorToggled(v) => {
+                self.supervisor_settings.enabled = v;
+                self.save_supervisor_settings();
+                Command::none()
+            }
+            Message::SupervisorMaxRestartsChanged(v) => {
+                self.supervisor_settings.max_restarts = v;
+                self.save_supervisor_settings();
+                Command::none()
+            }
+            Message::ComplianceToggled(v) => {
+                self.compliance.enabled = v;
+                if let Err(e) = save_config(COMPLIANCE_FILE, &self.compliance) {
+                    self.status = format!("Compliance settings save error: {}", e);
+                } else if v && self.enforce_compliance().is_empty() {
+                    self.status = "Compliance mode on; the settings already comply".to_string();
+                }
+                Command::none()
+            }
+            Message::AutostartToggled(v) => {
+                self.autostart.enabled = v;
+                self.save_autostart_settings();
+                Command::none()
+            }
+            Message::AutostartPresetSelected(v) => {
+                self.autostart.preset = Some(v);
+                self.save_autostart_settings();
+                Command::none()
+            }
+            Message::PresetIncludeDevicesToggled(v) => {
+                self.preset_include_devices = v;
+                Command::none()
+            }
+            Message::PresetIncludeFrequencyToggled(v) => {
+                self.preset_include_frequency = v;
+                Command::none()
+            }
+            Message::PresetRemoteKindSelected(v) => {
+                self.preset_remote.kind = v;
+                self.save_preset_remote();
+                Command::none()
+            }
+            Message::PresetRemoteLocationChanged(v) => {
+                self.preset_remote.location = v;
+                self.save_preset_remote();
+                Command::none()
+            }
+            Message::PresetRemoteTokenChanged(v) => {
+                self.preset_remote.token = v;
+                self.save_preset_remote();
+                Command::none()
+            }
+            Message::StorageDirChanged(v) => {
+                self.storage_dir_text = v;
+                Command::none()
+            }
+            Message::ApplyStorageDir => {
+                let dir = self.storage_dir_text.trim();
+                self.status = match storage::set_override_dir((!dir.is_empty()).then_some(dir)) {
+                    Ok(copied) if copied.is_empty() => "Settings folder saved; it is used from the next start".to_string(),
+                    Ok(copied) => format!("Settings folder saved with {} files copied; it is used from the next start", copied.len()),
+                    Err(e) => format!("Settings folder error: {}", e),
                 };
-                let pi = match parse_pi(&self.pi_hex) {
-                    Ok(v) => v,
+                Command::none()
+            }
+            Message::SettingsFileChanged(v) => {
+                self.settings_file_text = v;
+                Command::none()
+            }
+            Message::ExportSettings => {
+                let path = self.settings_file_text.trim();
+                let current = serde_json::json!({
+                    "preset": self.to_preset(),
+                    "input_device": self.selected_input,
+                    "output_device": self.selected_output,
+                });
+                self.status = match storage::export_settings(&PathBuf::from(path), current) {
+                    Ok(count) => format!("Exported {} settings files to {}", count, path),
+                    Err(e) => format!("Settings export error: {}", e),
+                };
+                Command::none()
+            }
+            Message::ImportSettings => {
+                let path = self.settings_file_text.trim();
+                let imported = storage::read_settings_export(&PathBuf::from(path))
+                    .and_then(|export| Ok((storage::import_settings(&export)?, export.current)));
+                match imported {
+                    Ok((written, current)) => {
+                        self.apply_imported(&current);
+                        self.status = format!(
+                            "Imported {} settings files (replaced ones kept as .bak); restart to load them all",
+                            written.len()
+                        );
+                    }
+                    Err(e) => self.status = format!("Settings import error: {}", e),
+                }
+                Command::none()
+            }
+            Message::HelpTopicSelected(v) => {
+                self.help_topic = v;
+                Command::none()
+            }
+            Message::PullPresets => {
+                if self.preset_syncing {
+                    return Command::none();
+                }
+                self.preset_syncing = true;
+                self.status = "Pulling presets...".to_string();
+                let remote = self.preset_remote.clone();
+                Command::perform(
+                    async move { preset_sync::pull(&remote, PRESETS_FILE).map_err(|e| e.to_string()) },
+                    Message::PresetsPulled,
+                )
+            }
+            Message::PushPresets => {
+                if self.preset_syncing {
+                    return Command::none();
+                }
+                let data = match serde_json::to_string_pretty(&self.presets) {
+                    Ok(data) => data,
                     Err(e) => {
-                        self.status = e;
+                        self.status = format!("Preset push error: {}", e);
                         return Command::none();
                     }
                 };
-                let config = AudioEngineConfig {
-                    input_device: self.selected_input.clone(),
-                    output_device: output,
-                    ps: self.ps.clone(),
-                    rt: self.rt.clone(),
-                    pi,
-                    tp: self.tp,
-                    ta: self.ta,
-                    pty: self.pty_selected.code,
-                    ms: self.ms,
-                    di: self.di_bits(),
-                    ab: self.ab_flag,
-                    ab_auto: self.ab_auto,
-                    ct_enabled: self.ct_enabled,
-                    af_list_mhz: parse_af_list(&self.af_list_text).0,
-                    ps_scroll_enabled: self.ps_scroll_enabled,
-                    ps_scroll_text: self.ps_scroll_text.clone(),
-                    ps_scroll_cps: self.ps_scroll_cps,
-                    rt_scroll_enabled: self.rt_scroll_enabled,
-                    rt_scroll_text: self.rt_scroll_text.clone(),
-                    rt_scroll_cps: self.rt_scroll_cps,
-                    output_gain: self.output_gain,
-                    limiter_enabled: self.limiter_enabled,
-                    limiter_threshold: self.limiter_threshold,
-                    limiter_lookahead: ((self.limiter_lookahead_ms / 1000.0) * 228000.0) as usize,
-                    pilot_level: self.pilot_level,
-                    rds_level: self.rds_level,
-                    stereo_separation: self.stereo_separation,
-                    preemphasis_tau: preemph_to_tau(self.preemphasis_selected.clone()),
-                    compressor_enabled: self.compressor_enabled,
-                    comp_threshold_db: self.comp_threshold,
-                    comp_ratio: self.comp_ratio,
-                    comp_attack: self.comp_attack,
-                    comp_release: self.comp_release,
-                    group_0a: self.group_0a.trim().parse::<usize>().unwrap_or(4),
-                    group_2a: self.group_2a.trim().parse::<usize>().unwrap_or(1),
-                    group_4a: self.group_4a.trim().parse::<usize>().unwrap_or(0),
-                    ct_interval_groups: self.ct_interval_groups.trim().parse::<usize>().unwrap_or(0),
-                    ps_alt_list: self.ps_alt_list_text
-                        .split('|')
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect(),
-                    ps_alt_interval: self.ps_alt_interval.trim().parse::<usize>().unwrap_or(0),
+                self.preset_syncing = true;
+                self.status = "Pushing presets...".to_string();
+                let remote = self.preset_remote.clone();
+                Command::perform(
+                    async move { preset_sync::push(&remote, PRESETS_FILE, &data).map_err(|e| e.to_string()) },
+                    Message::PresetsPushed,
+                )
+            }
+            Message::PresetsPulled(result) => {
+                self.preset_syncing = false;
+                // A bad download never replaces the local presets.
+                let presets = result.and_then(|data| serde_json::from_str::<Vec<Preset>>(&data).map_err(|e| e.to_string()));
+                match presets {
+                    Ok(presets) => match save_config(PRESETS_FILE, &presets) {
+                        Ok(()) => {
+                            if !presets.iter().any(|p| Some(&p.name) == self.preset_selected.as_ref()) {
+                                self.preset_selected = None;
+                            }
+                            self.status = format!("Pulled {} presets", presets.len());
+                            self.presets = presets;
+                        }
+                        Err(e) => self.status = format!("Preset save error: {}", e),
+                    },
+                    Err(e) => self.status = format!("Preset pull error: {}", e),
+                }
+                Command::none()
+            }
+            Message::PresetsPushed(result) => {
+                self.preset_syncing = false;
+                self.status = match result {
+                    Ok(()) => format!("Pushed {} presets", self.presets.len()),
+                    Err(e) => format!("Preset push error: {}", e),
                 };
-                match start_engine(config) {
-                    Ok(engine) => {
-                        self.engine = Some(engine);
-                        self.status = "Streaming (192 kHz)".to_string();
+                Command::none()
+            }
+            Message::ExportDiagnostics => {
+                if self.diagnostics_exporting {
+                    return Command::none();
+                }
+                let input = DiagnosticsInput {
+                    config_dir: config_dir().to_path_buf(),
+                    logs_dir: logs_dir(),
+                    current_settings: serde_json::json!({
+                        "preset": self.to_preset(),
+                        "input_device": self.selected_input,
+                        "output_device": self.selected_output,
+                        "streaming": self.engine.is_some(),
+                        "status": self.status,
+                    }),
+                    meters: self.meter_records.iter().cloned().collect(),
+                };
+                self.diagnostics_exporting = true;
+                self.status = "Exporting diagnostics...".to_string();
+                let path = diagnostics::bundle_path(&logs_dir());
+                Command::perform(
+                    async move {
+                        diagnostics::write_bundle(&path, &input)
+                            .map(|()| path.display().to_string())
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::DiagnosticsExported,
+                )
+            }
+            Message::DiagnosticsExported(result) => {
+                self.diagnostics_exporting = false;
+                self.status = match result {
+                    Ok(path) => format!("Diagnostics saved to {}", path),
+                    Err(e) => format!("Diagnostics export error: {}", e),
+                };
+                Command::none()
+            }
+            Message::LoadPreset => {
+                if let Some(name) = &self.preset_selected {
+                    if let Some(p) = self.presets.iter().find(|p| &p.name == name).cloned() {
+                        self.apply_preset(p);
                     }
-                    Err(e) => {
-                        self.status = format!("Stream error: {}", e);
+                }
+                Command::none()
+            }
+            Message::TabSelected(tab) => {
+                self.tab_selected = tab;
+                Command::none()
+            }
+            Message::Tick => {
+                if let Some(engine) = &self.engine {
+                    let snapshot = engine.meter_snapshot();
+                    self.meter_rms = snapshot.rms;
+                    self.meter_peak = snapshot.peak;
+                    self.injection = snapshot.injection;
+                    for i in 0..self.meter_bands_db.len() {
+                        let incoming = snapshot.bands_db[i];
+                        let prev = self.meter_bands_db[i];
+                        let decayed = prev - 1.5;
+                        self.meter_bands_db[i] = incoming.max(decayed);
+                    }
+                    self.input_levels = [
+                        (snapshot.input_left_rms, snapshot.input_left_peak),
+                        (snapshot.input_right_rms, snapshot.input_right_peak),
+                    ];
+                    self.meter_records.push_back(MeterRecord {
+                        time: chrono::Local::now().to_rfc3339(),
+                        rms: snapshot.rms,
+                        peak: snapshot.peak,
+                        pilot: snapshot.injection.pilot,
+                        stereo_carrier: snapshot.injection.stereo_carrier,
+                        rds: snapshot.injection.rds,
+                        xruns: snapshot.xrun_count,
+                        buffer_fill: snapshot.buffer_fill,
+                        latency_ms: snapshot.latency_ms,
+                        dsp_load_avg: snapshot.dsp_load_avg,
+                        dsp_load_worst: snapshot.dsp_load_worst,
+                        drift_ppm: snapshot.drift_ppm,
+                    });
+                    if self.meter_records.len() > METER_RECORDS {
+                        self.meter_records.pop_front();
+                    }
+                    self.xrun_count = snapshot.xrun_count;
+                    self.buffer_fill = snapshot.buffer_fill;
+                    self.latency_ms = snapshot.latency_ms;
+                    self.dsp_load_avg = snapshot.dsp_load_avg;
+                    self.dsp_load_worst = snapshot.dsp_load_worst;
+                    self.drift_ppm = snapshot.drift_ppm;
+                    self.mask_result = snapshot.mask;
+                    self.last_modulation = engine.take_modulation_stats();
+                    self.stage_levels = engine.take_stage_levels();
+                    self.test_status = engine.test_sequence_status();
+                    self.mod_history.add(&self.last_modulation, chrono::Local::now());
+                    self.mod_window.add(&self.last_modulation, Instant::now());
+                    if self.silence_enabled {
+                        let now = Instant::now();
+                        let events = [
+                            ("Program audio", self.program_silence.update(snapshot.program_peak, now)),
+                            ("MPX output", self.mpx_silence.update(snapshot.rms, now)),
+                        ];
+                        for (source, event) in events {
+                            let Some(event) = event else {
+                                continue;
+                            };
+                            let state = match event {
+                                SilenceEvent::Started => {
+                                    play_alert_tone();
+                                    self.status = format!("DEAD AIR: {} silent for {:.0} s", source, self.silence_hold_secs);
+                                    if self.alerts.dead_air {
+                                        self.send_alert(&self.status.clone());
+                                    }
+                                    "silence"
+                                }
+                                SilenceEvent::Cleared => {
+                                    self.status = format!("{} restored", source);
+                                    "restored"
+                                }
+                            };
+                            if !self.silence_webhook.trim().is_empty() {
+                                let body = serde_json::json!({
+                                    "event": state,
+                                    "source": source,
+                                    "station": self.ps.trim(),
+                                    "time": chrono::Local::now().to_rfc3339(),
+                                });
+                                post_webhook(self.silence_webhook.trim(), body.to_string());
+                            }
+                        }
+                        self.silence_flash = !self.silence_flash;
+                    }
+                    let now_time = chrono::Local::now().time();
+                    let mut applied = self.automation_applied.unwrap_or([f32::NAN; 4]);
+                    for param in AutomationParam::ALL {
+                        let (offset, ramp, curve) = scheduled_offset(&self.automation_rules, param, now_time);
+                        if applied[param.index()] != offset {
+                            let ramp = if self.automation_applied.is_some() { ramp } else { 0.0 };
+                            engine.update_automation(param, offset, ramp, curve);
+                            applied[param.index()] = offset;
+                        }
+                    }
+                    self.automation_applied = Some(applied);
+                    self.automation_offsets = engine.automation_offsets_db();
+                    if engine.take_device_error() {
+                        self.status = "Audio device error".to_string();
+                        if self.alerts.device_lost {
+                            self.send_alert("Audio device lost or failed");
+                        }
+                    }
+                    if let Some(count) = self.xrun_storm.update(snapshot.xrun_count, Instant::now()) {
+                        if self.alerts.xrun_storm {
+                            self.send_alert(&format!("Xrun storm: {} xruns in {} s", count, XRUN_STORM_WINDOW_SECS));
+                        }
+                    }
+                    if let Some(session) = self.session.as_mut() {
+                        session.record(
+                            snapshot.xrun_count,
+                            snapshot.buffer_fill,
+                            snapshot.peak_hold,
+                            snapshot.dsp_load_worst,
+                            engine.failover_status().switches,
+                        );
+                    }
+
+                    let failover = engine.failover_status();
+                    let previous = self.failover_status.map(|s| s.switches).unwrap_or(0);
+                    if failover.switches != previous {
+                        let source = if failover.on_backup { "backup" } else { "primary" };
+                        self.status = format!("Input switched to {} ({})", source, failover.reason);
                     }
+                    self.failover_status = Some(failover);
+                    self.rtp_status = engine.rtp_status();
+                    self.input_pilot = engine.input_pilot_status();
+                    self.program_delay_current = engine.program_delay_secs().0;
+
+                    let now = Instant::now();
+                    self.group_stats_samples.push_back((now, engine.rds_group_stats()));
+                    while self.group_stats_samples.len() > 2
+                        && now.duration_since(self.group_stats_samples[1].0) >= GROUP_STATS_WINDOW
+                    {
+                        self.group_stats_samples.pop_front();
+                    }
+                }
+                if self.stopping && self.engine.as_ref().map(|e| e.is_faded_out()).unwrap_or(true) {
+                    return self.dispatch_internal(Message::StopStream);
                 }
+                self.collect_rds_history();
+                self.poll_autostart();
+                self.supervise();
+                self.enforce_compliance();
+                self.poll_contacts();
+                self.serve_status();
+                self.serve_sync();
+                self.refresh_macro_texts(RdsChangeSource::Macro);
+                self.refresh_pty_schedule();
                 Command::none()
             }
-            Message::StopStream => {
+            Message::GraphTick => {
                 if let Some(engine) = &self.engine {
-                    engine.stop();
+                    let snapshot = engine.meter_snapshot();
+                    self.scope_prev = std::mem::replace(&mut self.scope_samples, snapshot.scope);
+                    self.spectrum_peak_db = snapshot.spectrum_peak_db;
+                    self.spectrum_avg_db = snapshot.spectrum_avg_db;
+                    self.input_spectrum_db = snapshot.input_spectrum_db;
                 }
-                self.engine = None;
-                self.status = "Stopped".to_string();
                 Command::none()
             }
-        }
-    }
-
-    fn view(&self) -> Element<'_, Self::Message> {
-        let preset_names = self.presets.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
-
-        let tab_button = |label: &str, tab: Tab| {
-            let selected = self.tab_selected == tab;
-            button(text(label).size(14))
-                .padding([8, 14])
-                .style(theme::Button::Custom(Box::new(TabButton { selected })))
-                .on_press(Message::TabSelected(tab))
-        };
-
-        let tabs = row![
-            tab_button("Dashboard", Tab::Dashboard),
-            tab_button("Audio", Tab::Audio),
-            tab_button("RDS", Tab::Rds),
-            tab_button("Processing", Tab::Processing),
-            tab_button("Meters", Tab::Meters),
-            tab_button("Export", Tab::Export),
-            tab_button("About", Tab::About),
-        ]
-        .spacing(10)
-        .align_items(Alignment::Center);
-
-        let presets_card = || {
-            card(
-                "Presets",
-                column![
-                    row![
-                        text("Preset:"),
-                        pick_list(preset_names.clone(), self.preset_selected.clone(), Message::PresetSelected),
-                        button("Load")
-                            .style(theme::Button::Custom(Box::new(GhostButton)))
-                            .on_press(Message::LoadPreset),
-                    ]
-                    .spacing(10)
-                    .align_items(Alignment::Center),
-                    row![
-                        text("Name:"),
-                        text_input("Preset name", &self.preset_name).on_input(Message::PresetNameChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                        button("Save")
-                            .style(theme::Button::Custom(Box::new(PrimaryButton)))
-                            .on_press(Message::SavePreset),
-                    ]
-                    .spacing(10)
-                    .align_items(Alignment::Center),
-                ],
-            )
-        };
-
-        let stream_card = || {
-            card(
-            "Stream",
-            column![
-                row![
-                    if self.engine.is_some() {
-                        button("Streaming...")
+            Message::GraphRateSelected(v) => {
+                self.refresh.graphs = v;
+                self.save_refresh_settings();
+                Command::none()
+            }
+            Message::TextRateSelected(v) => {
+                self.refresh.text = v;
+                self.save_refresh_settings();
+                Command::none()
+            }
+            Message::CountryCodeChanged(v) => {
+                self.pi_country_hex = v;
+                Command::none()
+            }
+            Message::AreaCodeChanged(v) => {
+                self.pi_area_hex = v;
+                Command::none()
+            }
+            Message::ProgramRefChanged(v) => {
+                self.pi_program_hex = v;
+                Command::none()
+            }
+            Message::EccChanged(v) => {
+                self.ecc_hex = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_ecc(parse_hex_byte(&self.ecc_hex));
+                }
+                Command::none()
+            }
+            Message::LicChanged(v) => {
+                self.lic_hex = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_lic(parse_hex_byte(&self.lic_hex));
+                }
+                Command::none()
+            }
+            Message::ApplyPiFromParts => {
+                match build_pi_from_parts(&self.pi_country_hex, &self.pi_area_hex, &self.pi_program_hex, &self.ecc_hex) {
+                    Ok(pi) => {
+                        self.pi_hex = format!("{:04X}", pi);
+                        if let Some(engine) = &self.engine {
+                            engine.update_pi(pi);
+                        }
+                    }
+                    Err(e) => {
+                        self.status = e;
+                    }
+                }
+                Command::none()
+            }
+            Message::DurationChanged(v) => {
+                self.duration = v;
+                Command::none()
+            }
+            Message::AudioChanged(v) => {
+                self.audio_path = v;
+                Command::none()
+            }
+            Message::OutputChanged(v) => {
+                self.output_path = v;
+                Command::none()
+            }
+            Message::ResumeExportChanged(v) => {
+                self.resume_export = v;
+                Command::none()
+            }
+            Message::ImpairmentChanged(index, v) => {
+                if let Some(input) = self.impairment_inputs.get_mut(index) {
+                    *input = v;
+                }
+                Command::none()
+            }
+            Message::IqExportToggled(v) => {
+                self.iq_export = v;
+                Command::none()
+            }
+            Message::IqRateChanged(v) => {
+                self.iq_rate = v;
+                Command::none()
+            }
+            Message::IqDeviationChanged(v) => {
+                self.iq_deviation = v;
+                Command::none()
+            }
+            Message::ProgramFeedPathChanged(v) => {
+                self.program_feed_path = v;
+                Command::none()
+            }
+            Message::ProgramFeedDelayChanged(v) => {
+                self.program_feed_delay = v;
+                Command::none()
+            }
+            Message::GroupSchedulePathChanged(v) => {
+                self.group_schedule_path = v;
+                Command::none()
+            }
+            Message::Generate => {
+                if self.generating {
+                    return Command::none();
+                }
+
+                let pi = match parse_pi(&self.pi_hex) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        self.status = e;
+                        return Command::none();
+                    }
+                };
+
+                let duration = match self.duration.trim().parse::<f32>() {
+                    Ok(v) if v > 0.0 => v,
+                    _ => {
+                        self.status = "Duration must be a positive number".to_string();
+                        return Command::none();
+                    }
+                };
+
+                let audio_path = self.audio_path.trim();
+                let audio_path = if audio_path.is_empty() {
+                    None
+                } else {
+                    Some(audio_path.to_string())
+                };
+
+                let config = self.generate_config(duration, audio_path, pi);
+
+                let output_path = self.output_path.trim().to_string();
+                if output_path.is_empty() {
+                    self.status = "Output path is required".to_string();
+                    return Command::none();
+                }
+
+                if self.iq_export {
+                    let iq = IqSettings {
+                        sample_rate: self.iq_rate.trim().parse::<u32>().unwrap_or(0),
+                        deviation_khz: self.iq_deviation.trim().parse::<f32>().unwrap_or(0.0),
+                    };
+                    if let Err(e) = iq.validate(228_000) {
+                        self.status = e.to_string();
+                        return Command::none();
+                    }
+                    self.status = "Generating IQ...".to_string();
+                    self.generating = true;
+                    return Command::perform(
+                        async move {
+                            generate_iq_wav(&config, &iq, &output_path, |_| {})
+                                .map_err(|e: anyhow::Error| e.to_string())
+                        },
+                        Message::MpxRendered,
+                    );
+                }
+
+                self.status = "Generating...".to_string();
+                self.generating = true;
+                let resume = self.resume_export;
+
+                Command::perform(
+                    async move {
+                        generate_mpx_wav(&config, &output_path, resume, |_| {})
+                            .map_err(|e: anyhow::Error| e.to_string())
+                    },
+                    Message::MpxRendered,
+                )
+            }
+            Message::MpxRendered(result) => {
+                self.generating = false;
+                match result {
+                    Ok(report) => self.status = format!("Done: {}", report),
+                    Err(e) => self.status = format!("Error: {}", e),
+                }
+                Command::none()
+            }
+            Message::Generated(result) => {
+                self.generating = false;
+                match result {
+                    Ok(()) => self.status = "Done".to_string(),
+                    Err(e) => self.status = format!("Error: {}", e),
+                }
+                Command::none()
+            }
+
+            Message::CopyPi => {
+                let pi = self.pi_hex.trim();
+                if pi.is_empty() {
+                    self.status = "PI is empty".to_string();
+                    return Command::none();
+                }
+                self.status = "PI copied".to_string();
+                Command::batch(vec![iced::clipboard::write(pi.to_string())])
+            }
+            Message::WindowResized(width, _height) => {
+                self.window_width = width as f32;
+                Command::none()
+            }
+            Message::NoOp => Command::none(),
+
+            Message::RefreshDevices => {
+                self.refresh_devices();
+                Command::none()
+            }
+            Message::InputSelected(v) => {
+                self.selected_input = Some(v);
+                Command::none()
+            }
+            Message::ProgramDelayChanged(v) => {
+                self.program_delay_secs = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_program_delay(self.program_delay_secs);
+                }
+                Command::none()
+            }
+            Message::DiversityDelayChanged(v) => {
+                self.diversity_delay_ms = v.clamp(0.0, MAX_DIVERSITY_DELAY_MS);
+                if let Some(engine) = &self.engine {
+                    engine.update_diversity_delay(self.diversity_delay_ms);
+                }
+                Command::none()
+            }
+            Message::DiversityDelayNudge(delta) => {
+                let v = self.diversity_delay_ms + delta;
+                iced::Application::update(self, Message::DiversityDelayChanged(v))
+            }
+            Message::AlertsToggled(v) => {
+                self.alerts.enabled = v;
+                self.save_alerts();
+                Command::none()
+            }
+            Message::TelegramTokenChanged(v) => {
+                self.alerts.telegram_token = v;
+                self.save_alerts();
+                Command::none()
+            }
+            Message::TelegramChatChanged(v) => {
+                self.alerts.telegram_chat_id = v;
+                self.save_alerts();
+                Command::none()
+            }
+            Message::AlertDeadAirToggled(v) => {
+                self.alerts.dead_air = v;
+                self.save_alerts();
+                Command::none()
+            }
+            Message::AlertDeviceLostToggled(v) => {
+                self.alerts.device_lost = v;
+                self.save_alerts();
+                Command::none()
+            }
+            Message::AlertXrunStormToggled(v) => {
+                self.alerts.xrun_storm = v;
+                self.save_alerts();
+                Command::none()
+            }
+            Message::AlertTest => {
+                match TelegramClient::new(&self.alerts.telegram_token, &self.alerts.telegram_chat_id) {
+                    Some(client) => {
+                        client.send(&format!("{}: test alert from PulseFM", self.ps.trim()));
+                        self.status = "Test alert sent".to_string();
+                    }
+                    None => self.status = "Enter a Telegram bot token and chat ID".to_string(),
+                }
+                Command::none()
+            }
+            Message::SilenceAlarmToggled(v) => {
+                self.silence_enabled = v;
+                self.program_silence.reset();
+                self.mpx_silence.reset();
+                Command::none()
+            }
+            Message::SilenceThresholdChanged(v) => {
+                self.silence_threshold_db = v;
+                self.program_silence.set_threshold(self.silence_threshold_db, self.silence_hold_secs);
+                self.mpx_silence.set_threshold(self.silence_threshold_db, self.silence_hold_secs);
+                Command::none()
+            }
+            Message::SilenceHoldChanged(v) => {
+                self.silence_hold_secs = v;
+                self.program_silence.set_threshold(self.silence_threshold_db, self.silence_hold_secs);
+                self.mpx_silence.set_threshold(self.silence_threshold_db, self.silence_hold_secs);
+                Command::none()
+            }
+            Message::SilenceWebhookChanged(v) => {
+                self.silence_webhook = v;
+                Command::none()
+            }
+            Message::FadeChanged(v) => {
+                self.fade_secs = v;
+                Command::none()
+            }
+            Message::DumpProgramDelay => {
+                if let Some(engine) = &self.engine {
+                    engine.dump_program_delay();
+                    self.status = "Delay dumped, back to real time".to_string();
+                }
+                Command::none()
+            }
+            Message::RtpToggled(v) => {
+                self.rtp_enabled = v;
+                Command::none()
+            }
+            Message::MpxInputToggled(v) => {
+                self.mpx_input = v;
+                if self.engine.is_some() {
+                    self.status = "Restart the stream to change the input mode".to_string();
+                }
+                Command::none()
+            }
+            Message::ExclusiveOutputToggled(v) => {
+                self.exclusive_output = v;
+                if self.engine.is_some() {
+                    self.status = "Restart the stream to change the output mode".to_string();
+                }
+                Command::none()
+            }
+            Message::MpxInputPilotToggled(v) => {
+                self.mpx_input_pilot = v;
+                if let Some(engine) = &self.engine {
+                    engine.update_mpx_input_pilot(self.mpx_input_pilot);
+                }
+                Command::none()
+            }
+            Message::MpxFileChanged(v) => {
+                self.mpx_file = v;
+                if self.engine.is_some() {
+                    self.status = "Restart the stream to change the input".to_string();
+                }
+                Command::none()
+            }
+            Message::MpxFileLoopToggled(v) => {
+                self.mpx_file_loop = v;
+                Command::none()
+            }
+            Message::RtpAddressChanged(v) => {
+                self.rtp_address = v;
+                Command::none()
+            }
+            Message::RtpPortChanged(v) => {
+                self.rtp_port = v;
+                Command::none()
+            }
+            Message::FailoverToggled(v) => {
+                self.failover_enabled = v;
+                Command::none()
+            }
+            Message::BackupInputSelected(v) => {
+                self.backup_input = Some(v);
+                Command::none()
+            }
+            Message::BackupFileChanged(v) => {
+                self.backup_file = v;
+                Command::none()
+            }
+            Message::FailoverSilenceChanged(v) => {
+                self.failover_silence_secs = v;
+                Command::none()
+            }
+            Message::FailoverRestoreChanged(v) => {
+                self.failover_restore_secs = v;
+                Command::none()
+            }
+            Message::TrafficToggled(v) => {
+                self.traffic_enabled = v;
+                Command::none()
+            }
+            Message::TrafficInputSelected(v) => {
+                self.traffic_input = Some(v);
+                Command::none()
+            }
+            Message::TrafficFileChanged(v) => {
+                self.traffic_file = v;
+                Command::none()
+            }
+            Message::TrafficCrossfadeChanged(v) => {
+                self.traffic_crossfade_secs = v;
+                Command::none()
+            }
+            Message::OutputSelected(v) => {
+                self.selected_output = Some(v);
+                Command::none()
+            }
+            Message::MonitorSelected(v) => {
+                self.selected_monitor = if v == MONITOR_OFF { None } else { Some(v) };
+                Command::none()
+            }
+            Message::StartStream => {
+                if self.engine.is_some() {
+                    return Command::none();
+                }
+                let output = match self.selected_output.clone() {
+                    Some(v) => v,
+                    None => {
+                        self.status = "Select an output device".to_string();
+                        return Command::none();
+                    }
+                };
+                let pi = match parse_pi(&self.pi_hex) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        self.status = e;
+                        return Command::none();
+                    }
+                };
+                let failover = match self.failover_config() {
+                    Ok(v) => v,
+                    Err(e) => {
+                        self.status = e;
+                        return Command::none();
+                    }
+                };
+                let traffic = match self.traffic_config() {
+                    Ok(v) => v,
+                    Err(e) => {
+                        self.status = e;
+                        return Command::none();
+                    }
+                };
+                let rtp_input = if self.rtp_enabled {
+                    let address = self.rtp_address.trim().parse();
+                    let port = self.rtp_port.trim().parse::<u16>();
+                    match (address, port) {
+                        (Ok(address), Ok(port)) => Some(RtpConfig::aes67(address, port)),
+                        _ => {
+                            self.status = "Invalid RTP address or port".to_string();
+                            return Command::none();
+                        }
+                    }
+                } else {
+                    None
+                };
+                // The main picks the trigger and sends it once its own engine
+                // is up; a standby gets it from the main.
+                let trigger = match self.sync_start_at.take() {
+                    Some(at) => Some((at, false)),
+                    None if self.sync_aligned_start && self.sync_role == SyncRole::Main && self.sync_link.is_some() => {
+                        Some((SystemTime::now() + START_LEAD, true))
+                    }
+                    None => None,
+                };
+                let config = AudioEngineConfig {
+                    input_device: self.selected_input.clone(),
+                    rtp_input,
+                    failover,
+                    traffic,
+                    rds_feed: self.rds_feed_config(),
+                    output_device: output,
+                    exclusive_output: self.exclusive_output,
+                    ps: self.expand_text(&self.ps),
+                    rt: self.expand_text(&self.rt),
+                    code_table: self.code_table,
+                    translit: self.translit.clone(),
+                    pi,
+                    ecc: parse_hex_byte(&self.ecc_hex),
+                    lic: parse_hex_byte(&self.lic_hex),
+                    tp: self.tp,
+                    ta: self.ta,
+                    pty: self.on_air_pty(),
+                    ms: self.ms,
+                    di: self.di_bits(),
+                    ab: self.ab_flag,
+                    ab_auto: self.ab_auto,
+                    ct_enabled: self.ct_enabled,
+                    af_list_mhz: validate_af_rows(&self.af_rows).0,
+                    af_regional_mhz: validate_af_rows(&self.af_rows).1,
+                    af_tuned_mhz: self.frequency_mhz.trim().parse::<f32>().ok(),
+                    ps_scroll_enabled: self.ps_scroll_enabled,
+                    ps_scroll_text: self.expand_text(&self.ps_scroll_text),
+                    ps_scroll_cps: self.ps_scroll_cps,
+                    ps_scroll_mode: self.ps_scroll_mode,
+                    ps_page_dwell_secs: self.ps_page_dwell,
+                    ps_page_center: self.ps_page_center,
+                    rt_scroll_enabled: self.rt_scroll_enabled,
+                    rt_scroll_text: self.expand_text(&self.rt_scroll_text),
+                    rt_scroll_cps: self.rt_scroll_cps,
+                    rt_overflow: self.rt_overflow,
+                    rt_ellipsis: self.rt_ellipsis.clone(),
+                    rt_part_secs: self.rt_part_secs,
+                    rate_guard: self.rate_guard,
+                    output_gain: db_to_linear(self.output_gain_db),
+                    limiter_enabled: self.limiter_enabled,
+                    limiter_threshold: db_to_linear(self.limiter_threshold_db),
+                    limiter_lookahead: ((self.limiter_lookahead_ms / 1000.0) * 228000.0) as usize,
+                    pilot_level: self.pilot_level,
+                    pilot_phase_deg: self.pilot_phase_deg,
+                    rds_level: self.rds_level,
+                    stereo_separation: self.stereo_separation,
+                    preemphasis_tau: preemph_to_tau(self.preemphasis_selected.clone()),
+                    lpf_cutoff_hz: self.lpf_cutoff_hz,
+                    pilot_notch: self.pilot_notch,
+                    stage_bypass: self.stage_bypass,
+                    mpx_input: self.mpx_input,
+                    mpx_input_pilot: self.mpx_input_pilot,
+                    mpx_file: (self.mpx_input && !self.mpx_file.trim().is_empty()).then(|| self.mpx_file.trim().to_string()),
+                    mpx_file_loop: self.mpx_file_loop,
+                    inserts: self.enabled_inserts.clone(),
+                    compressor_enabled: self.compressor_enabled,
+                    comp_threshold_db: self.comp_threshold,
+                    comp_ratio: self.comp_ratio,
+                    comp_attack: self.comp_attack,
+                    comp_release: self.comp_release,
+                    comp_makeup_db: self.comp_makeup,
+                    group_0a: self.group_0a.trim().parse::<usize>().unwrap_or(4),
+                    group_2a: self.group_2a.trim().parse::<usize>().unwrap_or(1),
+                    group_4a: self.group_4a.trim().parse::<usize>().unwrap_or(0),
+                    ct_interval_groups: self.ct_interval_groups.trim().parse::<usize>().unwrap_or(0),
+                    fast_tuning: self.fast_tuning,
+                    ps_alt_list: self.ps_alternates(),
+                    ps_alt_interval: self.ps_alt_interval.trim().parse::<usize>().unwrap_or(0),
+                    program_delay_secs: self.program_delay_secs,
+                    fade_secs: self.fade_secs,
+                    diversity_delay_ms: self.diversity_delay_ms,
+                    monitor_device: self.selected_monitor.clone(),
+                    start_at: trigger.map(|(at, _)| at),
+                };
+                match start_engine(config) {
+                    Ok(engine) => {
+                        engine.update_tx_mute(self.tx_mute);
+                        engine.update_analyzer(self.fft_size, self.fft_window);
+                        self.engine = Some(engine);
+                        self.session = Some(SessionStats::new());
+                        self.supervisor.started(Instant::now());
+                        self.program_silence.reset();
+                        self.mpx_silence.reset();
+                        self.xrun_storm.reset();
+                        self.automation_applied = None;
+                        self.status = if self.exclusive_output {
+                            "Streaming (192 kHz, exclusive)".to_string()
+                        } else {
+                            "Streaming (192 kHz)".to_string()
+                        };
+                        if let Some((at, send)) = trigger {
+                            if send {
+                                if let Some(link) = &self.sync_link {
+                                    let _ = link.send(&start_message(at));
+                                }
+                            }
+                            self.status.push_str(", aligned start");
+                        }
+                    }
+                    Err(e) => {
+                        self.status = format!("Stream error: {}", e);
+                    }
+                }
+                Command::none()
+            }
+            Message::StopStream => {
+                if self.autostart_pending.take().is_some() {
+                    self.status = "Auto-start cancelled".to_string();
+                }
+                if self.supervisor.is_pending() {
+                    self.status = "Engine restart cancelled".to_string();
+                }
+                self.supervisor.reset();
+                if let Some(engine) = &self.engine {
+                    if self.fade_secs > 0.0 && !self.stopping {
+                        engine.begin_fade_out(self.fade_secs);
+                        self.stopping = true;
+                        self.status = "Fading out...".to_string();
+                        return Command::none();
+                    }
+                    engine.stop();
+                }
+                self.stopping = false;
+                self.collect_rds_history();
+                let report = self.session.take().map(|session| session.report());
+                self.group_stats_samples.clear();
+                self.failover_status = None;
+                self.rtp_status = None;
+                self.input_pilot = None;
+                self.mod_window.clear();
+                self.program_delay_current = 0.0;
+                self.calibration_step = None;
+                self.engine = None;
+                self.status = "Stopped".to_string();
+                if let Some(report) = report {
+                    match write_session_report(&report, &logs_dir()) {
+                        Ok((json, _)) => {
+                            self.status = format!(
+                                "Stopped after {} s, {} xruns, report {}",
+                                report.uptime_secs,
+                                report.xruns,
+                                json.display()
+                            );
+                        }
+                        Err(e) => self.status = format!("Session report error: {}", e),
+                    }
+                }
+                Command::none()
+            }
+        }
+    }
+
+    fn view(&self) -> Element<'_, Self::Message> {
+        let preset_names = self.presets.iter().map(|p| p.name.clone()).collect::<Vec<_>>();
+
+        let tab_button = |label: &str, tab: Tab| {
+            let selected = self.tab_selected == tab;
+            button(text(label).size(14))
+                .padding([8, 14])
+                .style(theme::Button::Custom(Box::new(TabButton { selected })))
+                .on_press(Message::TabSelected(tab))
+        };
+
+        let tabs = row![
+            tab_button("Dashboard", Tab::Dashboard),
+            tab_button("Audio", Tab::Audio),
+            tab_button("RDS", Tab::Rds),
+            tab_button("Processing", Tab::Processing),
+            tab_button("Meters", Tab::Meters),
+            tab_button("Export", Tab::Export),
+            tab_button("About", Tab::About),
+        ]
+        .spacing(10)
+        .align_items(Alignment::Center);
+
+        let presets_card = || {
+            card(
+                "Presets",
+                column![
+                    row![
+                        text("Preset:"),
+                        pick_list(preset_names.clone(), self.preset_selected.clone(), Message::PresetSelected),
+                        button("Load")
+                            .style(theme::Button::Custom(Box::new(GhostButton)))
+                            .on_press(Message::LoadPreset),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text("Name:"),
+                        text_input("Preset name", &self.preset_name).on_input(Message::PresetNameChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        button("Save")
+                            .style(theme::Button::Custom(Box::new(PrimaryButton)))
+                            .on_press(Message::SavePreset),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        checkbox("Save audio devices", self.preset_include_devices, Message::PresetIncludeDevicesToggled),
+                        checkbox("Save frequency", self.preset_include_frequency, Message::PresetIncludeFrequencyToggled),
+                    ]
+                    .spacing(20)
+                    .align_items(Alignment::Center),
+                    text("A preset saved without them leaves the current devices or frequency alone when loaded.").size(12).style(color_muted()),
+                    row![
+                        text("Shared:"),
+                        pick_list(vec![RemoteKind::Http, RemoteKind::Git], Some(self.preset_remote.kind), Message::PresetRemoteKindSelected),
+                        text_input(
+                            match self.preset_remote.kind {
+                                RemoteKind::Http => "https://example.com/presets.json",
+                                RemoteKind::Git => "Path of a git clone",
+                            },
+                            &self.preset_remote.location,
+                        )
+                        .on_input(Message::PresetRemoteLocationChanged)
+                        .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text_input("Bearer token (optional)", &self.preset_remote.token)
+                            .on_input(Message::PresetRemoteTokenChanged)
+                            .password()
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        button("Pull")
+                            .style(theme::Button::Custom(Box::new(GhostButton)))
+                            .on_press(Message::PullPresets),
+                        button("Push")
+                            .style(theme::Button::Custom(Box::new(GhostButton)))
+                            .on_press(Message::PushPresets),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text("Pull replaces the local presets with the shared ones; Push uploads them (HTTP PUT, or commit and push in the clone).").size(12).style(color_muted()),
+                ],
+            )
+        };
+
+        // The pane follows the tab: a topic from another tab falls back to
+        // the first one of this tab.
+        let help_card = |area: HelpArea| {
+            let topics = HelpTopic::in_area(area);
+            let topic = if self.help_topic.area() == area { self.help_topic } else { topics[0] };
+            card(
+                "Help",
+                column![
+                    pick_list(topics, Some(topic), Message::HelpTopicSelected),
+                    text(topic.body()).size(13),
+                    text("Hover a label for a short hint.").size(12).style(color_muted()),
+                ]
+                .spacing(10),
+            )
+        };
+
+        let storage_card = || {
+            card(
+                "Settings Folder",
+                column![
+                    text(format!("In use: {}", config_dir().display())).size(12),
+                    row![
+                        text_input("Default platform folder", &self.storage_dir_text)
+                            .on_input(Message::StorageDirChanged)
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        button("Apply")
+                            .style(theme::Button::Custom(Box::new(GhostButton)))
+                            .on_press(Message::ApplyStorageDir),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(format!(
+                        "Presets and other settings live here. Apply copies them to the new folder, which is used from the next start; {} overrides it.",
+                        CONFIG_DIR_ENV
+                    ))
+                    .size(12)
+                    .style(color_muted()),
+                    row![
+                        text_input("Settings file", &self.settings_file_text)
+                            .on_input(Message::SettingsFileChanged)
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        button("Export")
+                            .style(theme::Button::Custom(Box::new(GhostButton)))
+                            .on_press(Message::ExportSettings),
+                        button("Import")
+                            .style(theme::Button::Custom(Box::new(GhostButton)))
+                            .on_press(Message::ImportSettings),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text("Export writes every settings file plus the live preset and audio devices into one JSON file, tokens included; Import restores them here.").size(12).style(color_muted()),
+                ]
+                .spacing(10),
+            )
+        };
+
+        let stream_card = || {
+            card(
+            "Stream",
+            column![
+                row![
+                    if self.engine.is_some() {
+                        button("Streaming...")
                             .padding(10)
                             .style(theme::Button::Custom(Box::new(GhostButton)))
                     } else {
-                        button("Start Stream")
-                            .on_press(Message::StartStream)
-                            .padding(10)
-                            .style(theme::Button::Custom(Box::new(PrimaryButton)))
+                        button("Start Stream")
+                            .on_press(Message::StartStream)
+                            .padding(10)
+                            .style(theme::Button::Custom(Box::new(PrimaryButton)))
+                    },
+                    button("Stop")
+                        .on_press(Message::StopStream)
+                        .padding(10)
+                        .style(theme::Button::Custom(Box::new(DangerButton))),
+                    text(&self.status).style(color_muted()),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text(format!("Fade in/out {:.1} s", self.fade_secs)),
+                    slider(0.0..=5.0, self.fade_secs, Message::FadeChanged)
+                        .step(0.1)
+                        .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text(format!("Delay {:.1} s", self.program_delay_secs)),
+                    slider(0.0..=MAX_DELAY_SECS, self.program_delay_secs, Message::ProgramDelayChanged)
+                        .step(0.5)
+                        .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    button("Dump")
+                        .on_press(Message::DumpProgramDelay)
+                        .style(theme::Button::Custom(Box::new(DangerButton))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                text(format!("On-air delay {:.1} s (rebuilds slowly after a dump)", self.program_delay_current))
+                    .size(12)
+                    .style(color_muted()),
+                row![
+                    text(format!("FM align {:.0} ms", self.diversity_delay_ms)),
+                    slider(0.0..=MAX_DIVERSITY_DELAY_MS, self.diversity_delay_ms, Message::DiversityDelayChanged)
+                        .step(1.0)
+                        .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    button("-10").on_press(Message::DiversityDelayNudge(-10.0)).style(theme::Button::Custom(Box::new(GhostButton))),
+                    button("-1").on_press(Message::DiversityDelayNudge(-1.0)).style(theme::Button::Custom(Box::new(GhostButton))),
+                    button("+1").on_press(Message::DiversityDelayNudge(1.0)).style(theme::Button::Custom(Box::new(GhostButton))),
+                    button("+10").on_press(Message::DiversityDelayNudge(10.0)).style(theme::Button::Custom(Box::new(GhostButton))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    checkbox("Restart the engine after a fault", self.supervisor_settings.enabled, Message::SupervisorToggled),
+                    text(format!("up to {} times", self.supervisor_settings.max_restarts)),
+                    slider(1..=20, self.supervisor_settings.max_restarts, Message::SupervisorMaxRestartsChanged)
+                        .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    checkbox("Start streaming on launch", self.autostart.enabled, Message::AutostartToggled),
+                    pick_list(preset_names.clone(), self.autostart.preset.clone(), Message::AutostartPresetSelected)
+                        .placeholder("Launch settings"),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                text("Loads the preset, waits for its devices and retries until streaming; Stop cancels. --autostart [preset] does the same for one launch.")
+                    .size(12)
+                    .style(color_muted()),
+            ],
+            )
+        };
+
+        let device_card = || {
+            card(
+                "Devices",
+                column![
+                    row![
+                        text("Input:"),
+                        pick_list(self.input_devices.clone(), self.selected_input.clone(), Message::InputSelected),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(input_hint(self.selected_input.as_deref(), &self.input_devices).unwrap_or_default())
+                        .size(12)
+                        .style(color_muted()),
+                    row![
+                        text("Output:"),
+                        pick_list(self.output_devices.clone(), self.selected_output.clone(), Message::OutputSelected),
+                        button("Refresh")
+                            .on_press(Message::RefreshDevices)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                        checkbox("Exclusive (native 192 kHz)", self.exclusive_output, Message::ExclusiveOutputToggled),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(if !self.exclusive_output {
+                        ""
+                    } else if cfg!(target_os = "macos") {
+                        "Takes the device in hog mode: other applications are locked out until the stream stops"
+                    } else if cfg!(target_os = "linux") {
+                        "Pick a hw: device; plughw, default and pulse resample or mix"
+                    } else {
+                        "Exclusive output is only built in for ALSA and CoreAudio"
+                    })
+                    .size(12)
+                    .style(color_muted()),
+                    row![
+                        text("Monitor (de-emphasized):"),
+                        pick_list(
+                            std::iter::once(MONITOR_OFF.to_string()).chain(self.output_devices.iter().cloned()).collect::<Vec<_>>(),
+                            Some(self.selected_monitor.clone().unwrap_or_else(|| MONITOR_OFF.to_string())),
+                            Message::MonitorSelected,
+                        ),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        checkbox("RTP/AES67 input (48 kHz, L24)", self.rtp_enabled, Message::RtpToggled),
+                        text_input("239.69.0.1", &self.rtp_address)
+                            .on_input(Message::RtpAddressChanged)
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text_input("5004", &self.rtp_port)
+                            .on_input(Message::RtpPortChanged)
+                            .width(Length::Fixed(70.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(match self.rtp_status {
+                        Some(s) => format!("RTP packets {}, lost {}, clock {:+} ppm", s.packets, s.lost, s.ppm),
+                        None if self.rtp_enabled => "RTP replaces the input device while enabled".to_string(),
+                        None => String::new(),
+                    })
+                    .size(12)
+                    .style(color_muted()),
+                    row![
+                        checkbox("Input is MPX (pass-through)", self.mpx_input, Message::MpxInputToggled),
+                        checkbox("Add local pilot", self.mpx_input_pilot, Message::MpxInputPilotToggled),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text_input("or replay an MPX WAV (192/228 kHz)", &self.mpx_file)
+                            .on_input(Message::MpxFileChanged)
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        checkbox("Loop", self.mpx_file_loop, Message::MpxFileLoopToggled),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(match self.input_pilot {
+                        Some(p) if p.locked => format!(
+                            "Input pilot {:.1}% at {:.2} Hz, RDS locked to it",
+                            p.level * 100.0,
+                            p.freq_hz
+                        ),
+                        Some(_) => "No pilot on the input, RDS runs from the internal clock".to_string(),
+                        None if self.mpx_input && !self.mpx_file.trim().is_empty() => {
+                            "The file's first channel replaces the input device as the finished MPX: 57 kHz is notched and local RDS is added".to_string()
+                        }
+                        None if self.mpx_input => {
+                            "Left input channel is taken as a finished MPX: stereo encoding and processing are skipped, 57 kHz is notched and local RDS is added".to_string()
+                        }
+                        None => String::new(),
+                    })
+                    .size(12)
+                    .style(color_muted()),
+                    checkbox("Backup input with automatic failover", self.failover_enabled, Message::FailoverToggled),
+                    row![
+                        text("Backup:"),
+                        pick_list(
+                            self.input_devices.iter().filter(|d| !d.starts_with(LOOPBACK_PREFIX)).cloned().collect::<Vec<_>>(),
+                            self.backup_input.clone(),
+                            Message::BackupInputSelected,
+                        ),
+                        text_input("or looped WAV file", &self.backup_file)
+                            .on_input(Message::BackupFileChanged)
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text("Silence (s):"),
+                        text_input("10", &self.failover_silence_secs)
+                            .on_input(Message::FailoverSilenceChanged)
+                            .width(Length::Fixed(70.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text("Restore after (s):"),
+                        text_input("30", &self.failover_restore_secs)
+                            .on_input(Message::FailoverRestoreChanged)
+                            .width(Length::Fixed(70.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(self.failover_label()).size(12).style(color_muted()),
+                    checkbox("Route traffic input while TA is on", self.traffic_enabled, Message::TrafficToggled),
+                    row![
+                        text("Traffic:"),
+                        pick_list(
+                            self.input_devices.iter().filter(|d| !d.starts_with(LOOPBACK_PREFIX)).cloned().collect::<Vec<_>>(),
+                            self.traffic_input.clone(),
+                            Message::TrafficInputSelected,
+                        ),
+                        text_input("or announcement WAV file", &self.traffic_file)
+                            .on_input(Message::TrafficFileChanged)
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text("Crossfade (s):"),
+                        text_input("0.5", &self.traffic_crossfade_secs)
+                            .on_input(Message::TrafficCrossfadeChanged)
+                            .width(Length::Fixed(70.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text("Raising TA switches the program to this source, lowering it switches back; a WAV restarts each time.")
+                        .size(12)
+                        .style(color_muted()),
+                ],
+            )
+        };
+
+        let contacts_card = || {
+            let rows = self
+                .contact_mappings
+                .iter()
+                .enumerate()
+                .map(|(i, mapping)| {
+                    let preset_input: Element<'_, Message> = if mapping.action == ContactAction::Preset {
+                        text_input("Preset name", &mapping.preset)
+                            .on_input(move |v| Message::ContactPresetChanged(i, v))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput)))
+                            .into()
+                    } else {
+                        text(" ").into()
+                    };
+                    row![
+                        pick_list(contact_line_items(), Some(mapping.line), move |v| Message::ContactLineChanged(i, v)),
+                        pick_list(contact_action_items(), Some(mapping.action), move |v| Message::ContactActionChanged(i, v)),
+                        preset_input,
+                        checkbox("Invert", mapping.invert, move |v| Message::ContactInvertChanged(i, v)),
+                        button("Remove")
+                            .on_press(Message::ContactRemoveMapping(i))
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            let connect = if self.contact_monitor.is_some() {
+                button("Disconnect")
+                    .on_press(Message::ContactDisconnect)
+                    .style(theme::Button::Custom(Box::new(DangerButton)))
+            } else {
+                button("Connect")
+                    .on_press(Message::ContactConnect)
+                    .style(theme::Button::Custom(Box::new(PrimaryButton)))
+            };
+            card(
+                "Contact Closures",
+                column![
+                    text("Serial modem lines (CTS/DSR/DCD/RI) from a COM port or USB relay/GPIO board. DTR and RTS are held high to power dry contacts.").style(color_muted()),
+                    row![
+                        text("Port:"),
+                        pick_list(self.contact_ports.clone(), self.contact_port.clone(), Message::ContactPortSelected),
+                        button("Refresh")
+                            .on_press(Message::ContactRefreshPorts)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                        connect,
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    Column::with_children(rows).spacing(6),
+                    row![
+                        button("Add mapping")
+                            .on_press(Message::ContactAddMapping)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                        checkbox("TX mute", self.tx_mute, Message::TxMuteChanged),
+                        text(if self.ta { "TA on" } else { "TA off" }).style(color_muted()),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                ],
+            )
+        };
+
+        let status_server_card = || {
+            card(
+                "Remote Status (HTTP)",
+                column![
+                    row![
+                        checkbox("Enable", self.status_server.is_some(), Message::StatusServerEnabled),
+                        text("Port:"),
+                        text_input("8089", &self.status_server_port).on_input(Message::StatusServerPortChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        checkbox("Allow LAN", self.status_server_lan, Message::StatusServerLanChanged),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text("GET /live /ta /mute /ps /rt (text) and /live.png /ta.png /mute.png (badges).").size(12).style(color_muted()),
+                    text("POST /live|/ta|/mute followed by /on, /off or /toggle.").size(12).style(color_muted()),
+                    text("POST /value/<name>?text=... sets a %name% PS/RT variable.").size(12).style(color_muted()),
+                ],
+            )
+        };
+
+        let rds_feed_card = || {
+            card(
+                "RDS Data Feed",
+                column![
+                    row![
+                        checkbox("Enable", self.rds_feed_enabled, Message::RdsFeedToggled),
+                        pick_list(vec![RdsFeedTransport::Udp, RdsFeedTransport::Tcp], Some(self.rds_feed_transport), Message::RdsFeedTransportSelected),
+                        pick_list(vec![RdsFeedFormat::Groups, RdsFeedFormat::Bits], Some(self.rds_feed_format), Message::RdsFeedFormatSelected),
+                        text_input("127.0.0.1:9100", &self.rds_feed_address).on_input(Message::RdsFeedAddressChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(match self.rds_feed_transport {
+                        RdsFeedTransport::Udp => "Sends to host:port. Applies on the next Start.",
+                        RdsFeedTransport::Tcp => "Listens on address:port (0.0.0.0 for LAN). Applies on the next Start.",
+                    })
+                    .size(12)
+                    .style(color_muted()),
+                    text("Frames: \"RD\", version, format, u32 group sequence, u16 count, then 8 bytes (blocks A-D) or 13 bytes (104 bits with check words) per group.").size(12).style(color_muted()),
+                ]
+                .spacing(10),
+            )
+        };
+
+        let silence_card = || {
+            let state = if !self.silence_enabled {
+                "Alarm disabled".to_string()
+            } else if self.silence_alarm_active() {
+                format!(
+                    "ALARM: {}{}",
+                    if self.program_silence.is_alarmed() { "program audio silent " } else { "" },
+                    if self.mpx_silence.is_alarmed() { "MPX output silent" } else { "" }
+                )
+            } else {
+                "Monitoring program audio and MPX output".to_string()
+            };
+            card(
+                "Silence Alarm",
+                column![
+                    checkbox("Dead-air alarm", self.silence_enabled, Message::SilenceAlarmToggled),
+                    row![
+                        text(format!("Threshold {:.0} dBFS", self.silence_threshold_db)),
+                        slider(-80.0..=-20.0, self.silence_threshold_db, Message::SilenceThresholdChanged)
+                            .step(1.0)
+                            .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text(format!("After {:.0} s", self.silence_hold_secs)),
+                        slider(1.0..=120.0, self.silence_hold_secs, Message::SilenceHoldChanged)
+                            .step(1.0)
+                            .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text("Webhook:"),
+                        text_input("http://host:port/path", &self.silence_webhook)
+                            .on_input(Message::SilenceWebhookChanged)
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(state).size(12).style(if self.silence_alarm_active() { color_danger() } else { color_muted() }),
+                ],
+            )
+        };
+
+        let alerts_card = || {
+            card(
+                "Alerts",
+                column![
+                    checkbox("Telegram alerts", self.alerts.enabled, Message::AlertsToggled),
+                    row![
+                        text("Bot token:"),
+                        text_input("123456:ABC...", &self.alerts.telegram_token)
+                            .on_input(Message::TelegramTokenChanged)
+                            .password()
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text("Chat ID:"),
+                        text_input("-1001234567890", &self.alerts.telegram_chat_id)
+                            .on_input(Message::TelegramChatChanged)
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        button("Send test")
+                            .on_press(Message::AlertTest)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        checkbox("Dead air", self.alerts.dead_air, Message::AlertDeadAirToggled),
+                        checkbox("Device lost", self.alerts.device_lost, Message::AlertDeviceLostToggled),
+                        checkbox("Xrun storm", self.alerts.xrun_storm, Message::AlertXrunStormToggled),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                ],
+            )
+        };
+
+        let sync_card = || {
+            let connect = if self.sync_link.is_some() {
+                button("Disconnect")
+                    .on_press(Message::SyncDisconnect)
+                    .style(theme::Button::Custom(Box::new(DangerButton)))
+            } else {
+                button("Connect")
+                    .on_press(Message::SyncConnect)
+                    .style(theme::Button::Custom(Box::new(PrimaryButton)))
+            };
+            let action = match self.sync_role {
+                SyncRole::Main => button("Hand over")
+                    .on_press(Message::SyncHandOver)
+                    .style(theme::Button::Custom(Box::new(GhostButton))),
+                _ => button("Take over")
+                    .on_press(Message::SyncTakeOver)
+                    .style(theme::Button::Custom(Box::new(GhostButton))),
+            };
+            card(
+                "Main/Standby Sync",
+                column![
+                    row![
+                        text("Role:"),
+                        pick_list(vec![SyncRole::Off, SyncRole::Main, SyncRole::Standby], Some(self.sync_role), Message::SyncRoleSelected),
+                        text("Peer:"),
+                        text_input("192.168.1.20:8090", &self.sync_peer).on_input(Message::SyncPeerChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text("Port:"),
+                        text_input("8090", &self.sync_port).on_input(Message::SyncPortChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text("Heartbeat timeout (s):"),
+                        text_input("5", &self.sync_timeout_secs).on_input(Message::SyncTimeoutChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        connect,
+                        action,
+                        checkbox("Aligned start", self.sync_aligned_start, Message::SyncAlignedStartToggled),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(self.sync_label()).size(12).style(color_muted()),
+                ],
+            )
+        };
+
+        let dsp_warning = if self.dsp_load_avg > DSP_LOAD_WARN {
+            "DSP load is high: close other audio apps, raise the device buffer size or disable the compressor/limiter lookahead."
+        } else if self.dsp_load_worst > 1.0 {
+            "A callback missed its deadline: check power saving / CPU frequency scaling and use a larger device buffer."
+        } else {
+            ""
+        };
+        let health_card = card(
+            "Device Health",
+            column![
+                row![
+                    text(format!("XRuns {}", self.xrun_count)).style(color_muted()),
+                    text(format!("Buffer {:.0}%", (self.buffer_fill * 100.0).clamp(0.0, 100.0))).style(color_muted()),
+                    text(format!("Latency {:.1} ms", self.latency_ms)).style(color_muted()),
+                    text(format!("DSP {:.0}% (worst {:.0}%)", self.dsp_load_avg * 100.0, self.dsp_load_worst * 100.0))
+                        .style(if dsp_warning.is_empty() { color_muted() } else { color_danger() }),
+                    text(format!("Drift {:+.0} ppm", self.drift_ppm)).style(color_muted()),
+                    text(match &self.session {
+                        Some(session) => {
+                            let secs = session.uptime_secs();
+                            format!("Uptime {}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+                        }
+                        None => "Uptime -".to_string(),
+                    })
+                    .style(color_muted()),
+                ]
+                .spacing(14)
+                .align_items(Alignment::Center),
+                text(dsp_warning).size(12).style(color_danger()),
+            ],
+        );
+
+        let charset_warning = {
+            let [ps, rt, ps_scroll, rt_scroll] = self.on_air_texts();
+            let alternates = self.ps_alternates().concat();
+            let mut missing: Vec<char> = Vec::new();
+            for field in [
+                ps.as_str(),
+                rt.as_str(),
+                ps_scroll.as_str(),
+                rt_scroll.as_str(),
+                alternates.as_str(),
+            ] {
+                let field = match &self.translit {
+                    Some(table) => table.transliterate(field, self.code_table),
+                    None => field.to_string(),
+                };
+                for ch in unmappable_chars(&field, self.code_table) {
+                    if !missing.contains(&ch) {
+                        missing.push(ch);
+                    }
+                }
+            }
+            if missing.is_empty() {
+                None
+            } else {
+                Some(format!(
+                    "Not in {} (sent as spaces): {}",
+                    self.code_table,
+                    missing.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" ")
+                ))
+            }
+        };
+
+        let station_issues = match parse_pi(&self.pi_hex) {
+            Ok(pi) => station_warnings(&StationCheck {
+                pi,
+                ecc: parse_hex_byte(&self.ecc_hex),
+                pty: self.pty_selected.code,
+                tp: self.tp,
+                ta: self.ta,
+                alarm: self.alarm,
+            }),
+            Err(e) => vec![e],
+        };
+
+        let station_card = || {
+            let issues = station_issues
+                .iter()
+                .map(|w| text(w).style(color_accent_warm()).into())
+                .collect::<Vec<Element<'_, Message>>>();
+            card(
+            "Station",
+            column![
+                row![
+                    with_help(text("PS:"), HelpTopic::Ps),
+                    text_input("BOUZIDFM", &self.ps).on_input(Message::PsChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    with_help(text("RT:"), HelpTopic::Rt),
+                    text_input("BOUZIDFM Sidi Bouzid 98.0 MHz", &self.rt).on_input(Message::RtChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                text(self.macro_preview()).size(12).style(color_muted()),
+                row![
+                    with_help(text("PI (hex):"), HelpTopic::Pi),
+                    text_input("7200", &self.pi_hex).on_input(Message::PiChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    with_help(checkbox("TP", self.tp, Message::TpChanged), HelpTopic::Tp),
+                    with_help(checkbox("TA", self.ta, Message::TaChanged), HelpTopic::Ta),
+                    with_help(checkbox("Music (MS)", self.ms, Message::MsChanged), HelpTopic::Ms),
+                    with_help(checkbox("CT", self.ct_enabled, Message::CtChanged), HelpTopic::Ct),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    with_help(text("PTY:"), HelpTopic::Pty),
+                    pick_list(self.pty_items.clone(), Some(self.pty_selected.clone()), Message::PtyChanged),
+                    checkbox("RT A/B", self.ab_flag, Message::AbChanged),
+                    checkbox("Auto A/B", self.ab_auto, Message::AbAutoChanged),
+                    checkbox("Alarm broadcast", self.alarm, Message::AlarmChanged),
+                    text("Charset:"),
+                    pick_list(self.code_table_items.clone(), Some(self.code_table), Message::CodeTableChanged),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                Column::with_children(issues).spacing(4),
+                row![
+                    checkbox("Transliterate", self.transliterate, Message::TransliterateChanged),
+                    text_input("Custom table (optional)", &self.translit_table_path).on_input(Message::TranslitPathChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    button("Load")
+                        .on_press(Message::ReloadTranslitTable)
+                        .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                if let Some(ref warning) = charset_warning {
+                    text(warning).style(color_accent_warm())
+                } else {
+                    text(" ").style(color_muted())
+                },
+            ],
+            )
+        };
+
+        let pi_preview = build_pi_from_parts(&self.pi_country_hex, &self.pi_area_hex, &self.pi_program_hex, &self.ecc_hex)
+            .map(|pi| format!("{:04X}", pi))
+            .unwrap_or_else(|_| "—".to_string());
+
+        let compliance_card = || {
+            let log = self
+                .compliance_log
+                .iter()
+                .rev()
+                .take(6)
+                .map(|(time, change)| text(format!("{}  {}", time.format("%H:%M:%S"), change)).size(12).style(color_muted()).into())
+                .collect::<Vec<Element<'_, Message>>>();
+            card(
+                "Compliance",
+                column![
+                    checkbox("Strict EN 50067 compliance", self.compliance.enabled, Message::ComplianceToggled),
+                    text(format!(
+                        "Keeps PS static (no scrolling or alternating names), CT on once a minute, at least {:.0} 0A groups a second and RDS injection at or under {:.0}% (measured while streaming). Settings that break these are changed back and listed here.",
+                        compliance::MIN_0A_PER_SEC,
+                        compliance::MAX_RDS_INJECTION * 100.0
+                    ))
+                    .size(12)
+                    .style(color_muted()),
+                    Column::with_children(log).spacing(4),
+                ],
+            )
+        };
+
+        let rds_identity_card = || {
+            card(
+                "Identity + DI",
+                column![
+                    text("PI (Program Identification) should come from your regulator. Use this helper to format a valid PI from parts.").style(color_muted()),
+                    text("Pick your country to fill the PI country code and ECC, or choose Custom and enter your assigned values.").style(color_muted()),
+                    row![
+                        text("Country preset:"),
+                        pick_list(self.country_items.clone(), Some(self.country_selected.clone()), Message::CountrySelected),
+                        text("PI builder:"),
+                        text_input("7", &self.pi_country_hex).on_input(Message::CountryCodeChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text_input("2", &self.pi_area_hex).on_input(Message::AreaCodeChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text_input("00", &self.pi_program_hex).on_input(Message::ProgramRefChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        button("Apply PI")
+                            .on_press(Message::ApplyPiFromParts)
+                            .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                        button("Copy PI")
+                            .on_press(Message::CopyPi)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                        button("Random PI")
+                            .on_press(Message::GenerateRandomPi)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text(format!("PI preview: {}", pi_preview)).style(color_muted()),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text("Random PI is for testing only. For production, use the code assigned by your regulator.").style(color_muted()),
+                    row![
+                        with_help(text("ECC (hex):"), HelpTopic::EccLic),
+                        text_input("E2", &self.ecc_hex).on_input(Message::EccChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text("LIC (hex):"),
+                        text_input("", &self.lic_hex).on_input(Message::LicChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text("ECC and LIC are sent in 1A groups. Leave LIC empty if unknown.").style(color_muted()),
+                        with_help(text("DI:"), HelpTopic::Di),
+                        checkbox("Stereo", self.di_stereo, Message::DiStereoChanged),
+                        checkbox("Artificial head", self.di_artificial, Message::DiArtificialChanged),
+                        checkbox("Compressed", self.di_compressed, Message::DiCompressedChanged),
+                        checkbox("Dynamic PTY", self.di_dynamic, Message::DiDynamicChanged),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                ],
+            )
+        };
+
+        let rds_schedule_card = || card(
+            "Group Scheduling",
+            column![
+                row![
+                    text("Mix 0A/2A/4A:"),
+                    text_input("4", &self.group_0a).on_input(Message::Group0aChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text_input("1", &self.group_2a).on_input(Message::Group2aChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text_input("0", &self.group_4a).on_input(Message::Group4aChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text("CT interval (groups):"),
+                    text_input("0", &self.ct_interval_groups).on_input(Message::CtIntervalGroupsChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    button("Apply")
+                        .on_press(Message::ApplyGroupMix)
+                        .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("Fast tuning (15B):"),
+                    pick_list(self.fast_tuning_items.clone(), Some(self.fast_tuning), Message::FastTuningChanged),
+                    text("a 15B after every 0A doubles the TA/PTY/MS refresh").size(12).style(color_muted()),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("Alternate PS:"),
+                    text_input("ALT1|ALT2", &self.ps_alt_list_text).on_input(Message::PsAltListChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text("Interval (groups):"),
+                    text_input("0", &self.ps_alt_interval).on_input(Message::PsAltIntervalChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    button("Apply PS")
+                        .on_press(Message::ApplyPsAlternates)
+                        .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            ],
+        );
+
+        let (af_freqs, af_regional, mut af_warnings) = validate_af_rows(&self.af_rows);
+        let af_tuned = self.frequency_mhz.trim().parse::<f32>().ok();
+        if af_tuned_missing(&af_regional, af_tuned) {
+            af_warnings.push("Regional AFs need a valid tuned frequency; until one is set they are sent as a plain list.".to_string());
+        }
+        let af_stream = af_code_stream(&af_freqs, &af_regional, af_tuned);
+        let mut af_bytes = af_stream.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ");
+        if af_freqs.is_empty() && af_regional.is_empty() {
+            af_bytes.push_str(" (no AF)");
+        }
+        let af_receiver = {
+            let mut all: Vec<(f32, bool)> = af_freqs
+                .iter()
+                .map(|&f| (f, false))
+                .chain(af_regional.iter().map(|&f| (f, true)))
+                .collect();
+            all.sort_by(|a, b| a.0.total_cmp(&b.0));
+            all.truncate(AF_MAX);
+            all.iter()
+                .map(|&(f, regional)| {
+                    let tuned = af_tuned.and_then(af_code) == af_code(f);
+                    format!(
+                        "{:.1}{}",
+                        f,
+                        if tuned { " (tuned)" } else if regional { " (regional)" } else { "" }
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" · ")
+        };
+        let af_method = if af_regional.is_empty() || af_tuned.and_then(af_code).is_none() { "A" } else { "B" };
+
+        let af_card = || {
+            let rows = self
+                .af_rows
+                .iter()
+                .enumerate()
+                .map(|(i, row)| {
+                    row![
+                        text(format!("AF {}:", i + 1)),
+                        text_input("98.0", &row.freq)
+                            .on_input(move |v| Message::AfRowFreqChanged(i, v))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        checkbox("Regional", row.regional, move |v| Message::AfRowRegionalChanged(i, v)),
+                        button("Remove")
+                            .on_press(Message::AfRemoveRow(i))
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            let add_button = button("Add AF").style(theme::Button::Custom(Box::new(GhostButton)));
+            let add_button = if self.af_rows.len() < AF_MAX {
+                add_button.on_press(Message::AfAddRow)
+            } else {
+                add_button
+            };
+            let warnings = af_warnings
+                .iter()
+                .map(|w| text(w).style(color_accent_warm()).into())
+                .collect::<Vec<Element<'_, Message>>>();
+            card(
+            "AF List",
+            column![
+                row![
+                    with_help(text("Tuned freq (MHz):"), HelpTopic::Af),
+                    text_input("98.0", &self.frequency_mhz).on_input(Message::FrequencyChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                Column::with_children(rows).spacing(6),
+                row![
+                    add_button,
+                    text(format!("{} / {}", self.af_rows.len(), AF_MAX)).style(color_muted()),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("Generate from:"),
+                    text_input("Base", &self.af_base).on_input(Message::AfBaseChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text_input("Spacing", &self.af_spacing).on_input(Message::AfSpacingChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text_input("Count", &self.af_count).on_input(Message::AfCountChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    button("Generate")
+                        .on_press(Message::AfGenerate)
+                        .style(theme::Button::Custom(Box::new(GhostButton))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                Column::with_children(warnings).spacing(4),
+                text(format!("Method {} bytes: {}", af_method, af_bytes)).size(12),
+                text(format!("Receiver list: {}", if af_receiver.is_empty() { "—" } else { af_receiver.as_str() })).size(12).style(color_muted()),
+                text("Every AF must carry this station's PI; regional variants may differ only in the area nibble.").size(12).style(color_muted()),
+            ],
+            )
+        };
+
+        let network_card = || {
+            let (plans, warnings) = plan_network(&self.network_sites);
+            let rows = self
+                .network_sites
+                .iter()
+                .enumerate()
+                .map(|(i, site)| {
+                    let plan = plans.iter().find(|plan| plan.name == site.name.trim());
+                    column![
+                        row![
+                            text_input("Name", &site.name)
+                                .on_input(move |v| Message::NetworkNameChanged(i, v))
+                                .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                            text_input("98.0", &site.freq_mhz)
+                                .on_input(move |v| Message::NetworkFreqChanged(i, v))
+                                .width(Length::Fixed(70.0))
+                                .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                            text_input("PI", &site.pi_hex)
+                                .on_input(move |v| Message::NetworkPiChanged(i, v))
+                                .width(Length::Fixed(70.0))
+                                .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                            text_input("Neighbours (empty = all)", &site.neighbours)
+                                .on_input(move |v| Message::NetworkNeighboursChanged(i, v))
+                                .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                            button("Use")
+                                .on_press(Message::NetworkUseSite(i))
+                                .style(theme::Button::Custom(Box::new(GhostButton))),
+                            button("Remove")
+                                .on_press(Message::NetworkRemoveSite(i))
+                                .style(theme::Button::Custom(Box::new(GhostButton))),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        text(match plan {
+                            Some(plan) if plan.af_text().is_empty() => "AF: none".to_string(),
+                            Some(plan) => format!("AF: {}", plan.af_text()),
+                            None => "AF: —".to_string(),
+                        })
+                        .size(12)
+                        .style(color_muted()),
+                    ]
+                    .spacing(4)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            let warnings = warnings
+                .iter()
+                .map(|w| text(w).style(color_accent_warm()).into())
+                .collect::<Vec<Element<'_, Message>>>();
+            card(
+                "Transmitter Network",
+                column![
+                    Column::with_children(rows).spacing(10),
+                    row![
+                        button("Add site")
+                            .on_press(Message::NetworkAddSite)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                        button("Export sites")
+                            .on_press(Message::NetworkExport)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10),
+                    Column::with_children(warnings).spacing(4),
+                    text("Sites with the same PI are AFs of each other; PIs differing only in a regional area nibble (4-F) are sent as regional variants. Export writes a presets entry per site and network_batch.toml for --batch.")
+                        .size(12)
+                        .style(color_muted()),
+                ]
+                .spacing(10),
+            )
+        };
+
+        let history_card = || {
+            let recent = self.rds_history
+                .iter()
+                .rev()
+                .take(8)
+                .map(|entry| {
+                    text(format!(
+                        "{}  {}  [{}]  {}",
+                        entry.timestamp.format("%H:%M:%S"),
+                        entry.field,
+                        entry.source,
+                        entry.text
+                    ))
+                    .size(12)
+                    .style(color_muted())
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            card(
+                "On-Air History",
+                column![
+                    row![
+                        text(format!("{} changes", self.rds_history.len())),
+                        text_input("rds_history.csv", &self.rds_history_path).on_input(Message::RdsHistoryPathChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        button("Export CSV")
+                            .on_press(Message::ExportRdsHistory)
+                            .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                        button("Clear")
+                            .on_press(Message::ClearRdsHistory)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    Column::with_children(recent).spacing(4),
+                ],
+            )
+        };
+
+        let data_providers_card = || {
+            let rows = self
+                .provider_feeds
+                .as_ref()
+                .map(|feeds| feeds.status())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|status| {
+                    let state = match (&status.error, status.updated) {
+                        (Some(e), _) => format!("error: {}", e),
+                        (None, Some(at)) => format!("updated {}", at.format("%H:%M:%S")),
+                        (None, None) => "waiting for first fetch".to_string(),
+                    };
+                    text(format!("{}: {}", status.name, state)).size(12).style(color_muted()).into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            card(
+                "Data Providers",
+                column![
+                    text(format!(
+                        "HTTP JSON sources from {}; each field maps a JSONPath to a %variable% for PS/RT.",
+                        config_path(PROVIDERS_FILE).display()
+                    ))
+                    .size(12)
+                    .style(color_muted()),
+                    Column::with_children(rows).spacing(4),
+                    row![
+                        button("Reload")
+                            .on_press(Message::ReloadDataProviders)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                        button("Add OpenWeather example")
+                            .on_press(Message::AddOpenWeatherExample)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                ],
+            )
+        };
+
+        let song_history_card = || {
+            let titles = self
+                .song_history
+                .titles()
+                .enumerate()
+                .map(|(i, title)| {
+                    let label = if i == 0 { "Now".to_string() } else { format!("-{}", i) };
+                    text(format!("{}  {}", label, title)).size(12).style(color_muted()).into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            card(
+                "Song History",
+                column![
+                    text("Titles come from the %song% variable (status server or a data provider).")
+                        .size(12)
+                        .style(color_muted()),
+                    row![
+                        checkbox("Rotate RT", self.previously_rt, Message::PreviouslyRtToggled),
+                        text_input(DEFAULT_PREVIOUSLY_RT, &self.previously_rt_text)
+                            .on_input(Message::PreviouslyRtTextChanged)
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text("Every (s):"),
+                        text_input("30", &self.previously_interval)
+                            .on_input(Message::PreviouslyIntervalChanged)
+                            .width(Length::Fixed(60.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text("Keep:"),
+                        text_input("5", &self.song_history_len)
+                            .on_input(Message::SongHistoryLenChanged)
+                            .width(Length::Fixed(60.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text("titles"),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text("%prev1%, %prev2%, ... and %previous% (all, joined) work in any PS/RT text.")
+                        .size(12)
+                        .style(color_muted()),
+                    Column::with_children(titles).spacing(4),
+                ],
+            )
+        };
+
+        let metadata_filter_card = || {
+            card(
+                "Metadata Filter",
+                column![
+                    text("Applied to pushed and provider values (%song%, %artist%, ...) before they reach PS/RT.")
+                        .size(12)
+                        .style(color_muted()),
+                    row![
+                        text("Banned words:"),
+                        text_input("word, another", &self.banned_words_text)
+                            .on_input(Message::BannedWordsChanged)
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        checkbox("Drop whole text", self.metadata_filter.drop_banned, Message::DropBannedToggled),
+                        checkbox("Strip (Official Video) etc.", self.metadata_filter.strip_brackets, Message::StripBracketsToggled),
+                    ]
+                    .spacing(16)
+                    .align_items(Alignment::Center),
+                    row![
+                        checkbox("Title Case ALL CAPS", self.metadata_filter.title_case, Message::TitleCaseToggled),
+                        text("Max length:"),
+                        text_input("off", &self.metadata_max_len)
+                            .on_input(Message::MetadataMaxLenChanged)
+                            .width(Length::Fixed(60.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text("Banned words are masked (f***) unless the whole text is dropped.")
+                        .size(12)
+                        .style(color_muted()),
+                ],
+            )
+        };
+
+        let group_cycle = group_cycle_for_mix(
+            self.group_0a.trim().parse::<usize>().unwrap_or(4),
+            self.group_2a.trim().parse::<usize>().unwrap_or(1),
+            self.group_4a.trim().parse::<usize>().unwrap_or(0),
+        );
+        let (min_ps_dwell, min_rt_interval) = rate_limits_for_cycle(&group_cycle);
+
+        let (group_stats, group_stats_secs, group_stats_live) = match (self.group_stats_samples.front(), self.group_stats_samples.back()) {
+            (Some(first), Some(last)) if last.0.duration_since(first.0).as_secs_f32() >= 1.0 => {
+                (last.1.since(&first.1), last.0.duration_since(first.0).as_secs_f32(), true)
+            }
+            _ => (GroupStats::predicted(&group_cycle, 60.0), 60.0, false),
+        };
+
+        let group_stats_card = || {
+            let total = group_stats.total().max(1) as f32;
+            let per_minute = 60.0 / group_stats_secs;
+            let lines = group_stats
+                .entries()
+                .into_iter()
+                .map(|(label, count)| {
+                    text(format!(
+                        "{}: {:.0}/min ({:.1}%)",
+                        label,
+                        count as f32 * per_minute,
+                        count as f32 * 100.0 / total
+                    ))
+                    .size(12)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            let (ps_refresh, rt_refresh) = group_stats.refresh_times(group_stats_secs);
+            let fmt_refresh = |v: Option<f32>| v.map(|s| format!("{:.2} s", s)).unwrap_or_else(|| "never".to_string());
+            card(
+                "Group Statistics",
+                column![
+                    text(if group_stats_live {
+                        format!("Measured over the last {:.0} s", group_stats_secs)
+                    } else {
+                        "Predicted from the group mix (start streaming for live counts)".to_string()
+                    })
+                    .style(color_muted()),
+                    Column::with_children(lines).spacing(4),
+                    text(format!(
+                        "Full PS every {} · full RT every {}",
+                        fmt_refresh(ps_refresh),
+                        fmt_refresh(rt_refresh)
+                    )),
+                ],
+            )
+        };
+
+        // What a receiver made of the PS: the on-air history while streaming,
+        // otherwise the scroll settings played forward.
+        let receiver_timeline = {
+            let window = RECEIVER_WINDOW_SECS;
+            let (mut changes, segments_per_sec) = if group_stats_live {
+                let now = chrono::Local::now();
+                let mut changes = Vec::new();
+                for entry in self.rds_history.iter().filter(|e| e.field == RdsField::Ps) {
+                    let at_secs = window - (now - entry.timestamp).num_milliseconds() as f32 / 1000.0;
+                    // Only the last change before the window matters.
+                    if at_secs < 0.0 {
+                        changes.clear();
+                    }
+                    changes.push(PsChange { at_secs, text: entry.text.clone() });
+                }
+                (changes, group_stats.count(0, false) as f32 / group_stats_secs)
+            } else {
+                let scroll = if self.ps_scroll_enabled { self.expand_text(&self.ps_scroll_text) } else { String::new() };
+                let changes = predicted_changes(
+                    &scroll,
+                    self.ps_scroll_mode,
+                    self.ps_scroll_cps,
+                    self.ps_page_dwell,
+                    self.ps_page_center,
+                    self.rate_guard.then_some(min_ps_dwell),
+                    window,
+                );
+                (changes, ps_segments_per_sec(&group_cycle))
+            };
+            if changes.is_empty() {
+                changes.push(PsChange { at_secs: -window, text: self.expand_text(&self.ps) });
+            }
+            ReceiverTimeline::simulate(&changes, segments_per_sec, window)
+        };
+
+        let receiver_card = || {
+            let warning = receiver_timeline.warning();
+            card(
+                "Receiver View",
+                column![
+                    text(if group_stats_live {
+                        format!("What a receiver displayed over the last {:.0} s", RECEIVER_WINDOW_SECS)
+                    } else {
+                        "Predicted from the scroll settings (start streaming to follow the air)".to_string()
+                    })
+                    .style(color_muted()),
+                    Canvas::new(ReceiverView { timeline: receiver_timeline.clone() })
+                        .width(Length::Fill)
+                        .height(56),
+                    text(format!(
+                        "{} · {:.1} PS segments/s, a full name every {:.2} s",
+                        receiver_timeline.summary(),
+                        receiver_timeline.segments_per_sec,
+                        4.0 / receiver_timeline.segments_per_sec.max(0.01)
+                    ))
+                    .size(12),
+                    text(warning).size(12).style(color_danger()),
+                    text("Modelled on a receiver that waits for all four PS segments of a name before showing it; orange names are up too briefly to read.")
+                        .size(12)
+                        .style(color_muted()),
+                ],
+            )
+        };
+
+        let scrolling_card = || card(
+            "Scrolling",
+            column![
+                row![
+                    checkbox("PS scroll", self.ps_scroll_enabled, Message::PsScrollEnabled),
+                    text_input("BOUZIDFM", &self.ps_scroll_text).on_input(Message::PsScrollTextChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text(format!("{:.1} cps", self.ps_scroll_cps)),
+                    slider(0.5..=10.0, self.ps_scroll_cps, Message::PsScrollSpeedChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("PS mode:"),
+                    pick_list(self.ps_scroll_mode_items.clone(), Some(self.ps_scroll_mode), Message::PsScrollModeChanged),
+                    text(format!("Dwell {:.1}s", self.ps_page_dwell)),
+                    slider(1.0..=10.0, self.ps_page_dwell, Message::PsPageDwellChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    checkbox("Center", self.ps_page_center, Message::PsPageCenterChanged),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    checkbox("RT scroll", self.rt_scroll_enabled, Message::RtScrollEnabled),
+                    text_input("BOUZIDFM Sidi Bouzid 98.0 MHz", &self.rt_scroll_text).on_input(Message::RtScrollTextChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text(format!("{:.1} cps", self.rt_scroll_cps)),
+                    slider(0.5..=10.0, self.rt_scroll_cps, Message::RtScrollSpeedChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("Long RT:"),
+                    pick_list(self.rt_overflow_items.clone(), Some(self.rt_overflow), Message::RtOverflowChanged),
+                    text_input(DEFAULT_RT_ELLIPSIS, &self.rt_ellipsis)
+                        .on_input(Message::RtEllipsisChanged)
+                        .width(Length::Fixed(60.0))
+                        .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text(format!("Part {:.0}s", self.rt_part_secs)),
+                    slider(2.0..=30.0, self.rt_part_secs, Message::RtPartSecsChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    checkbox("Rate guard", self.rate_guard, Message::RateGuardChanged),
+                    text(format!("PS ≥ {:.1}s per page, RT ≥ {:.1}s between changes", min_ps_dwell, min_rt_interval)).style(color_muted()),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            ],
+        );
+
+        let pty_schedule_card = || {
+            let pty_item = |code: u8| self.pty_items.iter().find(|item| item.code == code).cloned();
+            let rows = self
+                .pty_rules
+                .iter()
+                .enumerate()
+                .map(|(i, rule)| {
+                    row![
+                        pick_list(PtyDays::ALL.to_vec(), Some(rule.days), move |v| Message::PtyRuleDaysChanged(i, v)),
+                        text_input("00:00", &rule.start)
+                            .on_input(move |v| Message::PtyRuleStartChanged(i, v))
+                            .width(Length::Fixed(70.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        text("to"),
+                        text_input("00:05", &rule.end)
+                            .on_input(move |v| Message::PtyRuleEndChanged(i, v))
+                            .width(Length::Fixed(70.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        checkbox("Hourly", rule.every_hour, move |v| Message::PtyRuleEveryHourToggled(i, v)),
+                        pick_list(self.pty_items.clone(), pty_item(rule.pty), move |v| Message::PtyRulePtyChanged(i, v)),
+                        button("Remove")
+                            .on_press(Message::PtyRuleRemove(i))
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            let now = match (self.pty_schedule_running(), self.pty_scheduled.and_then(pty_item)) {
+                (false, _) if !self.pty_rules.is_empty() => format!("Manual: {}", self.pty_selected.label),
+                (false, _) => "No rules; the station PTY stays on air".to_string(),
+                (true, Some(item)) => format!("Now: {} (scheduled, DI dynamic PTY set)", item.label),
+                (true, None) => format!("Now: {} (station PTY, DI dynamic PTY set)", self.pty_selected.label),
+            };
+            card(
+                "PTY Schedule",
+                column![
+                    Column::with_children(rows).spacing(10),
+                    row![
+                        button("Add rule")
+                            .on_press(Message::PtyRuleAdd)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                        checkbox("Manual override", self.pty_override, Message::PtyOverrideToggled),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(now).size(12).style(color_muted()),
+                    text("The first matching rule wins (local time, HH:MM); Hourly compares minutes only.")
+                        .size(12)
+                        .style(color_muted()),
+                ]
+                .spacing(10),
+            )
+        };
+
+        let automation_card = || {
+            let rows = self
+                .automation_rules
+                .iter()
+                .enumerate()
+                .map(|(i, rule)| {
+                    column![
+                        row![
+                            pick_list(AutomationParam::ALL.to_vec(), Some(rule.param), move |v| Message::AutomationParamChanged(i, v)),
+                            text_input("22:00", &rule.start)
+                                .on_input(move |v| Message::AutomationStartChanged(i, v))
+                                .width(Length::Fixed(70.0))
+                                .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                            text("to"),
+                            text_input("06:00", &rule.end)
+                                .on_input(move |v| Message::AutomationEndChanged(i, v))
+                                .width(Length::Fixed(70.0))
+                                .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                            pick_list(vec![RampCurve::Linear, RampCurve::Log], Some(rule.curve), move |v| Message::AutomationCurveChanged(i, v)),
+                            button("Remove")
+                                .on_press(Message::AutomationRemoveRule(i))
+                                .style(theme::Button::Custom(Box::new(GhostButton))),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                        row![
+                            text(format!("{:+.1} dB", rule.offset_db)),
+                            slider(-12.0..=6.0, rule.offset_db, move |v| Message::AutomationOffsetChanged(i, v))
+                                .step(0.5)
+                                .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                            text(format!("Ramp {:.0} s", rule.ramp_secs)),
+                            slider(0.0..=600.0, rule.ramp_secs, move |v| Message::AutomationRampChanged(i, v))
+                                .step(5.0)
+                                .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                    ]
+                    .spacing(6)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            let offsets = AutomationParam::ALL
+                .iter()
+                .map(|p| format!("{} {:+.1} dB", p, self.automation_offsets[p.index()]))
+                .collect::<Vec<_>>()
+                .join(", ");
+            card(
+                "Automation",
+                column![
+                    Column::with_children(rows).spacing(10),
+                    button("Add rule")
+                        .on_press(Message::AutomationAddRule)
+                        .style(theme::Button::Custom(Box::new(GhostButton))),
+                    text(if self.engine.is_some() { format!("Now: {}", offsets) } else { "Rules apply while streaming (local time, HH:MM)".to_string() })
+                        .size(12)
+                        .style(color_muted()),
+                ]
+                .spacing(10),
+            )
+        };
+
+        let output_card = || card(
+            "Output",
+            column![
+                row![
+                    with_help(text(format!("Gain {:+.1} dB", self.output_gain_db)), HelpTopic::OutputGain),
+                    slider(MIN_GAIN_DB..=MAX_GAIN_DB, self.output_gain_db, Message::GainChanged)
+                        .step(0.1)
+                        .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    with_help(checkbox("Limiter", self.limiter_enabled, Message::LimiterEnabled), HelpTopic::Limiter),
+                    text(format!("Threshold {:.1} dBFS", self.limiter_threshold_db)),
+                    slider(MIN_THRESHOLD_DB..=MAX_THRESHOLD_DB, self.limiter_threshold_db, Message::LimiterThresholdChanged)
+                        .step(0.1)
+                        .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    text(format!("Lookahead {:.1} ms", self.limiter_lookahead_ms)),
+                    slider(0.5..=10.0, self.limiter_lookahead_ms, Message::LimiterLookaheadChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            ],
+        );
+
+        let levels_card = || card(
+            "Stereo + RDS",
+            column![
+                row![
+                    with_help(text(format!("Pilot {:.2}", self.pilot_level)), HelpTopic::PilotLevel),
+                    slider(0.2..=1.5, self.pilot_level, Message::PilotLevelChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    text(format!("Phase {:+.1}°", self.pilot_phase_deg)),
+                    slider(-MAX_PILOT_PHASE_DEG..=MAX_PILOT_PHASE_DEG, self.pilot_phase_deg, Message::PilotPhaseChanged)
+                        .step(0.5)
+                        .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    with_help(text(format!("RDS {:.2}", self.rds_level)), HelpTopic::RdsLevel),
+                    slider(0.2..=1.5, self.rds_level, Message::RdsLevelChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    with_help(text(format!("Stereo sep {:.2}", self.stereo_separation)), HelpTopic::StereoSeparation),
+                    slider(0.5..=1.5, self.stereo_separation, Message::StereoSeparationChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            ],
+        );
+
+        let calibration_card = || {
+            let pilot_khz = self.calibration.pilot_khz(self.last_modulation.pilot_peak * FULL_SCALE_KHZ);
+            let rds_khz = self.calibration.rds_khz(self.last_modulation.rds_peak * FULL_SCALE_KHZ);
+            let body: Element<'_, Message> = match self.calibration_step {
+                Some(step) => {
+                    let (label, nominal) = match step {
+                        CalibrationStep::PilotOnly => ("pilot", self.last_modulation.pilot_peak * FULL_SCALE_KHZ),
+                        CalibrationStep::RdsOnly => ("RDS", self.last_modulation.rds_peak * FULL_SCALE_KHZ),
+                    };
+                    column![
+                        text(format!("Step: {} (encoder reports {:.2} kHz)", step, nominal)).style(color_accent_warm()),
+                        row![
+                            text(format!("Measured {} deviation (kHz):", label)),
+                            text_input("0.00", &self.calibration_input)
+                                .on_input(Message::CalibrationMeasuredChanged)
+                                .on_submit(Message::CalibrationNext)
+                                .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                            button(if step == CalibrationStep::PilotOnly { "Next" } else { "Finish" })
+                                .on_press(Message::CalibrationNext)
+                                .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                            button("Cancel")
+                                .on_press(Message::CalibrationCancel)
+                                .style(theme::Button::Custom(Box::new(GhostButton))),
+                        ]
+                        .spacing(10)
+                        .align_items(Alignment::Center),
+                    ]
+                    .spacing(8)
+                    .into()
+                }
+                None => column![
+                    text(format!(
+                        "Correction: pilot x{:.3}, RDS x{:.3}",
+                        self.calibration.pilot_factor, self.calibration.rds_factor
+                    )),
+                    text(format!("Calibrated: pilot {:.2} kHz, RDS {:.2} kHz", pilot_khz, rds_khz)).style(color_muted()),
+                    row![
+                        button("Start calibration")
+                            .on_press(Message::CalibrationStart)
+                            .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                        button(text(format!("Apply {:.2} / {:.2} kHz", PILOT_TARGET_KHZ, RDS_TARGET_KHZ)))
+                            .on_press(Message::CalibrationApply)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                ]
+                .spacing(8)
+                .into(),
+            };
+            card("Deviation Calibration", column![body])
+        };
+
+        let processing_card = || card(
+            "Processing",
+            column![
+                row![
+                    text("Preset:"),
+                    pick_list(ProcessingPreset::ALL.to_vec(), self.processing_preset, Message::ProcessingPresetSelected)
+                        .placeholder("Custom"),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    with_help(text("Pre-emphasis:"), HelpTopic::Preemphasis),
+                    pick_list(self.preemphasis_items.clone(), Some(self.preemphasis_selected.clone()), Message::PreemphasisChanged),
+                    with_help(checkbox("Compressor", self.compressor_enabled, Message::CompressorEnabled), HelpTopic::Compressor),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    with_help(text(format!("Audio LPF {:.1} kHz", self.lpf_cutoff_hz / 1000.0)), HelpTopic::AudioLpf),
+                    slider(MIN_LPF_CUTOFF_HZ..=MAX_LPF_CUTOFF_HZ, self.lpf_cutoff_hz, Message::LpfCutoffChanged)
+                        .step(100.0)
+                        .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    checkbox("19 kHz notch", self.pilot_notch, Message::PilotNotchToggled),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text(format!("Thr {:.1} dB", self.comp_threshold)),
+                    slider(-30.0..=0.0, self.comp_threshold, Message::CompThresholdChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    text(format!("Ratio {:.1}", self.comp_ratio)),
+                    slider(1.0..=6.0, self.comp_ratio, Message::CompRatioChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text(format!("Attack {:.3}s", self.comp_attack)),
+                    slider(0.001..=0.1, self.comp_attack, Message::CompAttackChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                    text(format!("Release {:.2}s", self.comp_release)),
+                    slider(0.05..=1.0, self.comp_release, Message::CompReleaseChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text(format!("Makeup {:.1} dB", self.comp_makeup)),
+                    slider(0.0..=24.0, self.comp_makeup, Message::CompMakeupChanged)
+                        .step(0.5)
+                        .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            ],
+        );
+
+        let chain_card = || {
+            let rows = Stage::ALL
+                .iter()
+                .map(|&stage| {
+                    let levels = self.stage_levels[stage.index()];
+                    let active = match stage {
+                        Stage::PreEmphasis => preemph_to_tau(self.preemphasis_selected.clone()).is_some(),
+                        Stage::Compressor => self.compressor_enabled,
+                        Stage::PilotNotch => self.pilot_notch,
+                        Stage::Limiter => self.limiter_enabled,
+                    };
+                    let readout = if active && self.engine.is_some() {
+                        format!(
+                            "In {:.1} dB  Out {:.1} dB  GR {:.1} dB  Headroom {:.1} dB",
+                            levels.input_db(),
+                            levels.output_db(),
+                            levels.reduction_db,
+                            levels.headroom_db()
+                        )
+                    } else {
+                        "Off".to_string()
+                    };
+                    row![
+                        checkbox("Bypass", self.stage_bypass[stage.index()], move |v| Message::StageBypassToggled(stage, v)),
+                        text(stage.to_string()).width(Length::Fixed(110.0)),
+                        text(readout).size(12).style(color_muted()).width(Length::Fixed(320.0)),
+                        progress_bar(0.0..=20.0, levels.reduction_db.clamp(0.0, 20.0))
+                            .style(theme::ProgressBar::Custom(Box::new(WarmProgressBar))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            let insert_rows = inserts::registered()
+                .into_iter()
+                .map(|(name, point)| {
+                    row![
+                        checkbox(name, self.enabled_inserts.iter().any(|n| n == name), move |v| Message::InsertToggled(name, v)),
+                        text(point.to_string()).size(12).style(color_muted()),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            card(
+                "Processing Chain",
+                column![
+                    Column::with_children(rows).spacing(6),
+                    text("Bypassed stages keep their settings and still meter the gain reduction they would apply.")
+                        .size(12)
+                        .style(color_muted()),
+                    text("Inserts").style(color_text()),
+                    Column::with_children(insert_rows).spacing(6),
+                    text(format!(
+                        "Registered processing stages, run in the order enabled. Each insert point adds {:.2} ms of delay; changes apply on the next start.",
+                        inserts::INSERT_BLOCK as f32 * 1000.0 / inserts::INSERT_SAMPLE_RATE as f32
+                    ))
+                    .size(12)
+                    .style(color_muted()),
+                ]
+                .spacing(10),
+            )
+        };
+
+        let modulation_card = || {
+            let now = Instant::now();
+            let power = self.mod_window.mpx_power_dbr();
+            let peak = self.mod_window.peak_khz(now);
+            let state_color = |state: LimitState| match state {
+                LimitState::Ok => color_live(),
+                LimitState::Warn => color_accent_warm(),
+                LimitState::Over => color_danger(),
+            };
+            let (power_text, power_color) = if self.engine.is_some() && power.is_finite() {
+                (format!("{:+.1} dBr", power), state_color(self.mod_limits.power_state(power)))
+            } else {
+                ("-- dBr".to_string(), color_muted())
+            };
+            let (peak_text, peak_color) = if self.engine.is_some() {
+                (format!("{:.1} kHz", peak), state_color(self.mod_limits.deviation_state(peak)))
+            } else {
+                ("-- kHz".to_string(), color_muted())
+            };
+            card(
+                "Modulation",
+                column![
+                    row![
+                        column![
+                            text("MPX power (BS.412)").size(12).style(color_muted()),
+                            text(power_text).size(28).style(power_color),
+                            text(format!("{:.0} s of 60 s, limit {:+.1} dBr", self.mod_window.span().as_secs_f32(), self.mod_limits.power_limit_dbr))
+                                .size(12)
+                                .style(color_muted()),
+                        ]
+                        .spacing(4)
+                        .width(Length::FillPortion(1)),
+                        column![
+                            text("Peak deviation").size(12).style(color_muted()),
+                            text(peak_text).size(28).style(peak_color),
+                            text(format!("last 1 s, limit {:.1} kHz", self.mod_limits.deviation_limit_khz))
+                                .size(12)
+                                .style(color_muted()),
+                        ]
+                        .spacing(4)
+                        .width(Length::FillPortion(1)),
+                    ]
+                    .spacing(16),
+                ],
+            )
+        };
+
+        let mod_limits_card = || {
+            let labels = ["MPX power amber (dBr)", "MPX power limit (dBr)", "Deviation amber (kHz)", "Deviation limit (kHz)"];
+            let rows = labels
+                .iter()
+                .enumerate()
+                .map(|(i, label)| {
+                    row![
+                        text(*label).width(Length::Fixed(170.0)),
+                        text_input("0.0", &self.mod_limit_inputs[i])
+                            .on_input(move |v| Message::ModLimitChanged(i, v))
+                            .width(Length::Fixed(90.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            card(
+                "License Limits",
+                column![
+                    text("Thresholds for the Dashboard modulation readout: amber above the first value, red above the limit.")
+                        .size(12)
+                        .style(color_muted()),
+                    Column::with_children(rows).spacing(6),
+                    button("Save limits")
+                        .on_press(Message::SaveModLimits)
+                        .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                ]
+                .spacing(10),
+            )
+        };
+
+        let meter_summary_card = || {
+            card(
+                "Meters",
+                column![
+                    row![
+                        text(format!("RMS {:.2}", self.meter_rms)),
+                        progress_bar(0.0..=1.0, self.meter_rms).style(theme::ProgressBar::Custom(Box::new(CustomProgressBar))),
+                        text(format!("Peak {:.2}", self.meter_peak)),
+                        progress_bar(0.0..=1.0, self.meter_peak).style(theme::ProgressBar::Custom(Box::new(PeakyProgressBar))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text(format!("Pilot 19 kHz {:.1}%", self.injection.pilot * 100.0)),
+                        progress_bar(0.0..=INJECTION_BAR_MAX, self.injection.pilot).style(theme::ProgressBar::Custom(Box::new(CustomProgressBar))),
+                        text(format!("RDS 57 kHz {:.1}%", self.injection.rds * 100.0)),
+                        progress_bar(0.0..=INJECTION_BAR_MAX, self.injection.rds).style(theme::ProgressBar::Custom(Box::new(WarmProgressBar))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                ],
+            )
+        };
+
+        let meters_full = || card_accent(
+            "MPX Meter",
+            column![
+                row![
+                    text(format!("RMS {:.2}", self.meter_rms)).style(color_accent()),
+                    progress_bar(0.0..=1.0, self.meter_rms).style(theme::ProgressBar::Custom(Box::new(CustomProgressBar))),
+                    text(format!("Peak {:.2}", self.meter_peak)).style(color_danger()),
+                    progress_bar(0.0..=1.0, self.meter_peak).style(theme::ProgressBar::Custom(Box::new(PeakyProgressBar))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text(format!("Pilot 19 kHz {:.1}%", self.injection.pilot * 100.0)).style(color_accent_warm()),
+                    progress_bar(0.0..=INJECTION_BAR_MAX, self.injection.pilot).style(theme::ProgressBar::Custom(Box::new(WarmProgressBar))),
+                    text(format!("RDS 57 kHz {:.1}%", self.injection.rds * 100.0)).style(color_accent()),
+                    progress_bar(0.0..=INJECTION_BAR_MAX, self.injection.rds).style(theme::ProgressBar::Custom(Box::new(CustomProgressBar))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                text(format!(
+                    "38 kHz carrier residual {:.2}% ({:.0} dB below pilot)",
+                    self.injection.stereo_carrier * 100.0,
+                    20.0 * (self.injection.pilot.max(1e-6) / self.injection.stereo_carrier.max(1e-6)).log10()
+                ))
+                .style(color_muted()),
+                row![
+                    text(format!("XRuns {}", self.xrun_count)),
+                    text(format!("Buffer {:.0}%", (self.buffer_fill * 100.0).clamp(0.0, 100.0))),
+                    text(format!("Latency {:.1} ms", self.latency_ms)),
+                ]
+                .spacing(14)
+                .align_items(Alignment::Center),
+                row![
+                    text("FFT size:"),
+                    pick_list(FftSize::ALL.to_vec(), Some(self.fft_size), Message::FftSizeSelected),
+                    text("Window:"),
+                    pick_list(FftWindow::ALL.to_vec(), Some(self.fft_window), Message::FftWindowSelected),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("Graphs:"),
+                    pick_list(GraphRate::ALL.to_vec(), Some(self.refresh.graphs), Message::GraphRateSelected),
+                    text("Readouts:"),
+                    pick_list(TextRate::ALL.to_vec(), Some(self.refresh.text), Message::TextRateSelected),
+                    text("Lower rates save CPU on laptops").size(12).style(color_muted()),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("Spectrum (dB):"),
+                    Canvas::new(SpectrumView {
+                        spectrum_peak_db: self.spectrum_peak_db.clone(),
+                        spectrum_avg_db: self.spectrum_avg_db.clone(),
+                    })
+                    .width(Length::Fill)
+                    .height(200),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                match self.mask_result {
+                    Some(mask) if mask.passed() => text(format!("Spectrum mask: PASS ({:.1} dB margin)", mask.margin_db)).style(color_live()),
+                    Some(mask) => text(format!(
+                        "Spectrum mask: FAIL at {:.1} kHz ({:.1} dB over)",
+                        mask.worst_hz / 1000.0,
+                        -mask.margin_db
+                    ))
+                    .style(color_danger()),
+                    None => text("Spectrum mask: --").style(color_muted()),
+                },
+                row![
+                    text("Scope:"),
+                    Canvas::new(ScopeView { samples: self.scope_samples.clone(), prev: self.scope_prev.clone() })
+                        .width(Length::Fill)
+                        .height(180),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            ],
+        );
+
+        let input_card = || {
+            let level_rows = ["L", "R"]
+                .iter()
+                .zip(self.input_levels)
+                .map(|(label, (rms, peak))| {
+                    row![
+                        text(*label).width(Length::Fixed(20.0)),
+                        progress_bar(-60.0..=0.0, linear_to_db(rms).max(-60.0))
+                            .style(theme::ProgressBar::Custom(Box::new(CustomProgressBar))),
+                        text(format!("RMS {:.1}  Peak {:.1} dBFS", linear_to_db(rms), linear_to_db(peak)))
+                            .size(12)
+                            .style(if peak >= 1.0 { color_danger() } else { color_muted() })
+                            .width(Length::Fixed(200.0)),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            let reduction_rows = [Stage::Compressor, Stage::Limiter]
+                .iter()
+                .map(|&stage| {
+                    let reduction = self.stage_levels[stage.index()].reduction_db;
+                    row![
+                        text(format!("{} GR", stage)).width(Length::Fixed(110.0)),
+                        progress_bar(0.0..=20.0, reduction.clamp(0.0, 20.0))
+                            .style(theme::ProgressBar::Custom(Box::new(WarmProgressBar))),
+                        text(format!("{:.1} dB", reduction)).size(12).style(color_muted()).width(Length::Fixed(60.0)),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            card(
+                "Input & Processing",
+                column![
+                    Column::with_children(level_rows).spacing(6),
+                    row![
+                        text("Input (dB):"),
+                        Canvas::new(InputSpectrumView { spectrum_db: self.input_spectrum_db.clone() })
+                            .width(Length::Fill)
+                            .height(160),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    Column::with_children(reduction_rows).spacing(6),
+                ]
+                .spacing(10),
+            )
+        };
+
+        let mod_history_card = || card(
+            "Modulation History (24 h)",
+            column![
+                row![
+                    text("Peak kHz").style(color_danger()),
+                    text("Pilot kHz").style(color_accent_warm()),
+                    text("RDS kHz").style(color_accent()),
+                    text("MPX power dBr").style(color_muted()),
+                ]
+                .spacing(14)
+                .align_items(Alignment::Center),
+                Canvas::new(HistoryView { points: self.mod_history.points().cloned().collect() })
+                    .width(Length::Fill)
+                    .height(200),
+                row![
+                    text(format!("{} minutes", self.mod_history.len())),
+                    text_input("modulation_history.csv", &self.mod_history_path).on_input(Message::ModHistoryPathChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    button("Export CSV")
+                        .on_press(Message::ExportModHistory)
+                        .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+            ]
+            .spacing(10),
+        );
+
+        let test_sequence_card = || {
+            let steps = TestStep::ALL
+                .iter()
+                .enumerate()
+                .map(|(i, &step)| {
+                    let active = self.test_status.map(|(s, _)| s == step).unwrap_or(false);
+                    row![
+                        text(step.to_string()).width(Length::Fixed(110.0)).style(if active { color_live() } else { color_text() }),
+                        slider(1.0..=60.0, self.test_step_secs[i], move |v| Message::TestStepSecsChanged(i, v))
+                            .step(1.0)
+                            .style(theme::Slider::Custom(Box::new(CustomSlider))),
+                        text(format!("{:.0} s", self.test_step_secs[i])).width(Length::Fixed(40.0)),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+            let state = match self.test_status {
+                Some((step, remaining)) => format!("Running: {} ({:.0} s left)", step, remaining),
+                None => "Idle".to_string(),
+            };
+            card(
+                "Test Sequence",
+                column![
+                    Column::with_children(steps).spacing(6),
+                    text(state).style(color_muted()),
+                    row![
+                        if self.test_status.is_some() {
+                            button("Stop")
+                                .on_press(Message::StopTestSequence)
+                                .style(theme::Button::Custom(Box::new(GhostButton)))
+                        } else {
+                            button("Run live")
+                                .on_press(Message::RunTestSequence)
+                                .style(theme::Button::Custom(Box::new(PrimaryButton)))
+                        },
+                        text_input("test_sequence.wav", &self.test_output_path).on_input(Message::TestOutputChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        button("Render WAV")
+                            .on_press(Message::RenderTestSequence)
+                            .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        text("Pilot/38 kHz phase:"),
+                        text_input("phase_test.wav", &self.phase_test_path).on_input(Message::PhaseTestPathChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        button("Render phase test")
+                            .on_press(Message::RenderPhaseTest)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(match self.phase_report {
+                        Some(report) => report.to_string(),
+                        None => format!("Pilot and unmodulated 38 kHz only, {:.0} s, with the pilot phase offset applied", PHASE_TEST_SECS),
+                    })
+                    .size(12)
+                    .style(color_muted()),
+                    row![
+                        text("Stereo separation:"),
+                        button("Run self-test")
+                            .on_press(Message::RunSeparationTest)
+                            .style(theme::Button::Custom(Box::new(GhostButton))),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    match self.separation_report {
+                        Some(report) if report.passed() => text(report.to_string()).size(12).style(color_live()),
+                        Some(report) => text(report.to_string()).size(12).style(color_danger()),
+                        None => text("Left-only tones through the current processing and stereo encoder, decoded from the pilot like a receiver")
+                            .size(12)
+                            .style(color_muted()),
                     },
-                    button("Stop")
-                        .on_press(Message::StopStream)
-                        .padding(10)
-                        .style(theme::Button::Custom(Box::new(DangerButton))),
-                    text(&self.status).style(color_muted()),
+                ]
+                .spacing(10),
+            )
+        };
+
+        let export_card = || card(
+            "WAV Export",
+            column![
+                row![
+                    text("Duration (sec):"),
+                    text_input("10", &self.duration).on_input(Message::DurationChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("Audio WAV (optional):"),
+                    text_input("", &self.audio_path).on_input(Message::AudioChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("Output WAV:"),
+                    text_input("mpx.wav", &self.output_path).on_input(Message::OutputChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("Program feed WAV (optional):"),
+                    text_input("program.wav", &self.program_feed_path)
+                        .on_input(Message::ProgramFeedPathChanged)
+                        .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text("Delay (ms):"),
+                    text_input("0", &self.program_feed_delay)
+                        .on_input(Message::ProgramFeedDelayChanged)
+                        .width(Length::Fixed(80.0))
+                        .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                row![
+                    text("Group schedule (optional, .json or .csv):"),
+                    text_input("groups.json", &self.group_schedule_path)
+                        .on_input(Message::GroupSchedulePathChanged)
+                        .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                ]
+                .spacing(10)
+                .align_items(Alignment::Center),
+                checkbox("Resume interrupted export", self.resume_export, Message::ResumeExportChanged),
+                row![
+                    checkbox("FM-modulated IQ for SDR replay", self.iq_export, Message::IqExportToggled),
+                    text("Rate (Hz):"),
+                    text_input("2400000", &self.iq_rate)
+                        .on_input(Message::IqRateChanged)
+                        .width(Length::Fixed(110.0))
+                        .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                    text("Deviation (kHz):"),
+                    text_input("75", &self.iq_deviation)
+                        .on_input(Message::IqDeviationChanged)
+                        .width(Length::Fixed(70.0))
+                        .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
                 ]
                 .spacing(10)
                 .align_items(Alignment::Center),
+                text("Impairments for receiver testing (leave the SNR empty for no noise):").size(12).style(color_muted()),
+                Row::with_children(
+                    ["Noise SNR (dB)", "Ripple (dB p-p)", "Echo delay (us)", "GD tilt at 57 kHz (us)"]
+                        .iter()
+                        .enumerate()
+                        .map(|(i, label)| {
+                            column![
+                                text(*label).size(12),
+                                text_input("", &self.impairment_inputs[i])
+                                    .on_input(move |v| Message::ImpairmentChanged(i, v))
+                                    .width(Length::Fixed(110.0))
+                                    .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                            ]
+                            .spacing(4)
+                            .into()
+                        })
+                        .collect::<Vec<Element<'_, Message>>>(),
+                )
+                .spacing(10),
+                if self.generating {
+                    button("Generating...")
+                        .padding(10)
+                        .style(theme::Button::Custom(Box::new(GhostButton)))
+                } else {
+                    button("Generate")
+                        .on_press(Message::Generate)
+                        .padding(10)
+                        .style(theme::Button::Custom(Box::new(PrimaryButton)))
+                },
             ],
-            )
+        );
+
+        let about_tab = column![
+            card(
+                "About Pulse FM",
+                column![
+                    text("Pulse FM RDS Encoder is a cross‑platform MPX generator with RDS tools and a live audio pipeline.").style(color_muted()),
+                    text("Use it to generate MPX for analysis, streaming to 192 kHz devices, or offline WAV export.").style(color_muted()),
+                    text("It does not transmit RF and is intended for baseband audio only.").style(color_muted()),
+                ]
+                .spacing(6),
+            ),
+            card(
+                "Developer",
+                column![
+                    text("Hsouna Zinoubi").style(color_text()),
+                    text("BOUZIDFM").style(color_muted()),
+                    text("imhsouna@gmail.com").style(color_muted()),
+                ]
+                .spacing(4),
+            ),
+            card(
+                "Support",
+                column![
+                    text("Bundles settings (secrets blanked), audio device capabilities, recent logs, the last minute of meter readings and version info into a zip in logs/ to attach to a bug report.")
+                        .size(12)
+                        .style(color_muted()),
+                    button(if self.diagnostics_exporting { "Exporting..." } else { "Export diagnostics" })
+                        .on_press(Message::ExportDiagnostics)
+                        .style(theme::Button::Custom(Box::new(PrimaryButton))),
+                ]
+                .spacing(8),
+            ),
+            card(
+                "Quick Tips",
+                column![
+                    text("• Select an output device that supports 192 kHz float32.").style(color_muted()),
+                    text("• Use the Audio tab to refresh device lists.").style(color_muted()),
+
+                ]
+                .spacing(4),
+            ),
+        ]
+        .spacing(16)
+        .width(Length::Fill);
+
+        let compact = self.window_width < 980.0;
+
+        let status_pill = if self.engine.is_some() {
+            pill("● LIVE", color_live(), Color::from_rgb8(6, 24, 19))
+        } else {
+            pill("○ IDLE", color_surface_alt(), color_muted())
+        };
+
+        let status_text = text(&self.status).style(color_muted());
+
+        let hero = container(
+            row![
+                column![
+                    row![
+                        text("Pulse FM").size(32).style(color_text()),
+                        text("FM").size(32).style(rgba8f(56, 189, 248, 0.6)),
+                    ]
+                    .spacing(2)
+                    .align_items(Alignment::Center),
+                    text("RDS Encoder Studio").size(16).style(rgba8f(168, 85, 247, 0.7)),
+                    text("Live MPX pipeline • 192 kHz • FM/RDS broadcast tools").size(12).style(color_muted()),
+                ]
+                .spacing(4)
+                .width(Length::FillPortion(3)),
+                column![
+                    row![
+                        status_pill,
+                        status_text,
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        if self.locked {
+                            pill("LOCKED", color_accent_warm(), Color::from_rgb8(24, 16, 6))
+                        } else {
+                            pill("ENGINEER", color_surface_alt(), color_muted())
+                        },
+                        text_input("PIN", &self.lock_pin_input)
+                            .on_input(Message::LockPinChanged)
+                            .password()
+                            .width(Length::Fixed(90.0))
+                            .style(theme::TextInput::Custom(Box::new(CustomTextInput))),
+                        if self.locked {
+                            button("Unlock")
+                                .on_press(Message::EngineerUnlock)
+                                .style(theme::Button::Custom(Box::new(GhostButton)))
+                        } else {
+                            button("Lock")
+                                .on_press(Message::LockOperator)
+                                .style(theme::Button::Custom(Box::new(GhostButton)))
+                        },
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        container(
+                            row![
+                                text(format!("{}", self.xrun_count)).size(13).style(color_accent()),
+                                text("XRuns").size(11).style(color_muted()),
+                            ]
+                            .spacing(4)
+                            .align_items(Alignment::Center),
+                        )
+                        .padding([4, 10])
+                        .style(theme::Container::Custom(Box::new(MetricPill))),
+                        text("|").size(11).style(rgba8f(255, 255, 255, 0.06)),
+                        container(
+                            row![
+                                text(format!("{:.0}%", (self.buffer_fill * 100.0).clamp(0.0, 100.0))).size(13).style(color_accent_warm()),
+                                text("Buf").size(11).style(color_muted()),
+                            ]
+                            .spacing(4)
+                            .align_items(Alignment::Center),
+                        )
+                        .padding([4, 10])
+                        .style(theme::Container::Custom(Box::new(MetricPill))),
+                        text("|").size(11).style(rgba8f(255, 255, 255, 0.06)),
+                        container(
+                            row![
+                                text(format!("{:.1}", self.latency_ms)).size(13).style(color_live()),
+                                text("ms").size(11).style(color_muted()),
+                            ]
+                            .spacing(4)
+                            .align_items(Alignment::Center),
+                        )
+                        .padding([4, 10])
+                        .style(theme::Container::Custom(Box::new(MetricPill))),
+                    ]
+                    .spacing(0)
+                    .align_items(Alignment::Center),
+                ]
+                .spacing(8)
+                .width(Length::FillPortion(2)),
+            ]
+            .spacing(24)
+            .align_items(Alignment::Center),
+        )
+        .padding(20)
+        .width(Length::Fill)
+        .style(theme::Container::from(if self.silence_alarm_active() && self.silence_flash {
+            hero_alarm_style
+        } else {
+            hero_style
+        }));
+
+        let body: Element<'_, Message> = match self.tab_selected {
+            Tab::Dashboard => {
+                if compact {
+                    column![
+                        stream_card(),
+                        device_card(),
+                        presets_card(),
+                        storage_card(),
+                        station_card(),
+                        modulation_card(),
+                        meter_summary_card(),
+                    ]
+                    .spacing(16)
+                    .into()
+                } else {
+                    column![
+                        row![
+                            column![stream_card(), device_card(), presets_card(), storage_card()].spacing(16).width(Length::FillPortion(2)),
+                            column![station_card(), modulation_card(), meter_summary_card()].spacing(16).width(Length::FillPortion(3)),
+                        ]
+                        .spacing(16)
+                        .align_items(Alignment::Start),
+                    ]
+                    .into()
+                }
+            }
+            Tab::Audio => {
+                if compact {
+                    column![
+                        device_card(),
+                        stream_card(),
+                        health_card,
+                        meter_summary_card(),
+                        contacts_card(),
+                        status_server_card(),
+                        rds_feed_card(),
+                        sync_card(),
+                        silence_card(),
+                        alerts_card(),
+                    ]
+                    .spacing(16)
+                    .into()
+                } else {
+                    column![
+                        row![
+                            column![device_card(), stream_card(), health_card].spacing(16).width(Length::FillPortion(3)),
+                            column![meter_summary_card(), contacts_card(), status_server_card(), rds_feed_card(), sync_card(), silence_card(), alerts_card()].spacing(16).width(Length::FillPortion(2)),
+                        ]
+                        .spacing(16)
+                        .align_items(Alignment::Start),
+                    ]
+                    .into()
+                }
+            }
+            Tab::Rds => {
+                if compact {
+                    column![
+                        station_card(),
+                        rds_identity_card(),
+                        compliance_card(),
+                        rds_schedule_card(),
+                        pty_schedule_card(),
+                        group_stats_card(),
+                        af_card(),
+                        network_card(),
+                        scrolling_card(),
+                        receiver_card(),
+                        data_providers_card(),
+                        song_history_card(),
+                        metadata_filter_card(),
+                        history_card(),
+                        help_card(HelpArea::Rds),
+                    ]
+                    .spacing(16)
+                    .into()
+                } else {
+                    column![
+                        row![
+                            column![station_card(), rds_identity_card(), compliance_card(), help_card(HelpArea::Rds), history_card()].spacing(16).width(Length::FillPortion(3)),
+                            column![rds_schedule_card(), pty_schedule_card(), group_stats_card(), af_card(), network_card(), scrolling_card(), receiver_card(), data_providers_card(), song_history_card(), metadata_filter_card()].spacing(16).width(Length::FillPortion(2)),
+                        ]
+                        .spacing(16)
+                        .align_items(Alignment::Start),
+                    ]
+                    .into()
+                }
+            }
+            Tab::Processing => {
+                if compact {
+                    column![output_card(), levels_card(), calibration_card(), processing_card(), chain_card(), automation_card(), help_card(HelpArea::Processing)]
+                        .spacing(16)
+                        .into()
+                } else {
+                    column![
+                        row![
+                            column![output_card(), levels_card(), calibration_card(), help_card(HelpArea::Processing)].spacing(16).width(Length::FillPortion(3)),
+                            column![processing_card(), chain_card(), automation_card()].spacing(16).width(Length::FillPortion(2)),
+                        ]
+                        .spacing(16)
+                        .align_items(Alignment::Start),
+                    ]
+                    .into()
+                }
+            }
+            Tab::Meters => column![meters_full(), input_card(), mod_history_card(), mod_limits_card()].spacing(16).into(),
+            Tab::Export => column![export_card(), test_sequence_card()].spacing(16).into(),
+            Tab::About => about_tab.into(),
+        };
+
+        let content = column![
+            hero,
+            tabs,
+            body,
+        ]
+        .spacing(18)
+        .padding(24)
+        .width(Length::Fill);
+
+        let scroll = scrollable(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(theme::Scrollable::Custom(Box::new(HiddenScrollbar)));
+
+        container(scroll)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(theme::Container::from(body_style))
+            .into()
+    }
+}
+
+impl App {
+    fn di_bits(&self) -> u8 {
+        let mut bits = 0u8;
+        if self.di_stereo {
+            bits |= 0b1000;
+        }
+        if self.di_artificial {
+            bits |= 0b0100;
+        }
+        if self.di_compressed {
+            bits |= 0b0010;
+        }
+        if self.di_dynamic || self.pty_schedule_running() {
+            bits |= 0b0001;
+        }
+        bits
+    }
+
+    // A running PTY schedule changes PTY during the programme, which the
+    // dynamic PTY DI bit announces. The manual override stops it and puts
+    // the station PTY back.
+    fn pty_schedule_running(&self) -> bool {
+        !self.pty_override && !self.pty_rules.is_empty()
+    }
+
+    fn on_air_pty(&self) -> u8 {
+        self.pty_scheduled.unwrap_or(self.pty_selected.code)
+    }
+
+    fn refresh_pty_schedule(&mut self) {
+        let scheduled = if self.pty_override {
+            None
+        } else {
+            scheduled_pty(&self.pty_rules, chrono::Local::now().naive_local())
         };
+        if scheduled == self.pty_scheduled {
+            return;
+        }
+        self.pty_scheduled = scheduled;
+        if let Some(engine) = &self.engine {
+            engine.update_pty(self.on_air_pty());
+        }
+    }
 
-        let device_card = || {
-            card(
-                "Devices",
-                column![
-                    row![
-                        text("Input:"),
-                        pick_list(self.input_devices.clone(), self.selected_input.clone(), Message::InputSelected),
-                    ]
-                    .spacing(10)
-                    .align_items(Alignment::Center),
-                    row![
-                        text("Output:"),
-                        pick_list(self.output_devices.clone(), self.selected_output.clone(), Message::OutputSelected),
-                        button("Refresh")
-                            .on_press(Message::RefreshDevices)
-                            .style(theme::Button::Custom(Box::new(GhostButton))),
-                    ]
-                    .spacing(10)
-                    .align_items(Alignment::Center),
-                ],
-            )
+    // Rules or the override changed: the DI bit may follow as well as PTY.
+    fn pty_schedule_changed(&mut self) {
+        self.refresh_pty_schedule();
+        if let Some(engine) = &self.engine {
+            engine.update_di(self.di_bits());
+        }
+    }
+
+    fn save_refresh_settings(&mut self) {
+        if let Err(e) = save_config(REFRESH_FILE, &self.refresh) {
+            self.status = format!("Refresh settings save error: {}", e);
+        }
+    }
+
+    // Brings the RDS settings within EN 50067 while compliance mode is on,
+    // through the usual messages so a running engine follows. Called on
+    // every tick, which also catches presets, imports and remote changes.
+    // Returns the changes made, which are logged and shown in the status.
+    fn enforce_compliance(&mut self) -> Vec<String> {
+        if !self.compliance.enabled {
+            return Vec::new();
+        }
+        let settled = self.compliance_level_changed.map(|t| t.elapsed() >= COMPLIANCE_SETTLE).unwrap_or(true);
+        let rds_khz = (self.engine.is_some() && settled && self.last_modulation.rds_peak > 0.0)
+            .then(|| self.calibration.rds_khz(self.last_modulation.rds_peak * FULL_SCALE_KHZ));
+        let before = RdsOptions {
+            ps_scroll_enabled: self.ps_scroll_enabled,
+            ps_alt_interval: self.ps_alt_interval.trim().parse::<usize>().unwrap_or(0),
+            ct_enabled: self.ct_enabled,
+            ct_interval_groups: self.ct_interval_groups.trim().parse::<usize>().unwrap_or(0),
+            group_0a: self.group_0a.trim().parse::<usize>().unwrap_or(4),
+            group_2a: self.group_2a.trim().parse::<usize>().unwrap_or(1),
+            group_4a: self.group_4a.trim().parse::<usize>().unwrap_or(0),
+            rds_level: self.rds_level,
+            rds_khz,
         };
+        let mut options = before.clone();
+        let changes = compliance::enforce(&mut options);
+        if changes.is_empty() {
+            return changes;
+        }
 
-        let health_card = card(
-            "Device Health",
-            column![
-                row![
-                    text(format!("XRuns {}", self.xrun_count)).style(color_muted()),
-                    text(format!("Buffer {:.0}%", (self.buffer_fill * 100.0).clamp(0.0, 100.0))).style(color_muted()),
-                    text(format!("Latency {:.1} ms", self.latency_ms)).style(color_muted()),
-                ]
-                .spacing(14)
-                .align_items(Alignment::Center),
-            ],
-        );
+        if options.ps_scroll_enabled != before.ps_scroll_enabled {
+            let _ = self.dispatch_internal(Message::PsScrollEnabled(options.ps_scroll_enabled));
+        }
+        if options.ps_alt_interval != before.ps_alt_interval {
+            let _ = self.dispatch_internal(Message::PsAltIntervalChanged(options.ps_alt_interval.to_string()));
+            let _ = self.dispatch_internal(Message::ApplyPsAlternates);
+        }
+        if options.ct_enabled != before.ct_enabled {
+            let _ = self.dispatch_internal(Message::CtChanged(options.ct_enabled));
+        }
+        if options.ct_interval_groups != before.ct_interval_groups || options.group_0a != before.group_0a {
+            let _ = self.dispatch_internal(Message::CtIntervalGroupsChanged(options.ct_interval_groups.to_string()));
+            let _ = self.dispatch_internal(Message::Group0aChanged(options.group_0a.to_string()));
+            let _ = self.dispatch_internal(Message::ApplyGroupMix);
+        }
+        if options.rds_level != before.rds_level {
+            let _ = self.dispatch_internal(Message::RdsLevelChanged(options.rds_level));
+            self.compliance_level_changed = Some(Instant::now());
+        }
 
-        let station_card = || {
-            card(
-            "Station",
-            column![
-                row![
-                    text("PS:"),
-                    text_input("BOUZIDFM", &self.ps).on_input(Message::PsChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text("RT:"),
-                    text_input("BOUZIDFM Sidi Bouzid 98.0 MHz", &self.rt).on_input(Message::RtChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text("PI (hex):"),
-                    text_input("7200", &self.pi_hex).on_input(Message::PiChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    checkbox("TP", self.tp, Message::TpChanged),
-                    checkbox("TA", self.ta, Message::TaChanged),
-                    checkbox("Music (MS)", self.ms, Message::MsChanged),
-                    checkbox("CT", self.ct_enabled, Message::CtChanged),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text("PTY:"),
-                    pick_list(self.pty_items.clone(), Some(self.pty_selected.clone()), Message::PtyChanged),
-                    checkbox("RT A/B", self.ab_flag, Message::AbChanged),
-                    checkbox("Auto A/B", self.ab_auto, Message::AbAutoChanged),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-            ],
-            )
+        let now = chrono::Local::now();
+        self.compliance_log.extend(changes.iter().map(|change| (now, change.clone())));
+        if self.compliance_log.len() > COMPLIANCE_LOG_LIMIT {
+            let excess = self.compliance_log.len() - COMPLIANCE_LOG_LIMIT;
+            self.compliance_log.drain(..excess);
+        }
+        self.status = format!("Compliance mode: {}", changes.join("; "));
+        changes
+    }
+
+    fn save_supervisor_settings(&mut self) {
+        if let Err(e) = save_config(SUPERVISOR_FILE, &self.supervisor_settings) {
+            self.status = format!("Engine restart settings save error: {}", e);
+        }
+    }
+
+    // Restarts a faulted or stalled engine with the current settings, after
+    // a growing wait, until the attempts run out. Each step goes to
+    // logs/recovery.log.
+    fn supervise(&mut self) {
+        if !self.supervisor_settings.enabled {
+            return;
+        }
+        let now = Instant::now();
+        let max = self.supervisor_settings.max_restarts;
+        if let Some(engine) = &self.engine {
+            if self.stopping {
+                return;
+            }
+            let fault = match engine.fault() {
+                Some(fault) => fault.to_string(),
+                None if self.supervisor.stalled(engine.output_callbacks(), now) => "output stalled".to_string(),
+                None => return,
+            };
+            // Stopped at once, without a fade, keeping the attempt count.
+            let supervisor = std::mem::take(&mut self.supervisor);
+            self.stopping = true;
+            let _ = self.dispatch_internal(Message::StopStream);
+            self.supervisor = supervisor;
+            self.schedule_restart(&format!("Engine fault: {}", fault), max, now);
+            return;
+        }
+        if self.supervisor.due(now) {
+            let _ = self.dispatch_internal(Message::StartStream);
+            if self.engine.is_some() {
+                let _ = log_recovery(&logs_dir(), &format!("restarted on attempt {}", self.supervisor.attempts()));
+                self.status = format!("Recovered after {} restart(s): {}", self.supervisor.attempts(), self.status);
+            } else {
+                let reason = format!("Restart failed: {}", self.status);
+                self.schedule_restart(&reason, max, now);
+            }
+        }
+    }
+
+    fn schedule_restart(&mut self, reason: &str, max: u32, now: Instant) {
+        self.status = match self.supervisor.schedule(max, now) {
+            Some(wait) => format!(
+                "{}; restarting in {} s (attempt {} of {})",
+                reason,
+                wait.as_secs(),
+                self.supervisor.attempts(),
+                max
+            ),
+            None => format!("{}; gave up after {} restarts", reason, max),
         };
+        let _ = log_recovery(&logs_dir(), &self.status);
+        if !self.supervisor.is_pending() && self.alerts.device_lost {
+            self.send_alert(&format!("Off air: {}", self.status));
+        }
+    }
 
-        let pi_preview = build_pi_from_parts(&self.pi_country_hex, &self.pi_area_hex, &self.pi_program_hex, &self.ecc_hex)
-            .map(|pi| format!("{:04X}", pi))
-            .unwrap_or_else(|_| "—".to_string());
+    fn save_autostart_settings(&mut self) {
+        if let Err(e) = save_config(AUTOSTART_FILE, &self.autostart) {
+            self.status = format!("Auto-start save error: {}", e);
+        }
+    }
 
-        let rds_identity_card = || {
-            card(
-                "Identity + DI",
-                column![
-                    text("PI (Program Identification) should come from your regulator. Use this helper to format a valid PI from parts.").style(color_muted()),
-                    text("Tunisia example: country code 7, ECC E2. For other countries, enter your assigned values.").style(color_muted()),
-                    row![
-                        text("Country preset:"),
-                        pick_list(self.country_items.clone(), Some(self.country_selected.clone()), Message::CountrySelected),
-                        text("PI builder:"),
-                        text_input("7", &self.pi_country_hex).on_input(Message::CountryCodeChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                        text_input("2", &self.pi_area_hex).on_input(Message::AreaCodeChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                        text_input("00", &self.pi_program_hex).on_input(Message::ProgramRefChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                        button("Apply PI")
-                            .on_press(Message::ApplyPiFromParts)
-                            .style(theme::Button::Custom(Box::new(PrimaryButton))),
-                        button("Copy PI")
-                            .on_press(Message::CopyPi)
-                            .style(theme::Button::Custom(Box::new(GhostButton))),
-                        button("Random PI")
-                            .on_press(Message::GenerateRandomPi)
-                            .style(theme::Button::Custom(Box::new(GhostButton))),
-                    ]
-                    .spacing(10)
-                    .align_items(Alignment::Center),
-                    row![
-                        text(format!("PI preview: {}", pi_preview)).style(color_muted()),
-                    ]
-                    .spacing(10)
-                    .align_items(Alignment::Center),
-                    text("Random PI is for testing only. For production, use the code assigned by your regulator.").style(color_muted()),
-                    row![
-                        text("ECC (hex):"),
-                        text_input("E2", &self.ecc_hex).on_input(Message::EccChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                        text("ECC identifies country in RDS. Leave default if unknown.").style(color_muted()),
-                        text("DI:"),
-                        checkbox("Stereo", self.di_stereo, Message::DiStereoChanged),
-                        checkbox("Artificial head", self.di_artificial, Message::DiArtificialChanged),
-                        checkbox("Compressed", self.di_compressed, Message::DiCompressedChanged),
-                        checkbox("Dynamic PTY", self.di_dynamic, Message::DiDynamicChanged),
-                    ]
-                    .spacing(10)
-                    .align_items(Alignment::Center),
-                ],
-            )
+    // Loads the preset and arms the start; the devices are waited for on the
+    // text tick. A preset that cannot be found leaves the app idle rather
+    // than putting the launch settings on air.
+    fn begin_autostart(&mut self, preset: Option<String>) {
+        let mut devices = (None, None);
+        if let Some(name) = preset {
+            let Some(p) = self.presets.iter().find(|p| p.name == name).cloned() else {
+                self.status = format!("Auto-start: preset \"{}\" not found", name);
+                return;
+            };
+            devices = (p.input_device.clone(), p.output_device.clone());
+            self.apply_preset(p);
+            self.preset_selected = Some(name);
+        }
+        self.autostart_pending = Some(devices);
+        self.status = "Auto-start: waiting for devices".to_string();
+    }
+
+    fn poll_autostart(&mut self) {
+        let Some((input, output)) = self.autostart_pending.clone() else {
+            return;
         };
+        if self.engine.is_some() {
+            self.autostart_pending = None;
+            return;
+        }
+        if self.autostart_last_try.map(|t| t.elapsed() < AUTOSTART_RETRY).unwrap_or(false) {
+            return;
+        }
+        self.autostart_last_try = Some(Instant::now());
+        self.refresh_devices();
+        let mut missing = Vec::new();
+        for (wanted, available) in [(&input, &self.input_devices), (&output, &self.output_devices)] {
+            if let Some(device) = wanted.as_ref().filter(|d| !available.contains(*d)) {
+                missing.push(device.clone());
+            }
+        }
+        if self.output_devices.is_empty() && output.is_none() {
+            missing.push("an output device".to_string());
+        }
+        if !missing.is_empty() {
+            self.status = format!("Auto-start: waiting for {}", missing.join(", "));
+            return;
+        }
+        if input.is_some() {
+            self.selected_input = input;
+        }
+        if output.is_some() {
+            self.selected_output = output;
+        }
+        let _ = self.dispatch_internal(Message::StartStream);
+        if self.engine.is_some() {
+            self.autostart_pending = None;
+            self.status = format!("Auto-started: {}", self.status);
+        }
+    }
 
-        let rds_schedule_card = || card(
-            "Group Scheduling",
-            column![
-                row![
-                    text("Mix 0A/2A/4A:"),
-                    text_input("4", &self.group_0a).on_input(Message::Group0aChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    text_input("1", &self.group_2a).on_input(Message::Group2aChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    text_input("0", &self.group_4a).on_input(Message::Group4aChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    text("CT interval (groups):"),
-                    text_input("0", &self.ct_interval_groups).on_input(Message::CtIntervalGroupsChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    button("Apply")
-                        .on_press(Message::ApplyGroupMix)
-                        .style(theme::Button::Custom(Box::new(PrimaryButton))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text("Alternate PS:"),
-                    text_input("ALT1|ALT2", &self.ps_alt_list_text).on_input(Message::PsAltListChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    text("Interval (groups):"),
-                    text_input("0", &self.ps_alt_interval).on_input(Message::PsAltIntervalChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    button("Apply PS")
-                        .on_press(Message::ApplyPsAlternates)
-                        .style(theme::Button::Custom(Box::new(PrimaryButton))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-            ],
-        );
+    fn pty_rules_changed(&mut self) {
+        if let Err(e) = save_config(PTY_SCHEDULE_FILE, &self.pty_rules) {
+            self.status = format!("PTY schedule save error: {}", e);
+        }
+        self.pty_schedule_changed();
+    }
+
+    fn failover_config(&self) -> Result<Option<FailoverConfig>, String> {
+        if !self.failover_enabled {
+            return Ok(None);
+        }
+        let backup = if !self.backup_file.trim().is_empty() {
+            BackupInput::File(self.backup_file.trim().to_string())
+        } else if let Some(device) = self.backup_input.clone() {
+            BackupInput::Device(device)
+        } else {
+            return Err("Select a backup input device or WAV file".to_string());
+        };
+        let silence = self
+            .failover_silence_secs
+            .trim()
+            .parse::<f32>()
+            .map_err(|_| "Invalid failover silence time".to_string())?;
+        let restore = self
+            .failover_restore_secs
+            .trim()
+            .parse::<f32>()
+            .map_err(|_| "Invalid failover restore time".to_string())?;
+        let mut config = FailoverConfig::new(backup);
+        config.silence_secs = silence.max(0.5);
+        config.restore_secs = restore.max(0.5);
+        Ok(Some(config))
+    }
+
+    fn rds_feed_config(&self) -> Option<RdsFeedConfig> {
+        self.rds_feed_enabled.then(|| RdsFeedConfig {
+            transport: self.rds_feed_transport,
+            format: self.rds_feed_format,
+            address: self.rds_feed_address.clone(),
+        })
+    }
+
+    fn traffic_config(&self) -> Result<Option<TrafficRouting>, String> {
+        if !self.traffic_enabled {
+            return Ok(None);
+        }
+        let input = if !self.traffic_file.trim().is_empty() {
+            BackupInput::File(self.traffic_file.trim().to_string())
+        } else if let Some(device) = self.traffic_input.clone() {
+            BackupInput::Device(device)
+        } else {
+            return Err("Select a traffic input device or WAV file".to_string());
+        };
+        let crossfade = self
+            .traffic_crossfade_secs
+            .trim()
+            .parse::<f32>()
+            .map_err(|_| "Invalid traffic crossfade time".to_string())?;
+        let mut config = TrafficRouting::new(input);
+        config.crossfade_secs = crossfade.max(0.0);
+        Ok(Some(config))
+    }
+
+    fn failover_label(&self) -> String {
+        if !self.failover_enabled {
+            return "Failover disabled".to_string();
+        }
+        match (&self.engine, self.failover_status) {
+            (Some(_), Some(status)) if status.on_backup => {
+                format!("On backup input ({}), {} switches", status.reason, status.switches)
+            }
+            (Some(_), Some(status)) => format!("On primary input, {} switches", status.switches),
+            _ => "Switches to backup on silence or device failure, returns once primary is stable".to_string(),
+        }
+    }
+
+    fn reload_translit(&mut self) {
+        self.translit = if self.transliterate {
+            match TranslitTable::load(Some(self.translit_table_path.trim())) {
+                Ok(table) => Some(table),
+                Err(e) => {
+                    self.status = format!("Transliteration table error: {}", e);
+                    Some(TranslitTable::builtin())
+                }
+            }
+        } else {
+            None
+        };
+        if let Some(engine) = &self.engine {
+            engine.update_transliteration(self.translit.clone());
+        }
+    }
+
+    fn apply_af(&self) {
+        if let Some(engine) = &self.engine {
+            let (freqs, regional, _) = validate_af_rows(&self.af_rows);
+            engine.update_af_list(&freqs, &regional, self.frequency_mhz.trim().parse::<f32>().ok());
+        }
+    }
+
+    fn contact_mappings_changed(&mut self) {
+        if let Some(monitor) = &mut self.contact_monitor {
+            monitor.set_mappings(self.contact_mappings.clone());
+        }
+        self.save_contacts();
+    }
+
+    fn save_preset_remote(&mut self) {
+        if let Err(e) = save_config(PRESET_REMOTE_FILE, &self.preset_remote) {
+            self.status = format!("Preset remote save error: {}", e);
+        }
+    }
 
-        let af_card = || card(
-            "AF Helper",
-            column![
-                row![
-                    text("Ref freq (MHz):"),
-                    text_input("98.0", &self.frequency_mhz).on_input(Message::FrequencyChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text("AF list (MHz):"),
-                    text_input("98.0", &self.af_list_text).on_input(Message::AfListChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text("Generate from:"),
-                    text_input("Base", &self.af_base).on_input(Message::AfBaseChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    text_input("Spacing", &self.af_spacing).on_input(Message::AfSpacingChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    text_input("Count", &self.af_count).on_input(Message::AfCountChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    button("Generate")
-                        .on_press(Message::AfGenerate)
-                        .style(theme::Button::Custom(Box::new(GhostButton))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                if let Some(ref warning) = self.af_warning {
-                    text(warning).style(color_accent_warm())
-                } else {
-                    text(" ").style(color_muted())
-                },
-            ],
-        );
+    fn network_changed(&mut self) {
+        if let Err(e) = save_config(NETWORK_FILE, &self.network_sites) {
+            self.status = format!("Network save error: {}", e);
+        }
+    }
 
-        let scrolling_card = || card(
-            "Scrolling",
-            column![
-                row![
-                    checkbox("PS scroll", self.ps_scroll_enabled, Message::PsScrollEnabled),
-                    text_input("BOUZIDFM", &self.ps_scroll_text).on_input(Message::PsScrollTextChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    text(format!("{:.1} cps", self.ps_scroll_cps)),
-                    slider(0.5..=10.0, self.ps_scroll_cps, Message::PsScrollSpeedChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    checkbox("RT scroll", self.rt_scroll_enabled, Message::RtScrollEnabled),
-                    text_input("BOUZIDFM Sidi Bouzid 98.0 MHz", &self.rt_scroll_text).on_input(Message::RtScrollTextChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                    text(format!("{:.1} cps", self.rt_scroll_cps)),
-                    slider(0.5..=10.0, self.rt_scroll_cps, Message::RtScrollSpeedChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-            ],
-        );
+    // The current settings with a site's PI, frequency and AF list, without
+    // this machine's devices.
+    fn site_preset(&self, plan: &SitePlan) -> Preset {
+        let mut preset = self.to_preset();
+        preset.name = plan.name.clone();
+        preset.pi_hex = format!("{:04X}", plan.pi);
+        preset.frequency_mhz = format!("{:.1}", plan.tuned_mhz);
+        preset.af_list_text = af_rows_text(&parse_af_rows(&plan.af_text()));
+        // Each site has its own sound cards.
+        preset.input_device = None;
+        preset.output_device = None;
+        preset
+    }
 
-        let output_card = || card(
-            "Output",
-            column![
-                row![
-                    text(format!("Gain {:.2}x", self.output_gain)),
-                    slider(0.5..=2.0, self.output_gain, Message::GainChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    checkbox("Limiter", self.limiter_enabled, Message::LimiterEnabled),
-                    text(format!("Threshold {:.2}", self.limiter_threshold)),
-                    slider(0.5..=1.0, self.limiter_threshold, Message::LimiterThresholdChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                    text(format!("Lookahead {:.1} ms", self.limiter_lookahead_ms)),
-                    slider(0.5..=10.0, self.limiter_lookahead_ms, Message::LimiterLookaheadChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-            ],
-        );
+    fn automation_changed(&mut self) {
+        if let Err(e) = save_config(AUTOMATION_FILE, &self.automation_rules) {
+            self.status = format!("Automation save error: {}", e);
+        }
+    }
 
-        let levels_card = || card(
-            "Stereo + RDS",
-            column![
-                row![
-                    text(format!("Pilot {:.2}", self.pilot_level)),
-                    slider(0.2..=1.5, self.pilot_level, Message::PilotLevelChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text(format!("RDS {:.2}", self.rds_level)),
-                    slider(0.2..=1.5, self.rds_level, Message::RdsLevelChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                    text(format!("Stereo sep {:.2}", self.stereo_separation)),
-                    slider(0.5..=1.5, self.stereo_separation, Message::StereoSeparationChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-            ],
-        );
+    fn send_alert(&self, text: &str) {
+        if !self.alerts.enabled {
+            return;
+        }
+        if let Some(client) = TelegramClient::new(&self.alerts.telegram_token, &self.alerts.telegram_chat_id) {
+            client.send(&format!("{}: {}", self.ps.trim(), text));
+        }
+    }
 
-        let processing_card = || card(
-            "Processing",
-            column![
-                row![
-                    text("Pre-emphasis:"),
-                    pick_list(self.preemphasis_items.clone(), Some(self.preemphasis_selected.clone()), Message::PreemphasisChanged),
-                    checkbox("Compressor", self.compressor_enabled, Message::CompressorEnabled),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text(format!("Thr {:.1} dB", self.comp_threshold)),
-                    slider(-30.0..=0.0, self.comp_threshold, Message::CompThresholdChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                    text(format!("Ratio {:.1}", self.comp_ratio)),
-                    slider(1.0..=6.0, self.comp_ratio, Message::CompRatioChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text(format!("Attack {:.3}s", self.comp_attack)),
-                    slider(0.001..=0.1, self.comp_attack, Message::CompAttackChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                    text(format!("Release {:.2}s", self.comp_release)),
-                    slider(0.05..=1.0, self.comp_release, Message::CompReleaseChanged).style(theme::Slider::Custom(Box::new(CustomSlider))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-            ],
-        );
+    fn save_alerts(&mut self) {
+        if let Err(e) = save_config(ALERTS_FILE, &self.alerts) {
+            self.status = format!("Alert settings save error: {}", e);
+        }
+    }
 
-        let meter_summary_card = || {
-            card(
-                "Meters",
-                column![
-                    row![
-                        text(format!("RMS {:.2}", self.meter_rms)),
-                        progress_bar(0.0..=1.0, self.meter_rms).style(theme::ProgressBar::Custom(Box::new(CustomProgressBar))),
-                        text(format!("Peak {:.2}", self.meter_peak)),
-                        progress_bar(0.0..=1.0, self.meter_peak).style(theme::ProgressBar::Custom(Box::new(PeakyProgressBar))),
-                    ]
-                    .spacing(10)
-                    .align_items(Alignment::Center),
-                    row![
-                        text(format!("Pilot 19 kHz {:.2}", self.meter_pilot)),
-                        progress_bar(0.0..=1.0, self.meter_pilot).style(theme::ProgressBar::Custom(Box::new(CustomProgressBar))),
-                        text(format!("RDS 57 kHz {:.2}", self.meter_rds)),
-                        progress_bar(0.0..=1.0, self.meter_rds).style(theme::ProgressBar::Custom(Box::new(WarmProgressBar))),
-                    ]
-                    .spacing(10)
-                    .align_items(Alignment::Center),
-                ],
-            )
+    fn save_metadata_filter(&mut self) {
+        if let Err(e) = save_config(METADATA_FILTER_FILE, &self.metadata_filter) {
+            self.status = format!("Metadata filter save error: {}", e);
+        }
+    }
+
+    fn save_contacts(&mut self) {
+        let settings = ContactSettings {
+            port: self.contact_port.clone().unwrap_or_default(),
+            mappings: self.contact_mappings.clone(),
         };
+        if let Err(e) = save_config(CONTACTS_FILE, &settings) {
+            self.status = format!("Contact settings save error: {}", e);
+        }
+    }
 
-        let meters_full = || card_accent(
-            "MPX Meter",
-            column![
-                row![
-                    text(format!("RMS {:.2}", self.meter_rms)).style(color_accent()),
-                    progress_bar(0.0..=1.0, self.meter_rms).style(theme::ProgressBar::Custom(Box::new(CustomProgressBar))),
-                    text(format!("Peak {:.2}", self.meter_peak)).style(color_danger()),
-                    progress_bar(0.0..=1.0, self.meter_peak).style(theme::ProgressBar::Custom(Box::new(PeakyProgressBar))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text("Pilot 19 kHz").style(color_accent_warm()),
-                    progress_bar(0.0..=1.0, self.meter_pilot).style(theme::ProgressBar::Custom(Box::new(WarmProgressBar))),
-                    text("RDS 57 kHz").style(color_accent()),
-                    progress_bar(0.0..=1.0, self.meter_rds).style(theme::ProgressBar::Custom(Box::new(CustomProgressBar))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text(format!("XRuns {}", self.xrun_count)),
-                    text(format!("Buffer {:.0}%", (self.buffer_fill * 100.0).clamp(0.0, 100.0))),
-                    text(format!("Latency {:.1} ms", self.latency_ms)),
-                ]
-                .spacing(14)
-                .align_items(Alignment::Center),
-                row![
-                    text("Spectrum (dB):"),
-                    Canvas::new(SpectrumView {
-                        spectrum_peak_db: self.spectrum_peak_db.clone(),
-                        spectrum_avg_db: self.spectrum_avg_db.clone(),
-                    })
-                    .width(Length::Fill)
-                    .height(200),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text("Scope:"),
-                    Canvas::new(ScopeView { samples: self.scope_samples.clone(), prev: self.scope_prev.clone() })
-                        .width(Length::Fill)
-                        .height(180),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-            ],
-        );
+    fn serve_status(&mut self) {
+        let Some(server) = &self.status_server else {
+            return;
+        };
+        let commands = server.take_commands();
+        for command in commands {
+            match command {
+                RemoteCommand::Live(toggle) => {
+                    let message = if toggle.apply(self.engine.is_some()) {
+                        Message::StartStream
+                    } else {
+                        Message::StopStream
+                    };
+                    let _ = self.dispatch_internal(message);
+                }
+                RemoteCommand::Ta(toggle) => {
+                    self.ta = toggle.apply(self.ta);
+                    if let Some(engine) = &self.engine {
+                        engine.update_ta(self.ta);
+                    }
+                }
+                RemoteCommand::TxMute(toggle) => {
+                    self.tx_mute = toggle.apply(self.tx_mute);
+                    if let Some(engine) = &self.engine {
+                        engine.update_tx_mute(self.tx_mute);
+                    }
+                }
+                RemoteCommand::SetValue { name, value } => {
+                    self.macro_values.set(&name, &value);
+                    self.refresh_macro_texts(RdsChangeSource::Remote);
+                }
+            }
+        }
+        if let Some(server) = &self.status_server {
+            server.update(EncoderStatus {
+                live: self.engine.is_some(),
+                ta: self.ta,
+                tx_mute: self.tx_mute,
+                ps: self.expand_text(&self.ps),
+                rt: self.expand_text(&self.rt),
+            });
+        }
+    }
 
-        let export_card = || card(
-            "WAV Export",
-            column![
-                row![
-                    text("Duration (sec):"),
-                    text_input("10", &self.duration).on_input(Message::DurationChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text("Audio WAV (optional):"),
-                    text_input("", &self.audio_path).on_input(Message::AudioChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                row![
-                    text("Output WAV:"),
-                    text_input("mpx.wav", &self.output_path).on_input(Message::OutputChanged).style(theme::TextInput::Custom(Box::new(CustomTextInput))),
-                ]
-                .spacing(10)
-                .align_items(Alignment::Center),
-                if self.generating {
-                    button("Generating...")
-                        .padding(10)
-                        .style(theme::Button::Custom(Box::new(GhostButton)))
-                } else {
-                    button("Generate")
-                        .on_press(Message::Generate)
-                        .padding(10)
-                        .style(theme::Button::Custom(Box::new(PrimaryButton)))
-                },
-            ],
-        );
+    fn poll_contacts(&mut self) {
+        let Some(monitor) = &mut self.contact_monitor else {
+            return;
+        };
+        let events = match monitor.poll() {
+            Ok(events) => events,
+            Err(e) => {
+                self.status = format!("Contact closure error: {}", e);
+                self.contact_monitor = None;
+                return;
+            }
+        };
+        for event in events {
+            match event {
+                ContactEvent::Ta(v) => {
+                    self.ta = v;
+                    if let Some(engine) = &self.engine {
+                        engine.update_ta(self.ta);
+                    }
+                }
+                ContactEvent::TxMute(v) => {
+                    self.tx_mute = v;
+                    if let Some(engine) = &self.engine {
+                        engine.update_tx_mute(self.tx_mute);
+                    }
+                }
+                ContactEvent::Preset(name) => {
+                    if let Some(p) = self.presets.iter().find(|p| p.name == name).cloned() {
+                        self.apply_preset(p);
+                        self.preset_selected = Some(name);
+                    } else {
+                        self.status = format!("Contact closure: preset \"{}\" not found", name);
+                    }
+                }
+            }
+        }
+    }
+
+    fn collect_rds_history(&mut self) {
+        if let Some(engine) = &self.engine {
+            self.rds_history.extend(engine.take_rds_history());
+        }
+        if self.rds_history.len() > RDS_HISTORY_LIMIT {
+            let excess = self.rds_history.len() - RDS_HISTORY_LIMIT;
+            self.rds_history.drain(..excess);
+        }
+    }
+
+    fn refresh_devices(&mut self) {
+        match list_input_devices() {
+            Ok(devices) => {
+                if self.selected_input.is_none() && !devices.is_empty() {
+                    self.selected_input = Some(devices[0].clone());
+                }
+                self.input_devices = devices;
+            }
+            Err(e) => {
+                self.status = format!("Input device error: {}", e);
+            }
+        }
 
-        let about_tab = column![
-            card(
-                "About Pulse FM",
-                column![
-                    text("Pulse FM RDS Encoder is a cross‑platform MPX generator with RDS tools and a live audio pipeline.").style(color_muted()),
-                    text("Use it to generate MPX for analysis, streaming to 192 kHz devices, or offline WAV export.").style(color_muted()),
-                    text("It does not transmit RF and is intended for baseband audio only.").style(color_muted()),
-                ]
-                .spacing(6),
-            ),
-            card(
-                "Developer",
-                column![
-                    text("Hsouna Zinoubi").style(color_text()),
-                    text("BOUZIDFM").style(color_muted()),
-                    text("imhsouna@gmail.com").style(color_muted()),
-                ]
-                .spacing(4),
-            ),
-            card(
-                "Quick Tips",
-                column![
-                    text("• Select an output device that supports 192 kHz float32.").style(color_muted()),
-                    text("• Use the Audio tab to refresh device lists.").style(color_muted()),
+        match list_output_devices() {
+            Ok(devices) => {
+                if self.selected_output.is_none() && !devices.is_empty() {
+                    self.selected_output = Some(devices[0].clone());
+                }
+                self.output_devices = devices;
+            }
+            Err(e) => {
+                self.status = format!("Output device error: {}", e);
+            }
+        }
+    }
 
-                ]
-                .spacing(4),
-            ),
-        ]
-        .spacing(16)
-        .width(Length::Fill);
+    fn test_sequence(&self) -> TestSequence {
+        TestSequence {
+            steps: TestStep::ALL.iter().copied().zip(self.test_step_secs).collect(),
+        }
+    }
 
-        let compact = self.window_width < 980.0;
+    // Inbound values (pushed and fetched) go through the metadata filter; the
+    // song history is filled from the filtered `%song%`.
+    fn expand_text(&self, template: &str) -> String {
+        let filter = &self.metadata_filter;
+        let pushed = Filtered { inner: &self.macro_values, filter };
+        let fetched = self.provider_feeds.as_ref().map(|feeds| Filtered { inner: feeds, filter });
+        let mut providers: Vec<&dyn ValueProvider> = vec![&Clock, &pushed, &self.song_history];
+        if let Some(fetched) = &fetched {
+            providers.push(fetched);
+        }
+        expand(template, &providers)
+    }
 
-        let status_pill = if self.engine.is_some() {
-            pill("● LIVE", color_live(), Color::from_rgb8(6, 24, 19))
-        } else {
-            pill("○ IDLE", color_surface_alt(), color_muted())
-        };
+    fn restart_provider_feeds(&mut self) {
+        self.provider_feeds = None;
+        if !self.data_providers.is_empty() {
+            self.provider_feeds = Some(ProviderFeeds::start(&self.data_providers));
+        }
+    }
 
-        let status_text = text(&self.status).style(color_muted());
+    // PS, RT, PS scroll and RT scroll with their templates expanded.
+    fn on_air_texts(&self) -> [String; 4] {
+        [&self.ps, self.rt_template(), &self.ps_scroll_text, &self.rt_scroll_text].map(|t| self.expand_text(t))
+    }
 
-        let hero = container(
-            row![
-                column![
-                    row![
-                        text("Pulse FM").size(32).style(color_text()),
-                        text("FM").size(32).style(rgba8f(56, 189, 248, 0.6)),
-                    ]
-                    .spacing(2)
-                    .align_items(Alignment::Center),
-                    text("RDS Encoder Studio").size(16).style(rgba8f(168, 85, 247, 0.7)),
-                    text("Live MPX pipeline • 192 kHz • FM/RDS broadcast tools").size(12).style(color_muted()),
-                ]
-                .spacing(4)
-                .width(Length::FillPortion(3)),
-                column![
-                    row![
-                        status_pill,
-                        status_text,
-                    ]
-                    .spacing(10)
-                    .align_items(Alignment::Center),
-                    row![
-                        container(
-                            row![
-                                text(format!("{}", self.xrun_count)).size(13).style(color_accent()),
-                                text("XRuns").size(11).style(color_muted()),
-                            ]
-                            .spacing(4)
-                            .align_items(Alignment::Center),
-                        )
-                        .padding([4, 10])
-                        .style(theme::Container::Custom(Box::new(MetricPill))),
-                        text("|").size(11).style(rgba8f(255, 255, 255, 0.06)),
-                        container(
-                            row![
-                                text(format!("{:.0}%", (self.buffer_fill * 100.0).clamp(0.0, 100.0))).size(13).style(color_accent_warm()),
-                                text("Buf").size(11).style(color_muted()),
-                            ]
-                            .spacing(4)
-                            .align_items(Alignment::Center),
-                        )
-                        .padding([4, 10])
-                        .style(theme::Container::Custom(Box::new(MetricPill))),
-                        text("|").size(11).style(rgba8f(255, 255, 255, 0.06)),
-                        container(
-                            row![
-                                text(format!("{:.1}", self.latency_ms)).size(13).style(color_live()),
-                                text("ms").size(11).style(color_muted()),
-                            ]
-                            .spacing(4)
-                            .align_items(Alignment::Center),
-                        )
-                        .padding([4, 10])
-                        .style(theme::Container::Custom(Box::new(MetricPill))),
-                    ]
-                    .spacing(0)
-                    .align_items(Alignment::Center),
-                ]
-                .spacing(8)
-                .width(Length::FillPortion(2)),
-            ]
-            .spacing(24)
-            .align_items(Alignment::Center),
-        )
-        .padding(20)
-        .width(Length::Fill)
-        .style(theme::Container::from(hero_style));
+    // With the rotation on, RT alternates between the station text and the
+    // "previously played" text, one interval each, once there is a
+    // previous title.
+    fn rt_template(&self) -> &String {
+        let interval = self.previously_interval.trim().parse::<i64>().unwrap_or(30).max(5);
+        let rotated = self.previously_rt
+            && self.song_history.previous().next().is_some()
+            && chrono::Local::now().timestamp() / interval % 2 == 1;
+        if rotated {
+            &self.previously_rt_text
+        } else {
+            &self.rt
+        }
+    }
 
-        let body: Element<'_, Message> = match self.tab_selected {
-            Tab::Dashboard => {
-                if compact {
-                    column![
-                        stream_card(),
-                        device_card(),
-                        presets_card(),
-                        station_card(),
-                        meter_summary_card(),
-                    ]
-                    .spacing(16)
-                    .into()
-                } else {
-                    column![
-                        row![
-                            column![stream_card(), device_card(), presets_card()].spacing(16).width(Length::FillPortion(2)),
-                            column![station_card(), meter_summary_card()].spacing(16).width(Length::FillPortion(3)),
-                        ]
-                        .spacing(16)
-                        .align_items(Alignment::Start),
-                    ]
-                    .into()
-                }
+    fn ps_alternates(&self) -> Vec<String> {
+        self.ps_alt_list_text
+            .split('|')
+            .map(|s| self.expand_text(s.trim()))
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    // Re-expands the templates so clock and pushed values stay current on
+    // air. Only changed texts go to the engine; alternates follow on the
+    // next Apply.
+    fn refresh_macro_texts(&mut self, source: RdsChangeSource) {
+        let song = self.expand_text("%song%");
+        self.song_history.push(&song);
+        let texts = self.on_air_texts();
+        if texts == self.macro_sent {
+            return;
+        }
+        if let Some(engine) = &self.engine {
+            let [ps, rt, ps_scroll, rt_scroll] = &texts;
+            if *ps != self.macro_sent[0] {
+                engine.update_ps(ps, source);
             }
-            Tab::Audio => {
-                if compact {
-                    column![
-                        device_card(),
-                        stream_card(),
-                        health_card,
-                        meter_summary_card(),
-                    ]
-                    .spacing(16)
-                    .into()
-                } else {
-                    column![
-                        row![
-                            column![device_card(), stream_card(), health_card].spacing(16).width(Length::FillPortion(3)),
-                            column![meter_summary_card()].spacing(16).width(Length::FillPortion(2)),
-                        ]
-                        .spacing(16)
-                        .align_items(Alignment::Start),
-                    ]
-                    .into()
-                }
+            if *rt != self.macro_sent[1] {
+                engine.update_rt(rt, source);
             }
-            Tab::Rds => {
-                if compact {
-                    column![
-                        station_card(),
-                        rds_identity_card(),
-                        rds_schedule_card(),
-                        af_card(),
-                        scrolling_card(),
-                    ]
-                    .spacing(16)
-                    .into()
-                } else {
-                    column![
-                        row![
-                            column![station_card(), rds_identity_card()].spacing(16).width(Length::FillPortion(3)),
-                            column![rds_schedule_card(), af_card(), scrolling_card()].spacing(16).width(Length::FillPortion(2)),
-                        ]
-                        .spacing(16)
-                        .align_items(Alignment::Start),
-                    ]
-                    .into()
-                }
+            if *ps_scroll != self.macro_sent[2] {
+                engine.update_ps_scroll(self.ps_scroll_enabled, ps_scroll, self.ps_scroll_cps);
             }
-            Tab::Processing => {
-                if compact {
-                    column![output_card(), levels_card(), processing_card()]
-                        .spacing(16)
-                        .into()
-                } else {
-                    column![
-                        row![
-                            column![output_card(), levels_card()].spacing(16).width(Length::FillPortion(3)),
-                            column![processing_card()].spacing(16).width(Length::FillPortion(2)),
-                        ]
-                        .spacing(16)
-                        .align_items(Alignment::Start),
-                    ]
-                    .into()
-                }
+            if *rt_scroll != self.macro_sent[3] {
+                engine.update_rt_scroll(self.rt_scroll_enabled, rt_scroll, self.rt_scroll_cps);
             }
-            Tab::Meters => meters_full().into(),
-            Tab::Export => export_card().into(),
-            Tab::About => about_tab.into(),
-        };
+        }
+        self.macro_sent = texts;
+    }
+
+    fn macro_preview(&self) -> String {
+        let [ps, rt, ..] = self.on_air_texts();
+        if ps == self.ps && rt == self.rt {
+            return String::new();
+        }
+        format!("On air: PS \"{}\", RT \"{}\"", ps, rt)
+    }
+
+    fn engine_settings(&self) -> EngineSettings {
+        EngineSettings {
+            pi: parse_pi(&self.pi_hex).ok(),
+            ecc: parse_hex_byte(&self.ecc_hex),
+            lic: parse_hex_byte(&self.lic_hex),
+            code_table: self.code_table,
+            ps: self.expand_text(&self.ps),
+            rt: self.expand_text(&self.rt),
+            tp: self.tp,
+            ta: self.ta,
+            pty: self.on_air_pty(),
+            ms: self.ms,
+            di: self.di_bits(),
+            ab: self.ab_flag,
+            ab_auto: self.ab_auto,
+            ct_enabled: self.ct_enabled,
+            rate_guard: self.rate_guard,
+            ps_scroll_enabled: self.ps_scroll_enabled,
+            ps_scroll_text: self.expand_text(&self.ps_scroll_text),
+            ps_scroll_cps: self.ps_scroll_cps,
+            ps_scroll_mode: self.ps_scroll_mode,
+            ps_page_dwell_secs: self.ps_page_dwell,
+            ps_page_center: self.ps_page_center,
+            rt_scroll_enabled: self.rt_scroll_enabled,
+            rt_scroll_text: self.expand_text(&self.rt_scroll_text),
+            rt_scroll_cps: self.rt_scroll_cps,
+            rt_overflow: self.rt_overflow,
+            rt_ellipsis: self.rt_ellipsis.clone(),
+            rt_part_secs: self.rt_part_secs,
+            output_gain: db_to_linear(self.output_gain_db),
+            limiter_enabled: self.limiter_enabled,
+            limiter_threshold: db_to_linear(self.limiter_threshold_db),
+            limiter_lookahead: ((self.limiter_lookahead_ms / 1000.0) * 228000.0) as usize,
+            pilot_level: self.pilot_level,
+            pilot_phase_deg: self.pilot_phase_deg,
+            rds_level: self.rds_level,
+            stereo_separation: self.stereo_separation,
+            lpf_cutoff_hz: self.lpf_cutoff_hz,
+            pilot_notch: self.pilot_notch,
+            preemphasis_tau: preemph_to_tau(self.preemphasis_selected.clone()),
+            compressor_enabled: self.compressor_enabled,
+            comp_threshold_db: self.comp_threshold,
+            comp_ratio: self.comp_ratio,
+            comp_attack: self.comp_attack,
+            comp_release: self.comp_release,
+            comp_makeup_db: self.comp_makeup,
+            group_0a: self.group_0a.trim().parse::<usize>().unwrap_or(4),
+            group_2a: self.group_2a.trim().parse::<usize>().unwrap_or(1),
+            group_4a: self.group_4a.trim().parse::<usize>().unwrap_or(0),
+            ct_interval_groups: self.ct_interval_groups.trim().parse::<usize>().unwrap_or(0),
+            fast_tuning: self.fast_tuning,
+            ps_alt_list: self.ps_alternates(),
+            ps_alt_interval: self.ps_alt_interval.trim().parse::<usize>().unwrap_or(0),
+        }
+    }
+
+    fn generate_config(&self, duration: f32, audio_path: Option<String>, pi: u16) -> GenerateConfig {
+        GenerateConfig {
+            duration_secs: duration,
+            audio_path,
+            ps: self.expand_text(&self.ps),
+            rt: self.expand_text(&self.rt),
+            code_table: self.code_table,
+            translit: self.translit.clone(),
+            pi,
+            ecc: parse_hex_byte(&self.ecc_hex),
+            lic: parse_hex_byte(&self.lic_hex),
+            tp: self.tp,
+            ta: self.ta,
+            pty: self.on_air_pty(),
+            ms: self.ms,
+            di: self.di_bits(),
+            ab: self.ab_flag,
+            ab_auto: self.ab_auto,
+            ct_enabled: self.ct_enabled,
+            af_list_mhz: validate_af_rows(&self.af_rows).0,
+            af_regional_mhz: validate_af_rows(&self.af_rows).1,
+            af_tuned_mhz: self.frequency_mhz.trim().parse::<f32>().ok(),
+            ps_scroll_enabled: self.ps_scroll_enabled,
+            ps_scroll_text: self.expand_text(&self.ps_scroll_text),
+            ps_scroll_cps: self.ps_scroll_cps,
+            ps_scroll_mode: self.ps_scroll_mode,
+            ps_page_dwell_secs: self.ps_page_dwell,
+            ps_page_center: self.ps_page_center,
+            rt_scroll_enabled: self.rt_scroll_enabled,
+            rt_scroll_text: self.expand_text(&self.rt_scroll_text),
+            rt_scroll_cps: self.rt_scroll_cps,
+            rt_overflow: self.rt_overflow,
+            rt_ellipsis: self.rt_ellipsis.clone(),
+            rt_part_secs: self.rt_part_secs,
+            rate_guard: self.rate_guard,
+            output_gain: db_to_linear(self.output_gain_db),
+            limiter_enabled: self.limiter_enabled,
+            limiter_threshold: db_to_linear(self.limiter_threshold_db),
+            limiter_lookahead: ((self.limiter_lookahead_ms / 1000.0) * 228000.0) as usize,
+            pilot_level: self.pilot_level,
+            pilot_phase_deg: self.pilot_phase_deg,
+            rds_level: self.rds_level,
+            stereo_separation: self.stereo_separation,
+            preemphasis_tau: preemph_to_tau(self.preemphasis_selected.clone()),
+            lpf_cutoff_hz: self.lpf_cutoff_hz,
+            pilot_notch: self.pilot_notch,
+            compressor_enabled: self.compressor_enabled,
+            comp_threshold_db: self.comp_threshold,
+            comp_ratio: self.comp_ratio,
+            comp_attack: self.comp_attack,
+            comp_release: self.comp_release,
+            comp_makeup_db: self.comp_makeup,
+            group_0a: self.group_0a.trim().parse::<usize>().unwrap_or(4),
+            group_2a: self.group_2a.trim().parse::<usize>().unwrap_or(1),
+            group_4a: self.group_4a.trim().parse::<usize>().unwrap_or(0),
+            ct_interval_groups: self.ct_interval_groups.trim().parse::<usize>().unwrap_or(0),
+            fast_tuning: self.fast_tuning,
+            ps_alt_list: self.ps_alternates(),
+            ps_alt_interval: self.ps_alt_interval.trim().parse::<usize>().unwrap_or(0),
+            impairments: self.impairments(),
+            program_feed: Some(self.program_feed_path.trim())
+                .filter(|path| !path.is_empty())
+                .map(|path| ProgramFeed {
+                    path: path.to_string(),
+                    delay_ms: self.program_feed_delay.trim().parse::<f32>().unwrap_or(0.0),
+                }),
+            group_schedule: Some(self.group_schedule_path.trim())
+                .filter(|path| !path.is_empty())
+                .map(str::to_string),
+        }
+    }
 
-        let content = column![
-            hero,
-            tabs,
-            body,
-        ]
-        .spacing(18)
-        .padding(24)
-        .width(Length::Fill);
+    fn impairments(&self) -> Impairments {
+        let [snr, ripple, delay, tilt] = &self.impairment_inputs;
+        Impairments {
+            noise_snr_db: snr.trim().parse::<f32>().ok(),
+            ripple_db: ripple.trim().parse::<f32>().unwrap_or(0.0),
+            ripple_delay_us: delay.trim().parse::<f32>().unwrap_or(DEFAULT_RIPPLE_DELAY_US),
+            group_delay_tilt_us: tilt.trim().parse::<f32>().unwrap_or(0.0),
+        }
+    }
 
-        let scroll = scrollable(content)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .style(theme::Scrollable::Custom(Box::new(HiddenScrollbar)));
+    // Remote control, contact closures and timers act on behalf of the
+    // station, so they bypass the operator lock.
+    fn dispatch_internal(&mut self, message: Message) -> Command<Message> {
+        let locked = std::mem::replace(&mut self.locked, false);
+        let command = iced::Application::update(self, message);
+        self.locked = locked;
+        command
+    }
 
-        container(scroll)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .style(theme::Container::from(body_style))
-            .into()
+    fn silence_alarm_active(&self) -> bool {
+        self.silence_enabled && self.engine.is_some() && (self.program_silence.is_alarmed() || self.mpx_silence.is_alarmed())
     }
-}
 
-impl App {
-    fn di_bits(&self) -> u8 {
-        let mut bits = 0u8;
-        if self.di_stereo {
-            bits |= 0b1000;
-        }
-        if self.di_artificial {
-            bits |= 0b0100;
-        }
-        if self.di_compressed {
-            bits |= 0b0010;
-        }
-        if self.di_dynamic {
-            bits |= 0b0001;
+    fn set_tx_mute(&mut self, mute: bool) {
+        self.tx_mute = mute;
+        if let Some(engine) = &self.engine {
+            engine.update_tx_mute(self.tx_mute);
         }
-        bits
     }
 
-    fn refresh_devices(&mut self) {
-        match list_input_devices() {
-            Ok(devices) => {
-                if self.selected_input.is_none() && !devices.is_empty() {
-                    self.selected_input = Some(devices[0].clone());
+    fn set_standby_on_air(&mut self, on_air: bool) {
+        self.sync_on_air = on_air;
+        self.set_tx_mute(!on_air);
+    }
+
+    fn serve_sync(&mut self) {
+        let Some(link) = self.sync_link.as_mut() else {
+            return;
+        };
+        let messages = link.poll();
+        match self.sync_role {
+            SyncRole::Off => {}
+            SyncRole::Main => {
+                let due = self
+                    .sync_last_sent
+                    .map(|t| t.elapsed() >= Duration::from_secs(1))
+                    .unwrap_or(true);
+                if due {
+                    let on_air = self.engine.is_some() && !self.tx_mute;
+                    // The standby has its own sound cards.
+                    let preset = Preset {
+                        input_device: None,
+                        output_device: None,
+                        ..self.to_preset()
+                    };
+                    let state = serde_json::to_string(&preset).unwrap_or_default();
+                    if let Some(link) = self.sync_link.as_mut() {
+                        let _ = link.send_state(on_air, &state);
+                    }
+                    self.sync_last_sent = Some(Instant::now());
+                }
+                if messages.contains(&SyncMessage::Takeover) {
+                    self.set_tx_mute(true);
+                    self.status = "Standby took over output".to_string();
                 }
-                self.input_devices = devices;
             }
-            Err(e) => {
-                self.status = format!("Input device error: {}", e);
+            SyncRole::Standby => {
+                let timeout = self.sync_timeout_secs.trim().parse::<f32>().unwrap_or(5.0).max(1.0);
+                let lost = self
+                    .sync_link
+                    .as_ref()
+                    .map(|l| l.heartbeat_lost(Duration::from_secs_f32(timeout)))
+                    .unwrap_or(false);
+                for message in messages {
+                    match message {
+                        SyncMessage::State { on_air, state, .. } => {
+                            if state != self.sync_last_state {
+                                if let Ok(preset) = serde_json::from_str::<Preset>(&state) {
+                                    self.apply_preset_from(preset, RdsChangeSource::Sync);
+                                }
+                                self.sync_last_state = state;
+                            }
+                            if on_air && self.sync_on_air {
+                                self.set_standby_on_air(false);
+                                self.status = "Main is back on air, standby released output".to_string();
+                            }
+                        }
+                        SyncMessage::Handover => {
+                            self.set_standby_on_air(true);
+                            self.status = "Main handed over output to standby".to_string();
+                        }
+                        SyncMessage::Takeover => {}
+                        SyncMessage::Start { at_unix_us } => {
+                            if self.sync_aligned_start {
+                                self.start_aligned(start_time(at_unix_us));
+                            }
+                        }
+                    }
+                }
+                if lost && !self.sync_on_air {
+                    if let Some(link) = &self.sync_link {
+                        let _ = link.send(&SyncMessage::Takeover);
+                    }
+                    self.set_standby_on_air(true);
+                    self.status = format!("Main heartbeat lost for {:.0} s, standby took over output", timeout);
+                }
             }
         }
+    }
 
-        match list_output_devices() {
-            Ok(devices) => {
-                if self.selected_output.is_none() && !devices.is_empty() {
-                    self.selected_output = Some(devices[0].clone());
-                }
-                self.output_devices = devices;
-            }
-            Err(e) => {
-                self.status = format!("Output device error: {}", e);
+    // Restarts the stream on the main's trigger so the pilot of this engine
+    // starts on the same sample. The restart is immediate, without a fade, and
+    // goes through in operator mode since the engineer set up the link.
+    fn start_aligned(&mut self, at: SystemTime) {
+        if at < SystemTime::now() {
+            self.status = "Aligned start trigger arrived too late, stream left running".to_string();
+            return;
+        }
+        let locked = std::mem::replace(&mut self.locked, false);
+        if self.engine.is_some() {
+            self.stopping = true;
+            let _ = self.update(Message::StopStream);
+        }
+        self.sync_start_at = Some(at);
+        let _ = self.update(Message::StartStream);
+        self.sync_start_at = None;
+        self.locked = locked;
+    }
+
+    fn sync_label(&self) -> String {
+        let Some(link) = &self.sync_link else {
+            return "Standby mirrors RDS and processing settings of the main over UDP".to_string();
+        };
+        let heard = match link.last_heard() {
+            Some(d) => format!("peer heard {:.1} s ago", d.as_secs_f32()),
+            None => "peer not heard yet".to_string(),
+        };
+        match self.sync_role {
+            SyncRole::Standby if self.sync_on_air => format!("Standby ON AIR, {}", heard),
+            SyncRole::Standby => format!("Standby muted, mirroring main, {}", heard),
+            _ => format!("{}, {}", self.sync_role, heard),
+        }
+    }
+
+    // Takes over what can change without a restart: the preset list, the
+    // live preset and the audio devices where they exist on this machine.
+    fn apply_imported(&mut self, current: &serde_json::Value) {
+        if let Ok(presets) = load_config::<Vec<Preset>>(PRESETS_FILE) {
+            if !presets.iter().any(|p| Some(&p.name) == self.preset_selected.as_ref()) {
+                self.preset_selected = None;
             }
+            self.presets = presets;
+        }
+        if let Ok(preset) = serde_json::from_value::<Preset>(current["preset"].clone()) {
+            self.apply_preset(preset);
+        }
+        if let Some(device) = current["input_device"].as_str().filter(|d| self.input_devices.iter().any(|i| i == d)) {
+            self.selected_input = Some(device.to_string());
+        }
+        if let Some(device) = current["output_device"].as_str().filter(|d| self.output_devices.iter().any(|o| o == d)) {
+            self.selected_output = Some(device.to_string());
         }
     }
 
@@ -1899,6 +6743,12 @@ impl App {
             name: self.preset_name.clone(),
             ps: self.ps.clone(),
             rt: self.rt.clone(),
+            code_table: self.code_table.to_string(),
+            transliterate: self.transliterate,
+            ecc_hex: self.ecc_hex.clone(),
+            lic_hex: self.lic_hex.clone(),
+            alarm: self.alarm,
+            translit_table_path: self.translit_table_path.clone(),
             pi_hex: self.pi_hex.clone(),
             tp: self.tp,
             ta: self.ta,
@@ -1908,40 +6758,72 @@ impl App {
             ab: self.ab_flag,
             ab_auto: self.ab_auto,
             ct_enabled: self.ct_enabled,
-            af_list_text: self.af_list_text.clone(),
+            af_list_text: af_rows_text(&self.af_rows),
+            frequency_mhz: if self.preset_include_frequency { self.frequency_mhz.clone() } else { String::new() },
+            input_device: self.selected_input.clone().filter(|_| self.preset_include_devices),
+            output_device: self.selected_output.clone().filter(|_| self.preset_include_devices),
             ps_scroll_enabled: self.ps_scroll_enabled,
             ps_scroll_text: self.ps_scroll_text.clone(),
             ps_scroll_cps: self.ps_scroll_cps,
+            ps_scroll_mode: self.ps_scroll_mode.to_string(),
+            ps_page_dwell: self.ps_page_dwell,
+            ps_page_center: self.ps_page_center,
             rt_scroll_enabled: self.rt_scroll_enabled,
             rt_scroll_text: self.rt_scroll_text.clone(),
             rt_scroll_cps: self.rt_scroll_cps,
-            output_gain: self.output_gain,
+            rt_overflow: self.rt_overflow.to_string(),
+            rt_ellipsis: self.rt_ellipsis.clone(),
+            rt_part_secs: self.rt_part_secs,
+            rate_guard: self.rate_guard,
+            output_gain: Some(db_to_linear(self.output_gain_db)),
+            output_gain_db: Some(self.output_gain_db),
             limiter_enabled: self.limiter_enabled,
-            limiter_threshold: self.limiter_threshold,
+            limiter_threshold: Some(db_to_linear(self.limiter_threshold_db)),
+            limiter_threshold_db: Some(self.limiter_threshold_db),
             limiter_lookahead_ms: self.limiter_lookahead_ms,
             pilot_level: self.pilot_level,
+            pilot_phase_deg: self.pilot_phase_deg,
             rds_level: self.rds_level,
             stereo_separation: self.stereo_separation,
             preemphasis: self.preemphasis_selected.to_string(),
+            lpf_cutoff_hz: self.lpf_cutoff_hz,
+            pilot_notch: self.pilot_notch,
             compressor_enabled: self.compressor_enabled,
             comp_threshold: self.comp_threshold,
             comp_ratio: self.comp_ratio,
             comp_attack: self.comp_attack,
             comp_release: self.comp_release,
+            comp_makeup: self.comp_makeup,
             group_0a: self.group_0a.clone(),
             group_2a: self.group_2a.clone(),
             group_4a: self.group_4a.clone(),
             ct_interval_groups: self.ct_interval_groups.clone(),
+            fast_tuning: self.fast_tuning.to_string(),
             ps_alt_list_text: self.ps_alt_list_text.clone(),
             ps_alt_interval: self.ps_alt_interval.clone(),
+            previously_rt: self.previously_rt,
+            previously_rt_text: self.previously_rt_text.clone(),
+            previously_interval: self.previously_interval.clone(),
+            song_history_len: self.song_history_len.clone(),
         }
     }
 
     fn apply_preset(&mut self, p: Preset) {
+        self.apply_preset_from(p, RdsChangeSource::Manual);
+    }
+
+    // `source` is what the PS/RT change is logged as in the RDS history.
+    fn apply_preset_from(&mut self, p: Preset, source: RdsChangeSource) {
         self.preset_name = p.name.clone();
         self.ps = p.ps;
         self.rt = p.rt;
         self.pi_hex = p.pi_hex;
+        self.code_table = self
+            .code_table_items
+            .iter()
+            .copied()
+            .find(|t| t.to_string() == p.code_table)
+            .unwrap_or(RdsCodeTable::E1);
         self.tp = p.tp;
         self.ta = p.ta;
         self.ms = p.ms;
@@ -1951,18 +6833,60 @@ impl App {
         self.ab_flag = p.ab;
         self.ab_auto = p.ab_auto;
         self.ct_enabled = p.ct_enabled;
-        self.af_list_text = p.af_list_text;
+        self.af_rows = parse_af_rows(&p.af_list_text);
+        // A preset keeps its own choice of what it carries for the next save.
+        self.preset_include_frequency = !p.frequency_mhz.trim().is_empty();
+        if self.preset_include_frequency {
+            self.frequency_mhz = p.frequency_mhz;
+        }
+        self.preset_include_devices = p.input_device.is_some() || p.output_device.is_some();
+        let mut missing = Vec::new();
+        let mut devices_changed = false;
+        for (wanted, available, selected) in [
+            (p.input_device, &self.input_devices, &mut self.selected_input),
+            (p.output_device, &self.output_devices, &mut self.selected_output),
+        ] {
+            let Some(device) = wanted else {
+                continue;
+            };
+            if !available.contains(&device) {
+                missing.push(device);
+            } else if selected.as_ref() != Some(&device) {
+                *selected = Some(device);
+                devices_changed = true;
+            }
+        }
+        if !missing.is_empty() {
+            self.status = format!("Preset device not found: {}", missing.join(", "));
+        } else if devices_changed && self.engine.is_some() {
+            self.status = "Restart the stream to change the devices".to_string();
+        }
         self.ps_scroll_enabled = p.ps_scroll_enabled;
         self.ps_scroll_text = p.ps_scroll_text;
         self.ps_scroll_cps = p.ps_scroll_cps;
+        self.ps_scroll_mode = match p.ps_scroll_mode.as_str() {
+            "Word pages" => PsScrollMode::Paging,
+            _ => PsScrollMode::Character,
+        };
+        self.ps_page_dwell = p.ps_page_dwell;
+        self.ps_page_center = p.ps_page_center;
         self.rt_scroll_enabled = p.rt_scroll_enabled;
         self.rt_scroll_text = p.rt_scroll_text;
         self.rt_scroll_cps = p.rt_scroll_cps;
-        self.output_gain = p.output_gain;
+        self.rt_overflow = match p.rt_overflow.as_str() {
+            "Hard cut" => RtOverflow::Cut,
+            "Two parts" => RtOverflow::Rotate,
+            _ => RtOverflow::Ellipsis,
+        };
+        self.rt_ellipsis = p.rt_ellipsis;
+        self.rt_part_secs = p.rt_part_secs;
+        self.rate_guard = p.rate_guard;
+        self.output_gain_db = p.output_gain_db.or(p.output_gain.map(linear_to_db)).unwrap_or(0.0);
         self.limiter_enabled = p.limiter_enabled;
-        self.limiter_threshold = p.limiter_threshold;
+        self.limiter_threshold_db = p.limiter_threshold_db.or(p.limiter_threshold.map(linear_to_db)).unwrap_or(-0.5);
         self.limiter_lookahead_ms = p.limiter_lookahead_ms;
         self.pilot_level = p.pilot_level;
+        self.pilot_phase_deg = p.pilot_phase_deg;
         self.rds_level = p.rds_level;
         self.stereo_separation = p.stereo_separation;
         self.preemphasis_selected = match p.preemphasis.as_str() {
@@ -1970,55 +6894,43 @@ impl App {
             "75 µs" => Preemphasis::Us75,
             _ => Preemphasis::Off,
         };
+        self.lpf_cutoff_hz = p.lpf_cutoff_hz;
+        self.pilot_notch = p.pilot_notch;
         self.compressor_enabled = p.compressor_enabled;
         self.comp_threshold = p.comp_threshold;
         self.comp_ratio = p.comp_ratio;
         self.comp_attack = p.comp_attack;
         self.comp_release = p.comp_release;
+        self.comp_makeup = p.comp_makeup;
         self.group_0a = p.group_0a;
         self.group_2a = p.group_2a;
         self.group_4a = p.group_4a;
         self.ct_interval_groups = p.ct_interval_groups;
+        self.fast_tuning = match p.fast_tuning.as_str() {
+            "While TA" => FastTuning::WhileTa,
+            "Always" => FastTuning::Always,
+            _ => FastTuning::Off,
+        };
         self.ps_alt_list_text = p.ps_alt_list_text;
         self.ps_alt_interval = p.ps_alt_interval;
+        self.previously_rt = p.previously_rt;
+        self.previously_rt_text = p.previously_rt_text;
+        self.previously_interval = p.previously_interval;
+        self.song_history_len = p.song_history_len;
+        if let Ok(len) = self.song_history_len.trim().parse::<usize>() {
+            self.song_history.set_capacity(len);
+        }
+        self.transliterate = p.transliterate;
+        self.ecc_hex = p.ecc_hex;
+        self.lic_hex = p.lic_hex;
+        self.alarm = p.alarm;
+        self.translit_table_path = p.translit_table_path;
+        self.reload_translit();
+        self.apply_af();
 
         // Apply to engine if running
         if let Some(engine) = &self.engine {
-            if let Ok(pi) = parse_pi(&self.pi_hex) {
-                engine.update_pi(pi);
-            }
-            engine.update_ps(&self.ps);
-            engine.update_rt(&self.rt);
-            engine.update_tp(self.tp);
-            engine.update_ta(self.ta);
-            engine.update_pty(self.pty_selected.code);
-            engine.update_ms(self.ms);
-            engine.update_ab(self.ab_flag);
-            engine.update_ab_auto(self.ab_auto);
-            engine.update_ct_enabled(self.ct_enabled);
-            engine.update_af_list(&parse_af_list(&self.af_list_text).0);
-            engine.update_ps_scroll(self.ps_scroll_enabled, &self.ps_scroll_text, self.ps_scroll_cps);
-            engine.update_rt_scroll(self.rt_scroll_enabled, &self.rt_scroll_text, self.rt_scroll_cps);
-            engine.update_gain(self.output_gain);
-            engine.update_limiter(self.limiter_enabled, self.limiter_threshold);
-            engine.update_limiter_lookahead(((self.limiter_lookahead_ms / 1000.0) * 228000.0) as usize);
-            engine.update_pilot_level(self.pilot_level);
-            engine.update_rds_level(self.rds_level);
-            engine.update_stereo_separation(self.stereo_separation);
-            engine.update_preemphasis(preemph_to_tau(self.preemphasis_selected.clone()));
-            engine.update_compressor(self.compressor_enabled, self.comp_threshold, self.comp_ratio, self.comp_attack, self.comp_release);
-            engine.update_group_mix(
-                self.group_0a.trim().parse::<usize>().unwrap_or(4),
-                self.group_2a.trim().parse::<usize>().unwrap_or(1),
-                self.group_4a.trim().parse::<usize>().unwrap_or(0),
-            );
-            engine.update_ct_interval(self.ct_interval_groups.trim().parse::<usize>().unwrap_or(0));
-            let list = self.ps_alt_list_text
-                .split('|')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect::<Vec<_>>();
-            engine.update_ps_alternates(list, self.ps_alt_interval.trim().parse::<usize>().unwrap_or(0));
+            engine.apply_settings(&self.engine_settings(), source);
         }
     }
 }
@@ -2032,38 +6944,74 @@ fn parse_pi(input: &str) -> Result<u16, String> {
     u16::from_str_radix(t, 16).map_err(|_| "PI must be a 4-hex-digit value".to_string())
 }
 
-fn parse_af_list(input: &str) -> (Vec<f32>, Option<String>) {
-    let mut out = Vec::new();
-    let mut invalid = false;
-    for part in input.split(',') {
-        if part.trim().is_empty() {
+fn parse_hex_byte(input: &str) -> Option<u8> {
+    let t = input.trim().trim_start_matches("0x");
+    if t.is_empty() {
+        return None;
+    }
+    u8::from_str_radix(t, 16).ok()
+}
+
+fn parse_af_rows(input: &str) -> Vec<AfRow> {
+    input
+        .split(',')
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.strip_suffix(['R', 'r']) {
+            Some(freq) => AfRow { freq: freq.trim().to_string(), regional: true },
+            None => AfRow { freq: part.to_string(), regional: false },
+        })
+        .collect()
+}
+
+fn af_rows_text(rows: &[AfRow]) -> String {
+    rows.iter()
+        .filter(|row| !row.freq.trim().is_empty())
+        .map(|row| format!("{}{}", row.freq.trim(), if row.regional { "R" } else { "" }))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn validate_af_rows(rows: &[AfRow]) -> (Vec<f32>, Vec<f32>, Vec<String>) {
+    let mut freqs = Vec::new();
+    let mut regional = Vec::new();
+    let mut seen = Vec::new();
+    let mut warnings = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        if row.freq.trim().is_empty() {
             continue;
         }
-        if let Ok(freq) = part.trim().parse::<f32>() {
-            if (87.6..=107.9).contains(&freq) {
-                out.push(freq);
-            } else {
-                invalid = true;
-            }
+        let code = row.freq.trim().parse::<f32>().ok().and_then(|f| af_code(f).map(|c| (f, c)));
+        let Some((freq, code)) = code else {
+            warnings.push(format!("Row {}: \"{}\" is not a valid AF (87.6–107.9 MHz).", i + 1, row.freq.trim()));
+            continue;
+        };
+        if seen.contains(&code) {
+            warnings.push(format!("Row {}: {:.1} MHz is a duplicate and was ignored.", i + 1, freq));
+            continue;
+        }
+        seen.push(code);
+        if row.regional {
+            regional.push(freq);
         } else {
-            invalid = true;
+            freqs.push(freq);
         }
     }
-    let warning = if invalid {
-        Some("AF list: invalid entries were ignored (valid range 87.6–107.9 MHz).".to_string())
-    } else {
-        None
-    };
-    (out, warning)
+    // Method B, used once there is a regional variant, holds fewer.
+    let limit = if regional.is_empty() { AF_MAX } else { AF_METHOD_B_PAIRS };
+    if seen.len() > limit {
+        warnings.push(format!("Only the first {} AFs are transmitted.", limit));
+    }
+    (freqs, regional, warnings)
 }
 
 fn build_pi_from_parts(country_hex: &str, area_hex: &str, program_hex: &str, ecc_hex: &str) -> Result<u16, String> {
     let country = if country_hex.trim().is_empty() {
-        let ecc = ecc_hex.trim().trim_start_matches("0x").to_uppercase();
-        if ecc == "E2" {
-            0x7
-        } else {
-            return Err("Country code is empty and ECC is unknown. Set country code manually.".to_string());
+        let ecc = parse_hex_byte(ecc_hex);
+        let mut matches = RDS_COUNTRIES.iter().filter(|c| Some(c.ecc) == ecc).map(|c| c.country_code);
+        match (matches.next(), matches.next()) {
+            (Some(code), None) => code as u16,
+            _ => return Err("Country code is empty and cannot be derived from the ECC. Pick a country or set the code manually.".to_string()),
         }
     } else {
         u16::from_str_radix(country_hex.trim().trim_start_matches("0x"), 16)
@@ -2167,6 +7115,16 @@ fn hero_style(_theme: &Theme) -> container_widget::Appearance {
     }
 }
 
+fn hero_alarm_style(_theme: &Theme) -> container_widget::Appearance {
+    container_widget::Appearance {
+        background: Some(Background::Color(Color { a: 0.45, ..color_danger() })),
+        text_color: Some(color_text()),
+        border_radius: 20.0.into(),
+        border_width: 2.0,
+        border_color: color_danger(),
+    }
+}
+
 fn body_style(_theme: &Theme) -> container_widget::Appearance {
     container_widget::Appearance {
         background: Some(Background::Color(color_bg())),
@@ -2553,6 +7511,18 @@ struct Preset {
     name: String,
     ps: String,
     rt: String,
+    #[serde(default)]
+    code_table: String,
+    #[serde(default)]
+    transliterate: bool,
+    #[serde(default = "default_ecc_hex")]
+    ecc_hex: String,
+    #[serde(default)]
+    lic_hex: String,
+    #[serde(default)]
+    alarm: bool,
+    #[serde(default)]
+    translit_table_path: String,
     pi_hex: String,
     tp: bool,
     ta: bool,
@@ -2563,100 +7533,294 @@ struct Preset {
     ab_auto: bool,
     ct_enabled: bool,
     af_list_text: String,
+    #[serde(default)]
+    frequency_mhz: String,
+    // Left out when the preset was saved without them; loading then keeps
+    // the current devices.
+    #[serde(default)]
+    input_device: Option<String>,
+    #[serde(default)]
+    output_device: Option<String>,
     ps_scroll_enabled: bool,
     ps_scroll_text: String,
     ps_scroll_cps: f32,
+    #[serde(default)]
+    ps_scroll_mode: String,
+    #[serde(default = "default_ps_page_dwell")]
+    ps_page_dwell: f32,
+    #[serde(default)]
+    ps_page_center: bool,
     rt_scroll_enabled: bool,
     rt_scroll_text: String,
     rt_scroll_cps: f32,
-    output_gain: f32,
+    #[serde(default)]
+    rt_overflow: String,
+    #[serde(default = "default_rt_ellipsis")]
+    rt_ellipsis: String,
+    #[serde(default = "default_rt_part_secs")]
+    rt_part_secs: f32,
+    #[serde(default = "default_true")]
+    rate_guard: bool,
+    // Linear values are still written so older versions can read the preset.
+    #[serde(default)]
+    output_gain: Option<f32>,
+    #[serde(default)]
+    output_gain_db: Option<f32>,
     limiter_enabled: bool,
-    limiter_threshold: f32,
+    #[serde(default)]
+    limiter_threshold: Option<f32>,
+    #[serde(default)]
+    limiter_threshold_db: Option<f32>,
     limiter_lookahead_ms: f32,
     pilot_level: f32,
+    #[serde(default)]
+    pilot_phase_deg: f32,
     rds_level: f32,
     stereo_separation: f32,
     preemphasis: String,
+    #[serde(default = "default_lpf_cutoff")]
+    lpf_cutoff_hz: f32,
+    #[serde(default = "default_true")]
+    pilot_notch: bool,
     compressor_enabled: bool,
     comp_threshold: f32,
     comp_ratio: f32,
     comp_attack: f32,
     comp_release: f32,
+    #[serde(default)]
+    comp_makeup: f32,
     group_0a: String,
     group_2a: String,
     group_4a: String,
     ct_interval_groups: String,
+    #[serde(default)]
+    fast_tuning: String,
     ps_alt_list_text: String,
     ps_alt_interval: String,
+    #[serde(default)]
+    previously_rt: bool,
+    #[serde(default = "default_previously_rt_text")]
+    previously_rt_text: String,
+    #[serde(default = "default_previously_interval")]
+    previously_interval: String,
+    #[serde(default = "default_song_history_len")]
+    song_history_len: String,
+}
+
+fn default_previously_rt_text() -> String {
+    DEFAULT_PREVIOUSLY_RT.to_string()
+}
+
+fn default_previously_interval() -> String {
+    "30".to_string()
+}
+
+fn default_song_history_len() -> String {
+    DEFAULT_SONG_HISTORY_LEN.to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_ecc_hex() -> String {
+    "E2".to_string()
 }
 
-fn presets_path() -> PathBuf {
-    std::env::current_dir()
-        .unwrap_or_else(|_| PathBuf::from("."))
-        .join("presets.json")
+fn default_ps_page_dwell() -> f32 {
+    2.0
 }
 
-fn load_presets() -> Result<Vec<Preset>, String> {
-    let path = presets_path();
-    if !path.exists() {
-        return Ok(Vec::new());
+fn default_rt_ellipsis() -> String {
+    DEFAULT_RT_ELLIPSIS.to_string()
+}
+
+fn default_rt_part_secs() -> f32 {
+    DEFAULT_RT_PART_SECS
+}
+
+fn default_lpf_cutoff() -> f32 {
+    DEFAULT_LPF_CUTOFF_HZ
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ContactSettings {
+    #[serde(default)]
+    port: String,
+    #[serde(default)]
+    mappings: Vec<ContactMapping>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AlertSettings {
+    #[serde(default)]
+    enabled: bool,
+    #[serde(default)]
+    telegram_token: String,
+    #[serde(default)]
+    telegram_chat_id: String,
+    #[serde(default = "default_true")]
+    dead_air: bool,
+    #[serde(default = "default_true")]
+    device_lost: bool,
+    #[serde(default = "default_true")]
+    xrun_storm: bool,
+}
+
+impl Default for AlertSettings {
+    fn default() -> Self {
+        AlertSettings {
+            enabled: false,
+            telegram_token: String::new(),
+            telegram_chat_id: String::new(),
+            dead_air: true,
+            device_lost: true,
+            xrun_storm: true,
+        }
     }
-    let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    serde_json::from_str(&data).map_err(|e| e.to_string())
 }
 
-fn save_presets(presets: &[Preset]) -> Result<(), String> {
-    let data = serde_json::to_string_pretty(presets).map_err(|e| e.to_string())?;
-    fs::write(presets_path(), data).map_err(|e| e.to_string())
+fn network_batch_path() -> PathBuf {
+    config_path("network_batch.toml")
+}
+
+fn mod_limit_inputs(limits: &ModulationLimits) -> [String; 4] {
+    [
+        format!("{:.1}", limits.power_warn_dbr),
+        format!("{:.1}", limits.power_limit_dbr),
+        format!("{:.1}", limits.deviation_warn_khz),
+        format!("{:.1}", limits.deviation_limit_khz),
+    ]
+}
+
+fn with_help<'a>(label: impl Into<Element<'a, Message>>, topic: HelpTopic) -> Element<'a, Message> {
+    tooltip(label, topic.tooltip(), tooltip::Position::Top)
+        .gap(4)
+        .style(theme::Container::Box)
+        .into()
+}
+
+fn allowed_when_locked(message: &Message) -> bool {
+    matches!(
+        message,
+        Message::PsChanged(_)
+            | Message::RtChanged(_)
+            | Message::TaChanged(_)
+            | Message::PsScrollTextChanged(_)
+            | Message::RtScrollTextChanged(_)
+            | Message::PresetSelected(_)
+            | Message::LoadPreset
+            | Message::TabSelected(_)
+            | Message::HelpTopicSelected(_)
+            | Message::Tick
+            | Message::GraphTick
+            | Message::GraphRateSelected(_)
+            | Message::TextRateSelected(_)
+            | Message::WindowResized(_, _)
+            | Message::NoOp
+            | Message::CopyPi
+            | Message::Generated(_)
+            | Message::MpxRendered(_)
+            | Message::PhaseTestRendered(_)
+            | Message::SeparationTestDone(_)
+            | Message::PresetsPulled(_)
+            | Message::PresetsPushed(_)
+            | Message::DiagnosticsExported(_)
+            | Message::LockPinChanged(_)
+            | Message::EngineerUnlock
+    )
+}
+
+fn logs_dir() -> PathBuf {
+    config_path("logs")
 }
 
+// The canvases keep their background, grid and labels in a cache held in the
+// widget state, so a tick only rebuilds the traces; the cache redraws itself
+// when the canvas changes size.
 struct SpectrumView {
     spectrum_peak_db: Vec<f32>,
     spectrum_avg_db: Vec<f32>,
 }
 
 impl<Message> Program<Message, Renderer> for SpectrumView {
-    type State = ();
+    type State = Cache;
 
     fn draw(
         &self,
-        _state: &Self::State,
+        cache: &Self::State,
         renderer: &Renderer,
         _theme: &Theme,
         bounds: iced::Rectangle,
         _cursor: iced::mouse::Cursor,
     ) -> Vec<Geometry> {
-        let mut frame = Frame::new(renderer, bounds.size());
-        let bg = Path::rectangle(iced::Point::ORIGIN, frame.size());
-        frame.fill(&bg, Color::from_rgb8(6, 8, 20));
-
-        let width = frame.size().width;
-        let height = frame.size().height;
+        let width = bounds.width;
+        let height = bounds.height;
+
+        let background = cache.draw(renderer, bounds.size(), |frame| {
+            let bg = Path::rectangle(iced::Point::ORIGIN, frame.size());
+            frame.fill(&bg, Color::from_rgb8(6, 8, 20));
+
+            let grid_color = rgba8f(99, 102, 241, 0.08);
+            for i in 0..=6 {
+                let y = height * (i as f32 / 6.0);
+                let line = Path::line(iced::Point::new(0.0, y), iced::Point::new(width, y));
+                frame.stroke(&line, Stroke::default().with_width(1.0).with_color(grid_color));
+            }
 
-        let grid_color = rgba8f(99, 102, 241, 0.08);
-        for i in 0..=6 {
-            let y = height * (i as f32 / 6.0);
-            let line = Path::line(iced::Point::new(0.0, y), iced::Point::new(width, y));
-            frame.stroke(&line, Stroke::default().with_width(1.0).with_color(grid_color));
-        }
+            let labels = [-60.0, -40.0, -20.0, 0.0];
+            for (i, db) in labels.iter().enumerate() {
+                let y = height - (height * (i as f32 / 3.0));
+                frame.fill_text(Text {
+                    content: format!("{:>3} dB", db),
+                    position: iced::Point::new(6.0, y - 4.0),
+                    color: Color::from_rgb8(110, 120, 160),
+                    size: 11.0,
+                    ..Text::default()
+                });
+            }
 
-        let labels = [-60.0, -40.0, -20.0, 0.0];
-        for (i, db) in labels.iter().enumerate() {
-            let y = height - (height * (i as f32 / 3.0));
+            let rds_x = width * (57000.0 / 96000.0);
+            let rds_line = Path::line(
+                iced::Point::new(rds_x, 0.0),
+                iced::Point::new(rds_x, height),
+            );
+            frame.stroke(&rds_line, Stroke::default().with_width(2.0).with_color(rgba8f(251, 146, 60, 0.5)));
+            let glow_line = Path::line(
+                iced::Point::new(rds_x, 0.0),
+                iced::Point::new(rds_x, height),
+            );
+            frame.stroke(&glow_line, Stroke::default().with_width(6.0).with_color(rgba8f(251, 146, 60, 0.1)));
             frame.fill_text(Text {
-                content: format!("{:>3} dB", db),
-                position: iced::Point::new(6.0, y - 4.0),
-                color: Color::from_rgb8(110, 120, 160),
+                content: "RDS 57k".to_string(),
+                position: iced::Point::new(rds_x + 8.0, 8.0),
+                color: Color::from_rgb8(251, 170, 60),
                 size: 11.0,
                 ..Text::default()
             });
-        }
+
+            let markers = [0.0, 19000.0, 38000.0, 57000.0, 76000.0, 95000.0];
+            for freq in markers {
+                let x = width * (freq / 96000.0);
+                let line = Path::line(iced::Point::new(x, 0.0), iced::Point::new(x, height));
+                frame.stroke(&line, Stroke::default().with_width(1.0).with_color(rgba8f(99, 102, 241, 0.1)));
+                frame.fill_text(Text {
+                    content: format!("{:.0}k", freq / 1000.0),
+                    position: iced::Point::new(x + 4.0, height - 14.0),
+                    color: Color::from_rgb8(110, 120, 160),
+                    size: 10.0,
+                    ..Text::default()
+                });
+            }
+        });
+
+        let mut frame = Frame::new(renderer, bounds.size());
 
         let draw_fill = |frame: &mut Frame, data: &[f32], color: Color| {
             if data.len() < 2 {
                 return;
             }
-            let step = frame.size().width / (data.len() as f32 - 1.0);
+            let step = width / (data.len() as f32 - 1.0);
             let path = Path::new(|builder| {
                 builder.move_to(iced::Point::new(0.0, height));
                 for (i, db) in data.iter().enumerate() {
@@ -2704,76 +7868,130 @@ impl<Message> Program<Message, Renderer> for SpectrumView {
             1.0,
         );
 
-        let rds_x = width * (57000.0 / 96000.0);
-        let rds_line = Path::line(
-            iced::Point::new(rds_x, 0.0),
-            iced::Point::new(rds_x, height),
-        );
-        frame.stroke(&rds_line, Stroke::default().with_width(2.0).with_color(rgba8f(251, 146, 60, 0.5)));
-        let glow_line = Path::line(
-            iced::Point::new(rds_x, 0.0),
-            iced::Point::new(rds_x, height),
-        );
-        frame.stroke(&glow_line, Stroke::default().with_width(6.0).with_color(rgba8f(251, 146, 60, 0.1)));
-        frame.fill_text(Text {
-            content: "RDS 57k".to_string(),
-            position: iced::Point::new(rds_x + 8.0, 8.0),
-            color: Color::from_rgb8(251, 170, 60),
-            size: 11.0,
-            ..Text::default()
+        vec![background, frame.into_geometry()]
+    }
+}
+
+// Program input before processing, 0-20 kHz.
+struct InputSpectrumView {
+    spectrum_db: Vec<f32>,
+}
+
+impl<Message> Program<Message, Renderer> for InputSpectrumView {
+    type State = Cache;
+
+    fn draw(
+        &self,
+        cache: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: iced::Rectangle,
+        _cursor: iced::mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let width = bounds.width;
+        let height = bounds.height;
+
+        let background = cache.draw(renderer, bounds.size(), |frame| {
+            let bg = Path::rectangle(iced::Point::ORIGIN, frame.size());
+            frame.fill(&bg, Color::from_rgb8(6, 8, 20));
+
+            for db in [-60.0f32, -40.0, -20.0, 0.0] {
+                let y = height * (-db / 60.0);
+                let line = Path::line(iced::Point::new(0.0, y), iced::Point::new(width, y));
+                frame.stroke(&line, Stroke::default().with_width(1.0).with_color(rgba8f(99, 102, 241, 0.08)));
+                frame.fill_text(Text {
+                    content: format!("{:>3} dB", db),
+                    position: iced::Point::new(6.0, (y - 4.0).max(2.0)),
+                    color: Color::from_rgb8(110, 120, 160),
+                    size: 11.0,
+                    ..Text::default()
+                });
+            }
+
+            for khz in [0.0f32, 5.0, 10.0, 15.0, 20.0] {
+                let x = width * (khz / 20.0);
+                let line = Path::line(iced::Point::new(x, 0.0), iced::Point::new(x, height));
+                frame.stroke(&line, Stroke::default().with_width(1.0).with_color(rgba8f(99, 102, 241, 0.1)));
+                frame.fill_text(Text {
+                    content: format!("{:.0}k", khz),
+                    position: iced::Point::new(x + 4.0, height - 14.0),
+                    color: Color::from_rgb8(110, 120, 160),
+                    size: 10.0,
+                    ..Text::default()
+                });
+            }
         });
 
-        let markers = [0.0, 19000.0, 38000.0, 57000.0, 76000.0, 95000.0];
-        for freq in markers {
-            let x = width * (freq / 96000.0);
-            let line = Path::line(iced::Point::new(x, 0.0), iced::Point::new(x, height));
-            frame.stroke(&line, Stroke::default().with_width(1.0).with_color(rgba8f(99, 102, 241, 0.1)));
-            frame.fill_text(Text {
-                content: format!("{:.0}k", freq / 1000.0),
-                position: iced::Point::new(x + 4.0, height - 14.0),
-                color: Color::from_rgb8(110, 120, 160),
-                size: 10.0,
-                ..Text::default()
+        let mut frame = Frame::new(renderer, bounds.size());
+        if self.spectrum_db.len() >= 2 {
+            let step = width / (self.spectrum_db.len() as f32 - 1.0);
+            let point = |i: usize, db: f32| {
+                let unit = (db.clamp(-60.0, 0.0) + 60.0) / 60.0;
+                iced::Point::new(i as f32 * step, height - unit * height)
+            };
+            let fill = Path::new(|builder| {
+                builder.move_to(iced::Point::new(0.0, height));
+                for (i, &db) in self.spectrum_db.iter().enumerate() {
+                    builder.line_to(point(i, db));
+                }
+                builder.line_to(iced::Point::new(width, height));
+                builder.close();
             });
+            frame.fill(&fill, rgba8f(52, 211, 153, 0.08));
+            let line = Path::new(|builder| {
+                for (i, &db) in self.spectrum_db.iter().enumerate() {
+                    if i == 0 {
+                        builder.move_to(point(i, db));
+                    } else {
+                        builder.line_to(point(i, db));
+                    }
+                }
+            });
+            frame.stroke(&line, Stroke::default().with_width(1.5).with_color(rgba8f(52, 211, 153, 0.8)));
         }
 
-        vec![frame.into_geometry()]
+        vec![background, frame.into_geometry()]
     }
 }
 
+// Samples are the engine's min/max pairs; a line through them draws the
+// waveform's envelope.
 struct ScopeView {
     samples: Vec<f32>,
     prev: Vec<f32>,
 }
 
 impl<Message> Program<Message, Renderer> for ScopeView {
-    type State = ();
+    type State = Cache;
 
     fn draw(
         &self,
-        _state: &Self::State,
+        cache: &Self::State,
         renderer: &Renderer,
         _theme: &Theme,
         bounds: iced::Rectangle,
         _cursor: iced::mouse::Cursor,
     ) -> Vec<Geometry> {
-        let mut frame = Frame::new(renderer, bounds.size());
-        let bg = Path::rectangle(iced::Point::ORIGIN, frame.size());
-        frame.fill(&bg, Color::from_rgb8(5, 8, 18));
+        let width = bounds.width;
+        let height = bounds.height;
+        let mid_y = height / 2.0;
 
-        let width = frame.size().width;
-        let height = frame.size().height;
+        let background = cache.draw(renderer, bounds.size(), |frame| {
+            let bg = Path::rectangle(iced::Point::ORIGIN, frame.size());
+            frame.fill(&bg, Color::from_rgb8(5, 8, 18));
 
-        let mid_y = height / 2.0;
-        let mid_line = Path::line(iced::Point::new(0.0, mid_y), iced::Point::new(width, mid_y));
-        frame.stroke(&mid_line, Stroke::default().with_width(1.0).with_color(rgba8f(99, 102, 241, 0.1)));
+            let mid_line = Path::line(iced::Point::new(0.0, mid_y), iced::Point::new(width, mid_y));
+            frame.stroke(&mid_line, Stroke::default().with_width(1.0).with_color(rgba8f(99, 102, 241, 0.1)));
 
-        let grid_h_lines = 4;
-        for i in 1..grid_h_lines {
-            let y = height * (i as f32 / grid_h_lines as f32);
-            let line = Path::line(iced::Point::new(0.0, y), iced::Point::new(width, y));
-            frame.stroke(&line, Stroke::default().with_width(1.0).with_color(rgba8f(99, 102, 241, 0.04)));
-        }
+            let grid_h_lines = 4;
+            for i in 1..grid_h_lines {
+                let y = height * (i as f32 / grid_h_lines as f32);
+                let line = Path::line(iced::Point::new(0.0, y), iced::Point::new(width, y));
+                frame.stroke(&line, Stroke::default().with_width(1.0).with_color(rgba8f(99, 102, 241, 0.04)));
+            }
+        });
+
+        let mut frame = Frame::new(renderer, bounds.size());
 
         let draw_trace = |frame: &mut Frame, data: &[f32], width: f32, mid_y: f32, color: Color, thickness: f32| {
             if data.len() < 2 {
@@ -2819,6 +8037,139 @@ impl<Message> Program<Message, Renderer> for ScopeView {
             1.8,
         );
 
-        vec![frame.into_geometry()]
+        vec![background, frame.into_geometry()]
+    }
+}
+
+// The receiver view's timeline: a tick for each PS sent along the top and
+// the names as displayed below, oldest on the left.
+struct ReceiverView {
+    timeline: ReceiverTimeline,
+}
+
+impl<Message> Program<Message, Renderer> for ReceiverView {
+    type State = Cache;
+
+    fn draw(
+        &self,
+        cache: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: iced::Rectangle,
+        _cursor: iced::mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let width = bounds.width;
+        let height = bounds.height;
+        let window = self.timeline.window_secs.max(1.0);
+        let x_for = |secs: f32| width * (secs / window).clamp(0.0, 1.0);
+
+        let background = cache.draw(renderer, bounds.size(), |frame| {
+            let bg = Path::rectangle(iced::Point::ORIGIN, frame.size());
+            frame.fill(&bg, Color::from_rgb8(5, 8, 18));
+            for secs in (10..60).step_by(10) {
+                let x = x_for(secs as f32);
+                let line = Path::line(iced::Point::new(x, 0.0), iced::Point::new(x, height));
+                frame.stroke(&line, Stroke::default().with_width(1.0).with_color(rgba8f(99, 102, 241, 0.08)));
+            }
+        });
+
+        let mut frame = Frame::new(renderer, bounds.size());
+        let lane = 12.0;
+        for shown in &self.timeline.shown {
+            let x = x_for(shown.start_secs);
+            let w = (x_for(shown.start_secs + shown.secs) - x).max(1.0);
+            let color = if self.timeline.readable(shown) {
+                rgba8f(56, 189, 248, 0.35)
+            } else {
+                rgba8f(251, 146, 60, 0.6)
+            };
+            let block = Path::rectangle(iced::Point::new(x, lane + 4.0), iced::Size::new((w - 1.0).max(1.0), height - lane - 4.0));
+            frame.fill(&block, color);
+            if w > 56.0 {
+                frame.fill_text(Text {
+                    content: shown.text.clone(),
+                    position: iced::Point::new(x + 4.0, lane + 10.0),
+                    color: Color::from_rgb8(240, 244, 252),
+                    size: 12.0,
+                    ..Text::default()
+                });
+            }
+        }
+        for at in &self.timeline.sent_at {
+            let x = x_for(*at);
+            let tick = Path::line(iced::Point::new(x, 0.0), iced::Point::new(x, lane));
+            frame.stroke(&tick, Stroke::default().with_width(1.0).with_color(rgba8f(120, 145, 180, 0.8)));
+        }
+
+        vec![background, frame.into_geometry()]
+    }
+}
+
+struct HistoryView {
+    points: Vec<ModulationPoint>,
+}
+
+impl<Message> Program<Message, Renderer> for HistoryView {
+    type State = Cache;
+
+    fn draw(
+        &self,
+        cache: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: iced::Rectangle,
+        _cursor: iced::mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let width = bounds.width;
+        let height = bounds.height;
+        let max_khz = 100.0;
+        let khz_y = |khz: f32| height - (khz.clamp(0.0, max_khz) / max_khz) * height;
+        // MPX power is drawn on its own -12..+6 dBr scale.
+        let dbr_y = |dbr: f32| height - ((dbr.clamp(-12.0, 6.0) + 12.0) / 18.0) * height;
+
+        let background = cache.draw(renderer, bounds.size(), |frame| {
+            let bg = Path::rectangle(iced::Point::ORIGIN, frame.size());
+            frame.fill(&bg, Color::from_rgb8(5, 8, 18));
+
+            for hour in (0..24).step_by(3) {
+                let x = width * (hour as f32 / 24.0);
+                let line = Path::line(iced::Point::new(x, 0.0), iced::Point::new(x, height));
+                frame.stroke(&line, Stroke::default().with_width(1.0).with_color(rgba8f(99, 102, 241, 0.04)));
+            }
+            let limit = Path::line(iced::Point::new(0.0, khz_y(FULL_SCALE_KHZ)), iced::Point::new(width, khz_y(FULL_SCALE_KHZ)));
+            frame.stroke(&limit, Stroke::default().with_width(1.0).with_color(rgba8f(248, 113, 113, 0.3)));
+            let reference = Path::line(iced::Point::new(0.0, dbr_y(0.0)), iced::Point::new(width, dbr_y(0.0)));
+            frame.stroke(&reference, Stroke::default().with_width(1.0).with_color(rgba8f(148, 163, 184, 0.2)));
+        });
+
+        let mut frame = Frame::new(renderer, bounds.size());
+        let now = chrono::Local::now();
+        let x_for = |p: &ModulationPoint| {
+            let age = (now - p.time).num_seconds() as f32 / 60.0;
+            width * (1.0 - age / HISTORY_MINUTES as f32)
+        };
+        let mut draw_trace = |value: &dyn Fn(&ModulationPoint) -> f32, color: Color| {
+            if self.points.len() < 2 {
+                return;
+            }
+            let path = Path::new(|builder| {
+                for (i, p) in self.points.iter().enumerate() {
+                    let point = iced::Point::new(x_for(p), value(p));
+                    if i == 0 {
+                        builder.move_to(point);
+                    } else {
+                        builder.line_to(point);
+                    }
+                }
+            });
+            frame.stroke(&path, Stroke::default().with_width(1.5).with_color(color));
+        };
+
+        draw_trace(&|p| dbr_y(p.mpx_power_dbr), rgba8f(120, 145, 180, 0.8));
+        draw_trace(&|p| khz_y(p.peak_khz), rgba8f(248, 113, 113, 0.9));
+        draw_trace(&|p| khz_y(p.pilot_khz), rgba8f(251, 146, 60, 0.9));
+        draw_trace(&|p| khz_y(p.rds_khz), rgba8f(56, 189, 248, 0.9));
+
+        vec![background, frame.into_geometry()]
     }
 }
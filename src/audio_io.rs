@@ -1,20 +1,71 @@
 use std::collections::VecDeque;
-use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::time::{Duration, Instant, SystemTime};
 
 use anyhow::{anyhow, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use ringbuf::HeapRb;
-use rustfft::{FftPlanner, num_complex::Complex};
-
-use crate::rds::RdsGenerator;
+use ringbuf::{HeapConsumer, HeapProducer, HeapRb};
+use rustfft::{Fft, FftPlanner, num_complex::Complex};
+use serde::Serialize;
+
+use crate::audio::load_wav;
+use crate::automation::{AutomationParam, ParamRamp, RampCurve};
+use crate::delay::{ProgramDelay, StaticDelay};
+use crate::exclusive::{claim_output, ExclusiveOutput};
+use crate::calibration::CalibrationStep;
+use crate::chain::{Stage, StageLevels};
+use crate::fm_mpx::{design_low_pass, DEFAULT_LPF_CUTOFF_HZ, MAX_LPF_CUTOFF_HZ, MIN_LPF_CUTOFF_HZ};
+use crate::limiter::LookaheadLimiter;
+#[cfg(target_os = "linux")]
+use crate::loopback::PulseSource;
+use crate::loopback::{loopback_target, LOOPBACK_PREFIX};
+use crate::analyzer::{FftSize, FftWindow};
+use crate::injection::{Injection, InjectionMeter};
+use crate::inserts::{InsertChain, InsertPoint};
+use crate::mask::{MaskCheck, MaskResult};
+use crate::mod_history::ModulationStats;
+use crate::mpx_replay::MpxReplay;
+use crate::notch::{PilotNotch, RdsNotch};
+use crate::phase_align::pilot_table;
+use crate::pilot_pll::{PilotLockStatus, PilotPll};
+use crate::preemphasis::ShelvingFilter;
+use crate::smoothing::SmoothedParam;
+use crate::test_sequence::{TestSequence, TestSequencer, TestStep};
+use crate::traffic::{TrafficMixer, TrafficRouting};
+use crate::failover::{BackupInput, FailoverConfig, FailoverState, FailoverStatus, InputFailover, LoopedFile};
+use crate::rtp::{open_rtp_socket, parse_rtp_packet, ClockRecovery, RtpConfig, RtpStats, RtpStatus};
+use crate::rds::{FastTuning, GroupStats, PsScrollMode, RdsGenerator, RtOverflow};
+use crate::rds_feed::{RdsFeed, RdsFeedConfig};
+use crate::rds_history::{RdsChangeSource, RdsHistoryEntry};
+use crate::rds_schedule::GroupRecord;
+use crate::rds_strings::RdsCodeTable;
+use crate::rds_translit::TranslitTable;
 
 const INTERNAL_SAMPLE_RATE: u32 = 228_000;
 const OUTPUT_SAMPLE_RATE: u32 = 192_000;
 const SPECTRUM_BANDS: usize = 48;
+// Display points across 0-96 kHz; every FFT size is reduced to this by taking
+// the highest bin in each group, so the canvas strokes the same few hundred
+// points however fine the analysis.
 const SPECTRUM_BINS: usize = 256;
+// The scope shows the last SCOPE_WINDOW samples as SCOPE_POINTS min/max pairs.
+const SCOPE_WINDOW: usize = 2048;
+const SCOPE_POINTS: usize = 256;
+const INPUT_DECIMATION: usize = 4;
+const INPUT_FFT_SIZE: usize = 1024;
+// 0-20 kHz at 57 kHz / 1024 per bin.
+const INPUT_SPECTRUM_BINS: usize = 360;
 const SPECTRUM_MIN_DB: f32 = -60.0;
-const SPECTRUM_MAX_DB: f32 = 0.0;
+pub const DSP_LOAD_WARN: f32 = 0.7;
+// Attempts the output callback makes at the engine lock before giving up on
+// the block; the UI only holds it for short updates.
+const ENGINE_LOCK_TRIES: usize = 1000;
+// A full-scale sine reads 20*log10(4) dB low in a Hann-windowed, 1/N-scaled
+// one-sided spectrum.
+const HANN_SINE_REF_DB: f32 = 12.04;
 
 const FIR_HALF_SIZE: usize = 30;
 const FIR_SIZE: usize = 2 * FIR_HALF_SIZE - 1;
@@ -90,6 +141,349 @@ impl OutputResampler {
     }
 }
 
+// Decimates the processed program to the monitor device rate; the program is
+// already band-limited to 15 kHz, so plain sample picking is enough.
+struct MonitorTap {
+    prod: HeapProducer<Frame>,
+    step: f64,
+    phase: f64,
+}
+
+impl MonitorTap {
+    fn new(prod: HeapProducer<Frame>, device_rate: u32) -> Self {
+        MonitorTap {
+            prod,
+            step: INTERNAL_SAMPLE_RATE as f64 / device_rate as f64,
+            phase: 0.0,
+        }
+    }
+
+    fn push(&mut self, left: f32, right: f32) {
+        self.phase += 1.0;
+        if self.phase >= self.step {
+            self.phase -= self.step;
+            let _ = self.prod.push(Frame { left, right });
+        }
+    }
+}
+
+// Watches the program input before any processing. The spectrum runs on the
+// input decimated by 4 to 57 kHz (a 4-sample average keeps aliasing from
+// above 28.5 kHz down), giving ~56 Hz bins across 0-20 kHz. A full-scale sine
+// reads 0 dB.
+struct InputAnalyzer {
+    fft: Arc<dyn Fft<f32>>,
+    buf: Vec<Complex<f32>>,
+    pos: usize,
+    acc: f32,
+    acc_count: usize,
+    left_peak: f32,
+    right_peak: f32,
+    left_sq: f32,
+    right_sq: f32,
+    frames: usize,
+}
+
+impl InputAnalyzer {
+    fn new(planner: &mut FftPlanner<f32>) -> Self {
+        InputAnalyzer {
+            fft: planner.plan_fft_forward(INPUT_FFT_SIZE),
+            buf: vec![Complex::new(0.0, 0.0); INPUT_FFT_SIZE],
+            pos: 0,
+            acc: 0.0,
+            acc_count: 0,
+            left_peak: 0.0,
+            right_peak: 0.0,
+            left_sq: 0.0,
+            right_sq: 0.0,
+            frames: 0,
+        }
+    }
+
+    fn push(&mut self, frame: Frame) -> Option<Vec<f32>> {
+        self.left_peak = self.left_peak.max(frame.left.abs());
+        self.right_peak = self.right_peak.max(frame.right.abs());
+        self.left_sq += frame.left * frame.left;
+        self.right_sq += frame.right * frame.right;
+        self.frames += 1;
+
+        self.acc += 0.5 * (frame.left + frame.right);
+        self.acc_count += 1;
+        if self.acc_count < INPUT_DECIMATION {
+            return None;
+        }
+        self.buf[self.pos] = Complex::new(self.acc / INPUT_DECIMATION as f32, 0.0);
+        self.acc = 0.0;
+        self.acc_count = 0;
+        self.pos += 1;
+        if self.pos < INPUT_FFT_SIZE {
+            return None;
+        }
+        self.pos = 0;
+
+        let n = INPUT_FFT_SIZE as f32;
+        for (i, v) in self.buf.iter_mut().enumerate() {
+            v.re *= 0.5 - 0.5 * ((2.0 * std::f32::consts::PI * i as f32) / n).cos();
+        }
+        self.fft.process(&mut self.buf);
+        Some(
+            self.buf[..INPUT_SPECTRUM_BINS]
+                .iter()
+                .map(|v| 20.0 * ((v.re * v.re + v.im * v.im).sqrt() / n + 1e-9).log10() + HANN_SINE_REF_DB)
+                .collect(),
+        )
+    }
+
+    fn store_levels(&mut self, meter: &MeterState) {
+        if self.frames == 0 {
+            return;
+        }
+        let frames = self.frames as f32;
+        meter.input_left_peak.store(f32_to_u32(self.left_peak), Ordering::Relaxed);
+        meter.input_right_peak.store(f32_to_u32(self.right_peak), Ordering::Relaxed);
+        meter.input_left_rms.store(f32_to_u32((self.left_sq / frames).sqrt()), Ordering::Relaxed);
+        meter.input_right_rms.store(f32_to_u32((self.right_sq / frames).sqrt()), Ordering::Relaxed);
+        self.left_peak = 0.0;
+        self.right_peak = 0.0;
+        self.left_sq = 0.0;
+        self.right_sq = 0.0;
+        self.frames = 0;
+    }
+}
+
+// All metering that needs windows, FFTs, vectors or locks runs here, on its
+// own thread: the MPX spectrum, pilot/RDS readout, band meters, mask check
+// and scope, plus the input analyzer. Spectra and scope are published already
+// reduced to display points, so the UI copies and draws little. The audio
+// callbacks only push samples into the two ring buffers, so they never
+// allocate or wait on a meter lock; when this thread falls behind, the taps
+// drop samples instead. FFT size and window are read from the meter state
+// before each block, and levels are corrected to the Hann scale so the
+// display, bands and mask keep their meaning with any window.
+struct MeterAnalysis {
+    mpx_tap: HeapConsumer<f32>,
+    input_tap: HeapConsumer<Frame>,
+    input: InputAnalyzer,
+    injection: InjectionMeter,
+    meter: Arc<MeterState>,
+    scope_window: VecDeque<f32>,
+    scope: Arc<Mutex<Vec<f32>>>,
+    spectrum: Arc<Mutex<Vec<f32>>>,
+    spectrum_peak: Arc<Mutex<Vec<f32>>>,
+    spectrum_avg: Arc<Mutex<Vec<f32>>>,
+    input_spectrum: Arc<Mutex<Vec<f32>>>,
+    running: Arc<AtomicBool>,
+}
+
+impl MeterAnalysis {
+    fn run(mut self, mut planner: FftPlanner<f32>) {
+        let mut size = FftSize::default();
+        let mut window = FftWindow::default();
+        let mut fft = planner.plan_fft_forward(size.points());
+        let mut table = window.table(size.points());
+        let mut block: Vec<f32> = Vec::with_capacity(FftSize::N8192.points());
+        let mut buf = vec![Complex::new(0.0, 0.0); size.points()];
+        while self.running.load(Ordering::Relaxed) {
+            let next_size = FftSize::from_u32(self.meter.fft_size.load(Ordering::Relaxed));
+            let next_window = FftWindow::from_u32(self.meter.fft_window.load(Ordering::Relaxed));
+            if next_size != size || next_window != window {
+                if next_size != size {
+                    fft = planner.plan_fft_forward(next_size.points());
+                    buf = vec![Complex::new(0.0, 0.0); next_size.points()];
+                    block.clear();
+                }
+                size = next_size;
+                window = next_window;
+                table = window.table(size.points());
+            }
+
+            let mut idle = self.input_tap.is_empty() && self.mpx_tap.is_empty();
+            while let Some(frame) = self.input_tap.pop() {
+                if let Some(spec) = self.input.push(frame) {
+                    if let Ok(mut input_guard) = self.input_spectrum.lock() {
+                        if input_guard.len() != spec.len() {
+                            *input_guard = spec;
+                        } else {
+                            for i in 0..spec.len() {
+                                input_guard[i] = input_guard[i] * 0.7 + spec[i] * 0.3;
+                            }
+                        }
+                    }
+                }
+            }
+            self.input.store_levels(&self.meter);
+
+            let mut scope_changed = false;
+            while block.len() < size.points() {
+                let Some(sample) = self.mpx_tap.pop() else {
+                    break;
+                };
+                if self.scope_window.len() >= SCOPE_WINDOW {
+                    self.scope_window.pop_front();
+                }
+                self.scope_window.push_back(sample);
+                self.injection.process(sample);
+                block.push(sample);
+                scope_changed = true;
+            }
+            if scope_changed {
+                let points = min_max_bins(self.scope_window.make_contiguous(), SCOPE_POINTS);
+                if let Ok(mut scope_guard) = self.scope.lock() {
+                    *scope_guard = points;
+                }
+            }
+            let injection = self.injection.injection();
+            self.meter.pilot.store(f32_to_u32(injection.pilot), Ordering::Relaxed);
+            self.meter.stereo_carrier.store(f32_to_u32(injection.stereo_carrier), Ordering::Relaxed);
+            self.meter.rds.store(f32_to_u32(injection.rds), Ordering::Relaxed);
+            if block.len() == size.points() {
+                for ((v, &x), &w) in buf.iter_mut().zip(&block).zip(&table) {
+                    *v = Complex::new(x * w, 0.0);
+                }
+                block.clear();
+                fft.process(&mut buf);
+                self.publish(&buf, window.sine_ref_db() - HANN_SINE_REF_DB);
+                idle = false;
+            }
+            if idle {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+        }
+    }
+
+    fn publish(&self, bins: &[Complex<f32>], offset_db: f32) {
+        let mut bands = [SPECTRUM_MIN_DB; SPECTRUM_BANDS];
+        let n = bins.len() as f32;
+        let per_point = (bins.len() / 2 / SPECTRUM_BINS).max(1);
+        let mut spec = vec![SPECTRUM_MIN_DB; SPECTRUM_BINS];
+        let mut mask = MaskCheck::new(u32_to_f32(self.meter.lpf_cutoff_hz.load(Ordering::Relaxed)));
+        for (k, v) in bins.iter().enumerate().take(bins.len() / 2) {
+            let freq = k as f32 * OUTPUT_SAMPLE_RATE as f32 / n;
+            let mag = (v.re * v.re + v.im * v.im).sqrt() / n;
+            let db = 20.0 * (mag + 1e-9).log10() + offset_db;
+            mask.update(freq, db + HANN_SINE_REF_DB);
+            let point = k / per_point;
+            if point < SPECTRUM_BINS && (k % per_point == 0 || db > spec[point]) {
+                spec[point] = db;
+            }
+            let band = ((freq / (OUTPUT_SAMPLE_RATE as f32 / 2.0)) * SPECTRUM_BANDS as f32).floor() as usize;
+            if band < SPECTRUM_BANDS && db > bands[band] {
+                bands[band] = db;
+            }
+        }
+        if let Some(result) = mask.result() {
+            if result.margin_db < u32_to_f32(self.meter.mask_margin.load(Ordering::Relaxed)) {
+                self.meter.mask_margin.store(f32_to_u32(result.margin_db), Ordering::Relaxed);
+                self.meter.mask_worst_hz.store(f32_to_u32(result.worst_hz), Ordering::Relaxed);
+            }
+        }
+        for (band, db) in self.meter.bands_db.iter().zip(bands) {
+            band.store(f32_to_u32(db), Ordering::Relaxed);
+        }
+        if let Ok(mut spectrum_guard) = self.spectrum.lock() {
+            *spectrum_guard = spec.clone();
+        }
+        if let Ok(mut peak_guard) = self.spectrum_peak.lock() {
+            if peak_guard.len() != spec.len() {
+                *peak_guard = spec.clone();
+            } else {
+                for i in 0..spec.len() {
+                    peak_guard[i] = peak_guard[i].max(spec[i]);
+                }
+            }
+        }
+        if let Ok(mut avg_guard) = self.spectrum_avg.lock() {
+            if avg_guard.len() != spec.len() {
+                *avg_guard = spec;
+            } else {
+                for i in 0..spec.len() {
+                    avg_guard[i] = avg_guard[i] * 0.9 + spec[i] * 0.1;
+                }
+            }
+        }
+    }
+}
+
+// Splits `data` into `points` groups and keeps each group's minimum and
+// maximum, in that order, so a line through the result traces the waveform's
+// envelope without losing peaks.
+fn min_max_bins(data: &[f32], points: usize) -> Vec<f32> {
+    if data.len() <= points * 2 {
+        return data.to_vec();
+    }
+    let mut out = Vec::with_capacity(points * 2);
+    for i in 0..points {
+        let group = &data[i * data.len() / points..(i + 1) * data.len() / points];
+        let min = group.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = group.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        out.push(min);
+        out.push(max);
+    }
+    out
+}
+
+struct DriftResampler {
+    step: f64,
+    correction: f64,
+    target_fill: f64,
+    servo: bool,
+    phase: f64,
+    prev: Frame,
+    next: Frame,
+    primed: bool,
+}
+
+impl DriftResampler {
+    fn new(input_rate: u32, internal_rate: u32, servo: bool) -> Self {
+        DriftResampler {
+            step: input_rate as f64 / internal_rate as f64,
+            correction: 0.0,
+            target_fill: input_rate as f64 * 0.05,
+            servo,
+            phase: 0.0,
+            prev: Frame { left: 0.0, right: 0.0 },
+            next: Frame { left: 0.0, right: 0.0 },
+            primed: false,
+        }
+    }
+
+    fn update(&mut self, fill: usize) {
+        if !self.servo {
+            return;
+        }
+        let error = (fill as f64 - self.target_fill) / self.target_fill;
+        let wanted = (error * 0.001).clamp(-0.002, 0.002);
+        self.correction += (wanted - self.correction) * 0.01;
+    }
+
+    fn next_frame<F>(&mut self, mut pop: F) -> Option<Frame>
+    where
+        F: FnMut() -> Option<Frame>,
+    {
+        if !self.primed {
+            self.next = pop()?;
+            self.primed = true;
+        }
+        while self.phase >= 1.0 {
+            let frame = pop()?;
+            self.phase -= 1.0;
+            self.prev = self.next;
+            self.next = frame;
+        }
+        let t = self.phase as f32;
+        let frame = Frame {
+            left: self.prev.left + (self.next.left - self.prev.left) * t,
+            right: self.prev.right + (self.next.right - self.prev.right) * t,
+        };
+        self.phase += self.step * (1.0 + self.correction);
+        Some(frame)
+    }
+
+    fn drift_ppm(&self) -> f32 {
+        (self.correction * 1_000_000.0) as f32
+    }
+}
+
 struct LiveMpx {
     rds: RdsGenerator,
     low_pass_fir: [f32; FIR_HALF_SIZE],
@@ -99,50 +493,53 @@ struct LiveMpx {
     phase_38: usize,
     phase_19: usize,
 
-    gain: f32,
+    gain: SmoothedParam,
     limiter_enabled: bool,
-    limiter_threshold: f32,
-    limiter_lookahead: usize,
-    limiter_buffer: VecDeque<f32>,
+    limiter: LookaheadLimiter,
 
-    pilot_level: f32,
-    rds_level: f32,
-    stereo_separation: f32,
+    pilot_level: SmoothedParam,
+    pilot_table: [f32; 12],
+    rds_level: SmoothedParam,
+    stereo_separation: SmoothedParam,
 
     preemphasis_tau: Option<f32>,
-    preemph_prev_mono: f32,
-    preemph_prev_stereo: f32,
-    preemph_state_mono: f32,
-    preemph_state_stereo: f32,
+    preemphasis: Option<[ShelvingFilter; 2]>,
+    deemphasis: Option<[ShelvingFilter; 2]>,
+    monitor: Option<MonitorTap>,
+    pilot_notch: Option<[PilotNotch; 2]>,
 
     compressor_enabled: bool,
     comp_threshold_db: f32,
     comp_ratio: f32,
     comp_attack: f32,
     comp_release: f32,
+    comp_makeup_db: f32,
     comp_gain_db: f32,
+
+    mpx_input: bool,
+    mpx_input_pilot: bool,
+    rds_notch: RdsNotch,
+    pilot_pll: PilotPll,
+
+    tx_mute: bool,
+    program_delay: ProgramDelay,
+    diversity_delay: StaticDelay,
+    ramp: f32,
+    ramp_target: f32,
+    ramp_step: f32,
+    flush_remaining: usize,
+    automation: [ParamRamp; 4],
+    modulation: ModulationStats,
+    calibration: Option<CalibrationStep>,
+    test_sequencer: Option<TestSequencer>,
+    bypass: [bool; 4],
+    stage_levels: [StageLevels; 4],
+    inserts: InsertChain,
 }
 
 impl LiveMpx {
     fn new() -> Self {
-        let mut low_pass_fir = [0.0f32; FIR_HALF_SIZE];
-        let cutoff_freq = 15000.0 * 0.8;
-
-        low_pass_fir[FIR_HALF_SIZE - 1] = 2.0 * cutoff_freq / INTERNAL_SAMPLE_RATE as f32 / 2.0;
-
-        for i in 1..FIR_HALF_SIZE {
-            let idx = FIR_HALF_SIZE - 1 - i;
-            let sinc = (2.0 * std::f32::consts::PI * cutoff_freq * i as f32
-                / INTERNAL_SAMPLE_RATE as f32)
-                .sin()
-                / (std::f32::consts::PI * i as f32);
-            let window = 0.54
-                - 0.46
-                    * (2.0 * std::f32::consts::PI * (i + FIR_HALF_SIZE) as f32
-                        / (2.0 * FIR_HALF_SIZE as f32))
-                        .cos();
-            low_pass_fir[idx] = sinc * window;
-        }
+        let low_pass_fir = design_low_pass(DEFAULT_LPF_CUTOFF_HZ, INTERNAL_SAMPLE_RATE as f32);
 
         LiveMpx {
             rds: RdsGenerator::new(),
@@ -153,43 +550,79 @@ impl LiveMpx {
             phase_38: 0,
             phase_19: 0,
 
-            gain: 1.0,
+            gain: SmoothedParam::new(1.0, INTERNAL_SAMPLE_RATE),
             limiter_enabled: true,
-            limiter_threshold: 0.95,
-            limiter_lookahead: 256,
-            limiter_buffer: VecDeque::with_capacity(512),
+            limiter: LookaheadLimiter::new(INTERNAL_SAMPLE_RATE, 0.95, 256),
 
-            pilot_level: 0.9,
-            rds_level: 1.0,
-            stereo_separation: 1.0,
+            pilot_level: SmoothedParam::new(0.9, INTERNAL_SAMPLE_RATE),
+            pilot_table: CARRIER_19,
+            rds_level: SmoothedParam::new(1.0, INTERNAL_SAMPLE_RATE),
+            stereo_separation: SmoothedParam::new(1.0, INTERNAL_SAMPLE_RATE),
 
             preemphasis_tau: None,
-            preemph_prev_mono: 0.0,
-            preemph_prev_stereo: 0.0,
-            preemph_state_mono: 0.0,
-            preemph_state_stereo: 0.0,
+            preemphasis: None,
+            deemphasis: None,
+            monitor: None,
+            pilot_notch: None,
 
             compressor_enabled: false,
             comp_threshold_db: -18.0,
             comp_ratio: 3.0,
             comp_attack: 0.01,
             comp_release: 0.2,
+            comp_makeup_db: 0.0,
             comp_gain_db: 0.0,
+
+            mpx_input: false,
+            mpx_input_pilot: false,
+            rds_notch: RdsNotch::new(INTERNAL_SAMPLE_RATE),
+            pilot_pll: PilotPll::new(INTERNAL_SAMPLE_RATE),
+
+            tx_mute: false,
+            program_delay: ProgramDelay::new(INTERNAL_SAMPLE_RATE),
+            diversity_delay: StaticDelay::new(INTERNAL_SAMPLE_RATE),
+            ramp: 1.0,
+            ramp_target: 1.0,
+            ramp_step: 1.0,
+            flush_remaining: 0,
+            automation: std::array::from_fn(|_| ParamRamp::new()),
+            modulation: ModulationStats::default(),
+            calibration: None,
+            test_sequencer: None,
+            bypass: [false; 4],
+            stage_levels: [StageLevels::default(); 4],
+            inserts: InsertChain::empty(),
         }
     }
 
-    fn set_ps(&mut self, ps: &str) {
-        self.rds.set_ps(ps);
+    fn set_ps(&mut self, ps: &str, source: RdsChangeSource) {
+        self.rds.apply_ps(ps, source);
+    }
+
+    fn set_rt(&mut self, rt: &str, source: RdsChangeSource) {
+        self.rds.apply_rt(rt, source);
+    }
+
+    fn set_code_table(&mut self, table: RdsCodeTable) {
+        self.rds.set_code_table(table);
     }
 
-    fn set_rt(&mut self, rt: &str) {
-        self.rds.set_rt(rt);
+    fn set_transliteration(&mut self, table: Option<TranslitTable>) {
+        self.rds.set_transliteration(table);
     }
 
     fn set_pi(&mut self, pi: u16) {
         self.rds.set_pi(pi);
     }
 
+    fn set_ecc(&mut self, ecc: Option<u8>) {
+        self.rds.set_ecc(ecc);
+    }
+
+    fn set_lic(&mut self, lic: Option<u8>) {
+        self.rds.set_lic(lic);
+    }
+
     fn set_tp(&mut self, tp: bool) {
         self.rds.set_tp(tp);
     }
@@ -198,6 +631,10 @@ impl LiveMpx {
         self.rds.set_ta(ta);
     }
 
+    fn ta(&self) -> bool {
+        self.rds.ta()
+    }
+
     fn set_pty(&mut self, pty: u8) {
         self.rds.set_pty(pty);
     }
@@ -222,14 +659,26 @@ impl LiveMpx {
         self.rds.set_ct_enabled(enabled);
     }
 
-    fn set_af_list_mhz(&mut self, freqs: &[f32]) {
-        self.rds.set_af_list_mhz(freqs);
+    fn set_af_entries(&mut self, freqs: &[f32], regional: &[f32], tuned_mhz: Option<f32>) {
+        self.rds.set_af_entries(freqs, regional, tuned_mhz);
     }
 
     fn set_ps_scroll(&mut self, enabled: bool, text: &str, cps: f32) {
         self.rds.enable_ps_scroll(enabled, text, cps);
     }
 
+    fn set_ps_scroll_mode(&mut self, mode: PsScrollMode, page_dwell_secs: f32, center: bool) {
+        self.rds.set_ps_scroll_mode(mode, page_dwell_secs, center);
+    }
+
+    fn set_rate_guard(&mut self, enabled: bool) {
+        self.rds.set_rate_guard(enabled);
+    }
+
+    fn set_rt_overflow(&mut self, mode: RtOverflow, ellipsis: &str, part_secs: f32) {
+        self.rds.set_rt_overflow(mode, ellipsis, part_secs);
+    }
+
     fn set_rt_scroll(&mut self, enabled: bool, text: &str, cps: f32) {
         self.rds.enable_rt_scroll(enabled, text, cps);
     }
@@ -238,6 +687,10 @@ impl LiveMpx {
         self.rds.set_group_mix(count_0a, count_2a, count_4a);
     }
 
+    fn set_fast_tuning(&mut self, mode: FastTuning) {
+        self.rds.set_fast_tuning(mode);
+    }
+
     fn set_ct_interval(&mut self, interval_groups: usize) {
         self.rds.set_ct_interval_groups(interval_groups);
     }
@@ -246,50 +699,265 @@ impl LiveMpx {
         self.rds.set_ps_alternates(list, interval_groups);
     }
 
+    fn take_history(&mut self) -> Vec<RdsHistoryEntry> {
+        self.rds.take_history(chrono::Local::now())
+    }
+
+    fn group_stats(&self) -> GroupStats {
+        self.rds.group_stats()
+    }
+
+    fn set_group_log(&mut self, enabled: bool) {
+        self.rds.set_group_log(enabled);
+    }
+
+    fn take_group_log(&mut self) -> Vec<GroupRecord> {
+        self.rds.take_group_log()
+    }
+
     fn set_gain(&mut self, gain: f32) {
-        self.gain = gain;
+        self.gain.set_target(gain);
     }
 
     fn set_limiter(&mut self, enabled: bool, threshold: f32) {
         self.limiter_enabled = enabled;
-        self.limiter_threshold = threshold;
+        self.limiter.set_threshold(threshold);
     }
 
     fn set_limiter_lookahead(&mut self, samples: usize) {
-        self.limiter_lookahead = samples.max(1).min(2048);
-        self.limiter_buffer.clear();
+        self.limiter.set_lookahead(samples);
     }
 
     fn set_pilot_level(&mut self, level: f32) {
-        self.pilot_level = level.clamp(0.0, 2.0);
+        self.pilot_level.set_target(level.clamp(0.0, 2.0));
+    }
+
+    fn set_pilot_phase(&mut self, offset_deg: f32) {
+        self.pilot_table = pilot_table(offset_deg);
     }
 
     fn set_rds_level(&mut self, level: f32) {
-        self.rds_level = level.clamp(0.0, 2.0);
+        self.rds_level.set_target(level.clamp(0.0, 2.0));
     }
 
     fn set_stereo_separation(&mut self, level: f32) {
-        self.stereo_separation = level.clamp(0.0, 2.0);
+        self.stereo_separation.set_target(level.clamp(0.0, 2.0));
+    }
+
+    fn set_lpf_cutoff(&mut self, cutoff_hz: f32) {
+        let cutoff = cutoff_hz.clamp(MIN_LPF_CUTOFF_HZ, MAX_LPF_CUTOFF_HZ);
+        self.low_pass_fir = design_low_pass(cutoff, INTERNAL_SAMPLE_RATE as f32);
+    }
+
+    fn set_pilot_notch(&mut self, enabled: bool) {
+        if enabled == self.pilot_notch.is_some() {
+            return;
+        }
+        self.pilot_notch = enabled.then(|| [PilotNotch::new(INTERNAL_SAMPLE_RATE); 2]);
     }
 
     fn set_preemphasis(&mut self, tau_seconds: Option<f32>) {
+        if tau_seconds == self.preemphasis_tau {
+            return;
+        }
         self.preemphasis_tau = tau_seconds;
-        self.preemph_prev_mono = 0.0;
-        self.preemph_prev_stereo = 0.0;
-        self.preemph_state_mono = 0.0;
-        self.preemph_state_stereo = 0.0;
+        self.preemphasis = tau_seconds.map(|tau| [ShelvingFilter::preemphasis(tau, INTERNAL_SAMPLE_RATE); 2]);
+        self.deemphasis = tau_seconds.map(|tau| [ShelvingFilter::deemphasis(tau, INTERNAL_SAMPLE_RATE); 2]);
     }
 
-    fn set_compressor(&mut self, enabled: bool, threshold_db: f32, ratio: f32, attack: f32, release: f32) {
+    fn set_compressor(&mut self, enabled: bool, threshold_db: f32, ratio: f32, attack: f32, release: f32, makeup_db: f32) {
         self.compressor_enabled = enabled;
         self.comp_threshold_db = threshold_db;
         self.comp_ratio = ratio.max(1.0);
         self.comp_attack = attack.max(0.001);
         self.comp_release = release.max(0.01);
-        self.comp_gain_db = 0.0;
+        self.comp_makeup_db = makeup_db;
+        if !enabled {
+            self.comp_gain_db = 0.0;
+        }
+    }
+
+    fn set_mpx_input(&mut self, enabled: bool, pilot: bool) {
+        self.mpx_input = enabled;
+        self.mpx_input_pilot = pilot;
+    }
+
+    fn set_mpx_input_pilot(&mut self, enabled: bool) {
+        self.mpx_input_pilot = enabled;
+    }
+
+    fn set_tx_mute(&mut self, muted: bool) {
+        self.tx_mute = muted;
+    }
+
+    fn set_program_delay(&mut self, secs: f32) {
+        self.program_delay.set_delay(secs);
+    }
+
+    fn set_diversity_delay(&mut self, ms: f32) {
+        self.diversity_delay.set_delay_ms(ms);
+    }
+
+    fn start_ramp(&mut self, target: f32, secs: f32) {
+        self.ramp_target = target;
+        self.ramp_step = if secs > 0.0 {
+            1.0 / (secs * INTERNAL_SAMPLE_RATE as f32)
+        } else {
+            1.0
+        };
+        if target == 0.0 && self.limiter_enabled {
+            self.flush_remaining = self.limiter.lookahead();
+        }
+    }
+
+    fn set_automation(&mut self, param: AutomationParam, offset_db: f32, ramp_secs: f32, curve: RampCurve) {
+        self.automation[param.index()].set_target_db(offset_db, ramp_secs, INTERNAL_SAMPLE_RATE, curve);
+    }
+
+    fn set_calibration(&mut self, step: Option<CalibrationStep>) {
+        self.calibration = step;
+    }
+
+    fn set_test_sequence(&mut self, sequence: Option<&TestSequence>) {
+        self.test_sequencer = sequence.map(|s| TestSequencer::new(s, INTERNAL_SAMPLE_RATE));
+    }
+
+    fn set_bypass(&mut self, stage: Stage, bypassed: bool) {
+        self.bypass[stage.index()] = bypassed;
+    }
+
+    fn apply_settings(&mut self, s: &EngineSettings, source: RdsChangeSource) {
+        if let Some(pi) = s.pi {
+            self.set_pi(pi);
+        }
+        self.set_ecc(s.ecc);
+        self.set_lic(s.lic);
+        self.set_code_table(s.code_table);
+        self.set_rt_overflow(s.rt_overflow, &s.rt_ellipsis, s.rt_part_secs);
+        self.set_ps(&s.ps, source);
+        self.set_rt(&s.rt, source);
+        self.set_tp(s.tp);
+        self.set_ta(s.ta);
+        self.set_pty(s.pty);
+        self.set_ms(s.ms);
+        self.set_di(s.di);
+        self.set_ab(s.ab);
+        self.set_ab_auto(s.ab_auto);
+        self.set_ct_enabled(s.ct_enabled);
+        self.set_rate_guard(s.rate_guard);
+        self.set_ps_scroll_mode(s.ps_scroll_mode, s.ps_page_dwell_secs, s.ps_page_center);
+        self.set_ps_scroll(s.ps_scroll_enabled, &s.ps_scroll_text, s.ps_scroll_cps);
+        self.set_rt_scroll(s.rt_scroll_enabled, &s.rt_scroll_text, s.rt_scroll_cps);
+        self.set_gain(s.output_gain);
+        self.set_limiter(s.limiter_enabled, s.limiter_threshold);
+        self.set_limiter_lookahead(s.limiter_lookahead);
+        self.set_pilot_level(s.pilot_level);
+        self.set_pilot_phase(s.pilot_phase_deg);
+        self.set_rds_level(s.rds_level);
+        self.set_stereo_separation(s.stereo_separation);
+        self.set_lpf_cutoff(s.lpf_cutoff_hz);
+        self.set_pilot_notch(s.pilot_notch);
+        self.set_preemphasis(s.preemphasis_tau);
+        self.set_compressor(s.compressor_enabled, s.comp_threshold_db, s.comp_ratio, s.comp_attack, s.comp_release, s.comp_makeup_db);
+        self.set_group_mix(s.group_0a, s.group_2a, s.group_4a);
+        self.set_ct_interval(s.ct_interval_groups);
+        self.set_fast_tuning(s.fast_tuning);
+        self.set_ps_alternates(s.ps_alt_list.clone(), s.ps_alt_interval);
+    }
+
+    fn faded_out(&self) -> bool {
+        self.ramp == 0.0 && self.ramp_target == 0.0 && self.flush_remaining == 0
+    }
+
+    fn advance_ramp(&mut self) {
+        if self.ramp < self.ramp_target {
+            self.ramp = (self.ramp + self.ramp_step).min(self.ramp_target);
+        } else if self.ramp > self.ramp_target {
+            self.ramp = (self.ramp - self.ramp_step).max(self.ramp_target);
+        } else if self.ramp == 0.0 && self.flush_remaining > 0 {
+            self.flush_remaining -= 1;
+        }
+    }
+
+    fn advance_carriers(&mut self) {
+        self.phase_19 += 1;
+        self.phase_38 += 1;
+        if self.phase_19 >= CARRIER_19.len() {
+            self.phase_19 = 0;
+        }
+        if self.phase_38 >= CARRIER_38.len() {
+            self.phase_38 = 0;
+        }
+    }
+
+    // The input is already a complete MPX from another processor, read from
+    // the left channel at full scale = 100% modulation. The stereo encoder and
+    // the audio processing stages are skipped: the input only loses its
+    // 57 kHz band, then the local RDS and, if asked for, the local pilot are
+    // added on top. While a pilot is found on the input the RDS carrier is
+    // locked to its third harmonic, otherwise it runs from the internal clock.
+    fn next_passthrough_sample(&mut self, mpx: f32) -> f32 {
+        self.pilot_pll.process(mpx);
+        let mut rds_sample = 0.0f32;
+        if self.pilot_pll.locked() {
+            self.rds.get_rds_baseband(std::slice::from_mut(&mut rds_sample));
+            rds_sample *= self.pilot_pll.rds_carrier();
+        } else {
+            self.rds.get_rds_samples(std::slice::from_mut(&mut rds_sample));
+        }
+
+        let gain_auto = self.automation[AutomationParam::OutputGain.index()].step();
+        let pilot_auto = self.automation[AutomationParam::PilotLevel.index()].step();
+        let rds_auto = self.automation[AutomationParam::RdsLevel.index()].step();
+
+        let gain = self.gain.step();
+        let mut program = self.rds_notch.process(mpx);
+        let mut rds_term = self.rds_level.step() * rds_auto * rds_sample;
+        let pilot_level = self.pilot_level.step();
+        let mut pilot_term = if self.mpx_input_pilot {
+            pilot_level * pilot_auto * self.pilot_table[self.phase_19]
+        } else {
+            0.0
+        };
+        if let Some(step) = self.calibration {
+            program = 0.0;
+            match step {
+                CalibrationStep::PilotOnly => rds_term = 0.0,
+                CalibrationStep::RdsOnly => pilot_term = 0.0,
+            }
+        }
+
+        self.advance_ramp();
+        self.advance_carriers();
+
+        let level = gain * gain_auto * self.ramp;
+        let scale = 0.1 * level;
+        let out = program * level + (rds_term + pilot_term) * scale;
+        if self.tx_mute {
+            self.modulation.record(0.0, 0.0, 0.0);
+            return 0.0;
+        }
+        self.modulation.record(out, pilot_term * scale, rds_term * scale);
+        out
     }
 
     fn next_sample(&mut self, frame: Frame) -> f32 {
+        let (left, right) = self.program_delay.process(frame.left, frame.right);
+        let (left, right) = self.diversity_delay.process(left, right);
+        if self.mpx_input {
+            return self.next_passthrough_sample(left);
+        }
+        let (left, right) = self.inserts.process(InsertPoint::Program, left, right);
+        let test_tone = match self.test_sequencer.as_mut().map(|s| s.next_tone()) {
+            Some(Some(tone)) => Some(tone),
+            Some(None) => {
+                self.test_sequencer = None;
+                None
+            }
+            None => None,
+        };
+        let test_step = test_tone.map(|(step, _, _)| step);
+        let frame = Frame { left, right };
         let mut rds_sample = 0.0f32;
         self.rds.get_rds_samples(std::slice::from_mut(&mut rds_sample));
 
@@ -330,20 +998,18 @@ impl LiveMpx {
         let mut mono = out_mono;
         let mut stereo = out_stereo;
 
-        if let Some(tau) = self.preemphasis_tau {
-            let a = (-1.0 / (tau * INTERNAL_SAMPLE_RATE as f32)).exp();
-            let y_mono = mono - self.preemph_prev_mono + a * self.preemph_state_mono;
-            self.preemph_prev_mono = mono;
-            self.preemph_state_mono = y_mono;
-            mono = y_mono;
-
-            let y_stereo = stereo - self.preemph_prev_stereo + a * self.preemph_state_stereo;
-            self.preemph_prev_stereo = stereo;
-            self.preemph_state_stereo = y_stereo;
-            stereo = y_stereo;
+        if let Some([pre_mono, pre_stereo]) = self.preemphasis.as_mut() {
+            let input = mono.abs().max(stereo.abs());
+            let (pre_m, pre_s) = (pre_mono.process(mono), pre_stereo.process(stereo));
+            if !self.bypass[Stage::PreEmphasis.index()] {
+                mono = pre_m;
+                stereo = pre_s;
+            }
+            self.stage_levels[Stage::PreEmphasis.index()].record(input, mono.abs().max(stereo.abs()), 0.0);
         }
 
         if self.compressor_enabled {
+            let input = mono.abs().max(stereo.abs());
             let level = mono.abs().max(stereo.abs()).max(1e-6);
             let level_db = 20.0 * level.log10();
             let mut target_gain_db = 0.0;
@@ -357,71 +1023,179 @@ impl LiveMpx {
                 (-1.0 / (self.comp_release * INTERNAL_SAMPLE_RATE as f32)).exp()
             };
             self.comp_gain_db = target_gain_db + coeff * (self.comp_gain_db - target_gain_db);
-            let gain = 10f32.powf(self.comp_gain_db / 20.0);
-            mono *= gain;
-            stereo *= gain;
+            if !self.bypass[Stage::Compressor.index()] {
+                let gain = 10f32.powf((self.comp_gain_db + self.comp_makeup_db) / 20.0);
+                mono *= gain;
+                stereo *= gain;
+            }
+            self.stage_levels[Stage::Compressor.index()].record(input, mono.abs().max(stereo.abs()), -self.comp_gain_db);
         }
 
-        let mut mpx = self.rds_level * rds_sample + 4.05 * mono;
-        mpx += (4.05 * self.stereo_separation) * CARRIER_38[self.phase_38] * stereo
-            + self.pilot_level * CARRIER_19[self.phase_19];
+        if let Some([notch_mono, notch_stereo]) = self.pilot_notch.as_mut() {
+            let input = mono.abs().max(stereo.abs());
+            let (notch_m, notch_s) = (notch_mono.process(mono), notch_stereo.process(stereo));
+            if !self.bypass[Stage::PilotNotch.index()] {
+                mono = notch_m;
+                stereo = notch_s;
+            }
+            self.stage_levels[Stage::PilotNotch.index()].record(input, mono.abs().max(stereo.abs()), 0.0);
+        }
 
-        self.phase_19 += 1;
-        self.phase_38 += 1;
-        if self.phase_19 >= CARRIER_19.len() {
-            self.phase_19 = 0;
+        if self.inserts.is_active(InsertPoint::Processed) {
+            let (left, right) = self.inserts.process(InsertPoint::Processed, 0.5 * (mono + stereo), 0.5 * (mono - stereo));
+            mono = left + right;
+            stereo = left - right;
         }
-        if self.phase_38 >= CARRIER_38.len() {
-            self.phase_38 = 0;
+
+        // Test tones go straight to the stereo encoder: pre-emphasis, the
+        // compressor and the limiter would change the levels being measured.
+        if let Some((_, left, right)) = test_tone {
+            mono = left + right;
+            stereo = left - right;
         }
 
-        let mut out = mpx * 0.1 * self.gain;
-        if self.limiter_enabled {
-            self.limiter_buffer.push_back(out);
-            if self.limiter_buffer.len() < self.limiter_lookahead {
-                return 0.0;
+        if let Some(tap) = self.monitor.as_mut() {
+            let mut left = 0.5 * (mono + stereo);
+            let mut right = 0.5 * (mono - stereo);
+            if let Some([de_left, de_right]) = self.deemphasis.as_mut() {
+                left = de_left.process(left);
+                right = de_right.process(right);
             }
-            if self.limiter_buffer.len() > self.limiter_lookahead {
-                let _ = self.limiter_buffer.pop_front();
+            tap.push(left, right);
+        }
+
+        let gain_auto = self.automation[AutomationParam::OutputGain.index()].step();
+        let pilot_auto = self.automation[AutomationParam::PilotLevel.index()].step();
+        let rds_auto = self.automation[AutomationParam::RdsLevel.index()].step();
+        let separation_auto = self.automation[AutomationParam::StereoSeparation.index()].step();
+
+        let gain = self.gain.step();
+        let separation = self.stereo_separation.step();
+        let mut rds_term = self.rds_level.step() * rds_auto * rds_sample;
+        let mut pilot_term = self.pilot_level.step() * pilot_auto * self.pilot_table[self.phase_19];
+        if let Some(step) = self.calibration {
+            mono = 0.0;
+            stereo = 0.0;
+            match step {
+                CalibrationStep::PilotOnly => rds_term = 0.0,
+                CalibrationStep::RdsOnly => pilot_term = 0.0,
             }
-            let mut max = 0.0f32;
-            for v in self.limiter_buffer.iter() {
-                let a = v.abs();
-                if a > max {
-                    max = a;
-                }
+        }
+        if let Some(step) = test_step {
+            if !step.pilot() {
+                pilot_term = 0.0;
             }
-            let threshold = self.limiter_threshold.max(0.1);
-            let gain = if max > threshold { threshold / max } else { 1.0 };
-            if let Some(sample) = self.limiter_buffer.front() {
-                out = *sample * gain;
+            if !step.rds() {
+                rds_term = 0.0;
             }
         }
+        let mut audio = 4.05 * mono + (4.05 * separation * separation_auto) * CARRIER_38[self.phase_38] * stereo;
+        let mut carriers = rds_term + pilot_term;
+
+        self.advance_ramp();
+        audio *= self.ramp;
+        carriers *= self.ramp;
+
+        self.advance_carriers();
+
+        let scale = 0.1 * gain * gain_auto * self.ramp;
+        let level = 0.1 * gain * gain_auto;
+        let mut out = (audio + carriers) * level;
+        if self.limiter_enabled && test_step.is_none() {
+            let bypassed = self.bypass[Stage::Limiter.index()];
+            let Some((input, limited)) = self.limiter.process(audio * level, carriers * level, bypassed) else {
+                return 0.0;
+            };
+            out = limited;
+            self.stage_levels[Stage::Limiter.index()].record(input, out, self.limiter.reduction_db());
+        }
+        // Record what leaves the chain, so the modulation and limits views
+        // see the limited peaks.
+        if self.tx_mute {
+            self.modulation.record(0.0, 0.0, 0.0);
+            return 0.0;
+        }
+        self.modulation.record(out, pilot_term * scale, rds_term * scale);
         out
     }
 }
 
 pub struct AudioEngine {
     _input_stream: Option<cpal::Stream>,
+    _backup_stream: Option<cpal::Stream>,
+    _traffic_stream: Option<cpal::Stream>,
     _output_stream: cpal::Stream,
+    _monitor_stream: Option<cpal::Stream>,
     running: Arc<AtomicBool>,
     shared: Arc<Mutex<LiveMpx>>,
     meter: Arc<MeterState>,
-    scope: Arc<Mutex<VecDeque<f32>>>,
+    scope: Arc<Mutex<Vec<f32>>>,
     spectrum: Arc<Mutex<Vec<f32>>>,
     spectrum_peak: Arc<Mutex<Vec<f32>>>,
     spectrum_avg: Arc<Mutex<Vec<f32>>>,
+    input_spectrum: Arc<Mutex<Vec<f32>>>,
     xrun_count: Arc<AtomicU32>,
     buffer_fill: Arc<AtomicU32>,
     latency_ms: f32,
+    failover: Arc<FailoverState>,
+    rtp_stats: Option<Arc<RtpStats>>,
+    device_error: Arc<AtomicBool>,
+    fault: Arc<AtomicU8>,
+    output_callbacks: Arc<AtomicU64>,
+    // Released after the streams above are dropped.
+    _exclusive: Option<ExclusiveOutput>,
+}
+
+// Why an engine stopped producing usable output. The first fault is kept;
+// an input failure with failover configured is left to the failover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EngineFault {
+    Panic = 1,
+    OutputDevice = 2,
+    InputDevice = 3,
+}
+
+impl EngineFault {
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            1 => Some(EngineFault::Panic),
+            2 => Some(EngineFault::OutputDevice),
+            3 => Some(EngineFault::InputDevice),
+            _ => None,
+        }
+    }
+
+    fn record(self, fault: &AtomicU8) {
+        let _ = fault.compare_exchange(0, self as u8, Ordering::Relaxed, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Display for EngineFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            EngineFault::Panic => "internal error in the audio callback",
+            EngineFault::OutputDevice => "output device failed",
+            EngineFault::InputDevice => "input device failed",
+        };
+        write!(f, "{}", label)
+    }
 }
 
 pub struct AudioEngineConfig {
     pub input_device: Option<String>,
+    pub rtp_input: Option<RtpConfig>,
+    pub failover: Option<FailoverConfig>,
+    pub traffic: Option<TrafficRouting>,
+    pub rds_feed: Option<RdsFeedConfig>,
     pub output_device: String,
+    pub exclusive_output: bool,
     pub ps: String,
     pub rt: String,
+    pub code_table: RdsCodeTable,
+    pub translit: Option<TranslitTable>,
     pub pi: u16,
+    pub ecc: Option<u8>,
+    pub lic: Option<u8>,
     pub tp: bool,
     pub ta: bool,
     pub pty: u8,
@@ -431,17 +1205,27 @@ pub struct AudioEngineConfig {
     pub ab_auto: bool,
     pub ct_enabled: bool,
     pub af_list_mhz: Vec<f32>,
+    pub af_regional_mhz: Vec<f32>,
+    pub af_tuned_mhz: Option<f32>,
     pub ps_scroll_enabled: bool,
     pub ps_scroll_text: String,
     pub ps_scroll_cps: f32,
+    pub ps_scroll_mode: PsScrollMode,
+    pub ps_page_dwell_secs: f32,
+    pub ps_page_center: bool,
     pub rt_scroll_enabled: bool,
     pub rt_scroll_text: String,
     pub rt_scroll_cps: f32,
+    pub rt_overflow: RtOverflow,
+    pub rt_ellipsis: String,
+    pub rt_part_secs: f32,
+    pub rate_guard: bool,
     pub output_gain: f32,
     pub limiter_enabled: bool,
     pub limiter_threshold: f32,
     pub limiter_lookahead: usize,
     pub pilot_level: f32,
+    pub pilot_phase_deg: f32,
     pub rds_level: f32,
     pub stereo_separation: f32,
     pub preemphasis_tau: Option<f32>,
@@ -450,10 +1234,89 @@ pub struct AudioEngineConfig {
     pub comp_ratio: f32,
     pub comp_attack: f32,
     pub comp_release: f32,
+    pub comp_makeup_db: f32,
     pub group_0a: usize,
     pub group_2a: usize,
     pub group_4a: usize,
     pub ct_interval_groups: usize,
+    pub fast_tuning: FastTuning,
+    pub ps_alt_list: Vec<String>,
+    pub ps_alt_interval: usize,
+    pub program_delay_secs: f32,
+    pub diversity_delay_ms: f32,
+    pub fade_secs: f32,
+    pub monitor_device: Option<String>,
+    pub lpf_cutoff_hz: f32,
+    pub pilot_notch: bool,
+    pub stage_bypass: [bool; 4],
+    pub mpx_input: bool,
+    pub mpx_input_pilot: bool,
+    // A recorded MPX WAV played instead of the input device in pass-through
+    // mode, optionally looped.
+    pub mpx_file: Option<String>,
+    pub mpx_file_loop: bool,
+    // Registered inserts to run, by name; see inserts::register.
+    pub inserts: Vec<String>,
+    // Hold the output silent and start the MPX on the sample that plays at
+    // this time, so instances sharing the trigger start pilot-coherent.
+    pub start_at: Option<SystemTime>,
+}
+
+// Everything a station preset changes on a running engine, applied under a
+// single lock so the output callback sees the whole set at one buffer
+// boundary. Setters leave filter and limiter state alone when a value is
+// unchanged.
+#[derive(Clone, Debug)]
+pub struct EngineSettings {
+    pub pi: Option<u16>,
+    pub ecc: Option<u8>,
+    pub lic: Option<u8>,
+    pub code_table: RdsCodeTable,
+    pub ps: String,
+    pub rt: String,
+    pub tp: bool,
+    pub ta: bool,
+    pub pty: u8,
+    pub ms: bool,
+    pub di: u8,
+    pub ab: bool,
+    pub ab_auto: bool,
+    pub ct_enabled: bool,
+    pub rate_guard: bool,
+    pub ps_scroll_enabled: bool,
+    pub ps_scroll_text: String,
+    pub ps_scroll_cps: f32,
+    pub ps_scroll_mode: PsScrollMode,
+    pub ps_page_dwell_secs: f32,
+    pub ps_page_center: bool,
+    pub rt_scroll_enabled: bool,
+    pub rt_scroll_text: String,
+    pub rt_scroll_cps: f32,
+    pub rt_overflow: RtOverflow,
+    pub rt_ellipsis: String,
+    pub rt_part_secs: f32,
+    pub output_gain: f32,
+    pub limiter_enabled: bool,
+    pub limiter_threshold: f32,
+    pub limiter_lookahead: usize,
+    pub pilot_level: f32,
+    pub pilot_phase_deg: f32,
+    pub rds_level: f32,
+    pub stereo_separation: f32,
+    pub lpf_cutoff_hz: f32,
+    pub pilot_notch: bool,
+    pub preemphasis_tau: Option<f32>,
+    pub compressor_enabled: bool,
+    pub comp_threshold_db: f32,
+    pub comp_ratio: f32,
+    pub comp_attack: f32,
+    pub comp_release: f32,
+    pub comp_makeup_db: f32,
+    pub group_0a: usize,
+    pub group_2a: usize,
+    pub group_4a: usize,
+    pub ct_interval_groups: usize,
+    pub fast_tuning: FastTuning,
     pub ps_alt_list: Vec<String>,
     pub ps_alt_interval: usize,
 }
@@ -461,24 +1324,51 @@ pub struct AudioEngineConfig {
 pub struct MeterSnapshot {
     pub rms: f32,
     pub peak: f32,
-    pub pilot: f32,
-    pub rds: f32,
+    pub injection: Injection,
     pub bands_db: [f32; SPECTRUM_BANDS],
+    // Min/max pairs, oldest first.
     pub scope: Vec<f32>,
     pub spectrum_db: Vec<f32>,
     pub spectrum_peak_db: Vec<f32>,
     pub spectrum_avg_db: Vec<f32>,
+    pub input_spectrum_db: Vec<f32>,
+    pub input_left_peak: f32,
+    pub input_right_peak: f32,
+    pub input_left_rms: f32,
+    pub input_right_rms: f32,
     pub xrun_count: u32,
     pub buffer_fill: f32,
     pub latency_ms: f32,
+    pub dsp_load_avg: f32,
+    pub dsp_load_worst: f32,
+    pub drift_ppm: f32,
+    pub peak_hold: f32,
+    pub program_peak: f32,
+    pub mask: Option<MaskResult>,
 }
 
 struct MeterState {
     rms: AtomicU32,
     peak: AtomicU32,
     pilot: AtomicU32,
+    stereo_carrier: AtomicU32,
     rds: AtomicU32,
     bands_db: [AtomicU32; SPECTRUM_BANDS],
+    dsp_load_avg: AtomicU32,
+    dsp_load_worst: AtomicU32,
+    drift_ppm: AtomicU32,
+    peak_hold: AtomicU32,
+    program_peak: AtomicU32,
+    mask_margin: AtomicU32,
+    mask_worst_hz: AtomicU32,
+    // Audio low-pass cutoff, where the mask's stop band begins.
+    lpf_cutoff_hz: AtomicU32,
+    input_left_peak: AtomicU32,
+    input_right_peak: AtomicU32,
+    input_left_rms: AtomicU32,
+    input_right_rms: AtomicU32,
+    fft_size: AtomicU32,
+    fft_window: AtomicU32,
 }
 
 impl MeterState {
@@ -487,10 +1377,30 @@ impl MeterState {
             rms: AtomicU32::new(0),
             peak: AtomicU32::new(0),
             pilot: AtomicU32::new(0),
+            stereo_carrier: AtomicU32::new(0),
             rds: AtomicU32::new(0),
             bands_db: std::array::from_fn(|_| AtomicU32::new(f32_to_u32(SPECTRUM_MIN_DB))),
+            dsp_load_avg: AtomicU32::new(0),
+            dsp_load_worst: AtomicU32::new(0),
+            drift_ppm: AtomicU32::new(0),
+            peak_hold: AtomicU32::new(0),
+            program_peak: AtomicU32::new(0),
+            mask_margin: AtomicU32::new(f32_to_u32(f32::MAX)),
+            mask_worst_hz: AtomicU32::new(0),
+            lpf_cutoff_hz: AtomicU32::new(f32_to_u32(DEFAULT_LPF_CUTOFF_HZ)),
+            input_left_peak: AtomicU32::new(0),
+            input_right_peak: AtomicU32::new(0),
+            input_left_rms: AtomicU32::new(0),
+            input_right_rms: AtomicU32::new(0),
+            fft_size: AtomicU32::new(FftSize::default().to_u32()),
+            fft_window: AtomicU32::new(FftWindow::default().to_u32()),
         }
     }
+
+    fn set_lpf_cutoff(&self, cutoff_hz: f32) {
+        let cutoff = cutoff_hz.clamp(MIN_LPF_CUTOFF_HZ, MAX_LPF_CUTOFF_HZ);
+        self.lpf_cutoff_hz.store(f32_to_u32(cutoff), Ordering::Relaxed);
+    }
 }
 
 fn f32_to_u32(v: f32) -> u32 {
@@ -501,9 +1411,64 @@ fn u32_to_f32(v: u32) -> f32 {
     f32::from_bits(v)
 }
 
-fn db_to_unit(db: f32) -> f32 {
-    let clamped = db.clamp(SPECTRUM_MIN_DB, SPECTRUM_MAX_DB);
-    (clamped - SPECTRUM_MIN_DB) / (SPECTRUM_MAX_DB - SPECTRUM_MIN_DB)
+// The output callback never waits on the UI: it spins a bounded number of
+// times for the engine and otherwise lets the caller skip the block. A
+// poisoned lock is recorded as a panic fault rather than unwinding here, so
+// the app restarts the engine.
+fn try_lock_engine<'a>(shared: &'a Mutex<LiveMpx>, fault: &AtomicU8) -> Option<MutexGuard<'a, LiveMpx>> {
+    for _ in 0..ENGINE_LOCK_TRIES {
+        match shared.try_lock() {
+            Ok(engine) => return Some(engine),
+            Err(TryLockError::WouldBlock) => std::hint::spin_loop(),
+            Err(TryLockError::Poisoned(_)) => {
+                EngineFault::Panic.record(fault);
+                return None;
+            }
+        }
+    }
+    None
+}
+
+pub fn play_alert_tone() {
+    std::thread::spawn(|| {
+        if let Err(e) = alert_tone_blocking() {
+            eprintln!("alert tone error: {}", e);
+        }
+    });
+}
+
+fn alert_tone_blocking() -> Result<()> {
+    let host = cpal::default_host();
+    let device = host
+        .default_output_device()
+        .ok_or_else(|| anyhow!("No default output device"))?;
+    let supported = device.default_output_config()?;
+    if supported.sample_format() != cpal::SampleFormat::F32 {
+        return Err(anyhow!("Default output device does not accept float32"));
+    }
+    let config: cpal::StreamConfig = supported.into();
+    let rate = config.sample_rate.0 as f32;
+    let channels = config.channels as usize;
+    let mut n = 0u64;
+    let stream = device.build_output_stream(
+        &config,
+        move |data: &mut [f32], _| {
+            for frame in data.chunks_mut(channels) {
+                let t = n as f32 / rate;
+                let beep = (t * 4.0).fract() < 0.5;
+                let v = if beep { 0.2 * (2.0 * std::f32::consts::PI * 880.0 * t).sin() } else { 0.0 };
+                for sample in frame.iter_mut() {
+                    *sample = v;
+                }
+                n += 1;
+            }
+        },
+        |err| eprintln!("alert stream error: {}", err),
+        None,
+    )?;
+    stream.play()?;
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    Ok(())
 }
 
 pub fn list_input_devices() -> Result<Vec<String>> {
@@ -515,9 +1480,74 @@ pub fn list_input_devices() -> Result<Vec<String>> {
         }
     }
     devices.sort();
+    devices.extend(list_loopback_sources(&host));
     Ok(devices)
 }
 
+#[cfg(target_os = "windows")]
+fn list_loopback_sources(host: &cpal::Host) -> Vec<String> {
+    let mut sources = host
+        .output_devices()
+        .map(|devices| devices.filter_map(|d| d.name().ok()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    sources.sort();
+    sources.iter().map(|name| crate::loopback::loopback_name(name)).collect()
+}
+
+#[cfg(target_os = "linux")]
+fn list_loopback_sources(_host: &cpal::Host) -> Vec<String> {
+    crate::loopback::pulse_monitor_sources()
+        .iter()
+        .map(|name| crate::loopback::loopback_name(name))
+        .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn list_loopback_sources(_host: &cpal::Host) -> Vec<String> {
+    Vec::new()
+}
+
+fn open_input(host: &cpal::Host, name: &str) -> Result<(cpal::Device, cpal::SupportedStreamConfig)> {
+    if let Some(target) = loopback_target(name) {
+        return open_loopback(host, target);
+    }
+    let input_devices = host.input_devices()?.collect::<Vec<_>>();
+    let device = find_device_by_name(input_devices, name).ok_or_else(|| anyhow!("Input device not found"))?;
+    let supported = pick_config(&device, true)?;
+    Ok((device, supported))
+}
+
+// WASAPI records what an output device plays when an input stream is built
+// on it. Shared mode keeps the device's mix rate, so that rate is used and
+// the drift resampler takes it to 228 kHz.
+#[cfg(target_os = "windows")]
+fn open_loopback(host: &cpal::Host, target: &str) -> Result<(cpal::Device, cpal::SupportedStreamConfig)> {
+    let output_devices = host.output_devices()?.collect::<Vec<_>>();
+    let device = find_device_by_name(output_devices, target).ok_or_else(|| anyhow!("Loopback device not found"))?;
+    let supported = device.default_output_config()?;
+    if supported.sample_format() != cpal::SampleFormat::F32 {
+        return Err(anyhow!("Loopback device does not deliver float32"));
+    }
+    Ok((device, supported))
+}
+
+// The "pulse" device records from the monitor source that start_engine
+// selects with a PulseSource while the stream is built, capturing everything
+// played to that sink. PipeWire's pulse server honours the same variable.
+#[cfg(target_os = "linux")]
+fn open_loopback(host: &cpal::Host, _target: &str) -> Result<(cpal::Device, cpal::SupportedStreamConfig)> {
+    let input_devices = host.input_devices()?.collect::<Vec<_>>();
+    let device = find_device_by_name(input_devices, "pulse")
+        .ok_or_else(|| anyhow!("Loopback capture needs the ALSA \"pulse\" device (PulseAudio or pipewire-pulse)"))?;
+    let supported = pick_config(&device, true)?;
+    Ok((device, supported))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn open_loopback(_host: &cpal::Host, _target: &str) -> Result<(cpal::Device, cpal::SupportedStreamConfig)> {
+    Err(anyhow!("Loopback capture is not built in on this platform, use a virtual device such as BlackHole"))
+}
+
 pub fn list_output_devices() -> Result<Vec<String>> {
     let host = cpal::default_host();
     let mut devices = Vec::new();
@@ -526,8 +1556,106 @@ pub fn list_output_devices() -> Result<Vec<String>> {
             devices.push(name);
         }
     }
-    devices.sort();
-    Ok(devices)
+    devices.sort();
+    Ok(devices)
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DeviceCapabilities {
+    pub name: String,
+    pub direction: &'static str,
+    pub default_config: Option<String>,
+    pub supported_configs: Vec<String>,
+    // 192 kHz float32, as the engine opens shared devices.
+    pub engine_compatible: bool,
+}
+
+// Everything the audio host reports about each device, for support bundles.
+pub fn device_capabilities() -> Result<(String, Vec<DeviceCapabilities>)> {
+    let host = cpal::default_host();
+    let mut devices = Vec::new();
+    let inputs = host.input_devices()?.collect::<Vec<_>>();
+    let outputs = host.output_devices()?.collect::<Vec<_>>();
+    for (is_input, list) in [(true, inputs), (false, outputs)] {
+        for device in list {
+            let Ok(name) = device.name() else {
+                continue;
+            };
+            let default_config = if is_input { device.default_input_config() } else { device.default_output_config() };
+            let supported = if is_input {
+                device.supported_input_configs().map(|c| c.collect::<Vec<_>>())
+            } else {
+                device.supported_output_configs().map(|c| c.collect::<Vec<_>>())
+            };
+            devices.push(DeviceCapabilities {
+                name,
+                direction: if is_input { "input" } else { "output" },
+                default_config: default_config
+                    .ok()
+                    .map(|c| format!("{} {} ch {} Hz", c.sample_format(), c.channels(), c.sample_rate().0)),
+                supported_configs: supported
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|c| {
+                        format!(
+                            "{} {} ch {}-{} Hz",
+                            c.sample_format(),
+                            c.channels(),
+                            c.min_sample_rate().0,
+                            c.max_sample_rate().0
+                        )
+                    })
+                    .collect(),
+                engine_compatible: pick_config(&device, is_input).is_ok(),
+            });
+        }
+    }
+    Ok((host.id().name().to_string(), devices))
+}
+
+// A second program source read at the internal rate: a capture device
+// (failover backup, traffic input) or a looped WAV file.
+#[allow(clippy::type_complexity)]
+fn open_side_input(
+    host: &cpal::Host,
+    input: &BackupInput,
+    role: &str,
+) -> Result<(Option<cpal::Stream>, Option<HeapConsumer<Frame>>, Option<LoopedFile>)> {
+    match input {
+        BackupInput::Device(name) if name.starts_with(LOOPBACK_PREFIX) => {
+            Err(anyhow!("Loopback sources cannot be used as the {} input", role.to_lowercase()))
+        }
+        BackupInput::Device(name) => {
+            let input_devices = host.input_devices()?.collect::<Vec<_>>();
+            let device = find_device_by_name(input_devices, name)
+                .ok_or_else(|| anyhow!("{} input device not found", role))?;
+            let input_config: cpal::StreamConfig = pick_config(&device, true)?.into();
+            let channels = input_config.channels as usize;
+            let (mut prod, cons) = HeapRb::<Frame>::new(OUTPUT_SAMPLE_RATE as usize * 2).split();
+            let role = role.to_lowercase();
+            let err_fn = move |err| eprintln!("{} input stream error: {}", role, err);
+            let stream = device.build_input_stream(
+                &input_config,
+                move |data: &[f32], _| {
+                    let mut i = 0;
+                    while i + channels <= data.len() {
+                        let left = data[i];
+                        let right = if channels > 1 { data[i + 1] } else { data[i] };
+                        let _ = prod.push(Frame { left, right });
+                        i += channels;
+                    }
+                },
+                err_fn,
+                None,
+            )?;
+            Ok((Some(stream), Some(cons), None))
+        }
+        BackupInput::File(path) => {
+            let source = load_wav(path)?;
+            let file = LoopedFile::new(source.samples, source.channels, source.sample_rate, INTERNAL_SAMPLE_RATE);
+            Ok((None, None, Some(file)))
+        }
+    }
 }
 
 fn find_device_by_name(devices: Vec<cpal::Device>, name: &str) -> Option<cpal::Device> {
@@ -558,6 +1686,53 @@ fn pick_config(
     Err(anyhow!("Device does not support 192 kHz float32"))
 }
 
+// Hardware devices take the card's own formats, and float is rare on ALSA
+// `hw:`, so integer formats are accepted too and converted in the callback.
+fn pick_exclusive_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig> {
+    let configs = device.supported_output_configs()?.collect::<Vec<_>>();
+    for format in [cpal::SampleFormat::F32, cpal::SampleFormat::I32, cpal::SampleFormat::I16] {
+        for &cfg in &configs {
+            if cfg.sample_format() == format
+                && cfg.min_sample_rate().0 <= OUTPUT_SAMPLE_RATE
+                && cfg.max_sample_rate().0 >= OUTPUT_SAMPLE_RATE
+            {
+                return Ok(cfg.with_sample_rate(cpal::SampleRate(OUTPUT_SAMPLE_RATE)));
+            }
+        }
+    }
+    Err(anyhow!("Device does not run natively at 192 kHz"))
+}
+
+// Renders float into a scratch buffer and converts for devices that only take
+// integer samples.
+fn build_converted_output<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut render: impl FnMut(&mut [f32], &cpal::OutputCallbackInfo) + Send + 'static,
+    err_fn: impl FnMut(cpal::StreamError) + Send + 'static,
+) -> Result<cpal::Stream>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    let mut scratch = vec![0.0f32; OUTPUT_SAMPLE_RATE as usize / 10 * config.channels as usize];
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [T], info| {
+            if scratch.len() < data.len() {
+                scratch.resize(data.len(), 0.0);
+            }
+            let scratch = &mut scratch[..data.len()];
+            render(scratch, info);
+            for (out, &x) in data.iter_mut().zip(scratch.iter()) {
+                *out = <T as cpal::Sample>::from_sample(x.clamp(-1.0, 1.0));
+            }
+        },
+        err_fn,
+        None,
+    )?;
+    Ok(stream)
+}
+
 pub fn start_engine(config: AudioEngineConfig) -> Result<AudioEngine> {
     let host = cpal::default_host();
 
@@ -565,20 +1740,49 @@ pub fn start_engine(config: AudioEngineConfig) -> Result<AudioEngine> {
     let output_device = find_device_by_name(output_devices, &config.output_device)
         .ok_or_else(|| anyhow!("Output device not found"))?;
 
-    let output_supported = pick_config(&output_device, false)?;
-    let output_config: cpal::StreamConfig = output_supported.clone().into();
-
-    let input_device = if let Some(ref name) = config.input_device {
-        let input_devices = host.input_devices()?.collect::<Vec<_>>();
-        Some(find_device_by_name(input_devices, name).ok_or_else(|| anyhow!("Input device not found"))?)
+    if config.exclusive_output && config.monitor_device.as_deref() == Some(config.output_device.as_str()) {
+        return Err(anyhow!("The monitor cannot share an exclusive output device"));
+    }
+    // Claimed before the stream is built so the device is already at 192 kHz.
+    let exclusive = if config.exclusive_output {
+        Some(claim_output(&config.output_device, OUTPUT_SAMPLE_RATE)?)
     } else {
         None
     };
+    let output_supported = if exclusive.is_some() {
+        pick_exclusive_config(&output_device)?
+    } else {
+        pick_config(&output_device, false)?
+    };
+    let output_config: cpal::StreamConfig = output_supported.clone().into();
+
+    if config.mpx_input && config.rtp_input.is_some() {
+        return Err(anyhow!("MPX input needs a 192 kHz sound card input, not RTP"));
+    }
+    if config.mpx_file.is_some() && !config.mpx_input {
+        return Err(anyhow!("MPX file replay needs MPX pass-through"));
+    }
+    let mut mpx_replay = match config.mpx_file.as_deref() {
+        Some(path) => Some(MpxReplay::open(path, INTERNAL_SAMPLE_RATE, config.mpx_file_loop)?),
+        None => None,
+    };
+
+    if config.input_device.as_deref().and_then(loopback_target) == Some(config.output_device.as_str()) {
+        return Err(anyhow!("Loopback of the MPX output device would feed the MPX back into itself"));
+    }
 
-    let input_supported = if let Some(ref device) = input_device {
-        Some(pick_config(device, true)?)
+    #[cfg(target_os = "linux")]
+    let pulse_source = match config.input_device.as_deref().and_then(loopback_target) {
+        Some(target) if config.rtp_input.is_none() && mpx_replay.is_none() => Some(PulseSource::select(target)),
+        _ => None,
+    };
+    let (input_device, input_supported) = if config.rtp_input.is_some() || mpx_replay.is_some() {
+        (None, None)
+    } else if let Some(ref name) = config.input_device {
+        let (device, supported) = open_input(&host, name)?;
+        (Some(device), Some(supported))
     } else {
-        None
+        (None, None)
     };
 
     let ring = HeapRb::<Frame>::new(OUTPUT_SAMPLE_RATE as usize * 2);
@@ -587,12 +1791,33 @@ pub fn start_engine(config: AudioEngineConfig) -> Result<AudioEngine> {
     let xrun_count = Arc::new(AtomicU32::new(0));
     let buffer_fill = Arc::new(AtomicU32::new(0));
 
+    let running = Arc::new(AtomicBool::new(true));
+    let failover_state = Arc::new(FailoverState::new());
+    let mut rtp_stats = None;
+    let failover_for_input = Arc::clone(&failover_state);
+    let device_error = Arc::new(AtomicBool::new(false));
+    let device_error_for_input = Arc::clone(&device_error);
+    let fault = Arc::new(AtomicU8::new(0));
+    let fault_for_input = Arc::clone(&fault);
+    let input_has_failover = config.failover.is_some();
+
     let xrun_for_input = Arc::clone(&xrun_count);
     let fill_for_input = Arc::clone(&buffer_fill);
+    let mut drift_resampler = match input_supported {
+        Some(ref cfg) => DriftResampler::new(cfg.sample_rate().0, INTERNAL_SAMPLE_RATE, true),
+        None => DriftResampler::new(INTERNAL_SAMPLE_RATE, INTERNAL_SAMPLE_RATE, false),
+    };
     let input_stream = if let (Some(device), Some(cfg)) = (input_device, input_supported) {
         let input_config: cpal::StreamConfig = cfg.clone().into();
         let channels = input_config.channels as usize;
-        let err_fn = |err| eprintln!("input stream error: {}", err);
+        let err_fn = move |err| {
+            eprintln!("input stream error: {}", err);
+            failover_for_input.mark_primary_failed();
+            device_error_for_input.store(true, Ordering::Relaxed);
+            if !input_has_failover {
+                EngineFault::InputDevice.record(&fault_for_input);
+            }
+        };
         let stream = device.build_input_stream(
             &input_config,
             move |data: &[f32], _| {
@@ -614,15 +1839,76 @@ pub fn start_engine(config: AudioEngineConfig) -> Result<AudioEngine> {
         )?;
         Some(stream)
     } else {
+        if let Some(rtp) = config.rtp_input.clone() {
+            let socket = open_rtp_socket(&rtp)?;
+            let stats = Arc::new(RtpStats::default());
+            rtp_stats = Some(Arc::clone(&stats));
+            let running_for_rtp = Arc::clone(&running);
+            std::thread::spawn(move || {
+                let mut recovery = ClockRecovery::new(rtp.sample_rate, INTERNAL_SAMPLE_RATE, rtp.buffer_ms);
+                let mut buf = vec![0u8; 9000];
+                let mut out = Vec::new();
+                while running_for_rtp.load(Ordering::Relaxed) {
+                    let Ok(len) = socket.recv(&mut buf) else {
+                        continue;
+                    };
+                    let Some(packet) = parse_rtp_packet(&buf[..len], rtp.channels, rtp.bits) else {
+                        continue;
+                    };
+                    if !recovery.accept(packet.seq, &stats) {
+                        continue;
+                    }
+                    out.clear();
+                    recovery.process(&packet.frames, prod.len(), &mut out);
+                    stats.ppm.store(recovery.ratio_ppm(), Ordering::Relaxed);
+                    for &(left, right) in &out {
+                        if prod.push(Frame { left, right }).is_err() {
+                            xrun_for_input.fetch_add(1, Ordering::Relaxed);
+                        } else {
+                            let prev = fill_for_input.load(Ordering::Relaxed);
+                            fill_for_input.store(prev.saturating_add(1), Ordering::Relaxed);
+                        }
+                    }
+                }
+            });
+        }
         None
     };
+    // The capture stream is open, so the monitor source is no longer needed.
+    #[cfg(target_os = "linux")]
+    drop(pulse_source);
 
+    let (backup_stream, mut backup_cons, mut backup_file) = match config.failover.as_ref() {
+        Some(failover) => open_side_input(&host, &failover.backup, "Backup")?,
+        None => (None, None, None),
+    };
+    let (traffic_stream, mut traffic_cons, mut traffic_file) = match config.traffic.as_ref() {
+        Some(traffic) => open_side_input(&host, &traffic.input, "Traffic")?,
+        None => (None, None, None),
+    };
+    let mut traffic_mixer = config
+        .traffic
+        .as_ref()
+        .map(|t| TrafficMixer::new(t, INTERNAL_SAMPLE_RATE));
+    let mut input_failover = config
+        .failover
+        .as_ref()
+        .map(|f| InputFailover::new(f, INTERNAL_SAMPLE_RATE));
+    let failover_for_output = Arc::clone(&failover_state);
+
+    let inserts = InsertChain::new(&config.inserts)?;
     let shared = Arc::new(Mutex::new(LiveMpx::new()));
     {
         let mut engine = shared.lock().unwrap();
-        engine.set_ps(&config.ps);
-        engine.set_rt(&config.rt);
+        engine.inserts = inserts;
+        engine.set_code_table(config.code_table);
+        engine.set_transliteration(config.translit.clone());
+        engine.set_rt_overflow(config.rt_overflow, &config.rt_ellipsis, config.rt_part_secs);
+        engine.set_ps(&config.ps, RdsChangeSource::Manual);
+        engine.set_rt(&config.rt, RdsChangeSource::Manual);
         engine.set_pi(config.pi);
+        engine.set_ecc(config.ecc);
+        engine.set_lic(config.lic);
         engine.set_tp(config.tp);
         engine.set_ta(config.ta);
         engine.set_pty(config.pty);
@@ -631,15 +1917,24 @@ pub fn start_engine(config: AudioEngineConfig) -> Result<AudioEngine> {
         engine.set_ab(config.ab);
         engine.set_ab_auto(config.ab_auto);
         engine.set_ct_enabled(config.ct_enabled);
-        engine.set_af_list_mhz(&config.af_list_mhz);
+        engine.set_af_entries(&config.af_list_mhz, &config.af_regional_mhz, config.af_tuned_mhz);
+        engine.set_rate_guard(config.rate_guard);
+        engine.set_ps_scroll_mode(config.ps_scroll_mode, config.ps_page_dwell_secs, config.ps_page_center);
         engine.set_ps_scroll(config.ps_scroll_enabled, &config.ps_scroll_text, config.ps_scroll_cps);
         engine.set_rt_scroll(config.rt_scroll_enabled, &config.rt_scroll_text, config.rt_scroll_cps);
         engine.set_gain(config.output_gain);
         engine.set_limiter(config.limiter_enabled, config.limiter_threshold);
         engine.set_limiter_lookahead(config.limiter_lookahead);
         engine.set_pilot_level(config.pilot_level);
+        engine.set_pilot_phase(config.pilot_phase_deg);
         engine.set_rds_level(config.rds_level);
         engine.set_stereo_separation(config.stereo_separation);
+        engine.set_lpf_cutoff(config.lpf_cutoff_hz);
+        engine.set_pilot_notch(config.pilot_notch);
+        engine.set_mpx_input(config.mpx_input, config.mpx_input_pilot);
+        for stage in Stage::ALL {
+            engine.set_bypass(stage, config.stage_bypass[stage.index()]);
+        }
         engine.set_preemphasis(config.preemphasis_tau);
         engine.set_compressor(
             config.compressor_enabled,
@@ -647,34 +1942,109 @@ pub fn start_engine(config: AudioEngineConfig) -> Result<AudioEngine> {
             config.comp_ratio,
             config.comp_attack,
             config.comp_release,
+            config.comp_makeup_db,
         );
         engine.set_group_mix(config.group_0a, config.group_2a, config.group_4a);
         engine.set_ct_interval(config.ct_interval_groups);
+        engine.set_fast_tuning(config.fast_tuning);
         engine.set_ps_alternates(config.ps_alt_list.clone(), config.ps_alt_interval);
+        engine.set_program_delay(config.program_delay_secs);
+        engine.set_diversity_delay(config.diversity_delay_ms);
+        engine.set_group_log(config.rds_feed.is_some());
+        engine.ramp = 0.0;
+        engine.start_ramp(1.0, config.fade_secs);
+    }
+
+    // Groups are collected by the output callback and sent from here, so a
+    // slow network never blocks the audio.
+    if let Some(feed_config) = config.rds_feed.as_ref() {
+        let mut feed = RdsFeed::open(feed_config)?;
+        let shared_for_feed = Arc::clone(&shared);
+        let running_for_feed = Arc::clone(&running);
+        std::thread::spawn(move || {
+            while running_for_feed.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(20));
+                let groups = shared_for_feed
+                    .lock()
+                    .map(|mut engine| engine.take_group_log())
+                    .unwrap_or_default();
+                if !groups.is_empty() {
+                    feed.send(&groups);
+                }
+            }
+        });
     }
 
+    let monitor_stream = match config.monitor_device {
+        Some(ref name) => {
+            let devices = host.output_devices()?.collect::<Vec<_>>();
+            let device = find_device_by_name(devices, name).ok_or_else(|| anyhow!("Monitor device not found"))?;
+            let supported = device.default_output_config()?;
+            if supported.sample_format() != cpal::SampleFormat::F32 {
+                return Err(anyhow!("Monitor device does not accept float32"));
+            }
+            let monitor_config: cpal::StreamConfig = supported.into();
+            let channels = monitor_config.channels as usize;
+            let rate = monitor_config.sample_rate.0;
+            let (prod, mut monitor_cons) = HeapRb::<Frame>::new(rate as usize / 10).split();
+            shared.lock().unwrap().monitor = Some(MonitorTap::new(prod, rate));
+            let stream = device.build_output_stream(
+                &monitor_config,
+                move |data: &mut [f32], _| {
+                    for frame in data.chunks_mut(channels) {
+                        let f = monitor_cons.pop().unwrap_or(Frame { left: 0.0, right: 0.0 });
+                        for (ch, sample) in frame.iter_mut().enumerate() {
+                            *sample = if ch % 2 == 0 { f.left } else { f.right };
+                        }
+                    }
+                },
+                |err| eprintln!("monitor stream error: {}", err),
+                None,
+            )?;
+            Some(stream)
+        }
+        None => None,
+    };
+
     let mut output_resampler = OutputResampler::new(INTERNAL_SAMPLE_RATE, OUTPUT_SAMPLE_RATE);
 
     let meter = Arc::new(MeterState::new());
+    meter.set_lpf_cutoff(config.lpf_cutoff_hz);
     let meter_for_output = Arc::clone(&meter);
-    let scope = Arc::new(Mutex::new(VecDeque::with_capacity(2048)));
-    let scope_for_output = Arc::clone(&scope);
+    let scope = Arc::new(Mutex::new(Vec::with_capacity(SCOPE_POINTS * 2)));
 
     let mut fft_planner = FftPlanner::<f32>::new();
-    let fft = fft_planner.plan_fft_forward(1024);
-    let mut fft_buf: Vec<Complex<f32>> = vec![Complex::new(0.0, 0.0); 1024];
-    let mut fft_pos: usize = 0;
     let spectrum = Arc::new(Mutex::new(vec![SPECTRUM_MIN_DB; SPECTRUM_BINS]));
     let spectrum_peak = Arc::new(Mutex::new(vec![SPECTRUM_MIN_DB; SPECTRUM_BINS]));
     let spectrum_avg = Arc::new(Mutex::new(vec![SPECTRUM_MIN_DB; SPECTRUM_BINS]));
-    let spectrum_for_output = Arc::clone(&spectrum);
-    let spectrum_peak_for_output = Arc::clone(&spectrum_peak);
-    let spectrum_avg_for_output = Arc::clone(&spectrum_avg);
-
-    let err_fn = |err| eprintln!("output stream error: {}", err);
+    let input_spectrum = Arc::new(Mutex::new(vec![SPECTRUM_MIN_DB; INPUT_SPECTRUM_BINS]));
+    let (mut mpx_tap, mpx_tap_cons) = HeapRb::<f32>::new(OUTPUT_SAMPLE_RATE as usize / 2).split();
+    let (mut input_tap, input_tap_cons) = HeapRb::<Frame>::new(INTERNAL_SAMPLE_RATE as usize / 2).split();
+    let analysis = MeterAnalysis {
+        mpx_tap: mpx_tap_cons,
+        input_tap: input_tap_cons,
+        input: InputAnalyzer::new(&mut fft_planner),
+        injection: InjectionMeter::new(OUTPUT_SAMPLE_RATE),
+        meter: Arc::clone(&meter),
+        scope_window: VecDeque::with_capacity(SCOPE_WINDOW),
+        scope: Arc::clone(&scope),
+        spectrum: Arc::clone(&spectrum),
+        spectrum_peak: Arc::clone(&spectrum_peak),
+        spectrum_avg: Arc::clone(&spectrum_avg),
+        input_spectrum: Arc::clone(&input_spectrum),
+        running: Arc::clone(&running),
+    };
+    std::thread::spawn(move || analysis.run(fft_planner));
+
+    let device_error_for_output = Arc::clone(&device_error);
+    let fault_for_output_error = Arc::clone(&fault);
+    let err_fn = move |err| {
+        eprintln!("output stream error: {}", err);
+        device_error_for_output.store(true, Ordering::Relaxed);
+        EngineFault::OutputDevice.record(&fault_for_output_error);
+    };
     let xrun_for_output = Arc::clone(&xrun_count);
     let fill_for_output = Arc::clone(&buffer_fill);
-    let running = Arc::new(AtomicBool::new(true));
     let running_for_output = Arc::clone(&running);
     let latency_ms = match output_config.buffer_size {
         cpal::BufferSize::Fixed(frames) => frames as f32 / OUTPUT_SAMPLE_RATE as f32 * 1000.0,
@@ -682,140 +2052,200 @@ pub fn start_engine(config: AudioEngineConfig) -> Result<AudioEngine> {
     };
     let output_channels = output_config.channels as usize;
     let shared_for_output = Arc::clone(&shared);
-    let output_stream = output_device.build_output_stream(
-        &output_config,
-        move |data: &mut [f32], _| {
-            if !running_for_output.load(Ordering::Relaxed) {
-                for sample in data.iter_mut() {
+    let fault_for_lock = Arc::clone(&fault);
+    let mut start_at = config.start_at;
+    let mut render_block = move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
+        if !running_for_output.load(Ordering::Relaxed) {
+            for sample in data.iter_mut() {
+                *sample = 0.0;
+            }
+            meter_for_output.rms.store(f32_to_u32(0.0), Ordering::Relaxed);
+            meter_for_output.peak.store(f32_to_u32(0.0), Ordering::Relaxed);
+            meter_for_output.pilot.store(f32_to_u32(0.0), Ordering::Relaxed);
+            meter_for_output.stereo_carrier.store(f32_to_u32(0.0), Ordering::Relaxed);
+            meter_for_output.rds.store(f32_to_u32(0.0), Ordering::Relaxed);
+            for i in 0..SPECTRUM_BANDS {
+                meter_for_output.bands_db[i].store(f32_to_u32(SPECTRUM_MIN_DB), Ordering::Relaxed);
+            }
+            return;
+        }
+        let data = match start_at {
+            Some(at) => {
+                let timestamp = info.timestamp();
+                let latency = timestamp.playback.duration_since(&timestamp.callback).unwrap_or_default();
+                let wait = at.duration_since(SystemTime::now() + latency).unwrap_or_default();
+                let skip = ((wait.as_secs_f64() * OUTPUT_SAMPLE_RATE as f64).round() as usize)
+                    .saturating_mul(output_channels)
+                    .min(data.len());
+                for sample in data[..skip].iter_mut() {
                     *sample = 0.0;
                 }
-                meter_for_output.rms.store(f32_to_u32(0.0), Ordering::Relaxed);
-                meter_for_output.peak.store(f32_to_u32(0.0), Ordering::Relaxed);
-                meter_for_output.pilot.store(f32_to_u32(0.0), Ordering::Relaxed);
-                meter_for_output.rds.store(f32_to_u32(0.0), Ordering::Relaxed);
-                for i in 0..SPECTRUM_BANDS {
-                    meter_for_output.bands_db[i].store(f32_to_u32(SPECTRUM_MIN_DB), Ordering::Relaxed);
+                // Input that arrives before the trigger is dropped, so the
+                // program does not start a full ring buffer late.
+                while cons.len() as f64 > drift_resampler.target_fill && cons.pop().is_some() {
+                    let prev = fill_for_output.load(Ordering::Relaxed);
+                    fill_for_output.store(prev.saturating_sub(1), Ordering::Relaxed);
+                }
+                if skip == data.len() {
+                    return;
                 }
-                return;
+                start_at = None;
+                &mut data[skip..]
             }
-            let mut engine = shared_for_output.lock().unwrap();
-            let mut index = 0;
-            let mut sum_sq = 0.0f32;
-            let mut peak = 0.0f32;
-            while index + output_channels <= data.len() {
-                let out = output_resampler.next_sample(|| {
-                    let frame = match cons.pop() {
-                        Some(f) => {
+            None => data,
+        };
+        let callback_start = Instant::now();
+        let Some(mut engine) = try_lock_engine(&shared_for_output, &fault_for_lock) else {
+            data.fill(0.0);
+            xrun_for_output.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        if let Some(mixer) = traffic_mixer.as_mut() {
+            if mixer.set_ta(engine.ta()) {
+                if let Some(file) = traffic_file.as_mut() {
+                    file.restart();
+                }
+            }
+        }
+        let mut index = 0;
+        let mut sum_sq = 0.0f32;
+        let mut peak = 0.0f32;
+        let mut program_peak = 0.0f32;
+        drift_resampler.update(cons.len());
+        meter_for_output.drift_ppm.store(f32_to_u32(drift_resampler.drift_ppm()), Ordering::Relaxed);
+        while index + output_channels <= data.len() {
+            let out = output_resampler.next_sample(|| {
+                let primary = match mpx_replay.as_mut() {
+                    Some(replay) => {
+                        let sample = replay.next_sample();
+                        Some(Frame { left: sample, right: sample })
+                    }
+                    None => drift_resampler.next_frame(|| {
+                        let frame = cons.pop();
+                        if frame.is_some() {
                             let prev = fill_for_output.load(Ordering::Relaxed);
                             fill_for_output.store(prev.saturating_sub(1), Ordering::Relaxed);
-                            f
                         }
-                        None => {
-                            xrun_for_output.fetch_add(1, Ordering::Relaxed);
-                            Frame { left: 0.0, right: 0.0 }
-                        }
-                    };
-                    engine.next_sample(frame)
-                });
-                for ch in 0..output_channels {
-                    data[index + ch] = out;
+                        frame
+                    }),
+                };
+                if primary.is_none() {
+                    xrun_for_output.fetch_add(1, Ordering::Relaxed);
                 }
-                sum_sq += out * out;
-                if out.abs() > peak {
-                    peak = out.abs();
-                }
-
-                fft_buf[fft_pos].re = out;
-                fft_buf[fft_pos].im = 0.0;
-                fft_pos += 1;
-                if fft_pos >= fft_buf.len() {
-                    fft_pos = 0;
-                    let mut windowed = fft_buf.clone();
-                    let window_len = windowed.len() as f32;
-                    for (i, v) in windowed.iter_mut().enumerate() {
-                        let w = 0.5 - 0.5 * ((2.0 * std::f32::consts::PI * i as f32) / window_len).cos();
-                        v.re *= w;
-                    }
-                    fft.process(&mut windowed);
-                    let mut bands = [SPECTRUM_MIN_DB; SPECTRUM_BANDS];
-                    let mut pilot = 0.0f32;
-                    let mut rds = 0.0f32;
-                    let n = windowed.len() as f32;
-                    let mut spec = vec![SPECTRUM_MIN_DB; SPECTRUM_BINS];
-                    for (k, v) in windowed.iter().enumerate().take(windowed.len() / 2) {
-                        let freq = k as f32 * OUTPUT_SAMPLE_RATE as f32 / n;
-                        let mag = (v.re * v.re + v.im * v.im).sqrt() / n;
-                        let db = 20.0 * (mag + 1e-9).log10();
-                        let unit = db_to_unit(db);
-                        if (freq - 19000.0).abs() < 100.0 {
-                            pilot = pilot.max(unit);
-                        }
-                        if (freq - 57000.0).abs() < 150.0 {
-                            rds = rds.max(unit);
-                        }
-                        if k < SPECTRUM_BINS {
-                            spec[k] = db;
-                        }
-                        let band = ((freq / (OUTPUT_SAMPLE_RATE as f32 / 2.0)) * SPECTRUM_BANDS as f32)
-                            .floor() as usize;
-                        if band < SPECTRUM_BANDS && db > bands[band] {
-                            bands[band] = db;
-                        }
-                    }
-                    meter_for_output.pilot.store(f32_to_u32(pilot), Ordering::Relaxed);
-                    meter_for_output.rds.store(f32_to_u32(rds), Ordering::Relaxed);
-                    for i in 0..SPECTRUM_BANDS {
-                        meter_for_output.bands_db[i].store(f32_to_u32(bands[i]), Ordering::Relaxed);
-                    }
-                    if let Ok(mut spectrum_guard) = spectrum_for_output.lock() {
-                        *spectrum_guard = spec.clone();
-                    }
-                    if let Ok(mut peak_guard) = spectrum_peak_for_output.lock() {
-                        if peak_guard.len() != spec.len() {
-                            *peak_guard = spec.clone();
+                let frame = match input_failover.as_mut() {
+                    Some(failover) => {
+                        let backup = if let Some(file) = backup_file.as_mut() {
+                            file.next_frame()
+                        } else if let Some(f) = backup_cons.as_mut().and_then(|c| c.pop()) {
+                            (f.left, f.right)
                         } else {
-                            for i in 0..spec.len() {
-                                peak_guard[i] = peak_guard[i].max(spec[i]);
-                            }
-                        }
+                            (0.0, 0.0)
+                        };
+                        let (left, right) = failover.process(
+                            primary.map(|f| (f.left, f.right)),
+                            backup,
+                            &failover_for_output,
+                        );
+                        Frame { left, right }
                     }
-                    if let Ok(mut avg_guard) = spectrum_avg_for_output.lock() {
-                        if avg_guard.len() != spec.len() {
-                            *avg_guard = spec.clone();
-                        } else {
-                            for i in 0..spec.len() {
-                                avg_guard[i] = avg_guard[i] * 0.9 + spec[i] * 0.1;
-                            }
-                        }
+                    None => primary.unwrap_or(Frame { left: 0.0, right: 0.0 }),
+                };
+                // The device ring is drained even while TA is down, so a
+                // raised TA starts on live audio rather than a stale buffer.
+                let live_traffic = traffic_cons.as_mut().and_then(|c| c.pop());
+                let frame = match traffic_mixer.as_mut() {
+                    Some(mixer) if mixer.audible() => {
+                        let traffic = match (traffic_file.as_mut(), live_traffic) {
+                            (Some(file), _) => file.next_frame(),
+                            (None, Some(f)) => (f.left, f.right),
+                            (None, None) => (0.0, 0.0),
+                        };
+                        let (left, right) = mixer.process((frame.left, frame.right), traffic);
+                        Frame { left, right }
                     }
-                }
-                index += output_channels;
+                    _ => frame,
+                };
+                program_peak = program_peak.max(frame.left.abs()).max(frame.right.abs());
+                let _ = input_tap.push(frame);
+                engine.next_sample(frame)
+            });
+            for ch in 0..output_channels {
+                data[index + ch] = out;
             }
-            let rms = (sum_sq / (data.len() as f32 / output_channels as f32)).sqrt();
-            meter_for_output.rms.store(f32_to_u32(rms), Ordering::Relaxed);
-            meter_for_output.peak.store(f32_to_u32(peak), Ordering::Relaxed);
-
-            if let Ok(mut scope_buf) = scope_for_output.lock() {
-                for &sample in data.iter().step_by(output_channels) {
-                    if scope_buf.len() >= 2048 {
-                        scope_buf.pop_front();
-                    }
-                    scope_buf.push_back(sample);
-                }
+            sum_sq += out * out;
+            if out.abs() > peak {
+                peak = out.abs();
             }
-        },
-        err_fn,
-        None,
-    )?;
+
+            let _ = mpx_tap.push(out);
+            index += output_channels;
+        }
+        let rms = (sum_sq / (data.len() as f32 / output_channels as f32)).sqrt();
+        meter_for_output.rms.store(f32_to_u32(rms), Ordering::Relaxed);
+        meter_for_output.peak.store(f32_to_u32(peak), Ordering::Relaxed);
+        if program_peak > u32_to_f32(meter_for_output.program_peak.load(Ordering::Relaxed)) {
+            meter_for_output.program_peak.store(f32_to_u32(program_peak), Ordering::Relaxed);
+        }
+        if peak > u32_to_f32(meter_for_output.peak_hold.load(Ordering::Relaxed)) {
+            meter_for_output.peak_hold.store(f32_to_u32(peak), Ordering::Relaxed);
+        }
+
+        let frames = data.len() / output_channels.max(1);
+        if frames > 0 {
+            let deadline = frames as f32 / OUTPUT_SAMPLE_RATE as f32;
+            let load = callback_start.elapsed().as_secs_f32() / deadline;
+            let avg = u32_to_f32(meter_for_output.dsp_load_avg.load(Ordering::Relaxed));
+            meter_for_output.dsp_load_avg.store(f32_to_u32(avg * 0.95 + load * 0.05), Ordering::Relaxed);
+            let worst = u32_to_f32(meter_for_output.dsp_load_worst.load(Ordering::Relaxed));
+            if load > worst {
+                meter_for_output.dsp_load_worst.store(f32_to_u32(load), Ordering::Relaxed);
+            }
+        }
+    };
+    // A panic while rendering silences the output and is reported as a fault
+    // for the app to restart the engine, instead of killing the audio thread.
+    // The state it left behind is not trusted, so the output stays silent.
+    let output_callbacks = Arc::new(AtomicU64::new(0));
+    let callbacks_for_output = Arc::clone(&output_callbacks);
+    let fault_for_render = Arc::clone(&fault);
+    let mut render = move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
+        callbacks_for_output.fetch_add(1, Ordering::Relaxed);
+        if fault_for_render.load(Ordering::Relaxed) == EngineFault::Panic as u8
+            || catch_unwind(AssertUnwindSafe(|| render_block(data, info))).is_err()
+        {
+            data.fill(0.0);
+            EngineFault::Panic.record(&fault_for_render);
+        }
+    };
+    let output_stream = match output_supported.sample_format() {
+        cpal::SampleFormat::F32 => {
+            output_device.build_output_stream(&output_config, move |data: &mut [f32], info| render(data, info), err_fn, None)?
+        }
+        cpal::SampleFormat::I32 => build_converted_output::<i32>(&output_device, &output_config, render, err_fn)?,
+        cpal::SampleFormat::I16 => build_converted_output::<i16>(&output_device, &output_config, render, err_fn)?,
+        format => return Err(anyhow!("Unsupported output sample format {:?}", format)),
+    };
 
     if let Some(ref stream) = input_stream {
         stream.play()?;
     }
+    if let Some(ref stream) = backup_stream {
+        stream.play()?;
+    }
+    if let Some(ref stream) = traffic_stream {
+        stream.play()?;
+    }
     output_stream.play()?;
+    if let Some(ref stream) = monitor_stream {
+        stream.play()?;
+    }
 
     Ok(AudioEngine {
         _input_stream: input_stream,
+        _backup_stream: backup_stream,
+        _traffic_stream: traffic_stream,
         _output_stream: output_stream,
+        _monitor_stream: monitor_stream,
         running,
         shared,
         meter,
@@ -823,9 +2253,16 @@ pub fn start_engine(config: AudioEngineConfig) -> Result<AudioEngine> {
         spectrum,
         spectrum_peak,
         spectrum_avg,
+        input_spectrum,
         xrun_count,
         buffer_fill,
         latency_ms,
+        failover: failover_state,
+        rtp_stats,
+        device_error,
+        fault,
+        output_callbacks,
+        _exclusive: exclusive,
     })
 }
 
@@ -835,7 +2272,110 @@ impl AudioEngine {
         if let Some(ref stream) = self._input_stream {
             let _ = stream.pause();
         }
+        if let Some(ref stream) = self._backup_stream {
+            let _ = stream.pause();
+        }
+        if let Some(ref stream) = self._traffic_stream {
+            let _ = stream.pause();
+        }
         let _ = self._output_stream.pause();
+        if let Some(ref stream) = self._monitor_stream {
+            let _ = stream.pause();
+        }
+    }
+
+    pub fn update_automation(&self, param: AutomationParam, offset_db: f32, ramp_secs: f32, curve: RampCurve) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_automation(param, offset_db, ramp_secs, curve);
+        }
+    }
+
+    pub fn update_analyzer(&self, size: FftSize, window: FftWindow) {
+        self.meter.fft_size.store(size.to_u32(), Ordering::Relaxed);
+        self.meter.fft_window.store(window.to_u32(), Ordering::Relaxed);
+    }
+
+    pub fn update_calibration(&self, step: Option<CalibrationStep>) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_calibration(step);
+        }
+    }
+
+    pub fn start_test_sequence(&self, sequence: &TestSequence) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_test_sequence(Some(sequence));
+        }
+    }
+
+    pub fn stop_test_sequence(&self) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_test_sequence(None);
+        }
+    }
+
+    pub fn test_sequence_status(&self) -> Option<(TestStep, f32)> {
+        self.shared
+            .lock()
+            .ok()
+            .and_then(|engine| engine.test_sequencer.as_ref().and_then(|s| s.status(INTERNAL_SAMPLE_RATE)))
+    }
+
+    pub fn automation_offsets_db(&self) -> [f32; 4] {
+        self.shared
+            .lock()
+            .map(|engine| std::array::from_fn(|i| engine.automation[i].offset_db()))
+            .unwrap_or_default()
+    }
+
+    pub fn apply_settings(&self, settings: &EngineSettings, source: RdsChangeSource) {
+        self.meter.set_lpf_cutoff(settings.lpf_cutoff_hz);
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.apply_settings(settings, source);
+        }
+    }
+
+    pub fn update_bypass(&self, stage: Stage, bypassed: bool) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_bypass(stage, bypassed);
+        }
+    }
+
+    pub fn take_stage_levels(&self) -> [StageLevels; 4] {
+        self.shared
+            .lock()
+            .map(|mut engine| std::mem::take(&mut engine.stage_levels))
+            .unwrap_or_default()
+    }
+
+    pub fn take_modulation_stats(&self) -> ModulationStats {
+        self.shared
+            .lock()
+            .map(|mut engine| std::mem::take(&mut engine.modulation))
+            .unwrap_or_default()
+    }
+
+    pub fn take_device_error(&self) -> bool {
+        self.device_error.swap(false, Ordering::Relaxed)
+    }
+
+    pub fn fault(&self) -> Option<EngineFault> {
+        EngineFault::from_code(self.fault.load(Ordering::Relaxed))
+    }
+
+    // Output callbacks run so far; a count that stops moving means the
+    // stream has stalled without reporting an error.
+    pub fn output_callbacks(&self) -> u64 {
+        self.output_callbacks.load(Ordering::Relaxed)
+    }
+
+    pub fn begin_fade_out(&self, secs: f32) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.start_ramp(0.0, secs);
+        }
+    }
+
+    pub fn is_faded_out(&self) -> bool {
+        self.shared.lock().map(|engine| engine.faded_out()).unwrap_or(true)
     }
 
     pub fn meter_snapshot(&self) -> MeterSnapshot {
@@ -843,35 +2383,122 @@ impl AudioEngine {
         for i in 0..SPECTRUM_BANDS {
             bands[i] = u32_to_f32(self.meter.bands_db[i].load(Ordering::Relaxed));
         }
-        let scope = self.scope.lock().map(|buf| buf.iter().copied().collect()).unwrap_or_default();
+        let scope = self.scope.lock().map(|v| v.clone()).unwrap_or_default();
         let spectrum = self.spectrum.lock().map(|v| v.clone()).unwrap_or_default();
         let spectrum_peak = self.spectrum_peak.lock().map(|v| v.clone()).unwrap_or_default();
         let spectrum_avg = self.spectrum_avg.lock().map(|v| v.clone()).unwrap_or_default();
+        let input_spectrum = self.input_spectrum.lock().map(|v| v.clone()).unwrap_or_default();
         MeterSnapshot {
             rms: u32_to_f32(self.meter.rms.load(Ordering::Relaxed)),
             peak: u32_to_f32(self.meter.peak.load(Ordering::Relaxed)),
-            pilot: u32_to_f32(self.meter.pilot.load(Ordering::Relaxed)),
-            rds: u32_to_f32(self.meter.rds.load(Ordering::Relaxed)),
+            injection: Injection {
+                pilot: u32_to_f32(self.meter.pilot.load(Ordering::Relaxed)),
+                stereo_carrier: u32_to_f32(self.meter.stereo_carrier.load(Ordering::Relaxed)),
+                rds: u32_to_f32(self.meter.rds.load(Ordering::Relaxed)),
+            },
             bands_db: bands,
             scope,
             spectrum_db: spectrum,
             spectrum_peak_db: spectrum_peak,
             spectrum_avg_db: spectrum_avg,
+            input_spectrum_db: input_spectrum,
+            input_left_peak: u32_to_f32(self.meter.input_left_peak.load(Ordering::Relaxed)),
+            input_right_peak: u32_to_f32(self.meter.input_right_peak.load(Ordering::Relaxed)),
+            input_left_rms: u32_to_f32(self.meter.input_left_rms.load(Ordering::Relaxed)),
+            input_right_rms: u32_to_f32(self.meter.input_right_rms.load(Ordering::Relaxed)),
             xrun_count: self.xrun_count.load(Ordering::Relaxed),
             buffer_fill: self.buffer_fill.load(Ordering::Relaxed) as f32 / (OUTPUT_SAMPLE_RATE as f32 * 2.0),
             latency_ms: self.latency_ms,
+            dsp_load_avg: u32_to_f32(self.meter.dsp_load_avg.load(Ordering::Relaxed)),
+            dsp_load_worst: u32_to_f32(self.meter.dsp_load_worst.load(Ordering::Relaxed)),
+            drift_ppm: u32_to_f32(self.meter.drift_ppm.load(Ordering::Relaxed)),
+            peak_hold: u32_to_f32(self.meter.peak_hold.load(Ordering::Relaxed)),
+            program_peak: u32_to_f32(self.meter.program_peak.swap(0, Ordering::Relaxed)),
+            mask: {
+                let margin = u32_to_f32(self.meter.mask_margin.swap(f32_to_u32(f32::MAX), Ordering::Relaxed));
+                (margin != f32::MAX).then(|| MaskResult {
+                    margin_db: margin,
+                    worst_hz: u32_to_f32(self.meter.mask_worst_hz.load(Ordering::Relaxed)),
+                })
+            },
+        }
+    }
+
+    pub fn take_rds_history(&self) -> Vec<RdsHistoryEntry> {
+        self.shared
+            .lock()
+            .map(|mut engine| engine.take_history())
+            .unwrap_or_default()
+    }
+
+    pub fn program_delay_secs(&self) -> (f32, f32) {
+        self.shared
+            .lock()
+            .map(|engine| (engine.program_delay.current_secs(), engine.program_delay.target_secs()))
+            .unwrap_or_default()
+    }
+
+    pub fn update_program_delay(&self, secs: f32) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_program_delay(secs);
+        }
+    }
+
+    pub fn update_diversity_delay(&self, ms: f32) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_diversity_delay(ms);
+        }
+    }
+
+    pub fn dump_program_delay(&self) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.program_delay.dump();
+        }
+    }
+
+    pub fn input_pilot_status(&self) -> Option<PilotLockStatus> {
+        self.shared
+            .lock()
+            .ok()
+            .and_then(|engine| engine.mpx_input.then(|| engine.pilot_pll.status()))
+    }
+
+    pub fn rtp_status(&self) -> Option<RtpStatus> {
+        self.rtp_stats.as_ref().map(|stats| stats.status())
+    }
+
+    pub fn failover_status(&self) -> FailoverStatus {
+        self.failover.status()
+    }
+
+    pub fn rds_group_stats(&self) -> GroupStats {
+        self.shared
+            .lock()
+            .map(|engine| engine.group_stats())
+            .unwrap_or_default()
+    }
+
+    pub fn update_ps(&self, ps: &str, source: RdsChangeSource) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_ps(ps, source);
+        }
+    }
+
+    pub fn update_rt(&self, rt: &str, source: RdsChangeSource) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_rt(rt, source);
         }
     }
 
-    pub fn update_ps(&self, ps: &str) {
+    pub fn update_code_table(&self, table: RdsCodeTable) {
         if let Ok(mut engine) = self.shared.lock() {
-            engine.set_ps(ps);
+            engine.set_code_table(table);
         }
     }
 
-    pub fn update_rt(&self, rt: &str) {
+    pub fn update_transliteration(&self, table: Option<TranslitTable>) {
         if let Ok(mut engine) = self.shared.lock() {
-            engine.set_rt(rt);
+            engine.set_transliteration(table);
         }
     }
 
@@ -881,6 +2508,18 @@ impl AudioEngine {
         }
     }
 
+    pub fn update_ecc(&self, ecc: Option<u8>) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_ecc(ecc);
+        }
+    }
+
+    pub fn update_lic(&self, lic: Option<u8>) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_lic(lic);
+        }
+    }
+
     pub fn update_tp(&self, tp: bool) {
         if let Ok(mut engine) = self.shared.lock() {
             engine.set_tp(tp);
@@ -929,9 +2568,9 @@ impl AudioEngine {
         }
     }
 
-    pub fn update_af_list(&self, freqs: &[f32]) {
+    pub fn update_af_list(&self, freqs: &[f32], regional: &[f32], tuned_mhz: Option<f32>) {
         if let Ok(mut engine) = self.shared.lock() {
-            engine.set_af_list_mhz(freqs);
+            engine.set_af_entries(freqs, regional, tuned_mhz);
         }
     }
 
@@ -941,12 +2580,36 @@ impl AudioEngine {
         }
     }
 
+    pub fn update_ps_scroll_mode(&self, mode: PsScrollMode, page_dwell_secs: f32, center: bool) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_ps_scroll_mode(mode, page_dwell_secs, center);
+        }
+    }
+
+    pub fn update_rate_guard(&self, enabled: bool) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_rate_guard(enabled);
+        }
+    }
+
+    pub fn update_rt_overflow(&self, mode: RtOverflow, ellipsis: &str, part_secs: f32) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_rt_overflow(mode, ellipsis, part_secs);
+        }
+    }
+
     pub fn update_rt_scroll(&self, enabled: bool, text: &str, cps: f32) {
         if let Ok(mut engine) = self.shared.lock() {
             engine.set_rt_scroll(enabled, text, cps);
         }
     }
 
+    pub fn update_tx_mute(&self, muted: bool) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_tx_mute(muted);
+        }
+    }
+
     pub fn update_gain(&self, gain: f32) {
         if let Ok(mut engine) = self.shared.lock() {
             engine.set_gain(gain);
@@ -971,6 +2634,12 @@ impl AudioEngine {
         }
     }
 
+    pub fn update_pilot_phase(&self, offset_deg: f32) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_pilot_phase(offset_deg);
+        }
+    }
+
     pub fn update_rds_level(&self, level: f32) {
         if let Ok(mut engine) = self.shared.lock() {
             engine.set_rds_level(level);
@@ -983,15 +2652,34 @@ impl AudioEngine {
         }
     }
 
+    pub fn update_pilot_notch(&self, enabled: bool) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_pilot_notch(enabled);
+        }
+    }
+
+    pub fn update_mpx_input_pilot(&self, enabled: bool) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_mpx_input_pilot(enabled);
+        }
+    }
+
+    pub fn update_lpf_cutoff(&self, cutoff_hz: f32) {
+        self.meter.set_lpf_cutoff(cutoff_hz);
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_lpf_cutoff(cutoff_hz);
+        }
+    }
+
     pub fn update_preemphasis(&self, tau: Option<f32>) {
         if let Ok(mut engine) = self.shared.lock() {
             engine.set_preemphasis(tau);
         }
     }
 
-    pub fn update_compressor(&self, enabled: bool, threshold_db: f32, ratio: f32, attack: f32, release: f32) {
+    pub fn update_compressor(&self, enabled: bool, threshold_db: f32, ratio: f32, attack: f32, release: f32, makeup_db: f32) {
         if let Ok(mut engine) = self.shared.lock() {
-            engine.set_compressor(enabled, threshold_db, ratio, attack, release);
+            engine.set_compressor(enabled, threshold_db, ratio, attack, release, makeup_db);
         }
     }
 
@@ -1007,6 +2695,12 @@ impl AudioEngine {
         }
     }
 
+    pub fn update_fast_tuning(&self, mode: FastTuning) {
+        if let Ok(mut engine) = self.shared.lock() {
+            engine.set_fast_tuning(mode);
+        }
+    }
+
     pub fn update_ps_alternates(&self, list: Vec<String>, interval_groups: usize) {
         if let Ok(mut engine) = self.shared.lock() {
             engine.set_ps_alternates(list, interval_groups);
@@ -1020,6 +2714,15 @@ impl Drop for AudioEngine {
         if let Some(ref stream) = self._input_stream {
             let _ = stream.pause();
         }
+        if let Some(ref stream) = self._backup_stream {
+            let _ = stream.pause();
+        }
+        if let Some(ref stream) = self._traffic_stream {
+            let _ = stream.pause();
+        }
         let _ = self._output_stream.pause();
+        if let Some(ref stream) = self._monitor_stream {
+            let _ = stream.pause();
+        }
     }
 }
@@ -0,0 +1,161 @@
+use std::fmt;
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutomationParam {
+    OutputGain,
+    PilotLevel,
+    RdsLevel,
+    StereoSeparation,
+}
+
+impl AutomationParam {
+    pub const ALL: [AutomationParam; 4] = [
+        AutomationParam::OutputGain,
+        AutomationParam::PilotLevel,
+        AutomationParam::RdsLevel,
+        AutomationParam::StereoSeparation,
+    ];
+
+    pub fn index(self) -> usize {
+        match self {
+            AutomationParam::OutputGain => 0,
+            AutomationParam::PilotLevel => 1,
+            AutomationParam::RdsLevel => 2,
+            AutomationParam::StereoSeparation => 3,
+        }
+    }
+}
+
+impl fmt::Display for AutomationParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            AutomationParam::OutputGain => "Output gain",
+            AutomationParam::PilotLevel => "Pilot level",
+            AutomationParam::RdsLevel => "RDS level",
+            AutomationParam::StereoSeparation => "Stereo separation",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RampCurve {
+    Linear,
+    Log,
+}
+
+impl fmt::Display for RampCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RampCurve::Linear => write!(f, "Linear"),
+            RampCurve::Log => write!(f, "Log"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AutomationRule {
+    pub param: AutomationParam,
+    pub start: String,
+    pub end: String,
+    pub offset_db: f32,
+    pub ramp_secs: f32,
+    pub curve: RampCurve,
+}
+
+pub fn parse_time(value: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(value.trim(), "%H:%M:%S"))
+        .ok()
+}
+
+impl AutomationRule {
+    pub fn is_active(&self, now: NaiveTime) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            now >= start && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+// Combined offset of all active rules for a parameter, plus the ramp of the
+// first rule touching it.
+pub fn scheduled_offset(rules: &[AutomationRule], param: AutomationParam, now: NaiveTime) -> (f32, f32, RampCurve) {
+    let mut offset = 0.0;
+    let mut ramp = None;
+    for rule in rules.iter().filter(|r| r.param == param) {
+        if ramp.is_none() {
+            ramp = Some((rule.ramp_secs, rule.curve));
+        }
+        if rule.is_active(now) {
+            offset += rule.offset_db;
+        }
+    }
+    let (secs, curve) = ramp.unwrap_or((0.0, RampCurve::Linear));
+    (offset, secs, curve)
+}
+
+pub struct ParamRamp {
+    current: f32,
+    start: f32,
+    target: f32,
+    pos: u32,
+    len: u32,
+    curve: RampCurve,
+}
+
+impl ParamRamp {
+    pub fn new() -> Self {
+        ParamRamp {
+            current: 1.0,
+            start: 1.0,
+            target: 1.0,
+            pos: 0,
+            len: 0,
+            curve: RampCurve::Linear,
+        }
+    }
+
+    pub fn set_target_db(&mut self, offset_db: f32, ramp_secs: f32, sample_rate: u32, curve: RampCurve) {
+        let target = 10.0f32.powf(offset_db / 20.0);
+        if (target - self.target).abs() < 1e-6 {
+            return;
+        }
+        self.start = self.current;
+        self.target = target;
+        self.pos = 0;
+        self.len = (ramp_secs.max(0.0) * sample_rate as f32) as u32;
+        self.curve = curve;
+    }
+
+    pub fn step(&mut self) -> f32 {
+        if self.pos < self.len {
+            self.pos += 1;
+            let t = self.pos as f32 / self.len as f32;
+            self.current = match self.curve {
+                RampCurve::Linear => self.start + (self.target - self.start) * t,
+                RampCurve::Log => self.start * (self.target / self.start).powf(t),
+            };
+        } else {
+            self.current = self.target;
+        }
+        self.current
+    }
+
+    pub fn offset_db(&self) -> f32 {
+        20.0 * self.current.max(1e-6).log10()
+    }
+}
+
+impl Default for ParamRamp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+// How often a pending auto-start re-checks the devices and retries.
+pub const AUTOSTART_RETRY: Duration = Duration::from_secs(5);
+
+// Start streaming unattended when the app launches, e.g. on a transmitter PC
+// that has to come back on air by itself after a power cut.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutostartSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    // Loaded before starting; None streams the settings the app starts with.
+    #[serde(default)]
+    pub preset: Option<String>,
+}
+
+// `--autostart [preset]` on the app's command line, which works without
+// touching the saved settings, e.g. from a service or startup entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LaunchOptions {
+    pub autostart: bool,
+    pub preset: Option<String>,
+}
+
+impl LaunchOptions {
+    pub fn from_args(args: &[String]) -> Self {
+        let mut options = LaunchOptions::default();
+        if let Some(pos) = args.iter().position(|a| a == "--autostart") {
+            options.autostart = true;
+            options.preset = args.get(pos + 1).filter(|a| !a.starts_with("--")).cloned();
+        }
+        options
+    }
+}
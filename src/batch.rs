@@ -0,0 +1,88 @@
+use std::fs;
+
+use anyhow::{anyhow, Result};
+use toml::{Table, Value};
+
+// One render from a batch job file, as the CLI arguments that produce it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchJob {
+    pub out: String,
+    pub args: Vec<String>,
+}
+
+// A job file lists `[[job]]` tables whose keys are the CLI's long options
+// without the leading dashes; an optional `[defaults]` table applies to
+// every job and a job's own keys win:
+//
+//   [defaults]
+//   duration = 30
+//   audio = "program.wav"
+//
+//   [[job]]
+//   out = "ps_static.wav"
+//   ps = "PULSE FM"
+//
+//   [[job]]
+//   out = "rt_scroll_no_ct.wav"
+//   rt-scroll = true
+//   no-ct = true
+//
+// `true` passes a flag, `false` leaves it out, and strings and numbers are
+// passed as the option's value, in file order (defaults first), so flags
+// after a processing preset still override it. Every job needs its own
+// `out`.
+pub fn load_jobs(path: &str) -> Result<Vec<BatchJob>> {
+    let raw = fs::read_to_string(path)?;
+    parse_jobs(&raw).map_err(|e| anyhow!("{}: {}", path, e))
+}
+
+pub fn parse_jobs(raw: &str) -> Result<Vec<BatchJob>> {
+    let mut file: Table = raw.parse()?;
+    let defaults = match file.remove("defaults") {
+        Some(Value::Table(table)) => table,
+        Some(_) => return Err(anyhow!("[defaults] must be a table")),
+        None => Table::new(),
+    };
+    let jobs = match file.remove("job") {
+        Some(Value::Array(jobs)) => jobs,
+        Some(_) => return Err(anyhow!("jobs must be [[job]] tables")),
+        None => return Err(anyhow!("no [[job]] entries")),
+    };
+    if let Some(key) = file.keys().next() {
+        return Err(anyhow!("unknown top-level key: {}", key));
+    }
+    if defaults.contains_key("out") {
+        return Err(anyhow!("out cannot be set in [defaults]"));
+    }
+
+    jobs.into_iter()
+        .enumerate()
+        .map(|(i, job)| {
+            let Value::Table(job) = job else {
+                return Err(anyhow!("job {} is not a table", i + 1));
+            };
+            let out = match job.get("out") {
+                Some(Value::String(out)) if !out.trim().is_empty() => out.clone(),
+                _ => return Err(anyhow!("job {} has no out path", i + 1)),
+            };
+            let mut merged = defaults.clone();
+            merged.extend(job);
+            let mut args = Vec::new();
+            for (key, value) in &merged {
+                if key == "batch" {
+                    return Err(anyhow!("job {}: batch files cannot be nested", i + 1));
+                }
+                let option = format!("--{}", key);
+                match value {
+                    Value::Boolean(true) => args.push(option),
+                    Value::Boolean(false) => {}
+                    Value::String(s) => args.extend([option, s.clone()]),
+                    Value::Integer(n) => args.extend([option, n.to_string()]),
+                    Value::Float(x) => args.extend([option, x.to_string()]),
+                    _ => return Err(anyhow!("job {}: {} must be a string, number or boolean", i + 1, key)),
+                }
+            }
+            Ok(BatchJob { out, args })
+        })
+        .collect()
+}
@@ -1,8 +1,27 @@
 use std::env;
+use std::path::PathBuf;
 
 use anyhow::{anyhow, Result};
 
-use pulse_fm_rds_encoder::wav_writer::{generate_mpx_wav, GenerateConfig};
+use pulse_fm_rds_encoder::autostart::LaunchOptions;
+use pulse_fm_rds_encoder::batch::load_jobs;
+use pulse_fm_rds_encoder::compliance::{self, RdsOptions};
+use pulse_fm_rds_encoder::data_providers::DataProvider;
+use pulse_fm_rds_encoder::fm_mpx::DEFAULT_LPF_CUTOFF_HZ;
+use pulse_fm_rds_encoder::impairment::{Impairments, DEFAULT_RIPPLE_DELAY_US};
+use pulse_fm_rds_encoder::iq_export::IqSettings;
+use pulse_fm_rds_encoder::levels::db_to_linear;
+use pulse_fm_rds_encoder::processing_preset::ProcessingPreset;
+use pulse_fm_rds_encoder::program_feed::ProgramFeed;
+use pulse_fm_rds_encoder::rds::{af_tuned_missing, FastTuning, PsScrollMode, RtOverflow, DEFAULT_RT_ELLIPSIS, DEFAULT_RT_PART_SECS};
+use pulse_fm_rds_encoder::rds_checks::{station_warnings, StationCheck};
+use pulse_fm_rds_encoder::rds_macros::{expand, Clock, MacroValues};
+use pulse_fm_rds_encoder::rds_strings::RdsCodeTable;
+use pulse_fm_rds_encoder::rds_translit::TranslitTable;
+use pulse_fm_rds_encoder::service::{self, ServiceSpec};
+use pulse_fm_rds_encoder::storage::config_dir;
+use pulse_fm_rds_encoder::test_sequence::TestSequence;
+use pulse_fm_rds_encoder::wav_writer::{generate_iq_wav, generate_mpx_wav, generate_phase_test_wav, generate_test_sequence_wav, process_program_wav, run_null_test, run_separation_test, GenerateConfig};
 
 fn main() -> Result<()> {
     let args: Vec<String> = env::args().collect();
@@ -10,12 +29,109 @@ fn main() -> Result<()> {
         print_usage();
         return Ok(());
     }
+    if args[1] == "service" {
+        return run_service(&args[2..]);
+    }
+    if let Some(pos) = args.iter().position(|a| a == "--batch") {
+        let path = args.get(pos + 1).cloned().ok_or_else(|| anyhow!("missing batch file"))?;
+        let mut shared = args.clone();
+        shared.drain(pos..pos + 2);
+        return run_batch(&shared, &path);
+    }
+    run(&args)
+}
+
+// Installs the app to start streaming at login; the working directory (for
+// logs/) defaults to the settings folder, which is pinned as it resolves now.
+// `--autostart [preset]` is accepted as on the app's own command line.
+fn run_service(args: &[String]) -> Result<()> {
+    let action = args.first().map(String::as_str).unwrap_or("status");
+    let mut preset = None;
+    let mut exe = None;
+    let mut work_dir = None;
+    let mut i = 1;
+    while i < args.len() {
+        let value = || args.get(i + 1).cloned().ok_or_else(|| anyhow!("missing value for {}", args[i]));
+        match args[i].as_str() {
+            "--autostart" => {
+                let options = LaunchOptions::from_args(&args[i..]);
+                i += if options.preset.is_some() { 2 } else { 1 };
+                preset = options.preset.or(preset);
+                continue;
+            }
+            "--preset" => preset = Some(value()?),
+            "--exe" => exe = Some(PathBuf::from(value()?)),
+            "--workdir" => work_dir = Some(PathBuf::from(value()?)),
+            other => return Err(anyhow!("unknown service option: {}", other)),
+        }
+        i += 2;
+    }
+    match action {
+        "install" => {
+            let config_dir = config_dir().to_path_buf();
+            let spec = ServiceSpec {
+                exe: match exe {
+                    Some(exe) => exe,
+                    None => service::default_exe()?,
+                },
+                preset,
+                work_dir: work_dir.unwrap_or_else(|| config_dir.clone()),
+                config_dir,
+            };
+            let path = service::install(&spec)?;
+            println!("Installed {}", service::describe(&path));
+        }
+        "uninstall" => {
+            let path = service::uninstall()?;
+            println!("Removed {}", service::describe(&path));
+        }
+        "status" => {
+            let path = service::service_file()?;
+            let state = if service::is_installed() { "installed" } else { "not installed" };
+            println!("{}: {}", service::describe(&path), state);
+        }
+        other => return Err(anyhow!("unknown service action: {} (install, uninstall or status)", other)),
+    }
+    Ok(())
+}
 
+// Arguments given next to --batch apply to every job; each job's own options
+// come after them and override.
+fn run_batch(shared: &[String], path: &str) -> Result<()> {
+    let jobs = load_jobs(path)?;
+    let mut failed = 0;
+    for (i, job) in jobs.iter().enumerate() {
+        eprintln!("[{}/{}] {}", i + 1, jobs.len(), job.out);
+        let args = shared.iter().chain(&job.args).cloned().collect::<Vec<_>>();
+        if let Err(e) = run(&args) {
+            eprintln!("error: {}: {}", job.out, e);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(anyhow!("{} of {} batch jobs failed", failed, jobs.len()));
+    }
+    Ok(())
+}
+
+fn run(args: &[String]) -> Result<()> {
     let mut out = None;
-    let mut duration = 10.0f32;
+    let mut process = false;
+    let mut compare = false;
+    let mut preset_a: Option<ProcessingPreset> = None;
+    let mut preset_b: Option<ProcessingPreset> = None;
+    let mut duration: Option<f32> = None;
     let mut ps = "BOUZIDFM".to_string();
     let mut rt = "BOUZIDFM Sidi Bouzid 98.0 MHz".to_string();
     let mut pi = 0x7200u16;
+    let mut ecc: Option<u8> = None;
+    let mut lic: Option<u8> = None;
+    let mut alarm = false;
+    let mut strict = false;
+    let mut compliance_mode = false;
+    let mut code_table = RdsCodeTable::E1;
+    let mut transliterate = false;
+    let mut translit_table: Option<String> = None;
     let mut ta = false;
     let mut tp = false;
     let mut pty = 10u8;
@@ -25,46 +141,87 @@ fn main() -> Result<()> {
     let mut ab_auto = true;
     let mut ct_enabled = true;
     let mut af_list = vec![98.0f32];
+    let mut af_regional: Vec<f32> = Vec::new();
+    let mut af_tuned = None;
     let mut ps_scroll_enabled = false;
     let mut ps_scroll_text = "BOUZIDFM".to_string();
     let mut ps_scroll_cps = 2.0f32;
+    let mut ps_scroll_mode = PsScrollMode::Character;
+    let mut ps_page_dwell = 2.0f32;
+    let mut ps_page_center = false;
     let mut rt_scroll_enabled = false;
     let mut rt_scroll_text = "BOUZIDFM Sidi Bouzid 98.0 MHz".to_string();
     let mut rt_scroll_cps = 2.0f32;
+    let mut rt_overflow = RtOverflow::Ellipsis;
+    let mut rt_ellipsis = DEFAULT_RT_ELLIPSIS.to_string();
+    let mut rt_part_secs = DEFAULT_RT_PART_SECS;
+    let mut rate_guard = true;
     let mut output_gain = 1.0f32;
     let mut limiter_enabled = true;
     let mut limiter_threshold = 0.95f32;
     let mut limiter_lookahead = 256usize;
     let mut pilot_level = 0.9f32;
+    let mut pilot_phase = 0.0f32;
     let mut rds_level = 1.0f32;
     let mut stereo_separation = 1.0f32;
     let mut preemphasis_tau = Some(50e-6f32);
+    let mut lpf_cutoff = DEFAULT_LPF_CUTOFF_HZ;
+    let mut pilot_notch = true;
     let mut compressor_enabled = false;
     let mut comp_threshold = -18.0f32;
     let mut comp_ratio = 3.0f32;
     let mut comp_attack = 0.01f32;
     let mut comp_release = 0.2f32;
+    let mut comp_makeup = 0.0f32;
     let mut group_0a = 4usize;
     let mut group_2a = 1usize;
     let mut group_4a = 0usize;
     let mut ct_interval_groups = 0usize;
+    let mut fast_tuning = FastTuning::Off;
     let mut ps_alt_list: Vec<String> = Vec::new();
     let mut ps_alt_interval = 0usize;
+    let mut macro_values = MacroValues::default();
     let mut audio = None;
+    let mut test_sequence: Option<TestSequence> = None;
+    let mut phase_test = false;
+    let mut separation_test = false;
+    let mut resume = false;
+    let mut iq: Option<IqSettings> = None;
+    let mut program_out: Option<String> = None;
+    let mut program_delay_ms = 0.0f32;
+    let mut group_schedule: Option<String> = None;
+    let mut impairments = Impairments {
+        ripple_delay_us: DEFAULT_RIPPLE_DELAY_US,
+        ..Impairments::default()
+    };
 
     let mut i = 1;
     while i < args.len() {
         match args[i].as_str() {
+            "process" if i == 1 => process = true,
+            "compare" if i == 1 => compare = true,
+            "--preset-a" | "--preset-b" => {
+                let flag = args[i].clone();
+                i += 1;
+                let raw = args.get(i).cloned().ok_or_else(|| anyhow!("missing processing preset"))?;
+                let preset = ProcessingPreset::parse(&raw).ok_or_else(|| anyhow!("unknown processing preset: {}", raw))?;
+                if flag == "--preset-a" {
+                    preset_a = Some(preset);
+                } else {
+                    preset_b = Some(preset);
+                }
+            }
             "--out" => {
                 i += 1;
                 out = args.get(i).cloned();
             }
             "--duration" => {
                 i += 1;
-                duration = args
-                    .get(i)
-                    .ok_or_else(|| anyhow!("missing duration"))?
-                    .parse::<f32>()?;
+                duration = Some(
+                    args.get(i)
+                        .ok_or_else(|| anyhow!("missing duration"))?
+                        .parse::<f32>()?,
+                );
             }
             "--ps" => {
                 i += 1;
@@ -74,12 +231,50 @@ fn main() -> Result<()> {
                 i += 1;
                 rt = args.get(i).cloned().ok_or_else(|| anyhow!("missing rt"))?;
             }
+            "--code-table" => {
+                i += 1;
+                let raw = args.get(i).cloned().ok_or_else(|| anyhow!("missing code table"))?;
+                code_table = match raw.to_lowercase().as_str() {
+                    "e1" | "g0" => RdsCodeTable::E1,
+                    "e2" | "g1" | "e3" | "g2" => {
+                        return Err(anyhow!("code table {} is not available yet; its characters above 0x7F are not mapped", raw))
+                    }
+                    other => return Err(anyhow!("unknown code table: {}", other)),
+                };
+            }
+            "--transliterate" => {
+                transliterate = true;
+            }
+            "--translit-table" => {
+                i += 1;
+                translit_table = Some(args.get(i).cloned().ok_or_else(|| anyhow!("missing transliteration table"))?);
+                transliterate = true;
+            }
             "--pi" => {
                 i += 1;
                 let raw = args.get(i).cloned().ok_or_else(|| anyhow!("missing pi"))?;
                 let t = raw.trim_start_matches("0x");
                 pi = u16::from_str_radix(t, 16)?;
             }
+            "--ecc" => {
+                i += 1;
+                let raw = args.get(i).cloned().ok_or_else(|| anyhow!("missing ecc"))?;
+                ecc = Some(u8::from_str_radix(raw.trim_start_matches("0x"), 16)?);
+            }
+            "--lic" => {
+                i += 1;
+                let raw = args.get(i).cloned().ok_or_else(|| anyhow!("missing lic"))?;
+                lic = Some(u8::from_str_radix(raw.trim_start_matches("0x"), 16)?);
+            }
+            "--alarm" => {
+                alarm = true;
+            }
+            "--strict" => {
+                strict = true;
+            }
+            "--compliance" => {
+                compliance_mode = true;
+            }
             "--ta" => {
                 ta = true;
             }
@@ -120,10 +315,22 @@ fn main() -> Result<()> {
             "--af" => {
                 i += 1;
                 let raw = args.get(i).cloned().ok_or_else(|| anyhow!("missing af list"))?;
-                af_list = raw
-                    .split(',')
-                    .filter_map(|s| s.trim().parse::<f32>().ok())
-                    .collect();
+                af_list.clear();
+                af_regional.clear();
+                for part in raw.split(',') {
+                    let part = part.trim();
+                    if let Some(freq) = part.strip_suffix(['R', 'r']) {
+                        if let Ok(freq) = freq.trim().parse::<f32>() {
+                            af_regional.push(freq);
+                        }
+                    } else if let Ok(freq) = part.parse::<f32>() {
+                        af_list.push(freq);
+                    }
+                }
+            }
+            "--af-tuned" => {
+                i += 1;
+                af_tuned = Some(args.get(i).cloned().ok_or_else(|| anyhow!("missing tuned frequency"))?.parse::<f32>()?);
             }
             "--ps-scroll" => {
                 ps_scroll_enabled = true;
@@ -136,6 +343,17 @@ fn main() -> Result<()> {
                 i += 1;
                 ps_scroll_cps = args.get(i).cloned().ok_or_else(|| anyhow!("missing ps scroll cps"))?.parse::<f32>()?;
             }
+            "--ps-paging" => {
+                ps_scroll_enabled = true;
+                ps_scroll_mode = PsScrollMode::Paging;
+            }
+            "--ps-page-dwell" => {
+                i += 1;
+                ps_page_dwell = args.get(i).cloned().ok_or_else(|| anyhow!("missing ps page dwell"))?.parse::<f32>()?;
+            }
+            "--ps-page-center" => {
+                ps_page_center = true;
+            }
             "--rt-scroll" => {
                 rt_scroll_enabled = true;
             }
@@ -147,10 +365,34 @@ fn main() -> Result<()> {
                 i += 1;
                 rt_scroll_cps = args.get(i).cloned().ok_or_else(|| anyhow!("missing rt scroll cps"))?.parse::<f32>()?;
             }
+            "--rt-overflow" => {
+                i += 1;
+                rt_overflow = match args.get(i).map(String::as_str) {
+                    Some("cut") => RtOverflow::Cut,
+                    Some("ellipsis") => RtOverflow::Ellipsis,
+                    Some("rotate") => RtOverflow::Rotate,
+                    _ => return Err(anyhow!("rt overflow must be cut, ellipsis or rotate")),
+                };
+            }
+            "--rt-ellipsis" => {
+                i += 1;
+                rt_ellipsis = args.get(i).cloned().ok_or_else(|| anyhow!("missing rt ellipsis"))?;
+            }
+            "--rt-part-secs" => {
+                i += 1;
+                rt_part_secs = args.get(i).cloned().ok_or_else(|| anyhow!("missing rt part secs"))?.parse::<f32>()?;
+            }
+            "--no-rate-guard" => {
+                rate_guard = false;
+            }
             "--gain" => {
                 i += 1;
                 output_gain = args.get(i).cloned().ok_or_else(|| anyhow!("missing gain"))?.parse::<f32>()?;
             }
+            "--gain-db" => {
+                i += 1;
+                output_gain = db_to_linear(args.get(i).cloned().ok_or_else(|| anyhow!("missing gain"))?.parse::<f32>()?);
+            }
             "--limiter" => {
                 limiter_enabled = true;
             }
@@ -161,6 +403,10 @@ fn main() -> Result<()> {
                 i += 1;
                 limiter_threshold = args.get(i).cloned().ok_or_else(|| anyhow!("missing limiter threshold"))?.parse::<f32>()?;
             }
+            "--limiter-threshold-db" => {
+                i += 1;
+                limiter_threshold = db_to_linear(args.get(i).cloned().ok_or_else(|| anyhow!("missing limiter threshold"))?.parse::<f32>()?);
+            }
             "--lookahead" => {
                 i += 1;
                 limiter_lookahead = args.get(i).cloned().ok_or_else(|| anyhow!("missing lookahead"))?.parse::<usize>()?;
@@ -169,6 +415,60 @@ fn main() -> Result<()> {
                 i += 1;
                 pilot_level = args.get(i).cloned().ok_or_else(|| anyhow!("missing pilot level"))?.parse::<f32>()?;
             }
+            "--pilot-phase" => {
+                i += 1;
+                pilot_phase = args.get(i).cloned().ok_or_else(|| anyhow!("missing pilot phase"))?.parse::<f32>()?;
+            }
+            "--phase-test" => {
+                phase_test = true;
+            }
+            "--separation-test" => {
+                separation_test = true;
+            }
+            "--resume" => {
+                resume = true;
+            }
+            "--program-out" => {
+                i += 1;
+                program_out = Some(args.get(i).cloned().ok_or_else(|| anyhow!("missing program output path"))?);
+            }
+            "--program-delay" => {
+                i += 1;
+                program_delay_ms = args.get(i).cloned().ok_or_else(|| anyhow!("missing program delay"))?.parse::<f32>()?;
+            }
+            "--group-schedule" => {
+                i += 1;
+                group_schedule = Some(args.get(i).cloned().ok_or_else(|| anyhow!("missing group schedule path"))?);
+            }
+            "--iq" => {
+                iq.get_or_insert_with(IqSettings::default);
+            }
+            "--iq-rate" => {
+                i += 1;
+                iq.get_or_insert_with(IqSettings::default).sample_rate =
+                    args.get(i).cloned().ok_or_else(|| anyhow!("missing iq rate"))?.parse::<u32>()?;
+            }
+            "--deviation" => {
+                i += 1;
+                iq.get_or_insert_with(IqSettings::default).deviation_khz =
+                    args.get(i).cloned().ok_or_else(|| anyhow!("missing deviation"))?.parse::<f32>()?;
+            }
+            "--noise-snr" => {
+                i += 1;
+                impairments.noise_snr_db = Some(args.get(i).cloned().ok_or_else(|| anyhow!("missing noise snr"))?.parse::<f32>()?);
+            }
+            "--ripple" => {
+                i += 1;
+                impairments.ripple_db = args.get(i).cloned().ok_or_else(|| anyhow!("missing ripple"))?.parse::<f32>()?;
+            }
+            "--ripple-delay" => {
+                i += 1;
+                impairments.ripple_delay_us = args.get(i).cloned().ok_or_else(|| anyhow!("missing ripple delay"))?.parse::<f32>()?;
+            }
+            "--gd-tilt" => {
+                i += 1;
+                impairments.group_delay_tilt_us = args.get(i).cloned().ok_or_else(|| anyhow!("missing group delay tilt"))?.parse::<f32>()?;
+            }
             "--rds-level" => {
                 i += 1;
                 rds_level = args.get(i).cloned().ok_or_else(|| anyhow!("missing rds level"))?.parse::<f32>()?;
@@ -186,6 +486,33 @@ fn main() -> Result<()> {
             "--preemph-off" => {
                 preemphasis_tau = None;
             }
+            "--lpf" => {
+                i += 1;
+                lpf_cutoff = args.get(i).cloned().ok_or_else(|| anyhow!("missing lpf cutoff"))?.parse::<f32>()?;
+            }
+            "--no-pilot-notch" => {
+                pilot_notch = false;
+            }
+            "--processing-preset" => {
+                i += 1;
+                let raw = args.get(i).cloned().ok_or_else(|| anyhow!("missing processing preset"))?;
+                let preset = ProcessingPreset::parse(&raw).ok_or_else(|| anyhow!("unknown processing preset: {}", raw))?;
+                let s = preset.settings();
+                compressor_enabled = s.compressor_enabled;
+                comp_threshold = s.comp_threshold_db;
+                comp_ratio = s.comp_ratio;
+                comp_attack = s.comp_attack;
+                comp_release = s.comp_release;
+                comp_makeup = s.comp_makeup_db;
+                limiter_enabled = s.limiter_enabled;
+                limiter_threshold = db_to_linear(s.limiter_threshold_db);
+                limiter_lookahead = (s.limiter_lookahead_ms / 1000.0 * 228_000.0) as usize;
+                if !s.preemphasis {
+                    preemphasis_tau = None;
+                } else if preemphasis_tau.is_none() {
+                    preemphasis_tau = Some(50e-6);
+                }
+            }
             "--comp" => {
                 compressor_enabled = true;
             }
@@ -205,6 +532,10 @@ fn main() -> Result<()> {
                 i += 1;
                 comp_release = args.get(i).cloned().ok_or_else(|| anyhow!("missing comp release"))?.parse::<f32>()?;
             }
+            "--comp-makeup" => {
+                i += 1;
+                comp_makeup = args.get(i).cloned().ok_or_else(|| anyhow!("missing comp makeup"))?.parse::<f32>()?;
+            }
             "--group-mix" => {
                 i += 1;
                 let raw = args.get(i).cloned().ok_or_else(|| anyhow!("missing group mix"))?;
@@ -219,6 +550,15 @@ fn main() -> Result<()> {
                 i += 1;
                 ct_interval_groups = args.get(i).cloned().ok_or_else(|| anyhow!("missing ct interval"))?.parse::<usize>()?;
             }
+            "--fast-tuning" => {
+                i += 1;
+                fast_tuning = match args.get(i).map(String::as_str) {
+                    Some("off") => FastTuning::Off,
+                    Some("ta") => FastTuning::WhileTa,
+                    Some("always") => FastTuning::Always,
+                    _ => return Err(anyhow!("fast tuning must be off, ta or always")),
+                };
+            }
             "--ps-alt" => {
                 i += 1;
                 ps_alt_list = args.get(i).cloned().ok_or_else(|| anyhow!("missing ps alt list"))?
@@ -228,6 +568,27 @@ fn main() -> Result<()> {
                 i += 1;
                 ps_alt_interval = args.get(i).cloned().ok_or_else(|| anyhow!("missing ps alt interval"))?.parse::<usize>()?;
             }
+            "--value" => {
+                i += 1;
+                let pair = args.get(i).cloned().ok_or_else(|| anyhow!("missing value"))?;
+                let (name, value) = pair.split_once('=').ok_or_else(|| anyhow!("--value expects name=text"))?;
+                macro_values.set(name, value);
+            }
+            "--providers" => {
+                i += 1;
+                let path = args.get(i).cloned().ok_or_else(|| anyhow!("missing providers file"))?;
+                let providers: Vec<DataProvider> = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+                for provider in &providers {
+                    provider
+                        .fetch(&mut macro_values)
+                        .map_err(|e| anyhow!("provider {}: {}", provider.name, e))?;
+                }
+            }
+            "--test-sequence" => {
+                i += 1;
+                let durations = args.get(i).cloned().ok_or_else(|| anyhow!("missing test sequence durations"))?;
+                test_sequence = Some(TestSequence::parse_durations(&durations)?);
+            }
             other => {
                 return Err(anyhow!("unknown arg: {}", other));
             }
@@ -235,14 +596,58 @@ fn main() -> Result<()> {
         i += 1;
     }
 
-    let out = out.ok_or_else(|| anyhow!("--out is required"))?;
+    let warnings = station_warnings(&StationCheck { pi, ecc, pty, tp, ta, alarm });
+    for warning in &warnings {
+        eprintln!("warning: {}", warning);
+    }
+    if strict && !warnings.is_empty() {
+        return Err(anyhow!("refusing to render in --strict mode ({} warnings)", warnings.len()));
+    }
+    // RDS injection is only measured live, so a render keeps --rds-level.
+    if compliance_mode {
+        let mut options = RdsOptions {
+            ps_scroll_enabled,
+            ps_alt_interval,
+            ct_enabled,
+            ct_interval_groups,
+            group_0a,
+            group_2a,
+            group_4a,
+            rds_level,
+            rds_khz: None,
+        };
+        for change in compliance::enforce(&mut options) {
+            eprintln!("compliance: {}", change);
+        }
+        ps_scroll_enabled = options.ps_scroll_enabled;
+        ps_alt_interval = options.ps_alt_interval;
+        ct_enabled = options.ct_enabled;
+        ct_interval_groups = options.ct_interval_groups;
+        group_0a = options.group_0a;
+    }
+    let af_tuned = af_tuned.or_else(|| af_list.first().copied());
+    if af_tuned_missing(&af_regional, af_tuned) {
+        return Err(anyhow!("regional AFs need a valid tuned frequency: set --af-tuned or list a plain AF first"));
+    }
+    let translit = if transliterate {
+        Some(TranslitTable::load(translit_table.as_deref())?)
+    } else {
+        None
+    };
 
+    // Templates are expanded once, at the start of the render.
+    let expand_text = |template: &str| expand(template, &[&Clock, &macro_values]);
     let config = GenerateConfig {
-        duration_secs: duration,
+        // Processing and comparing take the whole input unless a duration is given.
+        duration_secs: duration.unwrap_or(if process || compare { f32::MAX } else { 10.0 }),
         audio_path: audio,
-        ps,
-        rt,
+        ps: expand_text(&ps),
+        rt: expand_text(&rt),
+        code_table,
+        translit,
         pi,
+        ecc,
+        lic,
         tp,
         ta,
         pty,
@@ -252,37 +657,105 @@ fn main() -> Result<()> {
         ab_auto,
         ct_enabled,
         af_list_mhz: af_list,
+        af_regional_mhz: af_regional,
+        af_tuned_mhz: af_tuned,
         ps_scroll_enabled,
-        ps_scroll_text,
+        ps_scroll_text: expand_text(&ps_scroll_text),
         ps_scroll_cps,
+        ps_scroll_mode,
+        ps_page_dwell_secs: ps_page_dwell,
+        ps_page_center,
         rt_scroll_enabled,
-        rt_scroll_text,
+        rt_scroll_text: expand_text(&rt_scroll_text),
         rt_scroll_cps,
+        rt_overflow,
+        rt_ellipsis,
+        rt_part_secs,
+        rate_guard,
         output_gain,
         limiter_enabled,
         limiter_threshold,
         limiter_lookahead,
         pilot_level,
+        pilot_phase_deg: pilot_phase,
         rds_level,
         stereo_separation,
         preemphasis_tau,
+        lpf_cutoff_hz: lpf_cutoff,
+        pilot_notch,
         compressor_enabled,
         comp_threshold_db: comp_threshold,
         comp_ratio,
         comp_attack,
         comp_release,
+        comp_makeup_db: comp_makeup,
         group_0a,
         group_2a,
         group_4a,
         ct_interval_groups,
-        ps_alt_list,
+        fast_tuning,
+        ps_alt_list: ps_alt_list.iter().map(|ps| expand_text(ps)).collect(),
         ps_alt_interval,
+        impairments,
+        program_feed: program_out.map(|path| ProgramFeed {
+            path,
+            delay_ms: program_delay_ms,
+        }),
+        group_schedule,
     };
 
-    generate_mpx_wav(&config, &out, |_| {})?;
+    if separation_test {
+        let report = run_separation_test(&config)?;
+        println!("{}", report);
+        if !report.passed() {
+            return Err(anyhow!("stereo separation self-test failed"));
+        }
+        return Ok(());
+    }
+    let out = out.ok_or_else(|| anyhow!("--out is required"))?;
+    if compare {
+        if config.audio_path.is_none() {
+            return Err(anyhow!("compare needs --audio"));
+        }
+        let (Some(a), Some(b)) = (preset_a, preset_b) else {
+            return Err(anyhow!("compare needs --preset-a and --preset-b"));
+        };
+        let report = run_null_test(&config, a, b, &out)?;
+        println!("{}", report);
+        return Ok(());
+    }
+    if process {
+        if config.audio_path.is_none() {
+            return Err(anyhow!("process needs --audio"));
+        }
+        let report = process_program_wav(&config, &out, |_| {})?;
+        eprintln!("{}", report);
+        return Ok(());
+    }
+    if phase_test {
+        let report = generate_phase_test_wav(&config, &out)?;
+        println!("{}", report);
+        return Ok(());
+    }
+    match (test_sequence, iq) {
+        (Some(_), Some(_)) => return Err(anyhow!("--iq cannot be combined with --test-sequence")),
+        (Some(sequence), None) => generate_test_sequence_wav(&config, &sequence, &out, |_| {})?,
+        (None, Some(iq)) => {
+            let report = generate_iq_wav(&config, &iq, &out, |_| {})?;
+            eprintln!("{}", report);
+        }
+        (None, None) => {
+            let report = generate_mpx_wav(&config, &out, resume, |_| {})?;
+            eprintln!("{}", report);
+        }
+    }
     Ok(())
 }
 
 fn print_usage() {
-    eprintln!("Usage: pulse-fm-rds-cli --out mpx.wav [--duration 10] [--ps text] [--rt text] [--pi 1234] [--tp] [--ta] [--pty N] [--ms|--speech] [--di 0xF] [--ab] [--no-ab-auto] [--no-ct] [--af 98.0,99.5] [--ps-scroll] [--ps-scroll-text t] [--ps-scroll-cps n] [--rt-scroll] [--rt-scroll-text t] [--rt-scroll-cps n] [--gain x] [--limiter|--no-limiter] [--limiter-threshold x] [--audio file.wav]");
+    eprintln!("Usage: pulse-fm-rds-cli --out mpx.wav [--duration 10] [--ps text] [--rt text] [--pi 1234] [--ecc E2] [--lic 0F] [--alarm] [--strict] [--compliance] [--code-table e1] [--transliterate] [--translit-table map.txt] [--tp] [--ta] [--pty N] [--ms|--speech] [--di 0xF] [--ab] [--no-ab-auto] [--no-ct] [--fast-tuning off|ta|always] [--af 98.0,99.5,101.2R] [--af-tuned 98.0] [--ps-scroll] [--ps-scroll-text t] [--ps-scroll-cps n] [--ps-paging] [--ps-page-dwell s] [--ps-page-center] [--rt-scroll] [--rt-scroll-text t] [--rt-scroll-cps n] [--rt-overflow cut|ellipsis|rotate] [--rt-ellipsis text] [--rt-part-secs s] [--no-rate-guard] [--gain x|--gain-db dB] [--limiter|--no-limiter] [--limiter-threshold x|--limiter-threshold-db dB] [--lpf hz] [--no-pilot-notch] [--processing-preset transparent|loud-pop|talk|classical] [--audio file.wav] [--test-sequence pilot,lr,l,r,rds,full] [--pilot-phase deg] [--phase-test] [--separation-test] [--resume] [--noise-snr dB] [--ripple dB] [--ripple-delay us] [--gd-tilt us] [--iq] [--iq-rate 2400000] [--deviation 75] [--program-out program.wav] [--program-delay ms] [--group-schedule groups.json|groups.csv] [--value name=text] [--providers providers.json]");
+    eprintln!("       pulse-fm-rds-cli process --audio in.wav --out processed.wav [--duration s] [--lpf hz] [--no-pilot-notch] [--processing-preset name] [--preemph-50|--preemph-75|--preemph-off] [--comp] [--comp-thr dB] [--comp-ratio n] [--comp-attack s] [--comp-release s] [--comp-makeup dB]");
+    eprintln!("       pulse-fm-rds-cli compare --audio in.wav --preset-a name --preset-b name --out difference.wav [--duration s] [--lpf hz] [--no-pilot-notch] [--preemph-50|--preemph-75]");
+    eprintln!("       pulse-fm-rds-cli service install|uninstall|status [--preset name|--autostart [name]] [--exe path] [--workdir dir]");
+    eprintln!("       pulse-fm-rds-cli --batch jobs.toml [options shared by every job]");
 }
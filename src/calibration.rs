@@ -0,0 +1,65 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+// Typical 9% pilot injection of 75 kHz.
+pub const PILOT_TARGET_KHZ: f32 = 6.75;
+pub const RDS_TARGET_KHZ: f32 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalibrationStep {
+    PilotOnly,
+    RdsOnly,
+}
+
+impl fmt::Display for CalibrationStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalibrationStep::PilotOnly => write!(f, "Pilot only"),
+            CalibrationStep::RdsOnly => write!(f, "RDS only"),
+        }
+    }
+}
+
+// Ratio of deviation read on the external mod-monitor to the deviation the
+// encoder believes it is producing.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviationCalibration {
+    pub pilot_factor: f32,
+    pub rds_factor: f32,
+}
+
+impl Default for DeviationCalibration {
+    fn default() -> Self {
+        DeviationCalibration {
+            pilot_factor: 1.0,
+            rds_factor: 1.0,
+        }
+    }
+}
+
+impl DeviationCalibration {
+    pub fn pilot_khz(&self, nominal_khz: f32) -> f32 {
+        nominal_khz * self.pilot_factor
+    }
+
+    pub fn rds_khz(&self, nominal_khz: f32) -> f32 {
+        nominal_khz * self.rds_factor
+    }
+}
+
+pub fn correction_factor(nominal_khz: f32, measured_khz: f32) -> Option<f32> {
+    if nominal_khz <= 0.0 || measured_khz <= 0.0 || !measured_khz.is_finite() {
+        return None;
+    }
+    Some(measured_khz / nominal_khz)
+}
+
+// Level that moves a measured deviation onto the target, assuming deviation
+// scales linearly with the level slider.
+pub fn level_for_target(level: f32, measured_khz: f32, target_khz: f32) -> f32 {
+    if measured_khz <= 0.0 {
+        return level;
+    }
+    level * target_khz / measured_khz
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+use crate::levels::linear_to_db;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    PreEmphasis,
+    Compressor,
+    PilotNotch,
+    Limiter,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [
+        Stage::PreEmphasis,
+        Stage::Compressor,
+        Stage::PilotNotch,
+        Stage::Limiter,
+    ];
+
+    pub fn index(self) -> usize {
+        match self {
+            Stage::PreEmphasis => 0,
+            Stage::Compressor => 1,
+            Stage::PilotNotch => 2,
+            Stage::Limiter => 3,
+        }
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Stage::PreEmphasis => "Pre-emphasis",
+            Stage::Compressor => "Compressor",
+            Stage::PilotNotch => "19 kHz notch",
+            Stage::Limiter => "Limiter",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// Peak levels either side of a stage and the deepest gain reduction it asked
+// for since the last read. A bypassed stage still reports the reduction it
+// would have applied.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StageLevels {
+    pub input_peak: f32,
+    pub output_peak: f32,
+    pub reduction_db: f32,
+}
+
+impl StageLevels {
+    pub fn record(&mut self, input: f32, output: f32, reduction_db: f32) {
+        self.input_peak = self.input_peak.max(input.abs());
+        self.output_peak = self.output_peak.max(output.abs());
+        self.reduction_db = self.reduction_db.max(reduction_db);
+    }
+
+    pub fn input_db(&self) -> f32 {
+        linear_to_db(self.input_peak)
+    }
+
+    pub fn output_db(&self) -> f32 {
+        linear_to_db(self.output_peak)
+    }
+
+    // Distance from the stage's output peak to full scale.
+    pub fn headroom_db(&self) -> f32 {
+        -self.output_db()
+    }
+}
@@ -0,0 +1,82 @@
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// Progress of a long export, saved next to the WAV after each flushed batch.
+// The fingerprint ties it to the settings it was started with, so a resume
+// never continues a file with different RDS or processing.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct RenderCheckpoint {
+    fingerprint: String,
+    samples: usize,
+}
+
+pub fn checkpoint_path(output_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.partial.json", output_path))
+}
+
+pub fn fingerprint(settings: &str) -> String {
+    hex(&Sha256::digest(settings.as_bytes()))
+}
+
+// Samples already in the file, or None when there is nothing to resume.
+pub fn load_checkpoint(output_path: &str, fingerprint: &str) -> Result<Option<usize>> {
+    let path = checkpoint_path(output_path);
+    if !path.exists() || !Path::new(output_path).exists() {
+        return Ok(None);
+    }
+    let checkpoint: RenderCheckpoint = serde_json::from_str(&fs::read_to_string(&path)?)?;
+    if checkpoint.fingerprint != fingerprint {
+        return Err(anyhow!(
+            "{} was started with different settings; delete {} to render it again",
+            output_path,
+            path.display()
+        ));
+    }
+    Ok(Some(checkpoint.samples))
+}
+
+// Written to a temporary file and renamed, so an interruption leaves either
+// the previous checkpoint or the new one.
+pub fn save_checkpoint(output_path: &str, fingerprint: &str, samples: usize) -> Result<()> {
+    let path = checkpoint_path(output_path);
+    let tmp = path.with_extension("tmp");
+    let checkpoint = RenderCheckpoint {
+        fingerprint: fingerprint.to_string(),
+        samples,
+    };
+    fs::write(&tmp, serde_json::to_string_pretty(&checkpoint)?)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+pub fn remove_checkpoint(output_path: &str) {
+    let _ = fs::remove_file(checkpoint_path(output_path));
+}
+
+// Writes `<file>.sha256` in the format `sha256sum -c` reads and returns the
+// digest.
+pub fn write_sha256_sidecar(path: &str) -> Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    let digest = hex(&hasher.finalize());
+    let name = Path::new(path).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    fs::write(format!("{}.sha256", path), format!("{}  {}\n", digest, name))?;
+    Ok(digest)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
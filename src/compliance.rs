@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use crate::calibration::level_for_target;
+use crate::mod_history::FULL_SCALE_KHZ;
+use crate::rds::{group_cycle_for_mix, ps_segments_per_sec};
+
+// EN 50067 limits: four 0A groups a second (a whole PS every second), RDS
+// injection of no more than 6% of full deviation.
+pub const MIN_0A_PER_SEC: f32 = 4.0;
+pub const MAX_RDS_INJECTION: f32 = 0.06;
+// Clamped injection sits this far under the ceiling, so the meter's
+// smoothing does not flip it back and forth.
+const INJECTION_MARGIN: f32 = 0.95;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComplianceSettings {
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+// The options compliance mode checks, as set in the app or on the command
+// line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RdsOptions {
+    pub ps_scroll_enabled: bool,
+    pub ps_alt_interval: usize,
+    pub ct_enabled: bool,
+    pub ct_interval_groups: usize,
+    pub group_0a: usize,
+    pub group_2a: usize,
+    pub group_4a: usize,
+    pub rds_level: f32,
+    // Calibrated RDS deviation measured at `rds_level`, when streaming.
+    pub rds_khz: Option<f32>,
+}
+
+// Brings the options within EN 50067 and describes each change made; an
+// empty list means they already were.
+pub fn enforce(options: &mut RdsOptions) -> Vec<String> {
+    let mut changes = Vec::new();
+    // PS is the station's name and must stay put; scrolling, paging and
+    // alternating names are all dynamic PS.
+    if options.ps_scroll_enabled {
+        options.ps_scroll_enabled = false;
+        changes.push("PS scrolling turned off (dynamic PS is not allowed)".to_string());
+    }
+    if options.ps_alt_interval > 0 {
+        options.ps_alt_interval = 0;
+        changes.push("PS alternation turned off (dynamic PS is not allowed)".to_string());
+    }
+    if !options.ct_enabled {
+        options.ct_enabled = true;
+        changes.push("CT turned on (clock time is sent once a minute)".to_string());
+    }
+    if options.ct_interval_groups > 0 {
+        options.ct_interval_groups = 0;
+        changes.push("Extra CT groups turned off (CT goes out at the start of each minute only)".to_string());
+    }
+    let before = options.group_0a;
+    while ps_segments_per_sec(&group_cycle_for_mix(options.group_0a, options.group_2a, options.group_4a)) < MIN_0A_PER_SEC {
+        options.group_0a += 1;
+    }
+    if options.group_0a != before {
+        changes.push(format!(
+            "0A groups raised from {} to {} per cycle (at least {:.0} a second)",
+            before, options.group_0a, MIN_0A_PER_SEC
+        ));
+    }
+    let max_khz = MAX_RDS_INJECTION * FULL_SCALE_KHZ;
+    if let Some(khz) = options.rds_khz.filter(|&khz| khz > max_khz) {
+        let target = max_khz * INJECTION_MARGIN;
+        let level = level_for_target(options.rds_level, khz, target);
+        changes.push(format!(
+            "RDS level lowered from {:.2} to {:.2} ({:.2} kHz measured, at most {:.2} kHz)",
+            options.rds_level, level, khz, max_khz
+        ));
+        options.rds_level = level;
+        options.rds_khz = Some(target);
+    }
+    changes
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContactLine {
+    Cts,
+    Dsr,
+    Dcd,
+    Ri,
+}
+
+impl std::fmt::Display for ContactLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContactLine::Cts => write!(f, "CTS"),
+            ContactLine::Dsr => write!(f, "DSR"),
+            ContactLine::Dcd => write!(f, "DCD"),
+            ContactLine::Ri => write!(f, "RI"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContactAction {
+    Ta,
+    TxMute,
+    Preset,
+}
+
+impl std::fmt::Display for ContactAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContactAction::Ta => write!(f, "Raise TA"),
+            ContactAction::TxMute => write!(f, "TX mute"),
+            ContactAction::Preset => write!(f, "Switch preset"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactMapping {
+    pub line: ContactLine,
+    pub action: ContactAction,
+    #[serde(default)]
+    pub preset: String,
+    #[serde(default)]
+    pub invert: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContactEvent {
+    Ta(bool),
+    TxMute(bool),
+    Preset(String),
+}
+
+pub fn list_serial_ports() -> Result<Vec<String>> {
+    Ok(serialport::available_ports()?
+        .into_iter()
+        .map(|p| p.port_name)
+        .collect())
+}
+
+pub struct ContactMonitor {
+    port: Box<dyn serialport::SerialPort>,
+    mappings: Vec<ContactMapping>,
+    states: Vec<Option<bool>>,
+}
+
+impl ContactMonitor {
+    pub fn open(port_name: &str, mappings: Vec<ContactMapping>) -> Result<Self> {
+        let mut port = serialport::new(port_name, 9600)
+            .timeout(Duration::from_millis(50))
+            .open()
+            .map_err(|e| anyhow!("cannot open {}: {}", port_name, e))?;
+        // Drive DTR/RTS high so a dry contact can loop them back onto CTS/DSR.
+        port.write_data_terminal_ready(true)?;
+        port.write_request_to_send(true)?;
+        let states = vec![None; mappings.len()];
+        Ok(ContactMonitor { port, mappings, states })
+    }
+
+    pub fn set_mappings(&mut self, mappings: Vec<ContactMapping>) {
+        self.states = vec![None; mappings.len()];
+        self.mappings = mappings;
+    }
+
+    fn read_line(&mut self, line: ContactLine) -> Result<bool> {
+        let level = match line {
+            ContactLine::Cts => self.port.read_clear_to_send()?,
+            ContactLine::Dsr => self.port.read_data_set_ready()?,
+            ContactLine::Dcd => self.port.read_carrier_detect()?,
+            ContactLine::Ri => self.port.read_ring_indicator()?,
+        };
+        Ok(level)
+    }
+
+    pub fn poll(&mut self) -> Result<Vec<ContactEvent>> {
+        let mut levels: Vec<(ContactLine, bool)> = Vec::new();
+        let mut events = Vec::new();
+        for i in 0..self.mappings.len() {
+            let mapping = self.mappings[i].clone();
+            let raw = match levels.iter().find(|(line, _)| *line == mapping.line) {
+                Some(&(_, level)) => level,
+                None => {
+                    let level = self.read_line(mapping.line)?;
+                    levels.push((mapping.line, level));
+                    level
+                }
+            };
+            let active = raw != mapping.invert;
+            let previous = self.states[i].replace(active);
+            if previous == Some(active) {
+                continue;
+            }
+            match mapping.action {
+                ContactAction::Ta => events.push(ContactEvent::Ta(active)),
+                ContactAction::TxMute => events.push(ContactEvent::TxMute(active)),
+                ContactAction::Preset => {
+                    if active && previous.is_some() && !mapping.preset.trim().is_empty() {
+                        events.push(ContactEvent::Preset(mapping.preset.clone()));
+                    }
+                }
+            }
+        }
+        Ok(events)
+    }
+}
@@ -0,0 +1,215 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::rds_macros::{MacroValues, ValueProvider};
+
+const MIN_REFRESH_SECS: u64 = 30;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+// An HTTP endpoint returning JSON, polled on its own thread; each field maps
+// a JSONPath in the response to a PS/RT template variable.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DataProvider {
+    pub name: String,
+    pub url: String,
+    #[serde(default = "default_refresh_secs")]
+    pub refresh_secs: u64,
+    pub fields: Vec<ProviderField>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProviderField {
+    pub variable: String,
+    pub path: String,
+    // Rounds numbers; None prints them as the API sent them.
+    #[serde(default)]
+    pub decimals: Option<usize>,
+}
+
+fn default_refresh_secs() -> u64 {
+    600
+}
+
+impl DataProvider {
+    // Current temperature, conditions and humidity, for RT such as
+    // "BOUZIDFM %temp%°C %weather%". Needs an API key from openweathermap.org.
+    pub fn openweather_example() -> Self {
+        DataProvider {
+            name: "OpenWeather".to_string(),
+            url: "https://api.openweathermap.org/data/2.5/weather?q=Sidi%20Bouzid,TN&units=metric&appid=YOUR_API_KEY"
+                .to_string(),
+            refresh_secs: 600,
+            fields: vec![
+                ProviderField {
+                    variable: "temp".to_string(),
+                    path: "$.main.temp".to_string(),
+                    decimals: Some(0),
+                },
+                ProviderField {
+                    variable: "weather".to_string(),
+                    path: "$.weather[0].description".to_string(),
+                    decimals: None,
+                },
+                ProviderField {
+                    variable: "humidity".to_string(),
+                    path: "$.main.humidity".to_string(),
+                    decimals: Some(0),
+                },
+            ],
+        }
+    }
+
+    pub fn fetch(&self, values: &mut MacroValues) -> Result<()> {
+        let body = ureq::get(&self.url)
+            .timeout(FETCH_TIMEOUT)
+            .call()
+            .map_err(|e| anyhow!("{}", e))?
+            .into_string()?;
+        let json: Value = serde_json::from_str(&body)?;
+        for field in &self.fields {
+            let value = json_path(&json, &field.path)
+                .ok_or_else(|| anyhow!("{} not found in response", field.path))?;
+            values.set(&field.variable, &format_value(value, field.decimals));
+        }
+        Ok(())
+    }
+}
+
+// The JSONPath subset API responses need: `$`, `.key`, `['key']` and `[n]`.
+pub fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.trim();
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut current = value;
+    while !rest.is_empty() {
+        if let Some(tail) = rest.strip_prefix('.') {
+            let end = tail.find(['.', '[']).unwrap_or(tail.len());
+            current = current.get(&tail[..end])?;
+            rest = &tail[end..];
+        } else if let Some(tail) = rest.strip_prefix('[') {
+            let end = tail.find(']')?;
+            let key = tail[..end].trim();
+            let quoted = key
+                .strip_prefix('\'')
+                .and_then(|k| k.strip_suffix('\''))
+                .or_else(|| key.strip_prefix('"').and_then(|k| k.strip_suffix('"')));
+            current = match quoted {
+                Some(name) => current.get(name)?,
+                None => current.get(key.parse::<usize>().ok()?)?,
+            };
+            rest = &tail[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some(current)
+}
+
+fn format_value(value: &Value, decimals: Option<usize>) -> String {
+    match (value, decimals) {
+        (Value::String(text), _) => text.clone(),
+        (Value::Number(n), Some(decimals)) => match n.as_f64() {
+            Some(x) => {
+                let rounded = format!("{:.*}", decimals, x);
+                // -0.2 rounds to "-0".
+                let unsigned = rounded.trim_start_matches('-');
+                if unsigned.chars().all(|c| c == '0' || c == '.') {
+                    unsigned.to_string()
+                } else {
+                    rounded
+                }
+            }
+            None => n.to_string(),
+        },
+        (Value::Null, _) => String::new(),
+        (other, _) => other.to_string(),
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProviderStatus {
+    pub name: String,
+    pub updated: Option<DateTime<Local>>,
+    pub error: Option<String>,
+}
+
+// Runs every provider until dropped. Values keep their last good reading
+// while a fetch fails.
+pub struct ProviderFeeds {
+    values: Arc<Mutex<MacroValues>>,
+    status: Arc<Mutex<Vec<ProviderStatus>>>,
+    running: Arc<AtomicBool>,
+}
+
+impl ProviderFeeds {
+    pub fn start(providers: &[DataProvider]) -> Self {
+        let values = Arc::new(Mutex::new(MacroValues::default()));
+        let running = Arc::new(AtomicBool::new(true));
+        let status = Arc::new(Mutex::new(
+            providers
+                .iter()
+                .map(|p| ProviderStatus {
+                    name: p.name.clone(),
+                    ..ProviderStatus::default()
+                })
+                .collect::<Vec<_>>(),
+        ));
+        for (index, provider) in providers.iter().cloned().enumerate() {
+            let values = Arc::clone(&values);
+            let status = Arc::clone(&status);
+            let running = Arc::clone(&running);
+            thread::spawn(move || {
+                let refresh = Duration::from_secs(provider.refresh_secs.max(MIN_REFRESH_SECS));
+                while running.load(Ordering::Relaxed) {
+                    let mut fetched = MacroValues::default();
+                    let result = provider.fetch(&mut fetched);
+                    if result.is_ok() {
+                        if let Ok(mut values) = values.lock() {
+                            for (name, value) in fetched.iter() {
+                                values.set(name, value);
+                            }
+                        }
+                    }
+                    if let Ok(mut status) = status.lock() {
+                        let entry = &mut status[index];
+                        match result {
+                            Ok(()) => {
+                                entry.updated = Some(Local::now());
+                                entry.error = None;
+                            }
+                            Err(e) => entry.error = Some(e.to_string()),
+                        }
+                    }
+                    let mut waited = Duration::ZERO;
+                    while waited < refresh && running.load(Ordering::Relaxed) {
+                        thread::sleep(Duration::from_millis(250));
+                        waited += Duration::from_millis(250);
+                    }
+                }
+            });
+        }
+        ProviderFeeds { values, status, running }
+    }
+
+    pub fn status(&self) -> Vec<ProviderStatus> {
+        self.status.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+impl ValueProvider for ProviderFeeds {
+    fn value(&self, name: &str) -> Option<String> {
+        self.values.lock().ok()?.value(name)
+    }
+}
+
+impl Drop for ProviderFeeds {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
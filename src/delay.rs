@@ -0,0 +1,168 @@
+pub const MAX_DELAY_SECS: f32 = 30.0;
+
+const REBUILD_RATE: f64 = 0.01;
+
+pub struct ProgramDelay {
+    buffer: Vec<(f32, f32)>,
+    write: usize,
+    sample_rate: u32,
+    delay: f64,
+    target: f64,
+    fade_len: usize,
+    fade_pos: Option<usize>,
+}
+
+impl ProgramDelay {
+    pub fn new(sample_rate: u32) -> Self {
+        ProgramDelay {
+            buffer: Vec::new(),
+            write: 0,
+            sample_rate,
+            delay: 0.0,
+            target: 0.0,
+            fade_len: (sample_rate as f32 * 0.25) as usize,
+            fade_pos: None,
+        }
+    }
+
+    pub fn set_delay(&mut self, secs: f32) {
+        let secs = secs.clamp(0.0, MAX_DELAY_SECS);
+        self.target = secs as f64 * self.sample_rate as f64;
+        if self.target > 0.0 && self.buffer.is_empty() {
+            let len = (MAX_DELAY_SECS * self.sample_rate as f32) as usize + 2;
+            self.buffer = vec![(0.0, 0.0); len];
+            self.write = 0;
+        }
+    }
+
+    pub fn dump(&mut self) {
+        if self.delay >= 1.0 && self.fade_pos.is_none() {
+            self.fade_pos = Some(0);
+        }
+    }
+
+    pub fn current_secs(&self) -> f32 {
+        (self.delay / self.sample_rate as f64) as f32
+    }
+
+    pub fn target_secs(&self) -> f32 {
+        (self.target / self.sample_rate as f64) as f32
+    }
+
+    pub fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if self.buffer.is_empty() {
+            return (left, right);
+        }
+        let len = self.buffer.len();
+        self.buffer[self.write] = (left, right);
+
+        if self.fade_pos.is_none() {
+            if self.delay < self.target {
+                self.delay = (self.delay + REBUILD_RATE).min(self.target);
+            } else if self.delay > self.target {
+                self.delay = (self.delay - REBUILD_RATE).max(self.target);
+            }
+        }
+
+        let delayed = if self.delay < 1.0 {
+            (left, right)
+        } else {
+            let pos = self.write as f64 - self.delay;
+            let pos = if pos < 0.0 { pos + len as f64 } else { pos };
+            let i0 = pos.floor() as usize % len;
+            let i1 = (i0 + 1) % len;
+            let t = (pos - pos.floor()) as f32;
+            let (a, b) = (self.buffer[i0], self.buffer[i1]);
+            (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+        };
+
+        self.write = (self.write + 1) % len;
+
+        match self.fade_pos {
+            Some(pos) => {
+                let t = pos as f32 / self.fade_len.max(1) as f32;
+                if pos + 1 >= self.fade_len {
+                    self.fade_pos = None;
+                    self.delay = 0.0;
+                } else {
+                    self.fade_pos = Some(pos + 1);
+                }
+                (
+                    delayed.0 * (1.0 - t) + left * t,
+                    delayed.1 * (1.0 - t) + right * t,
+                )
+            }
+            None => delayed,
+        }
+    }
+}
+
+pub const MAX_DIVERSITY_DELAY_MS: f32 = 10_000.0;
+
+pub struct StaticDelay {
+    buffer: Vec<(f32, f32)>,
+    write: usize,
+    sample_rate: u32,
+    delay: usize,
+    previous: usize,
+    fade_len: usize,
+    fade_pos: usize,
+}
+
+impl StaticDelay {
+    pub fn new(sample_rate: u32) -> Self {
+        StaticDelay {
+            buffer: Vec::new(),
+            write: 0,
+            sample_rate,
+            delay: 0,
+            previous: 0,
+            fade_len: (sample_rate / 100) as usize,
+            fade_pos: 0,
+        }
+    }
+
+    pub fn set_delay_ms(&mut self, ms: f32) {
+        let ms = ms.clamp(0.0, MAX_DIVERSITY_DELAY_MS);
+        let samples = (ms as f64 / 1000.0 * self.sample_rate as f64).round() as usize;
+        if samples > 0 && self.buffer.is_empty() {
+            let len = (MAX_DIVERSITY_DELAY_MS / 1000.0 * self.sample_rate as f32) as usize + 1;
+            self.buffer = vec![(0.0, 0.0); len];
+            self.write = 0;
+        }
+        if samples != self.delay {
+            self.previous = self.delay;
+            self.delay = samples;
+            self.fade_pos = self.fade_len;
+        }
+    }
+
+    pub fn delay_ms(&self) -> f32 {
+        self.delay as f32 * 1000.0 / self.sample_rate as f32
+    }
+
+    fn read(&self, delay: usize, live: (f32, f32)) -> (f32, f32) {
+        if delay == 0 {
+            return live;
+        }
+        let len = self.buffer.len();
+        self.buffer[(self.write + len - delay) % len]
+    }
+
+    pub fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if self.buffer.is_empty() {
+            return (left, right);
+        }
+        let live = (left, right);
+        self.buffer[self.write] = live;
+        let mut out = self.read(self.delay, live);
+        if self.fade_pos > 0 {
+            let old = self.read(self.previous, live);
+            let t = self.fade_pos as f32 / self.fade_len.max(1) as f32;
+            out = (out.0 * (1.0 - t) + old.0 * t, out.1 * (1.0 - t) + old.1 * t);
+            self.fade_pos -= 1;
+        }
+        self.write = (self.write + 1) % self.buffer.len();
+        out
+    }
+}
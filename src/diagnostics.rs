@@ -0,0 +1,163 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::Local;
+use serde::Serialize;
+use serde_json::Value;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::audio_io::device_capabilities;
+use crate::storage::CONFIG_FILES;
+
+// Meter readings kept for the bundle: one minute at the default text rate.
+pub const METER_RECORDS: usize = 300;
+// Newest files taken from the logs folder.
+const RECENT_LOGS: usize = 10;
+// Values under JSON keys containing these never leave the machine.
+const SECRET_KEYS: [&str; 3] = ["token", "secret", "password"];
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MeterRecord {
+    pub time: String,
+    pub rms: f32,
+    pub peak: f32,
+    pub pilot: f32,
+    pub stereo_carrier: f32,
+    pub rds: f32,
+    pub xruns: u32,
+    pub buffer_fill: f32,
+    pub latency_ms: f32,
+    pub dsp_load_avg: f32,
+    pub dsp_load_worst: f32,
+    pub drift_ppm: f32,
+}
+
+pub struct DiagnosticsInput {
+    pub config_dir: PathBuf,
+    pub logs_dir: PathBuf,
+    // The live settings, which may differ from anything saved.
+    pub current_settings: Value,
+    pub meters: Vec<MeterRecord>,
+}
+
+#[derive(Serialize)]
+struct Summary {
+    version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    audio_host: String,
+    created: String,
+    config_dir: String,
+    notes: Vec<String>,
+}
+
+pub fn bundle_path(dir: &Path) -> PathBuf {
+    dir.join(format!("diagnostics-{}.zip", Local::now().format("%Y%m%d-%H%M%S")))
+}
+
+// Writes summary.json, devices.json, meters.json, settings/ and logs/ into a
+// zip. Secrets are blanked, and a file that cannot be read is noted in the
+// summary rather than failing the bundle.
+pub fn write_bundle(path: &Path, input: &DiagnosticsInput) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let mut notes = Vec::new();
+
+    let mut current = input.current_settings.clone();
+    redact(&mut current);
+    add(&mut zip, "settings/current.json", serde_json::to_string_pretty(&current)?.as_bytes())?;
+    for name in CONFIG_FILES {
+        let file = input.config_dir.join(name);
+        if !file.is_file() {
+            continue;
+        }
+        // Only parsed files are included, so secrets can always be blanked.
+        match fs::read_to_string(&file).map_err(|e| e.to_string()).and_then(|data| {
+            serde_json::from_str::<Value>(&data).map_err(|e| e.to_string())
+        }) {
+            Ok(mut value) => {
+                redact(&mut value);
+                add(&mut zip, &format!("settings/{}", name), serde_json::to_string_pretty(&value)?.as_bytes())?;
+            }
+            Err(e) => notes.push(format!("settings/{}: {}", name, e)),
+        }
+    }
+
+    let audio_host = match device_capabilities() {
+        Ok((host, devices)) => {
+            add(&mut zip, "devices.json", serde_json::to_string_pretty(&devices)?.as_bytes())?;
+            host
+        }
+        Err(e) => {
+            notes.push(format!("devices: {}", e));
+            String::new()
+        }
+    };
+
+    add(&mut zip, "meters.json", serde_json::to_string_pretty(&input.meters)?.as_bytes())?;
+
+    for log in recent_logs(&input.logs_dir) {
+        let name = log.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        match fs::read(&log) {
+            Ok(data) => add(&mut zip, &format!("logs/{}", name), &data)?,
+            Err(e) => notes.push(format!("logs/{}: {}", name, e)),
+        }
+    }
+
+    let summary = Summary {
+        version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        audio_host,
+        created: Local::now().to_rfc3339(),
+        config_dir: input.config_dir.display().to_string(),
+        notes,
+    };
+    add(&mut zip, "summary.json", serde_json::to_string_pretty(&summary)?.as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
+
+fn add(zip: &mut ZipWriter<File>, name: &str, data: &[u8]) -> Result<()> {
+    zip.start_file(name, FileOptions::default().compression_method(CompressionMethod::Deflated))?;
+    zip.write_all(data)?;
+    Ok(())
+}
+
+fn recent_logs(dir: &Path) -> Vec<PathBuf> {
+    let mut logs = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_file())
+                .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    logs.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    logs.into_iter().take(RECENT_LOGS).map(|(_, path)| path).collect()
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SECRET_KEYS.iter().any(|secret| key.contains(secret)) {
+                    if value.as_str().map(|s| !s.is_empty()).unwrap_or(false) {
+                        *value = Value::String("<redacted>".to_string());
+                    }
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
@@ -0,0 +1,177 @@
+use anyhow::{anyhow, Result};
+
+// Exclusive output: the MPX goes straight to the hardware at its native
+// 192 kHz. Shared paths (dmix, PulseAudio/PipeWire, a CoreAudio device that
+// another client has set to 48 kHz) resample and mix, which smears the
+// 57 kHz RDS subcarrier. Holding the guard keeps the device claimed; drop it
+// after the output stream.
+pub struct ExclusiveOutput {
+    #[cfg(target_os = "macos")]
+    _hog: hog::HogMode,
+}
+
+// ALSA `hw:` devices are the card itself: one process at a time, no rate
+// conversion, no mixing. `plughw:`, `default`, `pulse` and `dmix` all are
+// shared or converting.
+#[cfg(target_os = "linux")]
+pub fn claim_output(name: &str, _sample_rate: u32) -> Result<ExclusiveOutput> {
+    if !name.starts_with("hw:") {
+        return Err(anyhow!(
+            "Exclusive output needs a direct ALSA hardware device (hw:CARD=...,DEV=...), not {}",
+            name
+        ));
+    }
+    Ok(ExclusiveOutput {})
+}
+
+// CoreAudio hog mode locks other clients out of the device, so nobody can
+// move its nominal rate away from the one set here.
+#[cfg(target_os = "macos")]
+pub fn claim_output(name: &str, sample_rate: u32) -> Result<ExclusiveOutput> {
+    let hog = hog::HogMode::take(name)?;
+    hog.set_nominal_rate(sample_rate)?;
+    Ok(ExclusiveOutput { _hog: hog })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn claim_output(_name: &str, _sample_rate: u32) -> Result<ExclusiveOutput> {
+    Err(anyhow!("Exclusive output is built in for ALSA (Linux) and CoreAudio (macOS) only"))
+}
+
+#[cfg(target_os = "macos")]
+mod hog {
+    use std::ffi::{c_void, CStr};
+    use std::mem::size_of;
+    use std::ptr::null;
+    use std::time::{Duration, Instant};
+
+    use anyhow::{anyhow, Result};
+    use coreaudio_sys::{
+        kAudioDevicePropertyDeviceNameCFString, kAudioDevicePropertyHogMode, kAudioDevicePropertyNominalSampleRate,
+        kAudioHardwarePropertyDevices, kAudioObjectPropertyElementMaster, kAudioObjectPropertyScopeGlobal,
+        kAudioObjectSystemObject, kCFStringEncodingUTF8, AudioDeviceID, AudioObjectGetPropertyData,
+        AudioObjectGetPropertyDataSize, AudioObjectID, AudioObjectPropertyAddress, AudioObjectPropertySelector,
+        AudioObjectSetPropertyData, CFIndex, CFRelease, CFStringGetCString, CFStringRef, OSStatus,
+    };
+
+    // The HAL applies a rate change asynchronously.
+    const RATE_SETTLE: Duration = Duration::from_secs(2);
+
+    pub struct HogMode {
+        device: AudioDeviceID,
+    }
+
+    impl HogMode {
+        pub fn take(name: &str) -> Result<Self> {
+            let device = device_ids()?
+                .into_iter()
+                .find(|&id| device_name(id).as_deref() == Some(name))
+                .ok_or_else(|| anyhow!("Output device not found"))?;
+            let pid = std::process::id() as i32;
+            let owner = get_property(device, kAudioDevicePropertyHogMode, -1i32)?;
+            if owner != -1 && owner != pid {
+                return Err(anyhow!("{} is held exclusively by another process (pid {})", name, owner));
+            }
+            // Setting the property toggles ownership; the value is ignored.
+            if owner != pid {
+                set_property(device, kAudioDevicePropertyHogMode, &pid)?;
+            }
+            let hog = HogMode { device };
+            if get_property(device, kAudioDevicePropertyHogMode, -1i32)? != pid {
+                return Err(anyhow!("Could not take exclusive access to {}", name));
+            }
+            Ok(hog)
+        }
+
+        pub fn set_nominal_rate(&self, sample_rate: u32) -> Result<()> {
+            let rate = sample_rate as f64;
+            set_property(self.device, kAudioDevicePropertyNominalSampleRate, &rate)
+                .map_err(|_| anyhow!("Device does not run natively at {} Hz", sample_rate))?;
+            let start = Instant::now();
+            while get_property(self.device, kAudioDevicePropertyNominalSampleRate, 0.0f64)? != rate {
+                if start.elapsed() > RATE_SETTLE {
+                    return Err(anyhow!("Device did not switch to {} Hz", sample_rate));
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for HogMode {
+        fn drop(&mut self) {
+            let pid = std::process::id() as i32;
+            if get_property(self.device, kAudioDevicePropertyHogMode, -1i32).ok() == Some(pid) {
+                let _ = set_property(self.device, kAudioDevicePropertyHogMode, &pid);
+            }
+        }
+    }
+
+    fn address(selector: AudioObjectPropertySelector) -> AudioObjectPropertyAddress {
+        AudioObjectPropertyAddress {
+            mSelector: selector,
+            mScope: kAudioObjectPropertyScopeGlobal,
+            mElement: kAudioObjectPropertyElementMaster,
+        }
+    }
+
+    fn check(status: OSStatus) -> Result<()> {
+        if status == 0 {
+            Ok(())
+        } else {
+            Err(anyhow!("CoreAudio error {}", status))
+        }
+    }
+
+    fn get_property<T: Copy>(object: AudioObjectID, selector: AudioObjectPropertySelector, mut value: T) -> Result<T> {
+        let address = address(selector);
+        let mut size = size_of::<T>() as u32;
+        let status = unsafe {
+            AudioObjectGetPropertyData(object, &address, 0, null(), &mut size, &mut value as *mut T as *mut c_void)
+        };
+        check(status)?;
+        Ok(value)
+    }
+
+    fn set_property<T>(object: AudioObjectID, selector: AudioObjectPropertySelector, value: &T) -> Result<()> {
+        let address = address(selector);
+        let status = unsafe {
+            AudioObjectSetPropertyData(object, &address, 0, null(), size_of::<T>() as u32, value as *const T as *const c_void)
+        };
+        check(status)
+    }
+
+    fn device_ids() -> Result<Vec<AudioDeviceID>> {
+        let address = address(kAudioHardwarePropertyDevices);
+        let mut size = 0u32;
+        check(unsafe { AudioObjectGetPropertyDataSize(kAudioObjectSystemObject, &address, 0, null(), &mut size) })?;
+        let mut ids = vec![0 as AudioDeviceID; size as usize / size_of::<AudioDeviceID>()];
+        check(unsafe {
+            AudioObjectGetPropertyData(
+                kAudioObjectSystemObject,
+                &address,
+                0,
+                null(),
+                &mut size,
+                ids.as_mut_ptr() as *mut c_void,
+            )
+        })?;
+        ids.truncate(size as usize / size_of::<AudioDeviceID>());
+        Ok(ids)
+    }
+
+    // The same property cpal names devices by, so the picker's names match.
+    fn device_name(device: AudioDeviceID) -> Option<String> {
+        let name: CFStringRef = get_property(device, kAudioDevicePropertyDeviceNameCFString, null()).ok()?;
+        if name.is_null() {
+            return None;
+        }
+        let mut buf = [0 as std::os::raw::c_char; 256];
+        let ok = unsafe { CFStringGetCString(name, buf.as_mut_ptr(), buf.len() as CFIndex, kCFStringEncodingUTF8) };
+        unsafe { CFRelease(name as *const c_void) };
+        if ok == 0 {
+            return None;
+        }
+        unsafe { CStr::from_ptr(buf.as_ptr()) }.to_str().ok().map(str::to_string)
+    }
+}
@@ -0,0 +1,261 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+// The primary's level is an RMS envelope over this time, so pauses between
+// words or notes do not count as silence or reset the restore timer.
+const ENVELOPE_SECS: f32 = 0.3;
+// The primary counts as back only this far above the silence threshold, so
+// a level hovering at the threshold does not flip between the two.
+const RESTORE_HYSTERESIS_DB: f32 = 6.0;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackupInput {
+    Device(String),
+    File(String),
+}
+
+impl fmt::Display for BackupInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackupInput::Device(name) => write!(f, "device {}", name),
+            BackupInput::File(path) => write!(f, "file {}", path),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FailoverConfig {
+    pub backup: BackupInput,
+    pub silence_threshold_db: f32,
+    pub silence_secs: f32,
+    pub restore_secs: f32,
+    pub crossfade_secs: f32,
+}
+
+impl FailoverConfig {
+    pub fn new(backup: BackupInput) -> Self {
+        FailoverConfig {
+            backup,
+            silence_threshold_db: -50.0,
+            silence_secs: 10.0,
+            restore_secs: 30.0,
+            crossfade_secs: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailoverReason {
+    None,
+    Silence,
+    DeviceFailure,
+    Restored,
+}
+
+impl FailoverReason {
+    fn from_u32(v: u32) -> Self {
+        match v {
+            1 => FailoverReason::Silence,
+            2 => FailoverReason::DeviceFailure,
+            3 => FailoverReason::Restored,
+            _ => FailoverReason::None,
+        }
+    }
+
+    fn to_u32(self) -> u32 {
+        match self {
+            FailoverReason::None => 0,
+            FailoverReason::Silence => 1,
+            FailoverReason::DeviceFailure => 2,
+            FailoverReason::Restored => 3,
+        }
+    }
+}
+
+impl fmt::Display for FailoverReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            FailoverReason::None => "none",
+            FailoverReason::Silence => "silence on primary",
+            FailoverReason::DeviceFailure => "primary device failure",
+            FailoverReason::Restored => "primary restored",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FailoverStatus {
+    pub on_backup: bool,
+    pub switches: u32,
+    pub reason: FailoverReason,
+}
+
+pub struct FailoverState {
+    on_backup: AtomicBool,
+    switches: AtomicU32,
+    reason: AtomicU32,
+    primary_failed: AtomicBool,
+}
+
+impl FailoverState {
+    pub fn new() -> Self {
+        FailoverState {
+            on_backup: AtomicBool::new(false),
+            switches: AtomicU32::new(0),
+            reason: AtomicU32::new(0),
+            primary_failed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn mark_primary_failed(&self) {
+        self.primary_failed.store(true, Ordering::Relaxed);
+    }
+
+    pub fn status(&self) -> FailoverStatus {
+        FailoverStatus {
+            on_backup: self.on_backup.load(Ordering::Relaxed),
+            switches: self.switches.load(Ordering::Relaxed),
+            reason: FailoverReason::from_u32(self.reason.load(Ordering::Relaxed)),
+        }
+    }
+
+    fn record_switch(&self, on_backup: bool, reason: FailoverReason) {
+        self.on_backup.store(on_backup, Ordering::Relaxed);
+        self.reason.store(reason.to_u32(), Ordering::Relaxed);
+        self.switches.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for FailoverState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct InputFailover {
+    threshold: f32,
+    restore_threshold: f32,
+    envelope_coeff: f32,
+    envelope: f32,
+    silence_samples: u64,
+    restore_samples: u64,
+    fade_step: f32,
+    primary_quiet: u64,
+    primary_active: u64,
+    on_backup: bool,
+    mix: f32,
+}
+
+impl InputFailover {
+    pub fn new(config: &FailoverConfig, sample_rate: u32) -> Self {
+        let rate = sample_rate as f32;
+        let fade_samples = (config.crossfade_secs.max(0.0) * rate).max(1.0);
+        InputFailover {
+            threshold: 10.0f32.powf(config.silence_threshold_db / 20.0),
+            restore_threshold: 10.0f32.powf((config.silence_threshold_db + RESTORE_HYSTERESIS_DB) / 20.0),
+            envelope_coeff: 1.0 - (-1.0 / (ENVELOPE_SECS * rate)).exp(),
+            envelope: 0.0,
+            silence_samples: (config.silence_secs.max(0.1) * rate) as u64,
+            restore_samples: (config.restore_secs.max(0.1) * rate) as u64,
+            fade_step: 1.0 / fade_samples,
+            primary_quiet: 0,
+            primary_active: 0,
+            on_backup: false,
+            mix: 0.0,
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        primary: Option<(f32, f32)>,
+        backup: (f32, f32),
+        state: &FailoverState,
+    ) -> (f32, f32) {
+        let mut failed = state.primary_failed.load(Ordering::Relaxed);
+        let (pl, pr) = primary.unwrap_or((0.0, 0.0));
+        let power = if primary.is_some() { 0.5 * (pl * pl + pr * pr) } else { 0.0 };
+        self.envelope += self.envelope_coeff * (power - self.envelope);
+        let level = self.envelope.sqrt();
+
+        // Between the two thresholds both counts hold.
+        if primary.is_some() && level >= self.restore_threshold {
+            self.primary_quiet = 0;
+            self.primary_active = self.primary_active.saturating_add(1);
+        } else if primary.is_none() || level < self.threshold {
+            self.primary_quiet = self.primary_quiet.saturating_add(1);
+            self.primary_active = 0;
+        }
+
+        // A failed device that delivers programme again for the restore
+        // time is trusted again.
+        if failed && self.primary_active >= self.restore_samples {
+            state.primary_failed.store(false, Ordering::Relaxed);
+            failed = false;
+        }
+
+        if !self.on_backup {
+            if failed {
+                self.on_backup = true;
+                state.record_switch(true, FailoverReason::DeviceFailure);
+            } else if self.primary_quiet >= self.silence_samples {
+                self.on_backup = true;
+                state.record_switch(true, FailoverReason::Silence);
+            }
+        } else if !failed && self.primary_active >= self.restore_samples {
+            self.on_backup = false;
+            state.record_switch(false, FailoverReason::Restored);
+        }
+
+        if self.on_backup {
+            self.mix = (self.mix + self.fade_step).min(1.0);
+        } else {
+            self.mix = (self.mix - self.fade_step).max(0.0);
+        }
+
+        let (bl, br) = backup;
+        (
+            pl * (1.0 - self.mix) + bl * self.mix,
+            pr * (1.0 - self.mix) + br * self.mix,
+        )
+    }
+}
+
+pub struct LoopedFile {
+    samples: Vec<f32>,
+    channels: usize,
+    pos: f64,
+    step: f64,
+}
+
+impl LoopedFile {
+    pub fn new(samples: Vec<f32>, channels: usize, source_rate: u32, target_rate: u32) -> Self {
+        LoopedFile {
+            samples,
+            channels: channels.max(1),
+            pos: 0.0,
+            step: source_rate as f64 / target_rate.max(1) as f64,
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.pos = 0.0;
+    }
+
+    pub fn next_frame(&mut self) -> (f32, f32) {
+        let frames = self.samples.len() / self.channels;
+        if frames == 0 {
+            return (0.0, 0.0);
+        }
+        let idx = (self.pos as usize % frames) * self.channels;
+        let left = self.samples[idx];
+        let right = if self.channels > 1 { self.samples[idx + 1] } else { left };
+        self.pos += self.step;
+        if self.pos >= frames as f64 {
+            self.pos -= frames as f64;
+        }
+        (left, right)
+    }
+}
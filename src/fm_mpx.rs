@@ -1,7 +1,12 @@
 use anyhow::Result;
 
 use crate::audio::AudioSource;
-use crate::rds::RdsGenerator;
+use crate::notch::PilotNotch;
+use crate::phase_align::pilot_table;
+use crate::preemphasis::ShelvingFilter;
+use crate::rds::{FastTuning, PsScrollMode, RdsGenerator, RtOverflow};
+use crate::rds_strings::RdsCodeTable;
+use crate::rds_translit::TranslitTable;
 
 const PI: f32 = 3.141592654;
 const MPX_SAMPLE_RATE: f32 = 228000.0;
@@ -33,13 +38,40 @@ const CARRIER_19: [f32; 12] = [
     -0.5,
 ];
 
-pub struct FmMpx {
-    pub rds: RdsGenerator,
+pub const DEFAULT_LPF_CUTOFF_HZ: f32 = 12_000.0;
+pub const MIN_LPF_CUTOFF_HZ: f32 = 10_000.0;
+pub const MAX_LPF_CUTOFF_HZ: f32 = 17_000.0;
 
-    audio: Option<AudioSource>,
-    downsample_factor: f32,
-    audio_pos: f32,
-    audio_index: usize,
+fn clamp_cutoff(cutoff_hz: f32, in_samplerate: f32) -> f32 {
+    cutoff_hz
+        .clamp(MIN_LPF_CUTOFF_HZ, MAX_LPF_CUTOFF_HZ)
+        .min((in_samplerate / 2.0) * 0.8)
+}
+
+pub(crate) fn design_low_pass(cutoff_freq: f32, sample_rate: f32) -> [f32; FIR_HALF_SIZE] {
+    let mut low_pass_fir = [0.0f32; FIR_HALF_SIZE];
+    low_pass_fir[FIR_HALF_SIZE - 1] = 2.0 * cutoff_freq / sample_rate / 2.0;
+
+    for i in 1..FIR_HALF_SIZE {
+        let idx = FIR_HALF_SIZE - 1 - i;
+        let sinc = (2.0 * PI * cutoff_freq * i as f32 / sample_rate).sin()
+            / (PI * i as f32);
+        let window = 0.54 - 0.46 * (2.0 * PI * (i + FIR_HALF_SIZE) as f32
+            / (2.0 * FIR_HALF_SIZE as f32))
+            .cos();
+        low_pass_fir[idx] = sinc * window;
+    }
+    low_pass_fir
+}
+
+// Everything in the MPX that comes from the program audio: low-pass,
+// pre-emphasis, compressor, pilot notch, stereo encoder and pilot. Its
+// position in the output is a plain sample count, so a copy can be moved to
+// any point of a render and warmed up there.
+#[derive(Clone)]
+pub struct ProgramEncoder {
+    downsample_factor: f64,
+    position: usize,
 
     low_pass_fir: [f32; FIR_HALF_SIZE],
     fir_buffer_mono: [f32; FIR_SIZE],
@@ -47,53 +79,39 @@ pub struct FmMpx {
     fir_index: usize,
 
     channels: usize,
-    phase_38: usize,
-    phase_19: usize,
 
     pilot_level: f32,
-    rds_level: f32,
+    pilot_table: [f32; 12],
     stereo_separation: f32,
 
-    preemphasis_tau: Option<f32>,
-    preemph_prev_mono: f32,
-    preemph_prev_stereo: f32,
-    preemph_state_mono: f32,
-    preemph_state_stereo: f32,
+    preemphasis: Option<[ShelvingFilter; 2]>,
+    pilot_notch: Option<[PilotNotch; 2]>,
 
     compressor_enabled: bool,
     comp_threshold_db: f32,
     comp_ratio: f32,
     comp_attack: f32,
     comp_release: f32,
+    comp_makeup_db: f32,
     comp_gain_db: f32,
 }
 
+pub struct FmMpx {
+    pub rds: RdsGenerator,
+
+    audio: Option<AudioSource>,
+    program: ProgramEncoder,
+    rds_level: f32,
+}
+
 impl FmMpx {
     pub fn new(audio: Option<AudioSource>) -> Self {
         let mut low_pass_fir = [0.0f32; FIR_HALF_SIZE];
 
         let (downsample_factor, channels) = if let Some(ref audio) = audio {
             let in_samplerate = audio.sample_rate as f32;
-            let downsample_factor = MPX_SAMPLE_RATE / in_samplerate;
-
-            let mut cutoff_freq = 15000.0 * 0.8;
-            if in_samplerate / 2.0 < cutoff_freq {
-                cutoff_freq = (in_samplerate / 2.0) * 0.8;
-            }
-
-            low_pass_fir[FIR_HALF_SIZE - 1] = 2.0 * cutoff_freq / MPX_SAMPLE_RATE / 2.0;
-
-            for i in 1..FIR_HALF_SIZE {
-                let idx = FIR_HALF_SIZE - 1 - i;
-                let sinc = (2.0 * PI * cutoff_freq * i as f32 / MPX_SAMPLE_RATE).sin()
-                    / (PI * i as f32);
-                let window = 0.54 - 0.46 * (2.0 * PI * (i + FIR_HALF_SIZE) as f32
-                    / (2.0 * FIR_HALF_SIZE as f32))
-                    .cos();
-                low_pass_fir[idx] = sinc * window;
-            }
-
-            (downsample_factor, audio.channels)
+            low_pass_fir = design_low_pass(clamp_cutoff(DEFAULT_LPF_CUTOFF_HZ, in_samplerate), MPX_SAMPLE_RATE);
+            (MPX_SAMPLE_RATE as f64 / audio.sample_rate as f64, audio.channels)
         } else {
             (1.0, 0)
         };
@@ -101,33 +119,31 @@ impl FmMpx {
         FmMpx {
             rds: RdsGenerator::new(),
             audio,
-            downsample_factor,
-            audio_pos: downsample_factor,
-            audio_index: 0,
-            low_pass_fir,
-            fir_buffer_mono: [0.0; FIR_SIZE],
-            fir_buffer_stereo: [0.0; FIR_SIZE],
-            fir_index: 0,
-            channels,
-            phase_38: 0,
-            phase_19: 0,
-
-            pilot_level: 0.9,
+            program: ProgramEncoder {
+                downsample_factor,
+                position: 0,
+                low_pass_fir,
+                fir_buffer_mono: [0.0; FIR_SIZE],
+                fir_buffer_stereo: [0.0; FIR_SIZE],
+                fir_index: 0,
+                channels,
+
+                pilot_level: 0.9,
+                pilot_table: CARRIER_19,
+                stereo_separation: 1.0,
+
+                preemphasis: None,
+                pilot_notch: None,
+
+                compressor_enabled: false,
+                comp_threshold_db: -18.0,
+                comp_ratio: 3.0,
+                comp_attack: 0.01,
+                comp_release: 0.2,
+                comp_makeup_db: 0.0,
+                comp_gain_db: 0.0,
+            },
             rds_level: 1.0,
-            stereo_separation: 1.0,
-
-            preemphasis_tau: None,
-            preemph_prev_mono: 0.0,
-            preemph_prev_stereo: 0.0,
-            preemph_state_mono: 0.0,
-            preemph_state_stereo: 0.0,
-
-            compressor_enabled: false,
-            comp_threshold_db: -18.0,
-            comp_ratio: 3.0,
-            comp_attack: 0.01,
-            comp_release: 0.2,
-            comp_gain_db: 0.0,
         }
     }
 
@@ -143,6 +159,14 @@ impl FmMpx {
         self.rds.set_pi(pi);
     }
 
+    pub fn set_rds_ecc(&mut self, ecc: Option<u8>) {
+        self.rds.set_ecc(ecc);
+    }
+
+    pub fn set_rds_lic(&mut self, lic: Option<u8>) {
+        self.rds.set_lic(lic);
+    }
+
     pub fn set_rds_tp(&mut self, tp: bool) {
         self.rds.set_tp(tp);
     }
@@ -176,7 +200,11 @@ impl FmMpx {
     }
 
     pub fn set_pilot_level(&mut self, level: f32) {
-        self.pilot_level = level.clamp(0.0, 2.0);
+        self.program.pilot_level = level.clamp(0.0, 2.0);
+    }
+
+    pub fn set_pilot_phase(&mut self, offset_deg: f32) {
+        self.program.pilot_table = pilot_table(offset_deg);
     }
 
     pub fn set_rds_level(&mut self, level: f32) {
@@ -184,34 +212,66 @@ impl FmMpx {
     }
 
     pub fn set_stereo_separation(&mut self, level: f32) {
-        self.stereo_separation = level.clamp(0.0, 2.0);
+        self.program.stereo_separation = level.clamp(0.0, 2.0);
+    }
+
+    pub fn set_lpf_cutoff(&mut self, cutoff_hz: f32) {
+        if let Some(ref audio) = self.audio {
+            self.program.low_pass_fir = design_low_pass(clamp_cutoff(cutoff_hz, audio.sample_rate as f32), MPX_SAMPLE_RATE);
+        }
+    }
+
+    pub fn set_pilot_notch(&mut self, enabled: bool) {
+        self.program.pilot_notch = enabled.then(|| [PilotNotch::new(MPX_SAMPLE_RATE as u32); 2]);
     }
 
     pub fn set_preemphasis(&mut self, tau: Option<f32>) {
-        self.preemphasis_tau = tau;
-        self.preemph_prev_mono = 0.0;
-        self.preemph_prev_stereo = 0.0;
-        self.preemph_state_mono = 0.0;
-        self.preemph_state_stereo = 0.0;
+        self.program.preemphasis = tau.map(|tau| [ShelvingFilter::preemphasis(tau, MPX_SAMPLE_RATE as u32); 2]);
     }
 
-    pub fn set_compressor(&mut self, enabled: bool, threshold_db: f32, ratio: f32, attack: f32, release: f32) {
-        self.compressor_enabled = enabled;
-        self.comp_threshold_db = threshold_db;
-        self.comp_ratio = ratio.max(1.0);
-        self.comp_attack = attack.max(0.001);
-        self.comp_release = release.max(0.01);
-        self.comp_gain_db = 0.0;
+    pub fn set_compressor(&mut self, enabled: bool, threshold_db: f32, ratio: f32, attack: f32, release: f32, makeup_db: f32) {
+        let program = &mut self.program;
+        program.compressor_enabled = enabled;
+        program.comp_threshold_db = threshold_db;
+        program.comp_ratio = ratio.max(1.0);
+        program.comp_attack = attack.max(0.001);
+        program.comp_release = release.max(0.01);
+        program.comp_makeup_db = makeup_db;
+        program.comp_gain_db = 0.0;
     }
 
     pub fn set_rds_af_list(&mut self, freqs: &[f32]) {
         self.rds.set_af_list_mhz(freqs);
     }
 
+    pub fn set_rds_af_entries(&mut self, freqs: &[f32], regional: &[f32], tuned_mhz: Option<f32>) {
+        self.rds.set_af_entries(freqs, regional, tuned_mhz);
+    }
+
     pub fn set_rds_ps_scroll(&mut self, enabled: bool, text: &str, cps: f32) {
         self.rds.enable_ps_scroll(enabled, text, cps);
     }
 
+    pub fn set_rds_ps_scroll_mode(&mut self, mode: PsScrollMode, page_dwell_secs: f32, center: bool) {
+        self.rds.set_ps_scroll_mode(mode, page_dwell_secs, center);
+    }
+
+    pub fn set_rds_code_table(&mut self, table: RdsCodeTable) {
+        self.rds.set_code_table(table);
+    }
+
+    pub fn set_rds_transliteration(&mut self, table: Option<TranslitTable>) {
+        self.rds.set_transliteration(table);
+    }
+
+    pub fn set_rds_rate_guard(&mut self, enabled: bool) {
+        self.rds.set_rate_guard(enabled);
+    }
+
+    pub fn set_rds_rt_overflow(&mut self, mode: RtOverflow, ellipsis: &str, part_secs: f32) {
+        self.rds.set_rt_overflow(mode, ellipsis, part_secs);
+    }
+
     pub fn set_rds_rt_scroll(&mut self, enabled: bool, text: &str, cps: f32) {
         self.rds.enable_rt_scroll(enabled, text, cps);
     }
@@ -224,44 +284,88 @@ impl FmMpx {
         self.rds.set_ct_interval_groups(interval_groups);
     }
 
+    pub fn set_rds_fast_tuning(&mut self, mode: FastTuning) {
+        self.rds.set_fast_tuning(mode);
+    }
+
     pub fn set_rds_ps_alternates(&mut self, list: Vec<String>, interval_groups: usize) {
         self.rds.set_ps_alternates(list, interval_groups);
     }
 
     pub fn get_samples(&mut self, mpx_buffer: &mut [f32]) -> Result<()> {
+        self.get_rds_samples(mpx_buffer);
+        if let Some(ref audio) = self.audio {
+            self.program.add_samples(audio, mpx_buffer, None);
+        }
+        Ok(())
+    }
+
+    // RDS at the configured level. The RDS generator is sequential (group
+    // cycle, scrolling, CT), so a parallel render takes it from here in
+    // order and adds the program separately.
+    pub fn get_rds_samples(&mut self, mpx_buffer: &mut [f32]) {
         self.rds.get_rds_samples(mpx_buffer);
         if (self.rds_level - 1.0).abs() > f32::EPSILON {
             for v in mpx_buffer.iter_mut() {
                 *v *= self.rds_level;
             }
         }
+    }
+
+    pub fn program(&self) -> Option<(&AudioSource, &ProgramEncoder)> {
+        self.audio.as_ref().map(|audio| (audio, &self.program))
+    }
+}
 
-        if self.audio.is_none() {
-            return Ok(());
+impl ProgramEncoder {
+    // Samples to run before a chunk so filters and the compressor gain have
+    // converged to what a continuous render would have at that point.
+    pub fn warmup_samples(&self) -> usize {
+        let mut secs = 0.05;
+        if self.compressor_enabled {
+            secs += 5.0 * self.comp_attack.max(self.comp_release);
         }
+        (secs * MPX_SAMPLE_RATE) as usize
+    }
+
+    // Adds the program for output samples start..start + buffer.len() to the
+    // buffer, from a copy of this encoder moved there and warmed up. With
+    // `program_out` (same length as the buffer) the processed left/right
+    // program that went into the MPX is stored there as well.
+    pub fn add_samples_at(
+        &self,
+        audio: &AudioSource,
+        start: usize,
+        mpx_buffer: &mut [f32],
+        program_out: Option<&mut [[f32; 2]]>,
+    ) {
+        let mut encoder = self.clone();
+        let warmup_start = start.saturating_sub(self.warmup_samples());
+        encoder.position = warmup_start;
+        let mut scratch = vec![0.0f32; start - warmup_start];
+        encoder.add_samples(audio, &mut scratch, None);
+        encoder.add_samples(audio, mpx_buffer, program_out);
+    }
 
-        let audio = self.audio.as_ref().unwrap();
+    fn add_samples(&mut self, audio: &AudioSource, mpx_buffer: &mut [f32], mut program_out: Option<&mut [[f32; 2]]>) {
         let total_samples = audio.samples.len();
         let channels = self.channels;
 
-        for i in 0..mpx_buffer.len() {
-            if self.audio_pos >= self.downsample_factor {
-                self.audio_pos -= self.downsample_factor;
-                if total_samples > 0 {
-                    self.audio_index = (self.audio_index + channels) % total_samples;
-                }
-            }
+        for (n, sample) in mpx_buffer.iter_mut().enumerate() {
+            // The first output sample already reads the second input frame.
+            let frame = 1 + (self.position as f64 / self.downsample_factor) as usize;
+            let audio_index = if total_samples > 0 { (frame * channels) % total_samples } else { 0 };
 
             let mono_sample;
             let stereo_sample;
             if channels <= 1 {
-                mono_sample = audio.samples.get(self.audio_index).copied().unwrap_or(0.0);
+                mono_sample = audio.samples.get(audio_index).copied().unwrap_or(0.0);
                 stereo_sample = 0.0;
             } else {
-                let left = audio.samples.get(self.audio_index).copied().unwrap_or(0.0);
+                let left = audio.samples.get(audio_index).copied().unwrap_or(0.0);
                 let right = audio
                     .samples
-                    .get(self.audio_index + 1)
+                    .get(audio_index + 1)
                     .copied()
                     .unwrap_or(0.0);
                 mono_sample = left + right;
@@ -306,17 +410,9 @@ impl FmMpx {
         let mut mono = out_mono;
         let mut stereo = out_stereo;
 
-        if let Some(tau) = self.preemphasis_tau {
-            let a = (-1.0 / (tau * MPX_SAMPLE_RATE)).exp();
-            let y_mono = mono - self.preemph_prev_mono + a * self.preemph_state_mono;
-            self.preemph_prev_mono = mono;
-            self.preemph_state_mono = y_mono;
-            mono = y_mono;
-
-            let y_stereo = stereo - self.preemph_prev_stereo + a * self.preemph_state_stereo;
-            self.preemph_prev_stereo = stereo;
-            self.preemph_state_stereo = y_stereo;
-            stereo = y_stereo;
+        if let Some([pre_mono, pre_stereo]) = self.preemphasis.as_mut() {
+            mono = pre_mono.process(mono);
+            stereo = pre_stereo.process(stereo);
         }
 
         if self.compressor_enabled {
@@ -333,30 +429,32 @@ impl FmMpx {
                 (-1.0 / (self.comp_release * MPX_SAMPLE_RATE)).exp()
             };
             self.comp_gain_db = target_gain_db + coeff * (self.comp_gain_db - target_gain_db);
-            let gain = 10f32.powf(self.comp_gain_db / 20.0);
+            let gain = 10f32.powf((self.comp_gain_db + self.comp_makeup_db) / 20.0);
             mono *= gain;
             stereo *= gain;
         }
 
-        mpx_buffer[i] += 4.05 * mono;
+        if let Some([notch_mono, notch_stereo]) = self.pilot_notch.as_mut() {
+            mono = notch_mono.process(mono);
+            stereo = notch_stereo.process(stereo);
+        }
 
-            if channels > 1 {
-            mpx_buffer[i] += (4.05 * self.stereo_separation) * CARRIER_38[self.phase_38] * stereo
-                + self.pilot_level * CARRIER_19[self.phase_19];
+        if let Some(frame) = program_out.as_deref_mut().and_then(|out| out.get_mut(n)) {
+            *frame = if channels > 1 {
+                [0.5 * (mono + stereo), 0.5 * (mono - stereo)]
+            } else {
+                [mono, mono]
+            };
+        }
 
-                self.phase_19 += 1;
-                self.phase_38 += 1;
-                if self.phase_19 >= CARRIER_19.len() {
-                    self.phase_19 = 0;
-                }
-                if self.phase_38 >= CARRIER_38.len() {
-                    self.phase_38 = 0;
-                }
+        *sample += 4.05 * mono;
+
+            if channels > 1 {
+                *sample += (4.05 * self.stereo_separation) * CARRIER_38[self.position % CARRIER_38.len()] * stereo
+                    + self.pilot_level * self.pilot_table[self.position % CARRIER_19.len()];
             }
 
-            self.audio_pos += 1.0;
+            self.position += 1;
         }
-
-        Ok(())
     }
 }
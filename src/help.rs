@@ -0,0 +1,155 @@
+use std::fmt;
+
+// In-app help: a one-line tooltip for each control and a longer text for the
+// help pane, grouped by the tab the control is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HelpArea {
+    Rds,
+    Processing,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HelpTopic {
+    Ps,
+    Rt,
+    Pi,
+    Tp,
+    Ta,
+    Ms,
+    Ct,
+    Pty,
+    Di,
+    EccLic,
+    Af,
+    OutputGain,
+    Limiter,
+    PilotLevel,
+    RdsLevel,
+    StereoSeparation,
+    Preemphasis,
+    Compressor,
+    AudioLpf,
+}
+
+impl HelpTopic {
+    pub const ALL: [HelpTopic; 19] = [
+        HelpTopic::Ps,
+        HelpTopic::Rt,
+        HelpTopic::Pi,
+        HelpTopic::Tp,
+        HelpTopic::Ta,
+        HelpTopic::Ms,
+        HelpTopic::Ct,
+        HelpTopic::Pty,
+        HelpTopic::Di,
+        HelpTopic::EccLic,
+        HelpTopic::Af,
+        HelpTopic::OutputGain,
+        HelpTopic::Limiter,
+        HelpTopic::PilotLevel,
+        HelpTopic::RdsLevel,
+        HelpTopic::StereoSeparation,
+        HelpTopic::Preemphasis,
+        HelpTopic::Compressor,
+        HelpTopic::AudioLpf,
+    ];
+
+    pub fn in_area(area: HelpArea) -> Vec<HelpTopic> {
+        HelpTopic::ALL.iter().copied().filter(|topic| topic.area() == area).collect()
+    }
+
+    pub fn area(self) -> HelpArea {
+        match self {
+            HelpTopic::Ps
+            | HelpTopic::Rt
+            | HelpTopic::Pi
+            | HelpTopic::Tp
+            | HelpTopic::Ta
+            | HelpTopic::Ms
+            | HelpTopic::Ct
+            | HelpTopic::Pty
+            | HelpTopic::Di
+            | HelpTopic::EccLic
+            | HelpTopic::Af => HelpArea::Rds,
+            _ => HelpArea::Processing,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            HelpTopic::Ps => "PS (Programme Service name)",
+            HelpTopic::Rt => "RT (RadioText)",
+            HelpTopic::Pi => "PI (Programme Identification)",
+            HelpTopic::Tp => "TP (Traffic Programme)",
+            HelpTopic::Ta => "TA (Traffic Announcement)",
+            HelpTopic::Ms => "MS (Music/Speech)",
+            HelpTopic::Ct => "CT (Clock Time)",
+            HelpTopic::Pty => "PTY (Programme Type)",
+            HelpTopic::Di => "DI (Decoder Identification)",
+            HelpTopic::EccLic => "ECC and LIC",
+            HelpTopic::Af => "AF (Alternative Frequencies)",
+            HelpTopic::OutputGain => "Output gain",
+            HelpTopic::Limiter => "MPX limiter",
+            HelpTopic::PilotLevel => "Pilot level",
+            HelpTopic::RdsLevel => "RDS level",
+            HelpTopic::StereoSeparation => "Stereo separation",
+            HelpTopic::Preemphasis => "Pre-emphasis",
+            HelpTopic::Compressor => "Compressor",
+            HelpTopic::AudioLpf => "Audio low-pass",
+        }
+    }
+
+    pub fn tooltip(self) -> &'static str {
+        match self {
+            HelpTopic::Ps => "Station name shown by receivers, 8 characters",
+            HelpTopic::Rt => "Free text up to 64 characters, e.g. now playing",
+            HelpTopic::Pi => "Unique station code from your regulator, 4 hex digits",
+            HelpTopic::Tp => "This station carries traffic announcements",
+            HelpTopic::Ta => "A traffic announcement is on air now",
+            HelpTopic::Ms => "On for music, off for speech",
+            HelpTopic::Ct => "Sends date and time in 4A groups once a minute",
+            HelpTopic::Pty => "Programme genre shown and searched by receivers",
+            HelpTopic::Di => "Describes the audio: stereo, artificial head, compressed, dynamic PTY",
+            HelpTopic::EccLic => "Extended Country Code and Language, sent in 1A groups",
+            HelpTopic::Af => "Other frequencies carrying this programme",
+            HelpTopic::OutputGain => "Scales the whole MPX before the sound card",
+            HelpTopic::Limiter => "Look-ahead peak limiter that keeps the MPX under the threshold",
+            HelpTopic::PilotLevel => "19 kHz pilot: 1.0 = 10% injection, keep 8-10%",
+            HelpTopic::RdsLevel => "57 kHz RDS subcarrier injection, typically 2-4 kHz deviation",
+            HelpTopic::StereoSeparation => "Scales L-R; 1.0 is full separation",
+            HelpTopic::Preemphasis => "50 µs in Europe and most of the world, 75 µs in the Americas and Korea",
+            HelpTopic::Compressor => "Evens out loudness before the limiter",
+            HelpTopic::AudioLpf => "Keeps program audio out of the pilot and stereo bands",
+        }
+    }
+
+    pub fn body(self) -> &'static str {
+        match self {
+            HelpTopic::Ps => "Eight characters sent in four 0A segments. Receivers show it as the station name. Scrolling or paging PS is tolerated but discouraged by many regulators, and a change faster than once a second is held back by the rate guard.",
+            HelpTopic::Rt => "Up to 64 characters in sixteen 2A segments. Flip the A/B flag, or leave Auto A/B on, so receivers clear the old text. Longer text is cut at a word, ellipsised or split in two as set under Scrolling.",
+            HelpTopic::Pi => "Sixteen bits as four hex digits: country, coverage area and programme reference. It must be unique in the reception area and is normally assigned by the regulator; all AFs and regional variants share it, apart from the area nibble for regional variants.",
+            HelpTopic::Tp => "Set when the station carries traffic information. Receivers with TA enabled only stay on stations with TP set.",
+            HelpTopic::Ta => "Raise while a traffic announcement is on air and lower it right after. Receivers switch to the station and raise the volume. TA without TP is flagged as a warning, since receivers ignore it.",
+            HelpTopic::Ms => "A hint for receivers with separate volume settings for music and speech.",
+            HelpTopic::Ct => "Sent in a 4A group at the start of each minute, from the computer clock in UTC with the local offset. Turn it off if the clock is not synchronised, since receivers may set their clock from it.",
+            HelpTopic::Pty => "Five bits from the RDS (Europe) table: News, Sport, Pop Music and so on. 31 is the alarm code and must only be used for real emergencies. With the PTY schedule running, the dynamic PTY DI bit is set.",
+            HelpTopic::Di => "Four bits sent one per PS segment. Stereo: the programme is stereo. Artificial head: binaural recording. Compressed: dynamic range compression. Dynamic PTY: PTY may change during the programme, which receivers then re-read.",
+            HelpTopic::EccLic => "ECC completes the country code of the PI for receivers, e.g. E2 for Tunisia; the PI builder can derive it from the country. LIC names the spoken language and can be left empty.",
+            HelpTopic::Af => "Up to 25 frequencies in 100 kHz steps (87.6-107.9 MHz). Method A sends a plain list; regional variants need method B with this transmitter's tuned frequency. Every AF must carry the same PI.",
+            HelpTopic::OutputGain => "Scales program and carriers together. Set it so the MPX reaches ±75 kHz deviation on the transmitter; the Meters tab shows deviation after calibration.",
+            HelpTopic::Limiter => "Holds the composite under the threshold by turning down the program only, so pilot and RDS injection stay constant and total deviation stays within ±75 kHz. A longer look-ahead gives cleaner limiting with more delay.",
+            HelpTopic::PilotLevel => "The 19 kHz pilot tells receivers to decode stereo. ITU-R BS.450 asks for 8-10% of maximum deviation (6-7.5 kHz at ±75 kHz); here 1.0 is 10%. Too little pilot drops receivers to mono, too much costs program modulation.",
+            HelpTopic::RdsLevel => "The 57 kHz subcarrier. EN 50067 allows ±1.0 to ±7.5 kHz deviation and recommends about ±2 kHz; 2-4 kHz is usual. Check the injection readout on the Meters tab after calibrating deviation.",
+            HelpTopic::StereoSeparation => "Gain of the L-R difference signal. Values below 1.0 narrow the image and reduce multipath noise; above 1.0 widens it at the cost of peak modulation.",
+            HelpTopic::Preemphasis => "Boosts treble before transmission to match receiver de-emphasis. Use 50 µs in Europe, Africa and Asia, 75 µs in the Americas and South Korea, and Off only when the input is already pre-emphasised.",
+            HelpTopic::Compressor => "Reduces level differences between songs and speech. Threshold and ratio set how much, attack and release how fast; the processing presets give tested starting points.",
+            HelpTopic::AudioLpf => "Low-passes the program so nothing lands on the 19 kHz pilot or spills past the stereo band. 15 kHz is the broadcast standard; higher cut-offs risk pilot interference.",
+        }
+    }
+}
+
+impl fmt::Display for HelpTopic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.title())
+    }
+}
@@ -0,0 +1,152 @@
+use std::f64::consts::PI;
+
+// Channel impairments for exported MPX, applied after output scaling so a
+// file can exercise an RDS decoder's tolerance to a degraded composite.
+// Echo ripple comes first, then the group-delay tilt, then noise.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Impairments {
+    // White noise over the whole file bandwidth, as the SNR against a
+    // full-scale (100% modulation) sine; None leaves the file clean.
+    pub noise_snr_db: Option<f32>,
+    // Peak-to-peak amplitude ripple across the band, from a single echo at
+    // `ripple_delay_us` (ripple period 1 / delay: 10 us gives 100 kHz).
+    pub ripple_db: f32,
+    pub ripple_delay_us: f32,
+    // Group delay at 57 kHz minus group delay at DC.
+    pub group_delay_tilt_us: f32,
+}
+
+pub const DEFAULT_RIPPLE_DELAY_US: f32 = 10.0;
+
+impl Impairments {
+    pub fn is_active(&self) -> bool {
+        self.noise_snr_db.is_some() || self.ripple_db > 0.0 || self.group_delay_tilt_us != 0.0
+    }
+}
+
+// A first-order section stays within +/-0.25 samples of tilt; larger tilts
+// cascade identical sections, up to 64 of them (about 70 us at 228 kHz).
+const TILT_PER_SECTION: f64 = 0.25;
+const MAX_SECTIONS: usize = 64;
+// Samples for a section's impulse response to die out (|a| <= 0.5).
+const SECTION_SETTLE: usize = 64;
+const NOISE_SEED: u64 = 0x5075_6c73_6546_4d00;
+
+#[derive(Clone, Copy, Debug)]
+struct Allpass {
+    a: f32,
+    x1: f32,
+    y1: f32,
+}
+
+impl Allpass {
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.a * x + self.x1 - self.a * self.y1;
+        self.x1 = x;
+        self.y1 = y;
+        y
+    }
+}
+
+pub struct ImpairmentChain {
+    noise_rms: f32,
+    echo_gain: f32,
+    echo: Vec<f32>,
+    echo_index: usize,
+    allpass: Vec<Allpass>,
+}
+
+impl ImpairmentChain {
+    pub fn new(impairments: &Impairments, sample_rate: u32) -> Self {
+        let fs = sample_rate as f64;
+
+        let noise_rms = impairments
+            .noise_snr_db
+            .map(|snr| std::f32::consts::FRAC_1_SQRT_2 * 10f32.powf(-snr / 20.0))
+            .unwrap_or(0.0);
+
+        // (1 + g) / (1 - g) is the peak-to-peak ripple of x + g * x[n - d].
+        let ripple = 10f32.powf(impairments.ripple_db.max(0.0) / 20.0);
+        let echo_gain = (ripple - 1.0) / (ripple + 1.0);
+        let delay = ((impairments.ripple_delay_us as f64 * 1e-6 * fs).round() as usize).max(1);
+
+        let tilt = impairments.group_delay_tilt_us as f64 * 1e-6 * fs;
+        let sections = ((tilt.abs() / TILT_PER_SECTION).ceil() as usize).min(MAX_SECTIONS);
+        let allpass = if sections == 0 {
+            Vec::new()
+        } else {
+            let per_section = (tilt / sections as f64).clamp(-TILT_PER_SECTION, TILT_PER_SECTION);
+            let w = 2.0 * PI * 57_000.0 / fs;
+            let a = allpass_coeff(per_section, w) as f32;
+            vec![Allpass { a, x1: 0.0, y1: 0.0 }; sections]
+        };
+
+        ImpairmentChain {
+            noise_rms,
+            echo_gain,
+            echo: vec![0.0; if echo_gain > 0.0 { delay } else { 0 }],
+            echo_index: 0,
+            allpass,
+        }
+    }
+
+    // Clean samples to run through before the first one that is kept, so a
+    // chain started mid-file (resumed export) has the state it would have
+    // had. The noise needs none: it is a function of the sample index.
+    pub fn warmup_samples(&self) -> usize {
+        self.echo.len() + SECTION_SETTLE * self.allpass.len()
+    }
+
+    pub fn process(&mut self, x: f32, index: usize) -> f32 {
+        let mut y = x;
+        if !self.echo.is_empty() {
+            let delayed = self.echo[self.echo_index];
+            self.echo[self.echo_index] = x;
+            self.echo_index = (self.echo_index + 1) % self.echo.len();
+            y += self.echo_gain * delayed;
+        }
+        for section in self.allpass.iter_mut() {
+            y = section.process(y);
+        }
+        if self.noise_rms > 0.0 {
+            y += self.noise_rms * gaussian(index as u64);
+        }
+        y
+    }
+}
+
+// Group delay of (a + z^-1) / (1 + a z^-1), in samples.
+fn group_delay(a: f64, w: f64) -> f64 {
+    (1.0 - a * a) / (1.0 + 2.0 * a * w.cos() + a * a)
+}
+
+// Coefficient whose delay at `w` exceeds its delay at DC by `tilt` samples.
+// The difference rises monotonically with `a` from -0.5 to 0.35.
+fn allpass_coeff(tilt: f64, w: f64) -> f64 {
+    let (mut lo, mut hi) = if tilt > 0.0 { (0.0, 0.35) } else { (-0.5, 0.0) };
+    for _ in 0..50 {
+        let mid = 0.5 * (lo + hi);
+        if group_delay(mid, w) - group_delay(mid, 0.0) < tilt {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+// Unit-variance Gaussian noise indexed by sample, so any part of a file can
+// be regenerated on its own (chunked and resumed renders).
+fn gaussian(index: u64) -> f32 {
+    let u1 = (splitmix64(NOISE_SEED ^ (2 * index)) >> 11) as f64 + 0.5;
+    let u2 = (splitmix64(NOISE_SEED ^ (2 * index + 1)) >> 11) as f64;
+    let scale = 1.0 / (1u64 << 53) as f64;
+    ((-2.0 * (u1 * scale).ln()).sqrt() * (2.0 * PI * u2 * scale).cos()) as f32
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
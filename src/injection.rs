@@ -0,0 +1,110 @@
+use std::f32::consts::PI;
+
+const PILOT_HZ: f32 = 19_000.0;
+const STEREO_CARRIER_HZ: f32 = 38_000.0;
+const RDS_HZ: f32 = 57_000.0;
+
+// The pilot and the 38 kHz residual are steady carriers, read through a
+// 10 Hz low-pass that rejects program audio and the stereo sidebands. RDS is
+// a suppressed-carrier biphase signal, so its detector keeps the data band
+// and reads the envelope peak instead.
+const CARRIER_BANDWIDTH_HZ: f32 = 10.0;
+const RDS_BANDWIDTH_HZ: f32 = 4_000.0;
+// The two-pole RDS low-pass takes 0.7 dB off the biphase waveform's peak.
+const RDS_PEAK_CORRECTION: f32 = 1.087;
+const RDS_PEAK_SECS: f32 = 0.05;
+const RDS_SMOOTHING_SECS: f32 = 0.5;
+
+// Levels as a fraction of output full scale (1.0 = 100% modulation).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Injection {
+    pub pilot: f32,
+    pub stereo_carrier: f32,
+    pub rds: f32,
+}
+
+// Quadrature detector: mixes the input down with a local oscillator at the
+// carrier frequency and low-passes I and Q through two one-pole sections.
+// The envelope does not depend on the carrier's phase.
+#[derive(Clone, Copy, Debug)]
+struct SyncDetector {
+    phase: f32,
+    step: f32,
+    coeff: f32,
+    i: [f32; 2],
+    q: [f32; 2],
+}
+
+impl SyncDetector {
+    fn new(freq: f32, bandwidth: f32, sample_rate: f32) -> Self {
+        SyncDetector {
+            phase: 0.0,
+            step: 2.0 * PI * freq / sample_rate,
+            coeff: 1.0 - (-2.0 * PI * bandwidth / sample_rate).exp(),
+            i: [0.0; 2],
+            q: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let (sin, cos) = self.phase.sin_cos();
+        self.phase += self.step;
+        if self.phase >= 2.0 * PI {
+            self.phase -= 2.0 * PI;
+        }
+        let mut i = 2.0 * x * cos;
+        let mut q = 2.0 * x * sin;
+        for (si, sq) in self.i.iter_mut().zip(self.q.iter_mut()) {
+            *si += self.coeff * (i - *si);
+            *sq += self.coeff * (q - *sq);
+            i = *si;
+            q = *sq;
+        }
+        (i * i + q * q).sqrt()
+    }
+}
+
+// Pilot, 38 kHz carrier residual and RDS injection of the composite output.
+pub struct InjectionMeter {
+    pilot: SyncDetector,
+    stereo_carrier: SyncDetector,
+    rds: SyncDetector,
+    rds_block: usize,
+    rds_count: usize,
+    rds_peak: f32,
+    rds_coeff: f32,
+    value: Injection,
+}
+
+impl InjectionMeter {
+    pub fn new(sample_rate: u32) -> Self {
+        let fs = sample_rate as f32;
+        InjectionMeter {
+            pilot: SyncDetector::new(PILOT_HZ, CARRIER_BANDWIDTH_HZ, fs),
+            stereo_carrier: SyncDetector::new(STEREO_CARRIER_HZ, CARRIER_BANDWIDTH_HZ, fs),
+            rds: SyncDetector::new(RDS_HZ, RDS_BANDWIDTH_HZ, fs),
+            rds_block: (RDS_PEAK_SECS * fs) as usize,
+            rds_count: 0,
+            rds_peak: 0.0,
+            rds_coeff: RDS_PEAK_SECS / RDS_SMOOTHING_SECS,
+            value: Injection::default(),
+        }
+    }
+
+    pub fn process(&mut self, x: f32) {
+        self.value.pilot = self.pilot.process(x);
+        self.value.stereo_carrier = self.stereo_carrier.process(x);
+        self.rds_peak = self.rds_peak.max(self.rds.process(x));
+        self.rds_count += 1;
+        if self.rds_count >= self.rds_block {
+            let peak = self.rds_peak * RDS_PEAK_CORRECTION;
+            self.value.rds += self.rds_coeff * (peak - self.value.rds);
+            self.rds_peak = 0.0;
+            self.rds_count = 0;
+        }
+    }
+
+    pub fn injection(&self) -> Injection {
+        self.value
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt;
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+
+// Inserts run inside the live engine at the internal rate, on blocks of
+// INSERT_BLOCK samples per channel. Each point with an insert enabled delays
+// the program by one block (about 0.3 ms) to collect it.
+pub const INSERT_SAMPLE_RATE: u32 = 228_000;
+pub const INSERT_BLOCK: usize = 64;
+
+// Where in the chain an insert runs. Both points carry program left/right;
+// the composite stays with the limiter so deviation is always guarded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InsertPoint {
+    // The program input after the delays, before the 15 kHz low-pass.
+    Program,
+    // After pre-emphasis, compressor and pilot notch, before stereo encoding
+    // and the limiter.
+    Processed,
+}
+
+impl InsertPoint {
+    pub const ALL: [InsertPoint; 2] = [InsertPoint::Program, InsertPoint::Processed];
+
+    pub fn index(self) -> usize {
+        match self {
+            InsertPoint::Program => 0,
+            InsertPoint::Processed => 1,
+        }
+    }
+}
+
+impl fmt::Display for InsertPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            InsertPoint::Program => "Program input",
+            InsertPoint::Processed => "After processing",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// A user processing stage. `process` is called on the audio thread with
+// INSERT_BLOCK samples in each slice and works in place; it must not block,
+// and should allocate in the factory rather than here.
+pub trait AudioInsert: Send {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]);
+}
+
+pub type InsertFactory = fn() -> Box<dyn AudioInsert>;
+
+#[derive(Clone, Copy)]
+struct Registration {
+    name: &'static str,
+    point: InsertPoint,
+    factory: InsertFactory,
+}
+
+static REGISTRY: Mutex<Vec<Registration>> = Mutex::new(Vec::new());
+
+// Makes an insert available to the engine under `name`. A host built on this
+// crate registers its stages before starting the engine; registering a name
+// again replaces the earlier entry.
+pub fn register(name: &'static str, point: InsertPoint, factory: InsertFactory) {
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    registry.retain(|r| r.name != name);
+    registry.push(Registration { name, point, factory });
+}
+
+// Name and point of every registered insert, in registration order.
+pub fn registered() -> Vec<(&'static str, InsertPoint)> {
+    let registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    registry.iter().map(|r| (r.name, r.point)).collect()
+}
+
+// The inserts that ship with the crate, also a reference for writing one.
+pub fn register_builtin() {
+    register("Subsonic filter", InsertPoint::Program, || Box::new(SubsonicFilter::default()));
+}
+
+// Removes DC and rumble below about 10 Hz with a one-pole high-pass.
+#[derive(Default)]
+struct SubsonicFilter {
+    state: [(f32, f32); 2],
+}
+
+impl AudioInsert for SubsonicFilter {
+    fn process(&mut self, left: &mut [f32], right: &mut [f32]) {
+        let r = 1.0 - 2.0 * std::f32::consts::PI * 10.0 / INSERT_SAMPLE_RATE as f32;
+        for (channel, (x1, y1)) in [left, right].into_iter().zip(self.state.iter_mut()) {
+            for x in channel.iter_mut() {
+                let y = *x - *x1 + r * *y1;
+                *x1 = *x;
+                *y1 = y;
+                *x = y;
+            }
+        }
+    }
+}
+
+// The enabled inserts at one point and the block they are filling. Output
+// lags input by one block, so a point without inserts is skipped entirely.
+struct InsertStage {
+    inserts: Vec<Box<dyn AudioInsert>>,
+    input: [[f32; INSERT_BLOCK]; 2],
+    output: [[f32; INSERT_BLOCK]; 2],
+    pos: usize,
+}
+
+impl InsertStage {
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if self.inserts.is_empty() {
+            return (left, right);
+        }
+        let out = (self.output[0][self.pos], self.output[1][self.pos]);
+        self.input[0][self.pos] = left;
+        self.input[1][self.pos] = right;
+        self.pos += 1;
+        if self.pos == INSERT_BLOCK {
+            self.pos = 0;
+            self.output = self.input;
+            let [out_left, out_right] = &mut self.output;
+            for insert in self.inserts.iter_mut() {
+                insert.process(out_left, out_right);
+            }
+        }
+        out
+    }
+}
+
+pub struct InsertChain {
+    stages: [InsertStage; 2],
+}
+
+impl InsertChain {
+    pub fn empty() -> Self {
+        InsertChain {
+            stages: InsertPoint::ALL.map(|_| InsertStage {
+                inserts: Vec::new(),
+                input: [[0.0; INSERT_BLOCK]; 2],
+                output: [[0.0; INSERT_BLOCK]; 2],
+                pos: 0,
+            }),
+        }
+    }
+
+    // Instantiates the named inserts, in the given order within each point.
+    pub fn new(names: &[String]) -> Result<Self> {
+        let registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut chain = InsertChain::empty();
+        for name in names {
+            let registration = registry
+                .iter()
+                .find(|r| r.name == name.as_str())
+                .ok_or_else(|| anyhow!("Insert \"{}\" is not registered", name))?;
+            chain.stages[registration.point.index()].inserts.push((registration.factory)());
+        }
+        Ok(chain)
+    }
+
+    pub fn is_active(&self, point: InsertPoint) -> bool {
+        !self.stages[point.index()].inserts.is_empty()
+    }
+
+    pub fn process(&mut self, point: InsertPoint, left: f32, right: f32) -> (f32, f32) {
+        self.stages[point.index()].process(left, right)
+    }
+}
@@ -0,0 +1,142 @@
+use std::f64::consts::PI;
+
+use anyhow::{anyhow, Result};
+
+pub const DEFAULT_IQ_SAMPLE_RATE: u32 = 2_400_000;
+pub const DEFAULT_DEVIATION_KHZ: f32 = 75.0;
+// Highest modulating frequency assumed for the Carson bandwidth check: RDS
+// upper sideband plus margin.
+const MAX_MPX_HZ: f64 = 60_000.0;
+// -3 dBFS, leaving headroom for SDR software that resamples on playback.
+const IQ_AMPLITUDE: f64 = 0.707;
+
+// Interpolation kernel: 32 taps at the input rate, cut off at 0.4 of it
+// (91 kHz for a 228 kHz MPX) so the stereo and RDS subcarriers pass flat
+// and the images above the input band are gone.
+const TAPS: usize = 32;
+const HALF: usize = TAPS / 2;
+const CUTOFF: f64 = 0.4;
+const PHASES: usize = 512;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IqSettings {
+    pub sample_rate: u32,
+    // Deviation at 100% modulation (output full scale).
+    pub deviation_khz: f32,
+}
+
+impl Default for IqSettings {
+    fn default() -> Self {
+        IqSettings {
+            sample_rate: DEFAULT_IQ_SAMPLE_RATE,
+            deviation_khz: DEFAULT_DEVIATION_KHZ,
+        }
+    }
+}
+
+impl IqSettings {
+    // The complex rate has to hold the whole FM signal: Carson's rule,
+    // 2 * (deviation + highest MPX frequency).
+    pub fn validate(&self, mpx_rate: u32) -> Result<()> {
+        if self.deviation_khz <= 0.0 {
+            return Err(anyhow!("deviation must be positive"));
+        }
+        let carson = 2.0 * (self.deviation_khz as f64 * 1000.0 + MAX_MPX_HZ);
+        if (self.sample_rate as f64) < carson.max(mpx_rate as f64) {
+            return Err(anyhow!(
+                "IQ sample rate must be at least {:.0} Hz for {:.0} kHz deviation",
+                carson.max(mpx_rate as f64),
+                self.deviation_khz
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Windowed-sinc interpolator from one sample rate up to a higher one.
+pub struct SincInterpolator {
+    history: [f32; TAPS],
+    kernel: Vec<f32>,
+    step: f64,
+    frac: f64,
+}
+
+impl SincInterpolator {
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        // Blackman-windowed sinc sampled at PHASES points per input sample.
+        let kernel = (0..=HALF * PHASES)
+            .map(|k| {
+                let u = k as f64 / PHASES as f64;
+                let x = 2.0 * CUTOFF * u;
+                let sinc = if x == 0.0 { 1.0 } else { (PI * x).sin() / (PI * x) };
+                let t = PI * u / HALF as f64;
+                let blackman = 0.42 + 0.5 * t.cos() + 0.08 * (2.0 * t).cos();
+                (2.0 * CUTOFF * sinc * blackman) as f32
+            })
+            .collect();
+        SincInterpolator {
+            history: [0.0; TAPS],
+            kernel,
+            step: from_rate as f64 / to_rate.max(1) as f64,
+            frac: 0.0,
+        }
+    }
+
+    // Takes one input sample and appends the output samples that fall
+    // before the next one. The output lags the input by half the kernel.
+    pub fn process(&mut self, x: f32, out: &mut Vec<f32>) {
+        self.history.copy_within(1.., 0);
+        self.history[TAPS - 1] = x;
+        while self.frac < 1.0 {
+            let mut y = 0.0f32;
+            for (j, &x) in self.history.iter().enumerate() {
+                let u = (TAPS - 1 - HALF) as f64 + self.frac - j as f64;
+                let k = (u.abs() * PHASES as f64).round() as usize;
+                y += x * self.kernel.get(k).copied().unwrap_or(0.0);
+            }
+            out.push(y);
+            self.frac += self.step;
+        }
+        self.frac -= 1.0;
+    }
+
+    // Input samples it takes for the last real one to come out.
+    pub fn latency(&self) -> usize {
+        HALF
+    }
+}
+
+// Upsamples the MPX to the IQ rate with a windowed-sinc interpolator and
+// frequency-modulates it onto a complex carrier at 0 Hz.
+pub struct IqModulator {
+    interpolator: SincInterpolator,
+    upsampled: Vec<f32>,
+    phase: f64,
+    phase_step: f64,
+}
+
+impl IqModulator {
+    pub fn new(settings: &IqSettings, mpx_rate: u32) -> Self {
+        IqModulator {
+            interpolator: SincInterpolator::new(mpx_rate, settings.sample_rate),
+            upsampled: Vec::new(),
+            phase: 0.0,
+            phase_step: 2.0 * PI * settings.deviation_khz as f64 * 1000.0 / settings.sample_rate as f64,
+        }
+    }
+
+    // Takes one MPX sample (1.0 = 100% modulation) and appends the IQ
+    // samples that fall before the next one. The output lags the input by
+    // half the kernel.
+    pub fn process(&mut self, mpx: f32, iq: &mut Vec<[f32; 2]>) {
+        self.upsampled.clear();
+        self.interpolator.process(mpx, &mut self.upsampled);
+        for &y in &self.upsampled {
+            self.phase = (self.phase + self.phase_step * y as f64) % (2.0 * PI);
+            iq.push([
+                (IQ_AMPLITUDE * self.phase.cos()) as f32,
+                (IQ_AMPLITUDE * self.phase.sin()) as f32,
+            ]);
+        }
+    }
+}
@@ -0,0 +1,14 @@
+// Output gain and limiter threshold are shown and stored in dB; the engine
+// still works with linear multipliers.
+pub const MIN_GAIN_DB: f32 = -6.0;
+pub const MAX_GAIN_DB: f32 = 6.0;
+pub const MIN_THRESHOLD_DB: f32 = -6.0;
+pub const MAX_THRESHOLD_DB: f32 = 0.0;
+
+pub fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+pub fn linear_to_db(linear: f32) -> f32 {
+    20.0 * linear.abs().max(1e-6).log10()
+}
@@ -1,7 +1,68 @@
+pub mod alerts;
+pub mod analyzer;
 pub mod audio;
 pub mod audio_io;
+pub mod automation;
+pub mod autostart;
+pub mod batch;
+pub mod calibration;
+pub mod chain;
+pub mod checkpoint;
+pub mod compliance;
+pub mod contacts;
+pub mod data_providers;
+pub mod delay;
+pub mod diagnostics;
+pub mod exclusive;
+pub mod failover;
 pub mod fm_mpx;
+pub mod help;
+pub mod impairment;
+pub mod injection;
+pub mod inserts;
+pub mod iq_export;
+pub mod levels;
+pub mod limiter;
+pub mod loopback;
+pub mod mask;
+pub mod metadata_filter;
+pub mod mod_history;
+pub mod mod_limits;
+pub mod mpx_replay;
+pub mod notch;
+pub mod null_test;
+pub mod phase_align;
+pub mod pilot_pll;
+pub mod preemphasis;
+pub mod preset_sync;
+pub mod processing_preset;
+pub mod program_feed;
+pub mod ps_receiver;
+pub mod pty_schedule;
 pub mod rds;
+pub mod rds_checks;
+pub mod rds_countries;
+pub mod rds_feed;
+pub mod rds_history;
+pub mod rds_macros;
+pub mod rds_network;
+pub mod rds_schedule;
 pub mod rds_strings;
+pub mod rds_translit;
+pub mod refresh;
+pub mod rtp;
+pub mod service;
+pub mod session;
+pub mod silence;
+pub mod smoothing;
+pub mod song_history;
+pub mod status_server;
+pub mod stereo_test;
+pub mod storage;
+pub mod supervisor;
+pub mod sync;
+pub mod test_sequence;
+pub mod traffic;
 pub mod waveform;
+pub mod wav_metadata;
 pub mod wav_writer;
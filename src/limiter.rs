@@ -0,0 +1,122 @@
+use std::collections::VecDeque;
+
+pub const MAX_LOOKAHEAD: usize = 2048;
+
+const KNEE_DB: f32 = 2.0;
+// Short peaks recover on the fast stage; sustained limiting builds up the
+// slow stage, which then holds the gain and avoids pumping on dense program.
+const FAST_RELEASE_SECS: f32 = 0.04;
+const SLOW_ATTACK_SECS: f32 = 0.3;
+const SLOW_RELEASE_SECS: f32 = 0.8;
+const CARRIER_DECAY_SECS: f32 = 1.0;
+
+// Lookahead limiter acting on the program part of the MPX only. Pilot and
+// RDS are delayed alongside it and added back after the gain, so their
+// injection does not change while audio is limited; the audio ceiling is
+// the threshold minus their recent peak.
+pub struct LookaheadLimiter {
+    threshold: f32,
+    lookahead: usize,
+    audio: VecDeque<f32>,
+    carriers: VecDeque<f32>,
+    carrier_peak: f32,
+    fast_db: f32,
+    slow_db: f32,
+    fast_release: f32,
+    slow_attack: f32,
+    slow_release: f32,
+    carrier_decay: f32,
+}
+
+impl LookaheadLimiter {
+    pub fn new(sample_rate: u32, threshold: f32, lookahead: usize) -> Self {
+        let coeff = |secs: f32| (-1.0 / (secs * sample_rate as f32)).exp();
+        let mut limiter = LookaheadLimiter {
+            threshold: threshold.max(0.1),
+            lookahead: 1,
+            audio: VecDeque::with_capacity(MAX_LOOKAHEAD + 1),
+            carriers: VecDeque::with_capacity(MAX_LOOKAHEAD + 1),
+            carrier_peak: 0.0,
+            fast_db: 0.0,
+            slow_db: 0.0,
+            fast_release: coeff(FAST_RELEASE_SECS),
+            slow_attack: coeff(SLOW_ATTACK_SECS),
+            slow_release: coeff(SLOW_RELEASE_SECS),
+            carrier_decay: coeff(CARRIER_DECAY_SECS),
+        };
+        limiter.set_lookahead(lookahead);
+        limiter
+    }
+
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold.max(0.1);
+    }
+
+    pub fn set_lookahead(&mut self, samples: usize) {
+        let samples = samples.clamp(1, MAX_LOOKAHEAD);
+        if samples == self.lookahead {
+            return;
+        }
+        self.lookahead = samples;
+        self.audio.clear();
+        self.carriers.clear();
+    }
+
+    pub fn lookahead(&self) -> usize {
+        self.lookahead
+    }
+
+    pub fn reduction_db(&self) -> f32 {
+        self.fast_db.max(self.slow_db)
+    }
+
+    // Returns the delayed (input, output) pair, or None while the lookahead
+    // window is still filling. A bypassed limiter keeps tracking gain
+    // reduction and latency but passes the input through.
+    pub fn process(&mut self, audio: f32, carriers: f32, bypass: bool) -> Option<(f32, f32)> {
+        self.audio.push_back(audio);
+        self.carriers.push_back(carriers);
+        self.carrier_peak = carriers.abs().max(self.carrier_peak * self.carrier_decay);
+        if self.audio.len() < self.lookahead {
+            return None;
+        }
+        while self.audio.len() > self.lookahead {
+            let _ = self.audio.pop_front();
+            let _ = self.carriers.pop_front();
+        }
+
+        let peak = self.audio.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        let ceiling = (self.threshold - self.carrier_peak).max(0.1 * self.threshold);
+        let target_db = soft_knee_reduction_db(20.0 * peak.max(1e-6).log10() - 20.0 * ceiling.log10());
+
+        self.fast_db = if target_db > self.fast_db {
+            target_db
+        } else {
+            target_db + self.fast_release * (self.fast_db - target_db)
+        };
+        let slow_coeff = if target_db > self.slow_db { self.slow_attack } else { self.slow_release };
+        self.slow_db = target_db + slow_coeff * (self.slow_db - target_db);
+
+        let audio = *self.audio.front()?;
+        let carriers = *self.carriers.front()?;
+        let input = audio + carriers;
+        if bypass {
+            return Some((input, input));
+        }
+        let gain = 10f32.powf(-self.reduction_db() / 20.0);
+        Some((input, audio * gain + carriers))
+    }
+}
+
+// Infinite-ratio knee centred on the ceiling. The quadratic section always
+// asks for at least the overshoot, so the ceiling still holds inside it.
+pub fn soft_knee_reduction_db(over_db: f32) -> f32 {
+    let half = KNEE_DB / 2.0;
+    if over_db <= -half {
+        0.0
+    } else if over_db >= half {
+        over_db
+    } else {
+        (over_db + half) * (over_db + half) / (2.0 * KNEE_DB)
+    }
+}
@@ -0,0 +1,100 @@
+use std::env;
+use std::ffi::OsString;
+use std::process::Command;
+
+// Input list entries that capture what another application plays rather than
+// a hardware input. On Windows the target is an output device (WASAPI
+// loopback); on Linux it is a PulseAudio/PipeWire monitor source.
+pub const LOOPBACK_PREFIX: &str = "Loopback: ";
+
+const VIRTUAL_DEVICES: [(&str, &str); 3] = [
+    ("blackhole", "BlackHole"),
+    ("soundflower", "Soundflower"),
+    ("loopback audio", "Loopback"),
+];
+
+pub fn loopback_name(target: &str) -> String {
+    format!("{}{}", LOOPBACK_PREFIX, target)
+}
+
+pub fn loopback_target(name: &str) -> Option<&str> {
+    name.strip_prefix(LOOPBACK_PREFIX)
+}
+
+// Every sink has a monitor source under PulseAudio and under PipeWire's
+// pulse server; `pactl` is present with either.
+pub fn pulse_monitor_sources() -> Vec<String> {
+    let Ok(output) = Command::new("pactl").args(["list", "short", "sources"]).output() else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    parse_monitor_sources(&String::from_utf8_lossy(&output.stdout))
+}
+
+// The ALSA pulse plugin records from the source named in PULSE_SOURCE when
+// a stream is opened. The variable is set only while this is held and put
+// back on drop, so side inputs and later restarts on the "pulse" device get
+// the default source again.
+pub struct PulseSource {
+    previous: Option<OsString>,
+}
+
+impl PulseSource {
+    pub fn select(source: &str) -> Self {
+        let previous = env::var_os("PULSE_SOURCE");
+        env::set_var("PULSE_SOURCE", source);
+        PulseSource { previous }
+    }
+}
+
+impl Drop for PulseSource {
+    fn drop(&mut self) {
+        match self.previous.take() {
+            Some(value) => env::set_var("PULSE_SOURCE", value),
+            None => env::remove_var("PULSE_SOURCE"),
+        }
+    }
+}
+
+fn parse_monitor_sources(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1))
+        .filter(|name| name.ends_with(".monitor"))
+        .map(str::to_string)
+        .collect()
+}
+
+fn virtual_device(name: &str) -> Option<&'static str> {
+    let lower = name.to_lowercase();
+    VIRTUAL_DEVICES
+        .iter()
+        .find(|(pattern, _)| lower.contains(pattern))
+        .map(|&(_, label)| label)
+}
+
+// Guidance shown under the input picker for the selected device, or on macOS
+// when no virtual loopback driver is installed at all.
+pub fn input_hint(selected: Option<&str>, devices: &[String]) -> Option<String> {
+    if let Some(target) = selected.and_then(loopback_target) {
+        return Some(format!(
+            "Captures what plays on {}; point the playout software there and keep it off the MPX output",
+            target
+        ));
+    }
+    if let Some(label) = selected.and_then(virtual_device) {
+        return Some(format!(
+            "{} is a virtual device: set it as the playout software's output (or add it to a Multi-Output Device to keep listening)",
+            label
+        ));
+    }
+    if cfg!(target_os = "macos") && !devices.iter().any(|d| virtual_device(d).is_some()) {
+        return Some(
+            "To encode another application's output, install BlackHole (existential.audio/blackhole), set it as the playout output and pick it here"
+                .to_string(),
+        );
+    }
+    None
+}
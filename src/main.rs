@@ -2,7 +2,10 @@ mod app;
 
 use app::App;
 use iced::Application;
+use pulse_fm_rds_encoder::autostart::LaunchOptions;
 
 fn main() -> iced::Result {
-    App::run(iced::Settings::default())
+    pulse_fm_rds_encoder::inserts::register_builtin();
+    let args: Vec<String> = std::env::args().collect();
+    App::run(iced::Settings::with_flags(LaunchOptions::from_args(&args[1..])))
 }
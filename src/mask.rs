@@ -0,0 +1,78 @@
+// Baseband limits, in dB relative to 100% modulation, that keep the FM
+// emission inside the ITU-R SM.328 / ETSI EN 302 018 mask: guard bands
+// around the pilot, the RDS channel and nothing above it.
+#[derive(Clone, Copy, Debug)]
+pub struct MaskSegment {
+    pub start_hz: f32,
+    pub end_hz: f32,
+    pub max_db: f32,
+}
+
+pub const MPX_MASK: [MaskSegment; 6] = [
+    MaskSegment { start_hz: 15_000.0, end_hz: 18_500.0, max_db: -30.0 },
+    MaskSegment { start_hz: 18_500.0, end_hz: 19_500.0, max_db: -14.0 },
+    MaskSegment { start_hz: 19_500.0, end_hz: 23_000.0, max_db: -30.0 },
+    MaskSegment { start_hz: 53_000.0, end_hz: 54_500.0, max_db: -30.0 },
+    MaskSegment { start_hz: 54_500.0, end_hz: 59_500.0, max_db: -20.0 },
+    MaskSegment { start_hz: 59_500.0, end_hz: 96_000.0, max_db: -40.0 },
+];
+
+// The audio stop band starts at 15 kHz, or at the low-pass cutoff when that
+// is set higher, so audio the filter is meant to pass is not held to it.
+pub fn mask_limit_db(freq: f32, audio_cutoff_hz: f32) -> Option<f32> {
+    if freq < audio_cutoff_hz && freq < MPX_MASK[0].end_hz {
+        return None;
+    }
+    MPX_MASK
+        .iter()
+        .find(|s| freq >= s.start_hz && freq < s.end_hz)
+        .map(|s| s.max_db)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MaskResult {
+    pub margin_db: f32,
+    pub worst_hz: f32,
+}
+
+impl MaskResult {
+    pub fn passed(&self) -> bool {
+        self.margin_db >= 0.0
+    }
+}
+
+pub struct MaskCheck {
+    audio_cutoff_hz: f32,
+    margin_db: f32,
+    worst_hz: f32,
+}
+
+impl MaskCheck {
+    pub fn new(audio_cutoff_hz: f32) -> Self {
+        MaskCheck {
+            audio_cutoff_hz,
+            margin_db: f32::MAX,
+            worst_hz: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, freq: f32, level_db: f32) {
+        if let Some(limit) = mask_limit_db(freq, self.audio_cutoff_hz) {
+            let margin = limit - level_db;
+            if margin < self.margin_db {
+                self.margin_db = margin;
+                self.worst_hz = freq;
+            }
+        }
+    }
+
+    pub fn result(&self) -> Option<MaskResult> {
+        if self.margin_db == f32::MAX {
+            return None;
+        }
+        Some(MaskResult {
+            margin_db: self.margin_db,
+            worst_hz: self.worst_hz,
+        })
+    }
+}
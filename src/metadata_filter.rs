@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+
+use crate::rds_macros::ValueProvider;
+
+// Bracketed groups holding one of these words are dropped: "(Official
+// Video)", "[HD]", "(Lyrics)". "(feat. X)" and "(Remix)" stay.
+const JUNK_WORDS: [&str; 13] = [
+    "official", "video", "audio", "lyric", "lyrics", "visualizer", "hd", "hq", "4k", "remaster", "remastered", "explicit",
+    "clip",
+];
+
+// Cleans metadata from automation and data providers before it reaches PS/RT.
+// Applied in order: bracketed junk, banned words, title case, whitespace,
+// length.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MetadataFilter {
+    #[serde(default)]
+    pub banned_words: Vec<String>,
+    // Drop the whole value instead of masking the word ("f***").
+    #[serde(default)]
+    pub drop_banned: bool,
+    #[serde(default = "default_true")]
+    pub strip_brackets: bool,
+    // Only text in all capitals or all lower case is recased.
+    #[serde(default)]
+    pub title_case: bool,
+    // Characters; longer values are cut at the last word that fits.
+    #[serde(default)]
+    pub max_len: Option<usize>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for MetadataFilter {
+    fn default() -> Self {
+        MetadataFilter {
+            banned_words: Vec::new(),
+            drop_banned: false,
+            strip_brackets: true,
+            title_case: false,
+            max_len: None,
+        }
+    }
+}
+
+impl MetadataFilter {
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        if self.strip_brackets {
+            text = strip_junk_brackets(&text);
+        }
+        if !self.banned_words.is_empty() {
+            match self.mask_banned(&text) {
+                Some(masked) => text = masked,
+                None => return String::new(),
+            }
+        }
+        if self.title_case {
+            text = title_case(&text);
+        }
+        let mut text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if let Some(max) = self.max_len {
+            text = cut_at_word(&text, max);
+        }
+        text
+    }
+
+    // None when a banned word is found and the value is to be dropped.
+    fn mask_banned(&self, text: &str) -> Option<String> {
+        let mut out = String::with_capacity(text.len());
+        let mut word = String::new();
+        for c in text.chars().chain(std::iter::once(' ')) {
+            if c.is_alphanumeric() || c == '\'' {
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                let lower = word.to_lowercase();
+                if self.banned_words.iter().any(|banned| banned.trim().to_lowercase() == lower) {
+                    if self.drop_banned {
+                        return None;
+                    }
+                    let mut chars = word.chars();
+                    out.extend(chars.next());
+                    out.extend(chars.map(|_| '*'));
+                } else {
+                    out.push_str(&word);
+                }
+                word.clear();
+            }
+            out.push(c);
+        }
+        out.pop();
+        Some(out)
+    }
+}
+
+fn strip_junk_brackets(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(open) = rest.find(['(', '[']) {
+        let close_char = if rest[open..].starts_with('(') { ')' } else { ']' };
+        let Some(len) = rest[open + 1..].find(close_char) else {
+            break;
+        };
+        let inner = &rest[open + 1..open + 1 + len];
+        let junk = inner
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| JUNK_WORDS.contains(&word.to_lowercase().as_str()));
+        out.push_str(&rest[..open]);
+        if !junk {
+            out.push_str(&rest[open..open + len + 2]);
+        }
+        rest = &rest[open + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn title_case(text: &str) -> String {
+    let has_upper = text.chars().any(char::is_uppercase);
+    let has_lower = text.chars().any(char::is_lowercase);
+    if has_upper && has_lower {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut start = true;
+    for c in text.chars() {
+        if start {
+            out.extend(c.to_uppercase());
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        start = c.is_whitespace() || c == '(' || c == '[' || c == '-' || c == '/';
+    }
+    out
+}
+
+fn cut_at_word(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut = text.chars().take(max).collect::<String>();
+    let next_is_space = text.chars().nth(max).is_none_or(char::is_whitespace);
+    match cut.rfind(' ') {
+        Some(space) if !next_is_space => cut[..space].trim_end().to_string(),
+        _ => cut.trim_end().to_string(),
+    }
+}
+
+// Filters another provider's values; the clock and other local values need
+// no filtering.
+pub struct Filtered<'a> {
+    pub inner: &'a dyn ValueProvider,
+    pub filter: &'a MetadataFilter,
+}
+
+impl ValueProvider for Filtered<'_> {
+    fn value(&self, name: &str) -> Option<String> {
+        self.inner.value(name).map(|value| self.filter.apply(&value))
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Timelike};
+
+// Output full scale (1.0) corresponds to 100% modulation.
+pub const FULL_SCALE_KHZ: f32 = 75.0;
+pub const HISTORY_MINUTES: usize = 24 * 60;
+
+// ITU-R SM.1268 reference: a sine giving +/-19 kHz deviation.
+const REFERENCE_POWER: f64 = (19.0 / 75.0) * (19.0 / 75.0) / 2.0;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModulationStats {
+    pub sum_sq: f64,
+    pub count: u64,
+    pub peak: f32,
+    pub pilot_peak: f32,
+    pub rds_peak: f32,
+}
+
+impl ModulationStats {
+    pub fn record(&mut self, out: f32, pilot: f32, rds: f32) {
+        self.sum_sq += (out as f64) * (out as f64);
+        self.count += 1;
+        self.peak = self.peak.max(out.abs());
+        self.pilot_peak = self.pilot_peak.max(pilot.abs());
+        self.rds_peak = self.rds_peak.max(rds.abs());
+    }
+
+    pub fn merge(&mut self, other: &ModulationStats) {
+        self.sum_sq += other.sum_sq;
+        self.count += other.count;
+        self.peak = self.peak.max(other.peak);
+        self.pilot_peak = self.pilot_peak.max(other.pilot_peak);
+        self.rds_peak = self.rds_peak.max(other.rds_peak);
+    }
+
+    pub fn mpx_power_dbr(&self) -> f32 {
+        if self.count == 0 {
+            return f32::NEG_INFINITY;
+        }
+        let power = self.sum_sq / self.count as f64;
+        (10.0 * (power.max(1e-12) / REFERENCE_POWER).log10()) as f32
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ModulationPoint {
+    pub time: DateTime<Local>,
+    pub mpx_power_dbr: f32,
+    pub peak_khz: f32,
+    pub pilot_khz: f32,
+    pub rds_khz: f32,
+}
+
+impl ModulationPoint {
+    fn from_stats(time: DateTime<Local>, stats: &ModulationStats) -> Self {
+        ModulationPoint {
+            time,
+            mpx_power_dbr: stats.mpx_power_dbr(),
+            peak_khz: stats.peak * FULL_SCALE_KHZ,
+            pilot_khz: stats.pilot_peak * FULL_SCALE_KHZ,
+            rds_khz: stats.rds_peak * FULL_SCALE_KHZ,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ModulationHistory {
+    points: VecDeque<ModulationPoint>,
+    current: Option<(DateTime<Local>, ModulationStats)>,
+}
+
+impl ModulationHistory {
+    pub fn add(&mut self, stats: &ModulationStats, now: DateTime<Local>) {
+        let minute = now.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(now);
+        match self.current.as_mut() {
+            Some((start, acc)) if *start == minute => acc.merge(stats),
+            _ => {
+                if let Some((start, acc)) = self.current.take() {
+                    self.points.push_back(ModulationPoint::from_stats(start, &acc));
+                    while self.points.len() > HISTORY_MINUTES {
+                        self.points.pop_front();
+                    }
+                }
+                self.current = Some((minute, *stats));
+            }
+        }
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &ModulationPoint> {
+        self.points.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn write_csv(&self, path: &str) -> Result<()> {
+        let mut out = BufWriter::new(File::create(Path::new(path))?);
+        writeln!(out, "minute,mpx_power_dbr,peak_khz,pilot_khz,rds_khz")?;
+        for p in &self.points {
+            writeln!(
+                out,
+                "{},{:.2},{:.2},{:.2},{:.2}",
+                p.time.to_rfc3339(),
+                p.mpx_power_dbr,
+                p.peak_khz,
+                p.pilot_khz,
+                p.rds_khz
+            )?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
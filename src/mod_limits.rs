@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::mod_history::{ModulationStats, FULL_SCALE_KHZ};
+
+// ITU-R BS.412 integrates MPX power over any 60 s period; the peak readout
+// holds the highest sample of the last second.
+const POWER_WINDOW: Duration = Duration::from_secs(60);
+const PEAK_WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitState {
+    Ok,
+    Warn,
+    Over,
+}
+
+// Thresholds from the station's license: above `warn` reads amber, above
+// `limit` red.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ModulationLimits {
+    pub power_warn_dbr: f32,
+    pub power_limit_dbr: f32,
+    pub deviation_warn_khz: f32,
+    pub deviation_limit_khz: f32,
+}
+
+impl Default for ModulationLimits {
+    fn default() -> Self {
+        ModulationLimits {
+            power_warn_dbr: -1.0,
+            power_limit_dbr: 0.0,
+            deviation_warn_khz: 72.0,
+            deviation_limit_khz: FULL_SCALE_KHZ,
+        }
+    }
+}
+
+impl ModulationLimits {
+    pub fn power_state(&self, dbr: f32) -> LimitState {
+        classify(dbr, self.power_warn_dbr, self.power_limit_dbr)
+    }
+
+    pub fn deviation_state(&self, khz: f32) -> LimitState {
+        classify(khz, self.deviation_warn_khz, self.deviation_limit_khz)
+    }
+}
+
+fn classify(value: f32, warn: f32, limit: f32) -> LimitState {
+    if value > limit {
+        LimitState::Over
+    } else if value > warn {
+        LimitState::Warn
+    } else {
+        LimitState::Ok
+    }
+}
+
+// Sliding window of the per-tick modulation stats behind the dashboard
+// readout.
+#[derive(Default)]
+pub struct ModulationWindow {
+    chunks: VecDeque<(Instant, ModulationStats)>,
+}
+
+impl ModulationWindow {
+    pub fn add(&mut self, stats: &ModulationStats, now: Instant) {
+        self.chunks.push_back((now, *stats));
+        while let Some(&(time, _)) = self.chunks.front() {
+            if now.duration_since(time) <= POWER_WINDOW {
+                break;
+            }
+            self.chunks.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    pub fn mpx_power_dbr(&self) -> f32 {
+        let mut total = ModulationStats::default();
+        for (_, stats) in &self.chunks {
+            total.merge(stats);
+        }
+        total.mpx_power_dbr()
+    }
+
+    pub fn peak_khz(&self, now: Instant) -> f32 {
+        self.chunks
+            .iter()
+            .rev()
+            .take_while(|(time, _)| now.duration_since(*time) <= PEAK_WINDOW)
+            .map(|(_, stats)| stats.peak)
+            .fold(0.0, f32::max)
+            * FULL_SCALE_KHZ
+    }
+
+    // How much of the 60 s power window has been filled since start.
+    pub fn span(&self) -> Duration {
+        match (self.chunks.front(), self.chunks.back()) {
+            (Some((first, _)), Some((last, _))) => last.duration_since(*first),
+            _ => Duration::ZERO,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{anyhow, Result};
+
+use crate::audio::load_wav;
+use crate::iq_export::SincInterpolator;
+
+// A recorded MPX needs 57 kHz and its sidebands below Nyquist.
+pub const MIN_MPX_FILE_RATE: u32 = 192_000;
+
+// A finished MPX from a WAV file (first channel), played as the pass-through
+// input: 228 kHz recordings are taken sample for sample, 192 kHz ones go
+// through the windowed-sinc interpolator the IQ export uses, which keeps the
+// 57 kHz RDS band clear of interpolation images. Without looping the file
+// plays once and then silence, with local RDS and pilot still on air.
+pub struct MpxReplay {
+    samples: Vec<f32>,
+    pos: usize,
+    looped: bool,
+    interpolator: Option<SincInterpolator>,
+    // Interpolated samples not yet played, and how many of them are.
+    pending: Vec<f32>,
+    played: usize,
+}
+
+impl MpxReplay {
+    pub fn open(path: &str, target_rate: u32, looped: bool) -> Result<Self> {
+        let source = load_wav(path)?;
+        if source.sample_rate < MIN_MPX_FILE_RATE {
+            return Err(anyhow!(
+                "MPX file is {} Hz; it needs at least {} Hz",
+                source.sample_rate,
+                MIN_MPX_FILE_RATE
+            ));
+        }
+        let samples = source
+            .samples
+            .chunks(source.channels)
+            .map(|frame| frame[0])
+            .collect::<Vec<_>>();
+        if samples.is_empty() {
+            return Err(anyhow!("MPX file is empty"));
+        }
+        Ok(MpxReplay {
+            samples,
+            pos: 0,
+            looped,
+            interpolator: (source.sample_rate != target_rate)
+                .then(|| SincInterpolator::new(source.sample_rate, target_rate)),
+            pending: Vec::new(),
+            played: 0,
+        })
+    }
+
+    // Input positions past the end, fed as silence so the interpolator
+    // lets the last samples out.
+    fn tail(&self) -> usize {
+        self.interpolator.as_ref().map_or(0, SincInterpolator::latency)
+    }
+
+    pub fn finished(&self) -> bool {
+        !self.looped && self.pos >= self.samples.len() + self.tail() && self.played >= self.pending.len()
+    }
+
+    fn next_input(&mut self) -> f32 {
+        let x = self.samples.get(self.pos).copied().unwrap_or(0.0);
+        self.pos += 1;
+        if self.looped && self.pos >= self.samples.len() {
+            self.pos = 0;
+        }
+        x
+    }
+
+    pub fn next_sample(&mut self) -> f32 {
+        if self.finished() {
+            return 0.0;
+        }
+        if self.interpolator.is_none() {
+            return self.next_input();
+        }
+        while self.played >= self.pending.len() {
+            self.pending.clear();
+            self.played = 0;
+            let x = self.next_input();
+            if let Some(interpolator) = self.interpolator.as_mut() {
+                interpolator.process(x, &mut self.pending);
+            }
+        }
+        self.played += 1;
+        self.pending[self.played - 1]
+    }
+}
@@ -0,0 +1,100 @@
+use std::f32::consts::PI;
+
+pub const PILOT_NOTCH_HZ: f32 = 19_000.0;
+pub const PILOT_NOTCH_HALF_WIDTH_HZ: f32 = 500.0;
+pub const RDS_NOTCH_HZ: f32 = 57_000.0;
+pub const RDS_NOTCH_HALF_WIDTH_HZ: f32 = 1_500.0;
+
+// Five staggered notches spread across 19 kHz +/- 500 Hz give over 35 dB of
+// rejection at the band edges (far more inside) at 228 kHz, while costing
+// about 0.6 dB at 15 kHz.
+const SECTIONS: usize = 5;
+const SECTION_Q: f32 = 12.0;
+
+#[derive(Clone, Copy, Debug)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    fn notch(freq: f32, q: f32, sample_rate: f32) -> Self {
+        let w = 2.0 * PI * freq / sample_rate;
+        let alpha = w.sin() / (2.0 * q);
+        let cos = w.cos();
+        let a0 = 1.0 + alpha;
+        Biquad {
+            b0: 1.0 / a0,
+            b1: -2.0 * cos / a0,
+            b2: 1.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+fn staggered(center: f32, half_width: f32, q: f32, sample_rate: u32) -> [Biquad; SECTIONS] {
+    let start = center - half_width;
+    let spacing = 2.0 * half_width / SECTIONS as f32;
+    std::array::from_fn(|i| Biquad::notch(start + (i as f32 + 0.5) * spacing, q, sample_rate as f32))
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PilotNotch {
+    sections: [Biquad; SECTIONS],
+}
+
+impl PilotNotch {
+    pub fn new(sample_rate: u32) -> Self {
+        PilotNotch {
+            sections: staggered(PILOT_NOTCH_HZ, PILOT_NOTCH_HALF_WIDTH_HZ, SECTION_Q, sample_rate),
+        }
+    }
+
+    pub fn process(&mut self, x: f32) -> f32 {
+        self.sections.iter_mut().fold(x, |v, section| section.process(v))
+    }
+}
+
+// Clears 57 kHz +/- 1.5 kHz, where most of an RDS signal's energy sits, from
+// an incoming MPX so the local RDS does not land on top of an upstream one.
+// Gives about 20 dB of rejection across that band at 228 kHz; the top of the
+// L-R sideband (15 kHz audio, 53 kHz) loses about 2 dB.
+const RDS_SECTION_Q: f32 = 16.0;
+
+#[derive(Clone, Copy, Debug)]
+pub struct RdsNotch {
+    sections: [Biquad; SECTIONS],
+}
+
+impl RdsNotch {
+    pub fn new(sample_rate: u32) -> Self {
+        RdsNotch {
+            sections: staggered(RDS_NOTCH_HZ, RDS_NOTCH_HALF_WIDTH_HZ, RDS_SECTION_Q, sample_rate),
+        }
+    }
+
+    pub fn process(&mut self, x: f32) -> f32 {
+        self.sections.iter_mut().fold(x, |v, section| section.process(v))
+    }
+}
@@ -0,0 +1,195 @@
+use std::f32::consts::PI;
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+
+use crate::audio::AudioSource;
+
+// BS.1770 gating: 400 ms blocks every 100 ms, an absolute gate at -70 LUFS
+// and a relative gate 10 LU below the ungated level.
+const BLOCK_SECS: f32 = 0.4;
+const BLOCK_STEP_SECS: f32 = 0.1;
+const ABSOLUTE_GATE_LUFS: f32 = -70.0;
+const RELATIVE_GATE_LU: f32 = 10.0;
+// Spectral tilt compares the energy above HIGH_BAND_HZ with that below
+// LOW_BAND_HZ; positive means brighter.
+const LOW_BAND_HZ: f32 = 500.0;
+const HIGH_BAND_HZ: f32 = 2_000.0;
+const SILENCE_DB: f32 = -120.0;
+
+#[derive(Clone, Copy, Debug)]
+struct Biquad {
+    b: [f32; 3],
+    a: [f32; 2],
+    x: [f32; 2],
+    y: [f32; 2],
+}
+
+impl Biquad {
+    fn new(b: [f32; 3], a: [f32; 2]) -> Self {
+        Biquad { b, a, x: [0.0; 2], y: [0.0; 2] }
+    }
+
+    // RBJ cookbook low/high-pass with Q = 0.707.
+    fn pass(freq: f32, sample_rate: u32, high: bool) -> Self {
+        let w = 2.0 * PI * freq / sample_rate as f32;
+        let alpha = w.sin() / (2.0 * std::f32::consts::FRAC_1_SQRT_2);
+        let cos = w.cos();
+        let a0 = 1.0 + alpha;
+        let (b0, b1) = if high { ((1.0 + cos) / 2.0, -(1.0 + cos)) } else { ((1.0 - cos) / 2.0, 1.0 - cos) };
+        Biquad::new([b0 / a0, b1 / a0, b0 / a0], [-2.0 * cos / a0, (1.0 - alpha) / a0])
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1] - self.a[0] * self.y[0] - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+// The two K-weighting stages of BS.1770, with the coefficients given for
+// 48 kHz (the rate of processed renders).
+fn k_weighting() -> [Biquad; 2] {
+    [
+        Biquad::new([1.535_124_9, -2.691_696_2, 1.198_392_8], [-1.690_659_3, 0.732_480_8]),
+        Biquad::new([1.0, -2.0, 1.0], [-1.990_047_5, 0.990_072_3]),
+    ]
+}
+
+fn db(power: f64) -> f32 {
+    if power > 0.0 {
+        (10.0 * power.log10() as f32).max(SILENCE_DB)
+    } else {
+        SILENCE_DB
+    }
+}
+
+// Level figures for one stereo program.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProgramStats {
+    // Gated integrated loudness (BS.1770 / EBU R 128).
+    pub loudness_lufs: f32,
+    pub peak_dbfs: f32,
+    pub rms_dbfs: f32,
+    pub tilt_db: f32,
+}
+
+impl ProgramStats {
+    pub fn measure(audio: &AudioSource) -> Result<Self> {
+        if audio.channels != 2 {
+            return Err(anyhow!("loudness is measured on stereo renders"));
+        }
+        if audio.sample_rate != 48_000 {
+            return Err(anyhow!("loudness is measured at 48 kHz, got {} Hz", audio.sample_rate));
+        }
+        let rate = audio.sample_rate;
+        let mut k = [k_weighting(), k_weighting()];
+        let mut low = [Biquad::pass(LOW_BAND_HZ, rate, false); 2];
+        let mut high = [Biquad::pass(HIGH_BAND_HZ, rate, true); 2];
+
+        let step = (BLOCK_STEP_SECS * rate as f32) as usize;
+        let steps_per_block = (BLOCK_SECS / BLOCK_STEP_SECS).round() as usize;
+        let mut step_power = Vec::new();
+        let (mut acc, mut in_step) = (0.0f64, 0usize);
+        let (mut peak, mut sum, mut low_sum, mut high_sum) = (0.0f32, 0.0f64, 0.0f64, 0.0f64);
+
+        for frame in audio.samples.chunks_exact(2) {
+            for (ch, &x) in frame.iter().enumerate() {
+                peak = peak.max(x.abs());
+                sum += (x * x) as f64;
+                let weighted = k[ch].iter_mut().fold(x, |v, stage| stage.process(v));
+                acc += (weighted * weighted) as f64;
+                let (l, h) = (low[ch].process(x), high[ch].process(x));
+                low_sum += (l * l) as f64;
+                high_sum += (h * h) as f64;
+            }
+            in_step += 1;
+            if in_step == step {
+                step_power.push(acc / step as f64);
+                acc = 0.0;
+                in_step = 0;
+            }
+        }
+
+        // Mean square per channel, summed over both channels (unit weights).
+        let blocks = step_power
+            .windows(steps_per_block)
+            .map(|w| w.iter().sum::<f64>() / steps_per_block as f64)
+            .collect::<Vec<_>>();
+        let lufs = |power: f64| -0.691 + db(power);
+        let gated_mean = |threshold: f32| {
+            let kept = blocks.iter().copied().filter(|&p| lufs(p) > threshold).collect::<Vec<_>>();
+            (!kept.is_empty()).then(|| kept.iter().sum::<f64>() / kept.len() as f64)
+        };
+        let loudness_lufs = gated_mean(ABSOLUTE_GATE_LUFS)
+            .and_then(|ungated| gated_mean(lufs(ungated) - RELATIVE_GATE_LU))
+            .map(lufs)
+            .unwrap_or(SILENCE_DB);
+
+        let samples = audio.samples.len().max(1) as f64;
+        Ok(ProgramStats {
+            loudness_lufs,
+            peak_dbfs: db((peak * peak) as f64),
+            rms_dbfs: db(sum / samples),
+            tilt_db: db(high_sum) - db(low_sum),
+        })
+    }
+}
+
+// Two renders of the same program and their sample-by-sample difference
+// (A minus B). The null depth is how far the difference sits below A; a
+// large value means the two settings are nearly indistinguishable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NullTestReport {
+    pub a_name: String,
+    pub b_name: String,
+    pub a: ProgramStats,
+    pub b: ProgramStats,
+    pub difference: ProgramStats,
+    pub null_depth_db: f32,
+}
+
+impl NullTestReport {
+    pub fn measure(a_name: &str, a: &AudioSource, b_name: &str, b: &AudioSource) -> Result<(Self, AudioSource)> {
+        let difference = AudioSource {
+            samples: a.samples.iter().zip(&b.samples).map(|(x, y)| x - y).collect(),
+            channels: a.channels,
+            sample_rate: a.sample_rate,
+        };
+        let a_stats = ProgramStats::measure(a)?;
+        let b_stats = ProgramStats::measure(b)?;
+        let difference_stats = ProgramStats::measure(&difference)?;
+        let report = NullTestReport {
+            a_name: a_name.to_string(),
+            b_name: b_name.to_string(),
+            a: a_stats,
+            b: b_stats,
+            difference: difference_stats,
+            null_depth_db: a_stats.rms_dbfs - difference_stats.rms_dbfs,
+        };
+        Ok((report, difference))
+    }
+}
+
+impl fmt::Display for NullTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let row = |f: &mut fmt::Formatter<'_>, name: &str, s: &ProgramStats| {
+            writeln!(
+                f,
+                "{:<12} {:>7.1} LUFS  peak {:>6.1} dBFS  rms {:>6.1} dBFS  tilt {:>+5.1} dB",
+                name, s.loudness_lufs, s.peak_dbfs, s.rms_dbfs, s.tilt_db
+            )
+        };
+        row(f, &format!("A {}", self.a_name), &self.a)?;
+        row(f, &format!("B {}", self.b_name), &self.b)?;
+        row(f, "A - B", &self.difference)?;
+        write!(
+            f,
+            "loudness difference {:+.1} LU, tilt difference {:+.1} dB, null depth {:.1} dB",
+            self.a.loudness_lufs - self.b.loudness_lufs,
+            self.a.tilt_db - self.b.tilt_db,
+            self.null_depth_db
+        )
+    }
+}
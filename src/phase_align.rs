@@ -0,0 +1,62 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+pub const PILOT_HZ: f64 = 19_000.0;
+pub const SUBCARRIER_HZ: f64 = 38_000.0;
+pub const MAX_PILOT_PHASE_DEG: f32 = 180.0;
+pub const PHASE_TEST_SECS: f32 = 1.0;
+
+// One 19 kHz period at 228 kHz, shifted by the pilot phase offset. With no
+// offset the pilot and the 38 kHz subcarrier cross zero upwards together.
+pub fn pilot_table(offset_deg: f32) -> [f32; 12] {
+    let offset = offset_deg as f64 * PI / 180.0;
+    std::array::from_fn(|i| (2.0 * PI * i as f64 / 12.0 + offset).sin() as f32)
+}
+
+// Phases are those of sine waves at sample 0. The error is 38 kHz phase
+// minus twice the pilot phase, i.e. in degrees of the subcarrier; a pilot
+// offset of x degrees shows up as -2x.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhaseReport {
+    pub pilot_deg: f32,
+    pub subcarrier_deg: f32,
+    pub error_deg: f32,
+}
+
+impl PhaseReport {
+    pub fn measure(samples: &[f32], sample_rate: u32) -> Self {
+        let pilot = tone_phase_deg(samples, PILOT_HZ, sample_rate);
+        let subcarrier = tone_phase_deg(samples, SUBCARRIER_HZ, sample_rate);
+        PhaseReport {
+            pilot_deg: pilot as f32,
+            subcarrier_deg: subcarrier as f32,
+            error_deg: wrap_deg(subcarrier - 2.0 * pilot) as f32,
+        }
+    }
+}
+
+impl fmt::Display for PhaseReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pilot {:.1}°, 38 kHz {:.1}°, 38 kHz vs 2x pilot {:+.1}°",
+            self.pilot_deg, self.subcarrier_deg, self.error_deg
+        )
+    }
+}
+
+fn tone_phase_deg(samples: &[f32], freq: f64, sample_rate: u32) -> f64 {
+    let w = 2.0 * PI * freq / sample_rate as f64;
+    let (mut i_sum, mut q_sum) = (0.0f64, 0.0f64);
+    for (n, &x) in samples.iter().enumerate() {
+        let (s, c) = (w * n as f64).sin_cos();
+        i_sum += x as f64 * c;
+        q_sum += x as f64 * s;
+    }
+    i_sum.atan2(q_sum) * 180.0 / PI
+}
+
+fn wrap_deg(deg: f64) -> f64 {
+    let wrapped = (deg + 180.0).rem_euclid(360.0) - 180.0;
+    if wrapped == -180.0 { 180.0 } else { wrapped }
+}
@@ -0,0 +1,125 @@
+use std::f32::consts::PI;
+
+const PILOT_HZ: f32 = 19_000.0;
+
+// A pilot below 2% of full scale (normal injection is 8-10%) is treated as
+// absent; the lock is dropped again below 1% so it does not chatter.
+const LOCK_LEVEL: f32 = 0.02;
+const UNLOCK_LEVEL: f32 = 0.01;
+const BANDPASS_Q: f32 = 20.0;
+const LOOP_BANDWIDTH_HZ: f32 = 10.0;
+const LOOP_DAMPING: f32 = 0.707;
+const MAX_OFFSET_HZ: f32 = 50.0;
+const DETECTOR_SECS: f32 = 0.02;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PilotLockStatus {
+    pub locked: bool,
+    pub level: f32,
+    pub freq_hz: f32,
+}
+
+// Tracks a 19 kHz pilot on an incoming MPX. The input goes through a narrow
+// band-pass first so program audio next to the pilot does not pull the loop;
+// the phase detector is normalised by the measured pilot level, so the
+// second-order loop keeps its 10 Hz bandwidth at any injection.
+#[derive(Clone, Copy, Debug)]
+pub struct PilotPll {
+    sample_rate: f32,
+    b0: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+    phase: f32,
+    nominal_step: f32,
+    freq_offset: f32,
+    max_offset: f32,
+    kp: f32,
+    ki: f32,
+    detector_coeff: f32,
+    in_phase: f32,
+    quadrature: f32,
+    carrier: f32,
+    locked: bool,
+}
+
+impl PilotPll {
+    pub fn new(sample_rate: u32) -> Self {
+        let fs = sample_rate as f32;
+        let w = 2.0 * PI * PILOT_HZ / fs;
+        let alpha = w.sin() / (2.0 * BANDPASS_Q);
+        let a0 = 1.0 + alpha;
+        let wn = 2.0 * LOOP_BANDWIDTH_HZ / (LOOP_DAMPING + 1.0 / (4.0 * LOOP_DAMPING)) / fs;
+        PilotPll {
+            sample_rate: fs,
+            b0: alpha / a0,
+            a1: -2.0 * w.cos() / a0,
+            a2: (1.0 - alpha) / a0,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+            phase: 0.0,
+            nominal_step: w,
+            freq_offset: 0.0,
+            max_offset: 2.0 * PI * MAX_OFFSET_HZ / fs,
+            kp: 2.0 * LOOP_DAMPING * wn,
+            ki: wn * wn,
+            detector_coeff: 1.0 / (DETECTOR_SECS * fs),
+            in_phase: 0.0,
+            quadrature: 0.0,
+            carrier: 0.0,
+            locked: false,
+        }
+    }
+
+    pub fn process(&mut self, x: f32) {
+        let y = self.b0 * (x - self.x2) - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+
+        let (sin, cos) = self.phase.sin_cos();
+        self.carrier = (3.0 * self.phase).sin();
+        self.in_phase += self.detector_coeff * (2.0 * y * sin - self.in_phase);
+        self.quadrature += self.detector_coeff * (2.0 * y * cos - self.quadrature);
+
+        let error = (2.0 * y * cos / self.in_phase.max(UNLOCK_LEVEL)).clamp(-1.0, 1.0);
+        self.freq_offset = (self.freq_offset + self.ki * error).clamp(-self.max_offset, self.max_offset);
+        self.phase += self.nominal_step + self.freq_offset + self.kp * error;
+        if self.phase >= 2.0 * PI {
+            self.phase -= 2.0 * PI;
+        } else if self.phase < 0.0 {
+            self.phase += 2.0 * PI;
+        }
+
+        if self.locked {
+            self.locked = self.in_phase >= UNLOCK_LEVEL;
+        } else {
+            self.locked = self.in_phase >= LOCK_LEVEL && self.quadrature.abs() < 0.5 * self.in_phase;
+        }
+    }
+
+    pub fn locked(&self) -> bool {
+        self.locked
+    }
+
+    // The 57 kHz RDS carrier for the sample last passed to process(), in
+    // phase with the third harmonic of the tracked pilot: the same
+    // relationship the internal pilot and carrier tables use.
+    pub fn rds_carrier(&self) -> f32 {
+        self.carrier
+    }
+
+    pub fn status(&self) -> PilotLockStatus {
+        PilotLockStatus {
+            locked: self.locked,
+            level: self.in_phase.max(0.0),
+            freq_hz: (self.nominal_step + self.freq_offset) * self.sample_rate / (2.0 * PI),
+        }
+    }
+}
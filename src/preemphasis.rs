@@ -0,0 +1,126 @@
+use std::f64::consts::PI;
+
+// The ideal 1 + jwt curve keeps rising; a pole well above the audio band
+// keeps the digital filter bounded while staying within 0.03 dB of the
+// analytic curve up to 15 kHz at 228 kHz.
+pub const SHELF_POLE_HZ: f64 = 100_000.0;
+
+// First-order shelf from (1 + s*t1) / (1 + s*t2) through the bilinear
+// transform, prewarped at the 1/t1 corner.
+#[derive(Clone, Copy, Debug)]
+pub struct ShelvingFilter {
+    b0: f32,
+    b1: f32,
+    a1: f32,
+    x1: f32,
+    y1: f32,
+}
+
+impl ShelvingFilter {
+    pub fn preemphasis(tau: f32, sample_rate: u32) -> Self {
+        let (zero, pole) = time_constants(tau);
+        Self::design(zero, pole, prewarp(tau, sample_rate))
+    }
+
+    // Exact inverse of the matching pre-emphasis, so the pair cancels.
+    pub fn deemphasis(tau: f32, sample_rate: u32) -> Self {
+        let (zero, pole) = time_constants(tau);
+        Self::design(pole, zero, prewarp(tau, sample_rate))
+    }
+
+    fn design(t_zero: f64, t_pole: f64, k: f64) -> Self {
+        let a0 = 1.0 + k * t_pole;
+        ShelvingFilter {
+            b0: ((1.0 + k * t_zero) / a0) as f32,
+            b1: ((1.0 - k * t_zero) / a0) as f32,
+            a1: ((1.0 - k * t_pole) / a0) as f32,
+            x1: 0.0,
+            y1: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 - self.a1 * self.y1;
+        self.x1 = x;
+        self.y1 = y;
+        y
+    }
+
+    pub fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.y1 = 0.0;
+    }
+
+    pub fn response_db(&self, freq: f32, sample_rate: u32) -> f32 {
+        let w = 2.0 * PI * freq as f64 / sample_rate as f64;
+        let (c, s) = (w.cos(), -w.sin());
+        let num = ((self.b0 as f64 + self.b1 as f64 * c).powi(2) + (self.b1 as f64 * s).powi(2)).sqrt();
+        let den = ((1.0 + self.a1 as f64 * c).powi(2) + (self.a1 as f64 * s).powi(2)).sqrt();
+        (20.0 * (num / den).log10()) as f32
+    }
+}
+
+fn time_constants(tau: f32) -> (f64, f64) {
+    (tau as f64, 1.0 / (2.0 * PI * SHELF_POLE_HZ))
+}
+
+fn prewarp(tau: f32, sample_rate: u32) -> f64 {
+    let w = 1.0 / tau as f64;
+    w / (w / (2.0 * sample_rate as f64)).tan()
+}
+
+// Analytic 50/75 us pre-emphasis response, 10*log10(1 + (w*tau)^2).
+pub fn analog_response_db(tau: f32, freq: f32) -> f32 {
+    let wt = 2.0 * PI * freq as f64 * tau as f64;
+    (10.0 * (1.0 + wt * wt).log10()) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MPX_RATE: u32 = 228_000;
+
+    // 30 Hz to 15 kHz, a few points per octave.
+    fn audio_band() -> impl Iterator<Item = f32> {
+        (0..=36).map(|i| 30.0 * 2f32.powf(i as f32 / 4.0)).filter(|&f| f <= 15_000.0).chain([15_000.0])
+    }
+
+    fn assert_tracks_analog(tau: f32) {
+        let filter = ShelvingFilter::preemphasis(tau, MPX_RATE);
+        for freq in audio_band() {
+            let digital = filter.response_db(freq, MPX_RATE);
+            let analog = analog_response_db(tau, freq);
+            assert!(
+                (digital - analog).abs() <= 0.05,
+                "{} us at {:.0} Hz: {:.3} dB, expected {:.3} dB",
+                tau * 1e6,
+                freq,
+                digital,
+                analog
+            );
+        }
+    }
+
+    #[test]
+    fn preemphasis_50us_tracks_the_analog_curve() {
+        assert_tracks_analog(50e-6);
+    }
+
+    #[test]
+    fn preemphasis_75us_tracks_the_analog_curve() {
+        assert_tracks_analog(75e-6);
+    }
+
+    #[test]
+    fn deemphasis_cancels_preemphasis() {
+        for tau in [50e-6, 75e-6] {
+            let pre = ShelvingFilter::preemphasis(tau, MPX_RATE);
+            let de = ShelvingFilter::deemphasis(tau, MPX_RATE);
+            for freq in audio_band() {
+                let sum = pre.response_db(freq, MPX_RATE) + de.response_db(freq, MPX_RATE);
+                assert!(sum.abs() <= 0.01, "{} us at {:.0} Hz: {:.3} dB", tau * 1e6, freq, sum);
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RemoteKind {
+    // GET pulls the presets JSON, PUT pushes it.
+    Http,
+    // A local clone; the presets file sits at its root and is pulled and
+    // pushed with the system git.
+    Git,
+}
+
+impl fmt::Display for RemoteKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            RemoteKind::Http => "HTTP URL",
+            RemoteKind::Git => "Git clone",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// Where the shared presets live, so operator laptops and the transmitter
+// site run identical station settings.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PresetRemote {
+    pub kind: RemoteKind,
+    // The URL, or the path of the git clone.
+    pub location: String,
+    // Sent as a bearer token over HTTP.
+    #[serde(default)]
+    pub token: String,
+}
+
+impl Default for PresetRemote {
+    fn default() -> Self {
+        PresetRemote {
+            kind: RemoteKind::Http,
+            location: String::new(),
+            token: String::new(),
+        }
+    }
+}
+
+// Returns the remote presets JSON; the caller checks it before use.
+pub fn pull(remote: &PresetRemote, file_name: &str) -> Result<String> {
+    let location = remote.location.trim();
+    if location.is_empty() {
+        return Err(anyhow!("no remote configured"));
+    }
+    match remote.kind {
+        RemoteKind::Http => {
+            let mut request = ureq::get(location).timeout(HTTP_TIMEOUT);
+            if !remote.token.trim().is_empty() {
+                request = request.set("Authorization", &format!("Bearer {}", remote.token.trim()));
+            }
+            request.call().map_err(|e| anyhow!("{}", e))?.into_string().map_err(Into::into)
+        }
+        RemoteKind::Git => {
+            git(location, &["pull", "--ff-only"])?;
+            Ok(fs::read_to_string(Path::new(location).join(file_name))?)
+        }
+    }
+}
+
+pub fn push(remote: &PresetRemote, file_name: &str, json: &str) -> Result<()> {
+    let location = remote.location.trim();
+    if location.is_empty() {
+        return Err(anyhow!("no remote configured"));
+    }
+    match remote.kind {
+        RemoteKind::Http => {
+            let mut request = ureq::put(location)
+                .timeout(HTTP_TIMEOUT)
+                .set("Content-Type", "application/json");
+            if !remote.token.trim().is_empty() {
+                request = request.set("Authorization", &format!("Bearer {}", remote.token.trim()));
+            }
+            request.send_string(json).map_err(|e| anyhow!("{}", e))?;
+            Ok(())
+        }
+        RemoteKind::Git => {
+            // Pull first so the push is a fast-forward of what others shared.
+            git(location, &["pull", "--ff-only"])?;
+            fs::write(Path::new(location).join(file_name), json)?;
+            git(location, &["add", file_name])?;
+            if git(location, &["diff", "--cached", "--quiet"]).is_ok() {
+                return Ok(());
+            }
+            git(location, &["commit", "-m", "Update station presets"])?;
+            git(location, &["push"])?;
+            Ok(())
+        }
+    }
+}
+
+fn git(dir: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(anyhow!("git {}: {}", args[0], stderr.trim()))
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessingPreset {
+    Transparent,
+    LoudPop,
+    Talk,
+    Classical,
+}
+
+// Compressor, limiter and pre-emphasis settings loaded by a processing
+// preset. Makeup gain follows the compressor and brings the reduced level
+// back up, so heavier compression comes out louder rather than quieter.
+// Pre-emphasis is only switched on or off; the 50/75 us time constant is a
+// regional setting and stays as configured.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProcessingSettings {
+    pub compressor_enabled: bool,
+    pub comp_threshold_db: f32,
+    pub comp_ratio: f32,
+    pub comp_attack: f32,
+    pub comp_release: f32,
+    pub comp_makeup_db: f32,
+    pub limiter_enabled: bool,
+    pub limiter_threshold_db: f32,
+    pub limiter_lookahead_ms: f32,
+    pub preemphasis: bool,
+}
+
+impl ProcessingPreset {
+    pub const ALL: [ProcessingPreset; 4] = [
+        ProcessingPreset::Transparent,
+        ProcessingPreset::LoudPop,
+        ProcessingPreset::Talk,
+        ProcessingPreset::Classical,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        let key: String = name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        ProcessingPreset::ALL
+            .iter()
+            .copied()
+            .find(|p| p.to_string().replace(' ', "").to_ascii_lowercase() == key)
+    }
+
+    pub fn settings(self) -> ProcessingSettings {
+        match self {
+            ProcessingPreset::Transparent => ProcessingSettings {
+                compressor_enabled: false,
+                comp_threshold_db: -18.0,
+                comp_ratio: 3.0,
+                comp_attack: 0.01,
+                comp_release: 0.2,
+                comp_makeup_db: 0.0,
+                limiter_enabled: true,
+                limiter_threshold_db: -0.2,
+                limiter_lookahead_ms: 2.0,
+                preemphasis: true,
+            },
+            ProcessingPreset::LoudPop => ProcessingSettings {
+                compressor_enabled: true,
+                comp_threshold_db: -24.0,
+                comp_ratio: 5.0,
+                comp_attack: 0.003,
+                comp_release: 0.1,
+                comp_makeup_db: 18.0,
+                limiter_enabled: true,
+                limiter_threshold_db: -1.0,
+                limiter_lookahead_ms: 1.0,
+                preemphasis: true,
+            },
+            ProcessingPreset::Talk => ProcessingSettings {
+                compressor_enabled: true,
+                comp_threshold_db: -20.0,
+                comp_ratio: 4.0,
+                comp_attack: 0.005,
+                comp_release: 0.3,
+                comp_makeup_db: 8.0,
+                limiter_enabled: true,
+                limiter_threshold_db: -0.5,
+                limiter_lookahead_ms: 2.0,
+                preemphasis: true,
+            },
+            ProcessingPreset::Classical => ProcessingSettings {
+                compressor_enabled: true,
+                comp_threshold_db: -12.0,
+                comp_ratio: 1.5,
+                comp_attack: 0.05,
+                comp_release: 1.0,
+                comp_makeup_db: 2.0,
+                limiter_enabled: true,
+                limiter_threshold_db: -0.2,
+                limiter_lookahead_ms: 5.0,
+                preemphasis: true,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ProcessingPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ProcessingPreset::Transparent => "Transparent",
+            ProcessingPreset::LoudPop => "Loud Pop",
+            ProcessingPreset::Talk => "Talk",
+            ProcessingPreset::Classical => "Classical",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::AudioSource;
+    use crate::fm_mpx::FmMpx;
+    use crate::null_test::ProgramStats;
+
+    const RATE: u32 = 48_000;
+    const MPX_RATE: usize = 228_000;
+
+    // Two seconds of program alternating between a loud and a quiet
+    // passage, processed with the preset and brought back to 48 kHz.
+    fn processed_loudness(preset: ProcessingPreset) -> f32 {
+        let samples = (0..2 * RATE as usize)
+            .flat_map(|n| {
+                let t = n as f32 / RATE as f32;
+                let level = if (n / 12_000) % 2 == 0 { 0.5 } else { 0.05 };
+                let x = level * (2.0 * std::f32::consts::PI * 440.0 * t).sin();
+                [x, x]
+            })
+            .collect();
+        let mut mpx = FmMpx::new(Some(AudioSource { samples, channels: 2, sample_rate: RATE }));
+        let s = preset.settings();
+        mpx.set_preemphasis(s.preemphasis.then_some(50e-6));
+        mpx.set_compressor(s.compressor_enabled, s.comp_threshold_db, s.comp_ratio, s.comp_attack, s.comp_release, s.comp_makeup_db);
+
+        let len = 2 * MPX_RATE;
+        let mut buffer = vec![0.0f32; len];
+        let mut program = vec![[0.0f32; 2]; len];
+        let (audio, encoder) = mpx.program().unwrap();
+        encoder.add_samples_at(audio, 0, &mut buffer, Some(&mut program));
+
+        let step = MPX_RATE as f64 / RATE as f64;
+        let samples = (0..(len as f64 / step) as usize)
+            .flat_map(|n| program[(n as f64 * step) as usize])
+            .collect();
+        ProgramStats::measure(&AudioSource { samples, channels: 2, sample_rate: RATE }).unwrap().loudness_lufs
+    }
+
+    #[test]
+    fn loud_pop_comes_out_louder_than_transparent() {
+        let transparent = processed_loudness(ProcessingPreset::Transparent);
+        let loud_pop = processed_loudness(ProcessingPreset::LoudPop);
+        assert!(loud_pop > transparent, "loud pop {} LUFS, transparent {} LUFS", loud_pop, transparent);
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use anyhow::Result;
+use hound::{SampleFormat, WavSpec, WavWriter};
+
+use crate::preemphasis::ShelvingFilter;
+
+pub const PROGRAM_SAMPLE_RATE: u32 = 48_000;
+
+// Second output of an MPX export: the processed stereo program that went into
+// the composite, e.g. for the digital side of a hybrid (HD Radio style)
+// station, so both feeds come from identical processing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgramFeed {
+    pub path: String,
+    // How far the program file lags the MPX.
+    pub delay_ms: f32,
+}
+
+// Takes the program at the MPX rate, undoes the pre-emphasis, resamples to
+// 48 kHz and writes a float stereo WAV behind `delay_ms` of silence, cut to
+// the length of the MPX. The program is band-limited well below 24 kHz by
+// the encoder's low-pass, so linear interpolation is enough.
+pub struct ProgramFeedWriter {
+    writer: WavWriter<BufWriter<File>>,
+    deemphasis: Option<[ShelvingFilter; 2]>,
+    step: f64,
+    phase: f64,
+    prev: [f32; 2],
+    remaining: usize,
+}
+
+impl ProgramFeedWriter {
+    pub fn create(
+        feed: &ProgramFeed,
+        preemphasis_tau: Option<f32>,
+        mpx_rate: u32,
+        duration_secs: f32,
+    ) -> Result<Self> {
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: PROGRAM_SAMPLE_RATE,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+        };
+        let mut writer = WavWriter::create(Path::new(&feed.path), spec)?;
+        let total = (duration_secs * PROGRAM_SAMPLE_RATE as f32) as usize;
+        let delay = ((feed.delay_ms.max(0.0) / 1000.0 * PROGRAM_SAMPLE_RATE as f32) as usize).min(total);
+        for _ in 0..delay * 2 {
+            writer.write_sample(0.0f32)?;
+        }
+        Ok(ProgramFeedWriter {
+            writer,
+            deemphasis: preemphasis_tau.map(|tau| [ShelvingFilter::deemphasis(tau, mpx_rate); 2]),
+            step: mpx_rate as f64 / PROGRAM_SAMPLE_RATE as f64,
+            phase: 0.0,
+            prev: [0.0; 2],
+            remaining: total - delay,
+        })
+    }
+
+    pub fn push(&mut self, frame: [f32; 2]) -> Result<()> {
+        let mut frame = frame;
+        if let Some(deemphasis) = self.deemphasis.as_mut() {
+            for (x, filter) in frame.iter_mut().zip(deemphasis.iter_mut()) {
+                *x = filter.process(*x);
+            }
+        }
+        while self.phase < 1.0 && self.remaining > 0 {
+            let t = self.phase as f32;
+            for (prev, next) in self.prev.iter().zip(frame) {
+                self.writer.write_sample(prev + (next - prev) * t)?;
+            }
+            self.remaining -= 1;
+            self.phase += self.step;
+        }
+        self.phase -= 1.0;
+        self.prev = frame;
+        Ok(())
+    }
+
+    pub fn finalize(mut self) -> Result<()> {
+        // A render shorter than the rounding leaves at most a frame or two.
+        for _ in 0..self.remaining * 2 {
+            self.writer.write_sample(0.0f32)?;
+        }
+        self.writer.finalize()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use crate::rds::{ps_pages, PsScrollMode, MIN_PS_DWELL_SECS};
+
+const PS_LENGTH: usize = 8;
+const PS_SEGMENTS: usize = 4;
+// A name on screen for less than this is hard to read; the same limit the
+// rate guard holds PS changes to.
+pub const READABLE_SECS: f32 = MIN_PS_DWELL_SECS;
+
+// A PS going on air, in seconds relative to the start of the window (times
+// before the window give what was already on air).
+#[derive(Clone, Debug, PartialEq)]
+pub struct PsChange {
+    pub at_secs: f32,
+    pub text: String,
+}
+
+// A name as the receiver displayed it, clipped to the window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShownPs {
+    pub start_secs: f32,
+    pub secs: f32,
+    pub text: String,
+}
+
+// What a typical receiver displays for a run of PS changes. The four
+// two-character segments arrive in turn, one per 0A group, and the display
+// only changes once the last four segments all belong to the same name, so
+// a name that is replaced before it has been sent whole is never seen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReceiverTimeline {
+    pub window_secs: f32,
+    pub segments_per_sec: f32,
+    pub shown: Vec<ShownPs>,
+    // When each change inside the window went on air.
+    pub sent_at: Vec<f32>,
+    // Of those, the ones no receiver displayed.
+    pub never_shown: usize,
+}
+
+impl ReceiverTimeline {
+    pub fn simulate(changes: &[PsChange], segments_per_sec: f32, window_secs: f32) -> Self {
+        let mut timeline = ReceiverTimeline {
+            window_secs,
+            segments_per_sec,
+            shown: Vec::new(),
+            sent_at: changes
+                .iter()
+                .map(|c| c.at_secs)
+                .filter(|&t| t >= 0.0 && t < window_secs)
+                .collect(),
+            never_shown: 0,
+        };
+        let Some(first) = changes.first() else {
+            return timeline;
+        };
+        if segments_per_sec <= 0.0 {
+            timeline.never_shown = timeline.sent_at.len();
+            return timeline;
+        }
+
+        let step = 1.0 / segments_per_sec;
+        let mut k = (first.at_secs / step).ceil() as i64;
+        let mut current = 0usize;
+        // Change index carried by each of the last four segments.
+        let mut recent = [None::<usize>; PS_SEGMENTS];
+        let mut displayed = None::<usize>;
+        let mut displayed_ever = vec![false; changes.len()];
+        loop {
+            let t = k as f32 * step;
+            if t >= window_secs {
+                break;
+            }
+            while current + 1 < changes.len() && changes[current + 1].at_secs <= t {
+                current += 1;
+            }
+            recent[k.rem_euclid(PS_SEGMENTS as i64) as usize] = Some(current);
+            if recent.iter().all(|&r| r == Some(current)) && displayed != Some(current) {
+                displayed = Some(current);
+                displayed_ever[current] = true;
+                timeline.show(&changes[current].text, t);
+            }
+            k += 1;
+        }
+        timeline.close();
+        timeline.never_shown = changes
+            .iter()
+            .zip(&displayed_ever)
+            .filter(|(c, &shown)| c.at_secs >= 0.0 && c.at_secs < window_secs && !shown)
+            .count();
+        timeline
+    }
+
+    fn show(&mut self, text: &str, at_secs: f32) {
+        self.close_at(at_secs);
+        self.shown.push(ShownPs {
+            start_secs: at_secs.max(0.0),
+            secs: 0.0,
+            text: text.to_string(),
+        });
+    }
+
+    fn close_at(&mut self, at_secs: f32) {
+        if let Some(last) = self.shown.last_mut() {
+            last.secs = (at_secs.min(self.window_secs) - last.start_secs).max(0.0);
+        }
+    }
+
+    fn close(&mut self) {
+        self.close_at(self.window_secs);
+        // Names replaced before the window started leave nothing to show.
+        self.shown.retain(|s| s.secs > 0.0 || s.start_secs > 0.0);
+    }
+
+    // Names shown and replaced inside the window; the first and last are cut
+    // off by its edges, so their times say nothing.
+    fn complete(&self) -> impl Iterator<Item = &ShownPs> {
+        let done = self.shown.len().saturating_sub(1);
+        self.shown[..done].iter().filter(|s| s.start_secs > 0.0)
+    }
+
+    // Display times snap to segment arrivals, so allow half a segment.
+    pub fn readable(&self, shown: &ShownPs) -> bool {
+        shown.secs + 0.5 / self.segments_per_sec.max(0.1) >= READABLE_SECS
+    }
+
+    pub fn shortest_secs(&self) -> Option<f32> {
+        self.complete().map(|s| s.secs).reduce(f32::min)
+    }
+
+    pub fn unreadable(&self) -> usize {
+        self.complete().filter(|s| !self.readable(s)).count()
+    }
+
+    pub fn summary(&self) -> String {
+        let shortest = self
+            .shortest_secs()
+            .map(|s| format!(", shortest {:.1} s on screen", s))
+            .unwrap_or_default();
+        format!(
+            "{} names sent, {} displayed, {} never complete{}",
+            self.sent_at.len(),
+            self.shown.len(),
+            self.never_shown,
+            shortest
+        )
+    }
+
+    // Empty when a listener could follow the names.
+    pub fn warning(&self) -> String {
+        if self.never_shown > 0 {
+            format!(
+                "{} of {} names changed before a receiver had all four segments; scroll slower or send more 0A groups",
+                self.never_shown,
+                self.sent_at.len()
+            )
+        } else if self.unreadable() > 0 {
+            format!(
+                "{} names stayed up less than {:.1} s, too short to read",
+                self.unreadable(),
+                READABLE_SECS
+            )
+        } else {
+            String::new()
+        }
+    }
+}
+
+// The PS changes a scroll produces over `window_secs`, for a view before
+// streaming; empty for no scroll text. `min_dwell_secs` is the rate guard's
+// limit when it is on.
+pub fn predicted_changes(
+    scroll: &str,
+    mode: PsScrollMode,
+    chars_per_sec: f32,
+    page_dwell_secs: f32,
+    center: bool,
+    min_dwell_secs: Option<f32>,
+    window_secs: f32,
+) -> Vec<PsChange> {
+    if scroll.trim().is_empty() {
+        return Vec::new();
+    }
+    let frames = match mode {
+        PsScrollMode::Paging => ps_pages(scroll, center),
+        PsScrollMode::Character => {
+            let padded = format!("{}   ", scroll).chars().collect::<Vec<_>>();
+            (0..padded.len())
+                .map(|pos| (0..PS_LENGTH).map(|i| padded[(pos + i) % padded.len()]).collect())
+                .collect()
+        }
+    };
+    if frames.is_empty() {
+        return Vec::new();
+    }
+    let interval = match mode {
+        PsScrollMode::Paging => page_dwell_secs.max(0.1),
+        PsScrollMode::Character => 1.0 / chars_per_sec.max(0.1),
+    };
+    let interval = interval.max(min_dwell_secs.unwrap_or(0.0));
+    // Start a little before the window so the receiver has settled.
+    let lead = (PS_SEGMENTS as f32 * 2.0).max(interval);
+    let count = ((window_secs + lead) / interval).ceil() as usize;
+    (0..count)
+        .map(|i| PsChange {
+            at_secs: i as f32 * interval - lead,
+            text: frames[i % frames.len()].clone(),
+        })
+        .collect()
+}
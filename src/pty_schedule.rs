@@ -0,0 +1,112 @@
+use std::fmt;
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+
+use crate::automation::parse_time;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PtyDays {
+    Daily,
+    Weekdays,
+    Weekends,
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl PtyDays {
+    pub const ALL: [PtyDays; 10] = [
+        PtyDays::Daily,
+        PtyDays::Weekdays,
+        PtyDays::Weekends,
+        PtyDays::Mon,
+        PtyDays::Tue,
+        PtyDays::Wed,
+        PtyDays::Thu,
+        PtyDays::Fri,
+        PtyDays::Sat,
+        PtyDays::Sun,
+    ];
+
+    pub fn includes(self, day: Weekday) -> bool {
+        let weekend = matches!(day, Weekday::Sat | Weekday::Sun);
+        match self {
+            PtyDays::Daily => true,
+            PtyDays::Weekdays => !weekend,
+            PtyDays::Weekends => weekend,
+            PtyDays::Mon => day == Weekday::Mon,
+            PtyDays::Tue => day == Weekday::Tue,
+            PtyDays::Wed => day == Weekday::Wed,
+            PtyDays::Thu => day == Weekday::Thu,
+            PtyDays::Fri => day == Weekday::Fri,
+            PtyDays::Sat => day == Weekday::Sat,
+            PtyDays::Sun => day == Weekday::Sun,
+        }
+    }
+}
+
+impl fmt::Display for PtyDays {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            PtyDays::Daily => "Every day",
+            PtyDays::Weekdays => "Mon-Fri",
+            PtyDays::Weekends => "Sat-Sun",
+            PtyDays::Mon => "Monday",
+            PtyDays::Tue => "Tuesday",
+            PtyDays::Wed => "Wednesday",
+            PtyDays::Thu => "Thursday",
+            PtyDays::Fri => "Friday",
+            PtyDays::Sat => "Saturday",
+            PtyDays::Sun => "Sunday",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// A programme type on air between two local times, e.g. Sport (4) on
+// weekends 14:00-18:00. With `every_hour` only the minutes count, so
+// 00:00-00:05 is the first five minutes of every hour (News, 1).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PtyRule {
+    pub days: PtyDays,
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub every_hour: bool,
+    pub pty: u8,
+}
+
+impl PtyRule {
+    pub fn is_active(&self, now: NaiveDateTime) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if !self.days.includes(now.weekday()) {
+            return false;
+        }
+        let (start, end, time) = if self.every_hour {
+            (within_hour(start), within_hour(end), within_hour(now.time()))
+        } else {
+            (start, end, now.time())
+        };
+        if start <= end {
+            time >= start && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+fn within_hour(time: NaiveTime) -> NaiveTime {
+    NaiveTime::from_hms_opt(0, time.minute(), time.second()).unwrap_or_default()
+}
+
+// The first active rule wins; None leaves the station PTY on air.
+pub fn scheduled_pty(rules: &[PtyRule], now: NaiveDateTime) -> Option<u8> {
+    rules.iter().find(|rule| rule.is_active(now)).map(|rule| rule.pty)
+}
@@ -1,7 +1,11 @@
-use chrono::{Datelike, Timelike, Offset};
+use chrono::{DateTime, Datelike, Local, Timelike, Offset};
 use chrono::NaiveDate;
+use std::collections::VecDeque;
 
-use crate::rds_strings::fill_rds_string;
+use crate::rds_history::{RdsChangeSource, RdsField, RdsHistoryEntry};
+use crate::rds_schedule::GroupRecord;
+use crate::rds_strings::{fill_rds_string_with_table, is_mappable, RdsCodeTable};
+use crate::rds_translit::TranslitTable;
 use crate::waveform::waveform_biphase;
 
 const RT_LENGTH: usize = 64;
@@ -17,6 +21,144 @@ const BITS_PER_GROUP: usize = GROUP_LENGTH * (BLOCK_SIZE + POLY_DEG);
 const SAMPLES_PER_BIT: usize = 192;
 
 const OFFSET_WORDS: [u16; 4] = [0x0FC, 0x198, 0x168, 0x1B4];
+// Offset C' replaces C in version B groups, whose block C repeats the PI.
+const OFFSET_C_PRIME: u16 = 0x350;
+
+const HISTORY_CAPACITY: usize = 4096;
+// One 1A slow labelling group roughly every two seconds.
+const SLOW_LABEL_INTERVAL_GROUPS: usize = 23;
+
+const GROUPS_PER_SEC: f32 = 1187.5 / 104.0;
+const RDS_SAMPLE_RATE: f32 = 228000.0;
+
+pub const MIN_PS_DWELL_SECS: f32 = 1.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PsScrollMode {
+    Character,
+    Paging,
+}
+
+impl std::fmt::Display for PsScrollMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PsScrollMode::Character => write!(f, "Character scroll"),
+            PsScrollMode::Paging => write!(f, "Word pages"),
+        }
+    }
+}
+
+// What happens to RT longer than the 64 characters a 2A message holds
+// (fewer with an E.2/E.3 designator). The length is counted after
+// transliteration, so one character is one RDS byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RtOverflow {
+    Cut,
+    Ellipsis,
+    Rotate,
+}
+
+impl std::fmt::Display for RtOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RtOverflow::Cut => write!(f, "Hard cut"),
+            RtOverflow::Ellipsis => write!(f, "Word + ellipsis"),
+            RtOverflow::Rotate => write!(f, "Two parts"),
+        }
+    }
+}
+
+// Group 15B repeats the 0A tuning flags (TP, PTY, TA, MS, DI) without PS.
+// One follows every 0A group, doubling their refresh rate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FastTuning {
+    Off,
+    WhileTa,
+    Always,
+}
+
+impl std::fmt::Display for FastTuning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FastTuning::Off => write!(f, "Off"),
+            FastTuning::WhileTa => write!(f, "While TA"),
+            FastTuning::Always => write!(f, "Always"),
+        }
+    }
+}
+
+pub const DEFAULT_RT_ELLIPSIS: &str = "...";
+pub const DEFAULT_RT_PART_SECS: f32 = 8.0;
+
+// Longest head of `text` that fits in `max` characters without splitting a
+// word (a single word longer than `max` is cut), and the rest.
+fn split_at_word(text: &str, max: usize) -> (String, String) {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= max {
+        return (text.trim_end().to_string(), String::new());
+    }
+    let end = if chars[max].is_whitespace() {
+        max
+    } else {
+        chars[..max].iter().rposition(|c| c.is_whitespace()).filter(|&i| i > 0).unwrap_or(max)
+    };
+    let head: String = chars[..end].iter().collect();
+    let tail: String = chars[end..].iter().collect();
+    (head.trim_end().to_string(), tail.trim_start().to_string())
+}
+
+// The RT messages to send for `text` with `capacity` characters per
+// message: one, or two alternating parts for `RtOverflow::Rotate`.
+pub fn rt_parts(text: &str, capacity: usize, mode: RtOverflow, ellipsis: &str) -> Vec<String> {
+    let text = text.trim_end();
+    let e = ellipsis.chars().count();
+    if text.chars().count() <= capacity || mode == RtOverflow::Cut || capacity <= 2 * e {
+        return vec![text.to_string()];
+    }
+    let (head, tail) = split_at_word(text, capacity - e);
+    if mode == RtOverflow::Ellipsis {
+        return vec![format!("{}{}", head, ellipsis)];
+    }
+    let (second, rest) = split_at_word(&tail, capacity - e);
+    if rest.is_empty() {
+        return vec![format!("{}{}", head, ellipsis), format!("{}{}", ellipsis, second)];
+    }
+    let (second, _) = split_at_word(&tail, capacity - 2 * e);
+    vec![format!("{}{}", head, ellipsis), format!("{}{}{}", ellipsis, second, ellipsis)]
+}
+
+/// Splits `text` into 8-character PS pages on word boundaries. Words longer
+/// than a page are broken into page-sized chunks.
+pub fn ps_pages(text: &str, center: bool) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let chars: Vec<char> = word.chars().collect();
+        for chunk in chars.chunks(PS_LENGTH) {
+            let chunk: String = chunk.iter().collect();
+            let current_len = current.chars().count();
+            if current_len == 0 {
+                current = chunk;
+            } else if current_len + 1 + chunk.chars().count() <= PS_LENGTH {
+                current.push(' ');
+                current.push_str(&chunk);
+            } else {
+                lines.push(std::mem::replace(&mut current, chunk));
+            }
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    if center {
+        for line in lines.iter_mut() {
+            let pad = (PS_LENGTH - line.chars().count()) / 2;
+            *line = format!("{}{}", " ".repeat(pad), line);
+        }
+    }
+    lines
+}
 
 #[derive(Clone)]
 pub struct RdsParams {
@@ -46,7 +188,7 @@ impl Default for RdsParams {
             ab: false,
             ab_auto: true,
             ct_enabled: true,
-            af_stream: Vec::new(),
+            af_stream: vec![AF_NONE_CODE, AF_FILLER_CODE],
             ps: [0x20; PS_LENGTH],
             rt: [0x20; RT_LENGTH],
         };
@@ -54,6 +196,186 @@ impl Default for RdsParams {
     }
 }
 
+pub const AF_MAX: usize = 25;
+// Pads the last pair of a method A list with an odd number of bytes.
+pub const AF_FILLER_CODE: u8 = 205;
+// "No AF exists": a count byte of zero, sent with a filler.
+pub const AF_NONE_CODE: u8 = 224;
+// A method B count byte covers the tuned frequency plus both codes of each
+// pair (2N + 1), which tops out at 12 pairs.
+pub const AF_METHOD_B_PAIRS: usize = 12;
+
+pub fn af_code(mhz: f32) -> Option<u8> {
+    if !(87.6..=107.9).contains(&mhz) {
+        return None;
+    }
+    let code = ((mhz - 87.6) * 10.0).round() as i32 + 1;
+    if (1..=204).contains(&code) {
+        Some(code as u8)
+    } else {
+        None
+    }
+}
+
+// Codes 1-204 are 87.6-107.9 MHz in 100 kHz steps, e.g. 87.6 -> 1,
+// 98.0 -> 105, 107.9 -> 204. Method A lists the AF codes after the count
+// byte and pads an odd byte count with the filler: {98.0, 99.5} is
+// E2 69 | 78 CD. An empty list is sent as E0 CD. When any entry is a
+// regional variant, method B is used instead: every pair carries the tuned
+// frequency, ascending for the same programme and descending for a regional
+// variant, after the count byte 224 + 2N + 1 and the tuned frequency.
+pub fn af_code_stream(freqs: &[f32], regional: &[f32], tuned_mhz: Option<f32>) -> Vec<u8> {
+    let regional_codes: Vec<u8> = regional.iter().filter_map(|&f| af_code(f)).collect();
+    let mut codes: Vec<u8> = freqs
+        .iter()
+        .chain(regional.iter())
+        .filter_map(|&f| af_code(f))
+        .collect();
+    codes.sort();
+    codes.dedup();
+
+    let tuned = tuned_mhz.and_then(af_code);
+    if let (Some(tuned), false) = (tuned, regional_codes.is_empty()) {
+        codes.retain(|&c| c != tuned);
+        codes.truncate(AF_METHOD_B_PAIRS);
+        let mut stream = Vec::with_capacity(codes.len() * 2 + 2);
+        stream.push(AF_NONE_CODE + (2 * codes.len() + 1) as u8);
+        stream.push(tuned);
+        for code in codes {
+            let ascending = !regional_codes.contains(&code);
+            let (lo, hi) = if tuned < code { (tuned, code) } else { (code, tuned) };
+            if ascending {
+                stream.push(lo);
+                stream.push(hi);
+            } else {
+                stream.push(hi);
+                stream.push(lo);
+            }
+        }
+        return stream;
+    }
+
+    if codes.is_empty() {
+        return vec![AF_NONE_CODE, AF_FILLER_CODE];
+    }
+    let count = codes.len().min(AF_MAX);
+    let mut stream = Vec::with_capacity(count + 2);
+    stream.push(AF_NONE_CODE + count as u8);
+    stream.extend(codes.into_iter().take(count));
+    if stream.len() % 2 != 0 {
+        stream.push(AF_FILLER_CODE);
+    }
+    stream
+}
+
+// Method B pairs every entry with the tuned frequency; regional variants
+// without a valid one would go out as a plain method A list, which has no
+// way to mark them regional.
+pub fn af_tuned_missing(regional: &[f32], tuned_mhz: Option<f32>) -> bool {
+    !regional.is_empty() && tuned_mhz.and_then(af_code).is_none()
+}
+
+pub fn group_cycle_for_mix(count_0a: usize, count_2a: usize, count_4a: usize) -> Vec<u8> {
+    let mut cycle = vec![0; count_0a.max(1)];
+    cycle.extend(std::iter::repeat_n(2, count_2a.max(1)));
+    cycle.extend(std::iter::repeat_n(4, count_4a));
+    cycle
+}
+
+fn group_share(cycle: &[u8], group_type: u8) -> f32 {
+    if cycle.is_empty() {
+        return 0.0;
+    }
+    let count = cycle.iter().filter(|&&g| g == group_type).count();
+    count as f32 / cycle.len() as f32
+}
+
+// Minimum PS dwell and RT change interval (seconds) for a group cycle: long
+// enough for a receiver to collect every PS/RT segment at least once.
+pub fn rate_limits_for_cycle(cycle: &[u8]) -> (f32, f32) {
+    let ps_per_sec = ps_segments_per_sec(cycle);
+    let rt_per_sec = GROUPS_PER_SEC * group_share(cycle, 2);
+    let ps_dwell = if ps_per_sec > 0.0 {
+        (4.0 / ps_per_sec).max(MIN_PS_DWELL_SECS)
+    } else {
+        MIN_PS_DWELL_SECS
+    };
+    let rt_interval = if rt_per_sec > 0.0 { 16.0 / rt_per_sec } else { 0.0 };
+    (ps_dwell, rt_interval)
+}
+
+// 0A groups, and so PS segments, sent per second for a group cycle.
+pub fn ps_segments_per_sec(cycle: &[u8]) -> f32 {
+    GROUPS_PER_SEC * group_share(cycle, 0)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GroupStats {
+    counts: [u64; 32],
+}
+
+impl GroupStats {
+    fn record(&mut self, block_b: u16) {
+        self.counts[(block_b >> 11) as usize] += 1;
+    }
+
+    pub fn predicted(cycle: &[u8], secs: f32) -> Self {
+        let mut stats = GroupStats::default();
+        for group_type in [0u8, 2, 4] {
+            let count = GROUPS_PER_SEC * secs * group_share(cycle, group_type);
+            stats.counts[(group_type as usize) << 1] = count.round() as u64;
+        }
+        stats
+    }
+
+    pub fn since(&self, earlier: &GroupStats) -> GroupStats {
+        let mut out = GroupStats::default();
+        for i in 0..out.counts.len() {
+            out.counts[i] = self.counts[i].saturating_sub(earlier.counts[i]);
+        }
+        out
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn count(&self, group_type: u8, version_b: bool) -> u64 {
+        self.counts[((group_type as usize & 0x0F) << 1) | version_b as usize]
+    }
+
+    pub fn entries(&self) -> Vec<(String, u64)> {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(i, &count)| (format!("{}{}", i >> 1, if i & 1 == 0 { 'A' } else { 'B' }), count))
+            .collect()
+    }
+
+    // Time for a receiver to see every PS (4 x 0A/0B) and RT (16 x 2A)
+    // segment once, given the counts were collected over `secs` seconds.
+    pub fn refresh_times(&self, secs: f32) -> (Option<f32>, Option<f32>) {
+        if secs <= 0.0 {
+            return (None, None);
+        }
+        let ps_rate = (self.count(0, false) + self.count(0, true)) as f32 / secs;
+        let rt_rate = self.count(2, false) as f32 / secs;
+        let ps = if ps_rate > 0.0 { Some(4.0 / ps_rate) } else { None };
+        let rt = if rt_rate > 0.0 { Some(16.0 / rt_rate) } else { None };
+        (ps, rt)
+    }
+}
+
+// A PS/RT change as the output callback logs it, before take_history dates
+// it.
+struct HistoryRecord {
+    sample: usize,
+    field: RdsField,
+    source: RdsChangeSource,
+    text: String,
+}
+
 pub struct RdsGenerator {
     params: RdsParams,
     state: usize,
@@ -83,15 +405,57 @@ pub struct RdsGenerator {
     ps_scroll_interval_samples: usize,
     rt_scroll_interval_samples: usize,
     sample_ticks: usize,
+    ps_scroll_mode: PsScrollMode,
+    ps_pages: Vec<String>,
+    ps_page_index: usize,
+    ps_page_interval_samples: usize,
+    ps_page_center: bool,
+
+    code_table: RdsCodeTable,
+    translit: Option<TranslitTable>,
+    ps_text: String,
+    rt_text: String,
+    ps_scroll_source: String,
+    rt_scroll_source: String,
+
+    rate_guard: bool,
+    ps_min_dwell_samples: usize,
+    rt_min_interval_samples: usize,
+    last_ps_change: Option<usize>,
+    last_rt_change: Option<usize>,
+    ps_pending: Option<(String, RdsChangeSource)>,
+    rt_pending: Option<(String, RdsChangeSource)>,
+    ps_staged: Option<([u8; PS_LENGTH], String, RdsChangeSource)>,
+    rt_staged: Option<([u8; RT_LENGTH], String, RdsChangeSource)>,
+
+    rt_overflow: RtOverflow,
+    rt_ellipsis: String,
+    // Parts of an RT rotated for `RtOverflow::Rotate`, empty when it fits.
+    rt_parts: Vec<String>,
+    rt_part_index: usize,
+    rt_part_since: usize,
+    rt_part_interval_samples: usize,
 
     group_cycle: Vec<u8>,
     group_index: usize,
     ct_interval_groups: usize,
     ct_counter: usize,
+    ecc: Option<u8>,
+    lic: Option<u8>,
+    slow_label_counter: usize,
+    slow_label_lic_next: bool,
+    fast_tuning: FastTuning,
+    fast_tuning_due: bool,
+    fast_tuning_state: usize,
     ps_alt_list: Vec<String>,
     ps_alt_index: usize,
     ps_alt_interval: usize,
     ps_alt_counter: usize,
+
+    history: VecDeque<HistoryRecord>,
+    group_stats: GroupStats,
+    // Every group sent, while an export asks for its schedule.
+    group_log: Option<Vec<GroupRecord>>,
 }
 
 impl RdsGenerator {
@@ -99,7 +463,7 @@ impl RdsGenerator {
         let filter_size = waveform_biphase().len();
         let sample_buffer_size = SAMPLES_PER_BIT + filter_size;
 
-        RdsGenerator {
+        let mut generator = RdsGenerator {
             params: RdsParams::default(),
             state: 0,
             ps_state: 0,
@@ -128,16 +492,58 @@ impl RdsGenerator {
             ps_scroll_interval_samples: 228000 / 2,
             rt_scroll_interval_samples: 228000 / 2,
             sample_ticks: 0,
+            ps_scroll_mode: PsScrollMode::Character,
+            ps_pages: Vec::new(),
+            ps_page_index: 0,
+            ps_page_interval_samples: 228000 * 2,
+            ps_page_center: false,
+
+            code_table: RdsCodeTable::E1,
+            translit: None,
+            ps_text: String::new(),
+            rt_text: String::new(),
+            ps_scroll_source: String::new(),
+            rt_scroll_source: String::new(),
+
+            rate_guard: true,
+            ps_min_dwell_samples: 0,
+            rt_min_interval_samples: 0,
+            last_ps_change: None,
+            last_rt_change: None,
+            ps_pending: None,
+            rt_pending: None,
+            ps_staged: None,
+            rt_staged: None,
+
+            rt_overflow: RtOverflow::Ellipsis,
+            rt_ellipsis: DEFAULT_RT_ELLIPSIS.to_string(),
+            rt_parts: Vec::new(),
+            rt_part_index: 0,
+            rt_part_since: 0,
+            rt_part_interval_samples: (RDS_SAMPLE_RATE * DEFAULT_RT_PART_SECS) as usize,
 
             group_cycle: vec![0, 0, 0, 0, 2],
             group_index: 0,
             ct_interval_groups: 0,
             ct_counter: 0,
+            ecc: None,
+            lic: None,
+            slow_label_counter: 0,
+            slow_label_lic_next: false,
+            fast_tuning: FastTuning::Off,
+            fast_tuning_due: false,
+            fast_tuning_state: 0,
             ps_alt_list: Vec::new(),
             ps_alt_index: 0,
             ps_alt_interval: 0,
             ps_alt_counter: 0,
-        }
+
+            history: VecDeque::with_capacity(HISTORY_CAPACITY),
+            group_stats: GroupStats::default(),
+            group_log: None,
+        };
+        generator.update_rate_limits();
+        generator
     }
 
     pub fn set_pi(&mut self, pi_code: u16) {
@@ -149,13 +555,82 @@ impl RdsGenerator {
     }
 
     pub fn set_rt(&mut self, rt: &str) {
+        self.apply_rt(rt, RdsChangeSource::Manual);
+    }
+
+    pub fn apply_rt(&mut self, rt: &str, source: RdsChangeSource) {
+        if self.rate_guard && !Self::change_allowed(self.last_rt_change, self.sample_ticks, self.rt_min_interval_samples) {
+            self.rt_pending = Some((rt.to_string(), source));
+            return;
+        }
+        self.commit_rt(rt, source);
+    }
+
+    fn commit_rt(&mut self, rt: &str, source: RdsChangeSource) {
+        self.rt_pending = None;
+        self.rt_text = rt.to_string();
+        let on_air = self.encode_text(rt);
+        // Scroll windows are already one message wide.
+        let parts = if source == RdsChangeSource::Scroll {
+            vec![on_air]
+        } else {
+            let capacity = RT_LENGTH - self.code_table.designator().map_or(0, |d| d.len());
+            rt_parts(&on_air, capacity, self.rt_overflow, &self.rt_ellipsis())
+        };
+        if parts.len() > 1 && parts == self.rt_parts {
+            return;
+        }
+        self.rt_part_index = 0;
+        self.rt_part_since = self.sample_ticks;
+        self.rt_parts = if parts.len() > 1 { parts.clone() } else { Vec::new() };
+        self.stage_rt(&parts[0], source);
+    }
+
+    // The configured ellipsis, or "..." when the code table cannot carry it.
+    fn rt_ellipsis(&self) -> String {
+        if self.rt_ellipsis.chars().all(|c| is_mappable(c, self.code_table)) {
+            self.rt_ellipsis.clone()
+        } else {
+            DEFAULT_RT_ELLIPSIS.to_string()
+        }
+    }
+
+    fn stage_rt(&mut self, on_air: &str, source: RdsChangeSource) {
         let mut next = [0u8; RT_LENGTH];
-        fill_rds_string(&mut next, rt);
-        if next != self.params.rt {
-            if self.params.ab_auto {
-                self.params.ab = !self.params.ab;
+        fill_rds_string_with_table(&mut next, on_air, self.code_table);
+        let latest = self.rt_staged.as_ref().map_or(self.params.rt, |s| s.0);
+        if next != latest {
+            self.rt_staged = Some((next, on_air.to_string(), source));
+        }
+    }
+
+    // RT switches only when the 2A segment address wraps to 0 so a receiver
+    // never assembles a message from two different texts.
+    fn swap_staged_rt(&mut self) {
+        if let Some((next, text, source)) = self.rt_staged.take() {
+            if next != self.params.rt {
+                if self.params.ab_auto {
+                    self.params.ab = !self.params.ab;
+                }
+                self.params.rt = next;
+                self.last_rt_change = Some(self.sample_ticks);
+                self.record_history(RdsField::Rt, source, text, RT_LENGTH);
             }
-            self.params.rt = next;
+        }
+    }
+
+    pub fn set_rt_overflow(&mut self, mode: RtOverflow, ellipsis: &str, part_secs: f32) {
+        let interval = ((RDS_SAMPLE_RATE * part_secs.max(1.0)) as usize).max(1);
+        if mode == self.rt_overflow && ellipsis == self.rt_ellipsis && interval == self.rt_part_interval_samples {
+            return;
+        }
+        self.rt_overflow = mode;
+        self.rt_ellipsis = ellipsis.to_string();
+        self.rt_part_interval_samples = interval;
+        self.rt_parts.clear();
+        if !self.rt_text.is_empty() && self.rt_scroll.is_none() {
+            let rt = self.rt_text.clone();
+            self.commit_rt(&rt, RdsChangeSource::Manual);
         }
     }
 
@@ -184,11 +659,188 @@ impl RdsGenerator {
     }
 
     pub fn set_ps(&mut self, ps: &str) {
-        fill_rds_string(&mut self.params.ps, ps);
+        self.apply_ps(ps, RdsChangeSource::Manual);
+    }
+
+    pub fn apply_ps(&mut self, ps: &str, source: RdsChangeSource) {
+        if self.rate_guard && !Self::change_allowed(self.last_ps_change, self.sample_ticks, self.ps_min_dwell_samples) {
+            self.ps_pending = Some((ps.to_string(), source));
+            return;
+        }
+        self.commit_ps(ps, source);
+    }
+
+    fn commit_ps(&mut self, ps: &str, source: RdsChangeSource) {
+        self.ps_pending = None;
+        self.ps_text = ps.to_string();
+        let on_air = self.encode_text(ps);
+        let mut next = [0u8; PS_LENGTH];
+        fill_rds_string_with_table(&mut next, &on_air, self.code_table);
+        let latest = self.ps_staged.as_ref().map_or(self.params.ps, |s| s.0);
+        if next != latest {
+            self.ps_staged = Some((next, on_air, source));
+        }
+    }
+
+    fn swap_staged_ps(&mut self) {
+        if let Some((next, text, source)) = self.ps_staged.take() {
+            if next != self.params.ps {
+                self.params.ps = next;
+                self.last_ps_change = Some(self.sample_ticks);
+                self.record_history(RdsField::Ps, source, text, PS_LENGTH);
+            }
+        }
+    }
+
+    pub fn set_code_table(&mut self, table: RdsCodeTable) {
+        if table == self.code_table {
+            return;
+        }
+        self.code_table = table;
+        self.refresh_texts();
+    }
+
+    pub fn set_transliteration(&mut self, table: Option<TranslitTable>) {
+        self.translit = table;
+        self.refresh_texts();
+    }
+
+    fn encode_text(&self, text: &str) -> String {
+        match &self.translit {
+            Some(table) => table.transliterate(text, self.code_table),
+            None => text.to_string(),
+        }
+    }
+
+    fn refresh_texts(&mut self) {
+        if self.ps_scroll.is_some() {
+            self.ps_scroll = Some(self.encode_text(&self.ps_scroll_source));
+            self.ps_scroll_pos = 0;
+            self.rebuild_ps_pages();
+        }
+        if self.rt_scroll.is_some() {
+            self.rt_scroll = Some(self.encode_text(&self.rt_scroll_source));
+            self.rt_scroll_pos = 0;
+        }
+        let ps = self.ps_text.clone();
+        let rt = self.rt_text.clone();
+        if !ps.is_empty() {
+            self.commit_ps(&ps, RdsChangeSource::Manual);
+        }
+        if !rt.is_empty() {
+            self.commit_rt(&rt, RdsChangeSource::Manual);
+        }
+    }
+
+    fn change_allowed(last_change: Option<usize>, now: usize, min_interval: usize) -> bool {
+        match last_change {
+            Some(last) => now.saturating_sub(last) >= min_interval,
+            None => true,
+        }
+    }
+
+    pub fn set_rate_guard(&mut self, enabled: bool) {
+        self.rate_guard = enabled;
+    }
+
+    // 15B groups take slots from the cycle, so they count towards the
+    // PS/RT refresh time.
+    fn update_rate_limits(&mut self) {
+        let cycle: Vec<u8> = if self.fast_tuning_active() {
+            self.group_cycle
+                .iter()
+                .flat_map(|&g| if g == 0 { vec![0, 15] } else { vec![g] })
+                .collect()
+        } else {
+            self.group_cycle.clone()
+        };
+        let (ps_dwell, rt_interval) = rate_limits_for_cycle(&cycle);
+        self.ps_min_dwell_samples = (ps_dwell * RDS_SAMPLE_RATE) as usize;
+        self.rt_min_interval_samples = (rt_interval * RDS_SAMPLE_RATE) as usize;
+    }
+
+    fn guarded_interval(&self, interval: usize, min_interval: usize) -> usize {
+        if self.rate_guard {
+            interval.max(min_interval).max(1)
+        } else {
+            interval.max(1)
+        }
+    }
+
+    // Runs in the output callback: the staged text is cut in place and the
+    // wall-clock time is left to take_history.
+    fn record_history(&mut self, field: RdsField, source: RdsChangeSource, mut text: String, len: usize) {
+        let designator_len = self.code_table.designator().map_or(0, |d| d.len());
+        if let Some((end, _)) = text.char_indices().nth(len - designator_len) {
+            text.truncate(end);
+        }
+        if self.history.len() >= HISTORY_CAPACITY {
+            self.history.pop_front();
+        }
+        self.history.push_back(HistoryRecord {
+            sample: self.sample_ticks.saturating_sub(1),
+            field,
+            source,
+            text,
+        });
+    }
+
+    // Each change is dated back from `now` by the samples sent since.
+    pub fn take_history(&mut self, now: DateTime<Local>) -> Vec<RdsHistoryEntry> {
+        let ticks = self.sample_ticks;
+        self.history
+            .drain(..)
+            .map(|record| {
+                let ago = ticks.saturating_sub(record.sample) as f64 / RDS_SAMPLE_RATE as f64;
+                RdsHistoryEntry {
+                    timestamp: now - chrono::Duration::microseconds((ago * 1e6) as i64),
+                    sample: record.sample,
+                    field: record.field,
+                    source: record.source,
+                    text: record.text,
+                }
+            })
+            .collect()
+    }
+
+    pub fn group_stats(&self) -> GroupStats {
+        self.group_stats
+    }
+
+    pub fn set_group_log(&mut self, enabled: bool) {
+        self.group_log = enabled.then(Vec::new);
+    }
+
+    pub fn take_group_log(&mut self) -> Vec<GroupRecord> {
+        self.group_log.as_mut().map(std::mem::take).unwrap_or_default()
     }
 
     pub fn set_ta(&mut self, ta: bool) {
+        if ta == self.params.ta {
+            return;
+        }
         self.params.ta = ta;
+        self.update_rate_limits();
+    }
+
+    pub fn ta(&self) -> bool {
+        self.params.ta
+    }
+
+    pub fn set_fast_tuning(&mut self, mode: FastTuning) {
+        if mode == self.fast_tuning {
+            return;
+        }
+        self.fast_tuning = mode;
+        self.update_rate_limits();
+    }
+
+    fn fast_tuning_active(&self) -> bool {
+        match self.fast_tuning {
+            FastTuning::Off => false,
+            FastTuning::WhileTa => self.params.ta,
+            FastTuning::Always => true,
+        }
     }
 
     pub fn set_defaults_tunisia(&mut self) {
@@ -206,22 +858,80 @@ impl RdsGenerator {
     }
 
     pub fn set_group_mix(&mut self, count_0a: usize, count_2a: usize, count_4a: usize) {
-        let mut cycle = Vec::new();
-        cycle.extend(std::iter::repeat(0).take(count_0a.max(1)));
-        cycle.extend(std::iter::repeat(2).take(count_2a.max(1)));
-        if count_4a > 0 {
-            cycle.extend(std::iter::repeat(4).take(count_4a));
+        let cycle = group_cycle_for_mix(count_0a, count_2a, count_4a);
+        if cycle == self.group_cycle {
+            return;
         }
         self.group_cycle = cycle;
         self.group_index = 0;
+        self.update_rate_limits();
+    }
+
+    pub fn set_ecc(&mut self, ecc: Option<u8>) {
+        self.ecc = ecc;
+    }
+
+    pub fn set_lic(&mut self, lic: Option<u8>) {
+        self.lic = lic;
+    }
+
+    fn get_rds_slow_label_group(&mut self, blocks: &mut [u16; GROUP_LENGTH]) -> bool {
+        if self.ecc.is_none() && self.lic.is_none() {
+            return false;
+        }
+        self.slow_label_counter += 1;
+        if self.slow_label_counter < SLOW_LABEL_INTERVAL_GROUPS {
+            return false;
+        }
+        self.slow_label_counter = 0;
+
+        let (variant, data) = match (self.ecc, self.lic) {
+            (Some(_), Some(lic)) if self.slow_label_lic_next => (3u16, lic as u16),
+            (Some(ecc), _) => (0u16, ecc as u16),
+            (None, Some(lic)) => (3u16, lic as u16),
+            (None, None) => return false,
+        };
+        self.slow_label_lic_next = variant == 0;
+
+        blocks[1] = (1u16 << 12)
+            | ((self.params.tp as u16) << 10)
+            | ((self.params.pty as u16) << 5);
+        blocks[2] = (variant << 12) | (data & 0x0FFF);
+        blocks[3] = 0x0000;
+        true
+    }
+
+    fn get_rds_fast_tuning_group(&mut self, blocks: &mut [u16; GROUP_LENGTH]) -> bool {
+        if !std::mem::take(&mut self.fast_tuning_due) || !self.fast_tuning_active() {
+            return false;
+        }
+        let di_bit = (self.params.di >> (3 - self.fast_tuning_state)) & 0x01;
+        blocks[1] = (15u16 << 12)
+            | (1u16 << 11)
+            | ((self.params.tp as u16) << 10)
+            | ((self.params.pty as u16) << 5)
+            | ((self.params.ta as u16) << 4)
+            | ((self.params.ms as u16) << 3)
+            | ((di_bit as u16) << 2)
+            | (self.fast_tuning_state as u16);
+        blocks[2] = self.params.pi;
+        blocks[3] = blocks[1];
+        self.fast_tuning_state = (self.fast_tuning_state + 1) % 4;
+        true
     }
 
     pub fn set_ct_interval_groups(&mut self, interval: usize) {
+        if interval == self.ct_interval_groups {
+            return;
+        }
         self.ct_interval_groups = interval;
         self.ct_counter = 0;
     }
 
     pub fn set_ps_alternates(&mut self, list: Vec<String>, interval_groups: usize) {
+        if list == self.ps_alt_list && interval_groups == self.ps_alt_interval {
+            return;
+        }
         self.ps_alt_list = list;
         self.ps_alt_interval = interval_groups;
         self.ps_alt_index = 0;
@@ -229,33 +939,14 @@ impl RdsGenerator {
     }
 
     pub fn set_af_list_mhz(&mut self, freqs: &[f32]) {
-        let mut codes = Vec::new();
-        for &mhz in freqs {
-            if mhz < 87.6 || mhz > 107.9 {
-                continue;
-            }
-            let code = ((mhz - 87.6) * 10.0).round() as i32 + 1;
-            if code >= 1 && code <= 204 {
-                codes.push(code as u8);
-            }
-        }
-
-        codes.sort();
-        codes.dedup();
+        self.set_af_entries(freqs, &[], None);
+    }
 
-        if codes.is_empty() {
-            self.params.af_stream.clear();
-            self.af_pos = 0;
+    pub fn set_af_entries(&mut self, freqs: &[f32], regional: &[f32], tuned_mhz: Option<f32>) {
+        let stream = af_code_stream(freqs, regional, tuned_mhz);
+        if stream == self.params.af_stream {
             return;
         }
-
-        let count = codes.len().min(25);
-        let mut stream = Vec::with_capacity(count + 1);
-        stream.push(0xE0 + count as u8);
-        stream.extend(codes.into_iter().take(count));
-        if stream.len() % 2 != 0 {
-            stream.push(0x00);
-        }
         self.params.af_stream = stream;
         self.af_pos = 0;
     }
@@ -265,12 +956,39 @@ impl RdsGenerator {
             self.ps_scroll = None;
             return;
         }
-        self.ps_scroll = Some(text.to_string());
-        self.ps_scroll_pos = 0;
         let cps = if chars_per_sec <= 0.1 { 0.1 } else { chars_per_sec };
-        self.ps_scroll_interval_samples = (228000.0 / cps) as usize;
-        if self.ps_scroll_interval_samples == 0 {
-            self.ps_scroll_interval_samples = 1;
+        let interval = ((228000.0 / cps) as usize).max(1);
+        if self.ps_scroll.is_some() && self.ps_scroll_source == text && self.ps_scroll_interval_samples == interval {
+            return;
+        }
+        self.ps_scroll_source = text.to_string();
+        self.ps_scroll = Some(self.encode_text(text));
+        self.ps_scroll_pos = 0;
+        self.ps_scroll_interval_samples = interval;
+        self.rebuild_ps_pages();
+    }
+
+    pub fn set_ps_scroll_mode(&mut self, mode: PsScrollMode, page_dwell_secs: f32, center: bool) {
+        let dwell = page_dwell_secs.max(0.1);
+        let interval = ((228000.0 * dwell) as usize).max(1);
+        if mode == self.ps_scroll_mode && interval == self.ps_page_interval_samples && center == self.ps_page_center {
+            return;
+        }
+        self.ps_scroll_mode = mode;
+        self.ps_page_interval_samples = interval;
+        self.ps_page_center = center;
+        self.rebuild_ps_pages();
+    }
+
+    fn rebuild_ps_pages(&mut self) {
+        self.ps_pages = match (&self.ps_scroll, self.ps_scroll_mode) {
+            (Some(text), PsScrollMode::Paging) => ps_pages(text, self.ps_page_center),
+            _ => Vec::new(),
+        };
+        self.ps_page_index = 0;
+        if let Some(first) = self.ps_pages.first().cloned() {
+            self.apply_ps(&first, RdsChangeSource::Scroll);
+            self.ps_page_index = 1 % self.ps_pages.len();
         }
     }
 
@@ -279,13 +997,15 @@ impl RdsGenerator {
             self.rt_scroll = None;
             return;
         }
-        self.rt_scroll = Some(text.to_string());
-        self.rt_scroll_pos = 0;
         let cps = if chars_per_sec <= 0.1 { 0.1 } else { chars_per_sec };
-        self.rt_scroll_interval_samples = (228000.0 / cps) as usize;
-        if self.rt_scroll_interval_samples == 0 {
-            self.rt_scroll_interval_samples = 1;
+        let interval = ((228000.0 / cps) as usize).max(1);
+        if self.rt_scroll.is_some() && self.rt_scroll_source == text && self.rt_scroll_interval_samples == interval {
+            return;
         }
+        self.rt_scroll_source = text.to_string();
+        self.rt_scroll = Some(self.encode_text(text));
+        self.rt_scroll_pos = 0;
+        self.rt_scroll_interval_samples = interval;
     }
 
     fn crc(block: u16) -> u16 {
@@ -348,11 +1068,12 @@ impl RdsGenerator {
 
         if self.ps_alt_interval > 0 && !self.ps_alt_list.is_empty() {
             self.ps_alt_counter += 1;
-            if self.ps_alt_counter >= self.ps_alt_interval {
+            let min_groups = (self.ps_min_dwell_samples as f32 / RDS_SAMPLE_RATE * GROUPS_PER_SEC).ceil() as usize;
+            if self.ps_alt_counter >= self.guarded_interval(self.ps_alt_interval, min_groups) {
                 self.ps_alt_counter = 0;
                 self.ps_alt_index = (self.ps_alt_index + 1) % self.ps_alt_list.len();
                 let ps = self.ps_alt_list[self.ps_alt_index].clone();
-                self.set_ps(&ps);
+                self.apply_ps(&ps, RdsChangeSource::Alternate);
             }
         }
 
@@ -366,7 +1087,11 @@ impl RdsGenerator {
             }
         }
 
-        if !sent_ct && !self.get_rds_ct_group(&mut blocks) {
+        if !sent_ct
+            && !self.get_rds_fast_tuning_group(&mut blocks)
+            && !self.get_rds_ct_group(&mut blocks)
+            && !self.get_rds_slow_label_group(&mut blocks)
+        {
             let group_type = if self.group_cycle.is_empty() {
                 0
             } else {
@@ -376,6 +1101,9 @@ impl RdsGenerator {
             };
 
             if group_type == 0 && self.state < 4 {
+                if self.ps_state == 0 {
+                    self.swap_staged_ps();
+                }
                 let di_bit = (self.params.di >> (3 - self.ps_state)) & 0x01;
                 blocks[1] = (0u16 << 12)
                     | ((self.params.tp as u16) << 10)
@@ -385,7 +1113,7 @@ impl RdsGenerator {
                     | ((di_bit as u16) << 2)
                     | (self.ps_state as u16);
                 if self.params.af_stream.is_empty() {
-                    blocks[2] = 0xCDCD;
+                    blocks[2] = ((AF_NONE_CODE as u16) << 8) | (AF_FILLER_CODE as u16);
                 } else {
                     let af1 = self.params.af_stream[self.af_pos % self.params.af_stream.len()];
                     let af2 = self.params.af_stream[(self.af_pos + 1) % self.params.af_stream.len()];
@@ -398,7 +1126,11 @@ impl RdsGenerator {
                 if self.ps_state >= 4 {
                     self.ps_state = 0;
                 }
+                self.fast_tuning_due = true;
             } else if group_type == 2 {
+                if self.rt_state == 0 {
+                    self.swap_staged_rt();
+                }
                 blocks[1] = (2u16 << 12)
                     | ((self.params.tp as u16) << 10)
                     | ((self.params.pty as u16) << 5)
@@ -422,10 +1154,20 @@ impl RdsGenerator {
             }
         }
 
+        self.group_stats.record(blocks[1]);
+        if let Some(log) = self.group_log.as_mut() {
+            // The first bit's shaped waveform starts on the next output
+            // sample.
+            log.push(GroupRecord {
+                sample: self.sample_ticks,
+                blocks,
+            });
+        }
+
         let mut out_index = 0;
         for i in 0..GROUP_LENGTH {
             let mut block = blocks[i];
-            let mut check = Self::crc(block) ^ OFFSET_WORDS[i];
+            let mut check = Self::crc(block) ^ offset_word(i, &blocks);
             for _ in 0..BLOCK_SIZE {
                 buffer[out_index] = if (block & (1 << (BLOCK_SIZE - 1))) != 0 { 1 } else { 0 };
                 out_index += 1;
@@ -440,13 +1182,56 @@ impl RdsGenerator {
     }
 
     pub fn get_rds_samples(&mut self, buffer: &mut [f32]) {
+        self.fill_samples(buffer, true);
+    }
+
+    // The biphase-coded data before it is put on the 57 kHz carrier, for
+    // callers that supply their own carrier, e.g. one locked to an external
+    // pilot.
+    pub fn get_rds_baseband(&mut self, buffer: &mut [f32]) {
+        self.fill_samples(buffer, false);
+    }
+
+    fn fill_samples(&mut self, buffer: &mut [f32], carrier: bool) {
         let filter = waveform_biphase();
         let sample_buffer_size = self.sample_buffer.len();
 
         for sample in buffer.iter_mut() {
             self.sample_ticks += 1;
-            if let Some(ref text) = self.ps_scroll {
-                if self.sample_ticks % self.ps_scroll_interval_samples == 0 {
+            if self.ps_pending.is_some()
+                && Self::change_allowed(self.last_ps_change, self.sample_ticks, self.ps_min_dwell_samples)
+            {
+                if let Some((text, source)) = self.ps_pending.take() {
+                    self.commit_ps(&text, source);
+                }
+            }
+            if self.rt_pending.is_some()
+                && Self::change_allowed(self.last_rt_change, self.sample_ticks, self.rt_min_interval_samples)
+            {
+                if let Some((text, source)) = self.rt_pending.take() {
+                    self.commit_rt(&text, source);
+                }
+            }
+            if self.rt_parts.len() > 1 && self.rt_scroll.is_none() {
+                let interval = self.guarded_interval(self.rt_part_interval_samples, self.rt_min_interval_samples);
+                if self.sample_ticks.saturating_sub(self.rt_part_since) >= interval {
+                    self.rt_part_since = self.sample_ticks;
+                    self.rt_part_index = (self.rt_part_index + 1) % self.rt_parts.len();
+                    let part = self.rt_parts[self.rt_part_index].clone();
+                    self.stage_rt(&part, RdsChangeSource::Alternate);
+                }
+            }
+            let ps_page_interval = self.guarded_interval(self.ps_page_interval_samples, self.ps_min_dwell_samples);
+            let ps_scroll_interval = self.guarded_interval(self.ps_scroll_interval_samples, self.ps_min_dwell_samples);
+            let rt_scroll_interval = self.guarded_interval(self.rt_scroll_interval_samples, self.rt_min_interval_samples);
+            if self.ps_scroll_mode == PsScrollMode::Paging {
+                if !self.ps_pages.is_empty() && self.sample_ticks.is_multiple_of(ps_page_interval) {
+                    let page = self.ps_pages[self.ps_page_index].clone();
+                    self.ps_page_index = (self.ps_page_index + 1) % self.ps_pages.len();
+                    self.apply_ps(&page, RdsChangeSource::Scroll);
+                }
+            } else if let Some(ref text) = self.ps_scroll {
+                if self.sample_ticks.is_multiple_of(ps_scroll_interval) {
                     let mut window = String::new();
                     let padded = format!("{}   ", text);
                     for i in 0..PS_LENGTH {
@@ -454,11 +1239,11 @@ impl RdsGenerator {
                         window.push(padded.as_bytes()[idx] as char);
                     }
                     self.ps_scroll_pos = (self.ps_scroll_pos + 1) % padded.len();
-                    self.set_ps(&window);
+                    self.apply_ps(&window, RdsChangeSource::Scroll);
                 }
             }
             if let Some(ref text) = self.rt_scroll {
-                if self.sample_ticks % self.rt_scroll_interval_samples == 0 {
+                if self.sample_ticks.is_multiple_of(rt_scroll_interval) {
                     let mut window = String::new();
                     let padded = format!("{}   ", text);
                     for i in 0..RT_LENGTH {
@@ -466,7 +1251,7 @@ impl RdsGenerator {
                         window.push(padded.as_bytes()[idx] as char);
                     }
                     self.rt_scroll_pos = (self.rt_scroll_pos + 1) % padded.len();
-                    self.set_rt(&window);
+                    self.apply_rt(&window, RdsChangeSource::Scroll);
                 }
             }
             if self.sample_count >= SAMPLES_PER_BIT {
@@ -511,15 +1296,17 @@ impl RdsGenerator {
                 self.out_sample_index = 0;
             }
 
-            match self.phase {
-                0 | 2 => out = 0.0,
-                1 => {}
-                3 => out = -out,
-                _ => {}
-            }
-            self.phase += 1;
-            if self.phase >= 4 {
-                self.phase = 0;
+            if carrier {
+                match self.phase {
+                    0 | 2 => out = 0.0,
+                    1 => {}
+                    3 => out = -out,
+                    _ => {}
+                }
+                self.phase += 1;
+                if self.phase >= 4 {
+                    self.phase = 0;
+                }
             }
 
             *sample = out;
@@ -527,3 +1314,84 @@ impl RdsGenerator {
         }
     }
 }
+
+// Block C of a version B group (bit 11 of block B) carries offset C'.
+fn offset_word(index: usize, blocks: &[u16; GROUP_LENGTH]) -> u16 {
+    if index == 2 && blocks[1] & 0x0800 != 0 {
+        OFFSET_C_PRIME
+    } else {
+        OFFSET_WORDS[index]
+    }
+}
+
+// The 104 bits of a group as sent before differential and biphase coding:
+// each block followed by its check word, packed MSB first into 13 bytes.
+pub fn group_bits(blocks: &[u16; GROUP_LENGTH]) -> [u8; BITS_PER_GROUP / 8] {
+    let mut bits: u128 = 0;
+    for (i, &block) in blocks.iter().enumerate() {
+        let check = RdsGenerator::crc(block) ^ offset_word(i, blocks);
+        bits = (bits << BLOCK_SIZE) | block as u128;
+        bits = (bits << POLY_DEG) | check as u128;
+    }
+    let mut out = [0u8; BITS_PER_GROUP / 8];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = (bits >> (BITS_PER_GROUP - 8 * (i + 1))) as u8;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn af_method_a_matches_the_coded_example() {
+        assert_eq!(af_code_stream(&[98.0, 99.5], &[], None), vec![0xE2, 0x69, 0x78, 0xCD]);
+    }
+
+    #[test]
+    fn af_method_a_empty_list() {
+        assert_eq!(af_code_stream(&[], &[], None), vec![0xE0, 0xCD]);
+    }
+
+    #[test]
+    fn af_method_a_odd_count_is_not_padded_twice() {
+        assert_eq!(af_code_stream(&[98.0], &[], None), vec![0xE1, 0x69]);
+    }
+
+    #[test]
+    fn af_method_b_pairs_carry_the_tuned_frequency() {
+        // Tuned 98.0 (0x69), same programme on 99.5 (0x78), regional variant
+        // on 101.2 (0x89): 2 pairs, so the count is 224 + 5.
+        let stream = af_code_stream(&[99.5], &[101.2], Some(98.0));
+        assert_eq!(stream, vec![0xE5, 0x69, 0x69, 0x78, 0x89, 0x69]);
+    }
+
+    #[test]
+    fn af_method_b_is_capped_at_twelve_pairs() {
+        let freqs = (0..20).map(|i| 88.0 + i as f32).collect::<Vec<_>>();
+        let stream = af_code_stream(&freqs, &[107.5], Some(87.6));
+        assert_eq!(stream[0], AF_NONE_CODE + 25);
+        assert_eq!(stream.len(), 2 + 2 * AF_METHOD_B_PAIRS);
+    }
+
+    #[test]
+    fn af_regional_entries_need_a_tuned_frequency() {
+        assert!(af_tuned_missing(&[99.5], None));
+        assert!(af_tuned_missing(&[99.5], Some(120.0)));
+        assert!(!af_tuned_missing(&[99.5], Some(98.0)));
+        assert!(!af_tuned_missing(&[], None));
+    }
+
+    #[test]
+    fn history_is_dated_back_from_the_time_it_is_taken() {
+        let mut rds = RdsGenerator::new();
+        rds.set_ps("PULSE FM");
+        rds.get_rds_samples(&mut vec![0.0; 228_000]);
+        let now = Local::now();
+        let history = rds.take_history(now);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].text, "PULSE FM");
+        assert!(history[0].timestamp < now && history[0].timestamp > now - chrono::Duration::seconds(1));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::rds_countries::{countries_for, is_known_ecc};
+
+pub const PTY_ALARM: u8 = 31;
+
+#[derive(Clone, Copy, Debug)]
+pub struct StationCheck {
+    pub pi: u16,
+    pub ecc: Option<u8>,
+    pub pty: u8,
+    pub tp: bool,
+    pub ta: bool,
+    pub alarm: bool,
+}
+
+pub fn station_warnings(check: &StationCheck) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if check.pi == 0x0000 {
+        warnings.push("PI 0000 is not a valid programme identifier.".to_string());
+    }
+    if let Some(ecc) = check.ecc {
+        let country_code = (check.pi >> 12) as u8;
+        if is_known_ecc(ecc) && countries_for(ecc, country_code).is_empty() {
+            warnings.push(format!(
+                "PI country nibble {:X} is not assigned to any country under ECC {:02X}.",
+                country_code, ecc
+            ));
+        }
+    }
+    if check.pty == PTY_ALARM && !check.alarm {
+        warnings.push("PTY 31 (Alarm) is reserved for emergency broadcasts.".to_string());
+    }
+    if check.ta && !check.tp {
+        warnings.push("TA is set while TP is clear; receivers will not treat this as a traffic announcement.".to_string());
+    }
+    warnings
+}
@@ -0,0 +1,92 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RdsCountry {
+    pub name: &'static str,
+    pub country_code: u8,
+    pub ecc: u8,
+}
+
+const fn country(name: &'static str, country_code: u8, ecc: u8) -> RdsCountry {
+    RdsCountry { name, country_code, ecc }
+}
+
+// European Broadcasting Area, ECC tables E0-E4.
+pub const RDS_COUNTRIES: &[RdsCountry] = &[
+    country("Albania", 0x9, 0xE0),
+    country("Algeria", 0x2, 0xE0),
+    country("Andorra", 0x3, 0xE0),
+    country("Armenia", 0xA, 0xE4),
+    country("Austria", 0xA, 0xE0),
+    country("Azerbaijan", 0xB, 0xE3),
+    country("Belarus", 0xF, 0xE3),
+    country("Belgium", 0x6, 0xE0),
+    country("Bosnia and Herzegovina", 0xF, 0xE4),
+    country("Bulgaria", 0x8, 0xE1),
+    country("Croatia", 0xC, 0xE3),
+    country("Cyprus", 0x2, 0xE1),
+    country("Czechia", 0x2, 0xE2),
+    country("Denmark", 0x9, 0xE1),
+    country("Egypt", 0xF, 0xE0),
+    country("Estonia", 0x2, 0xE4),
+    country("Finland", 0x6, 0xE1),
+    country("France", 0xF, 0xE1),
+    country("Georgia", 0xC, 0xE4),
+    country("Germany", 0xD, 0xE0),
+    country("Germany (alt)", 0x1, 0xE0),
+    country("Gibraltar", 0xA, 0xE1),
+    country("Greece", 0x1, 0xE1),
+    country("Hungary", 0xB, 0xE0),
+    country("Iceland", 0xA, 0xE2),
+    country("Iraq", 0xB, 0xE1),
+    country("Ireland", 0x2, 0xE3),
+    country("Israel", 0x4, 0xE0),
+    country("Italy", 0x5, 0xE0),
+    country("Jordan", 0x5, 0xE1),
+    country("Kazakhstan", 0xD, 0xE3),
+    country("Kosovo", 0x7, 0xE4),
+    country("Kyrgyzstan", 0x3, 0xE4),
+    country("Latvia", 0x9, 0xE3),
+    country("Lebanon", 0xA, 0xE3),
+    country("Libya", 0xD, 0xE1),
+    country("Liechtenstein", 0x9, 0xE2),
+    country("Lithuania", 0xC, 0xE2),
+    country("Luxembourg", 0x7, 0xE1),
+    country("Malta", 0xC, 0xE0),
+    country("Moldova", 0x1, 0xE4),
+    country("Monaco", 0xB, 0xE2),
+    country("Montenegro", 0x1, 0xE3),
+    country("Morocco", 0x1, 0xE2),
+    country("Netherlands", 0x8, 0xE3),
+    country("North Macedonia", 0x4, 0xE3),
+    country("Norway", 0xF, 0xE2),
+    country("Palestine", 0x8, 0xE0),
+    country("Poland", 0x3, 0xE2),
+    country("Portugal", 0x8, 0xE4),
+    country("Romania", 0xE, 0xE1),
+    country("Russia", 0x7, 0xE0),
+    country("San Marino", 0x3, 0xE1),
+    country("Serbia", 0xD, 0xE2),
+    country("Slovakia", 0x5, 0xE2),
+    country("Slovenia", 0x9, 0xE4),
+    country("Spain", 0xE, 0xE2),
+    country("Sweden", 0xE, 0xE3),
+    country("Switzerland", 0x4, 0xE1),
+    country("Syria", 0x6, 0xE2),
+    country("Tunisia", 0x7, 0xE2),
+    country("Turkey", 0x3, 0xE3),
+    country("Turkmenistan", 0xE, 0xE4),
+    country("Ukraine", 0x6, 0xE4),
+    country("United Kingdom", 0xC, 0xE1),
+    country("Uzbekistan", 0xB, 0xE4),
+    country("Vatican City", 0x4, 0xE2),
+];
+
+pub fn is_known_ecc(ecc: u8) -> bool {
+    RDS_COUNTRIES.iter().any(|c| c.ecc == ecc)
+}
+
+pub fn countries_for(ecc: u8, country_code: u8) -> Vec<&'static RdsCountry> {
+    RDS_COUNTRIES
+        .iter()
+        .filter(|c| c.ecc == ecc && c.country_code == country_code)
+        .collect()
+}
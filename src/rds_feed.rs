@@ -0,0 +1,166 @@
+use std::fmt;
+use std::io::{ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+
+use crate::rds::group_bits;
+use crate::rds_schedule::GroupRecord;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RdsFeedFormat {
+    // Blocks A-D, 8 bytes per group.
+    Groups,
+    // The 1187.5 bps bitstream with check words, 13 bytes per group.
+    Bits,
+}
+
+impl fmt::Display for RdsFeedFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            RdsFeedFormat::Groups => "Groups",
+            RdsFeedFormat::Bits => "Bitstream",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RdsFeedTransport {
+    // Datagrams to one destination.
+    Udp,
+    // A listening socket; every connected client gets the same frames.
+    Tcp,
+}
+
+impl fmt::Display for RdsFeedTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            RdsFeedTransport::Udp => "UDP",
+            RdsFeedTransport::Tcp => "TCP server",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RdsFeedConfig {
+    pub transport: RdsFeedTransport,
+    pub format: RdsFeedFormat,
+    // UDP: destination host:port. TCP: address to listen on.
+    pub address: String,
+}
+
+pub const FRAME_MAGIC: [u8; 2] = *b"RD";
+pub const FRAME_VERSION: u8 = 1;
+const FRAME_HEADER_LEN: usize = 10;
+// Keeps a UDP frame well under a 1500-byte MTU.
+const MAX_GROUPS_PER_FRAME: usize = 64;
+
+// Frame layout, big-endian:
+//   0  "RD"
+//   2  version (1)
+//   3  format: 0 groups, 1 bitstream
+//   4  u32 sequence number of the first group, counting every group sent
+//   8  u16 group count
+//  10  count groups of 8 (blocks A-D) or 13 bytes (104 bits, MSB first)
+// A gap in the sequence numbers means groups were lost; over TCP the count
+// gives the frame length.
+pub fn encode_frame(format: RdsFeedFormat, seq: u32, groups: &[GroupRecord]) -> Vec<u8> {
+    let group_len = match format {
+        RdsFeedFormat::Groups => 8,
+        RdsFeedFormat::Bits => 13,
+    };
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LEN + groups.len() * group_len);
+    frame.extend_from_slice(&FRAME_MAGIC);
+    frame.push(FRAME_VERSION);
+    frame.push(match format {
+        RdsFeedFormat::Groups => 0,
+        RdsFeedFormat::Bits => 1,
+    });
+    frame.extend_from_slice(&seq.to_be_bytes());
+    frame.extend_from_slice(&(groups.len() as u16).to_be_bytes());
+    for group in groups {
+        match format {
+            RdsFeedFormat::Groups => {
+                for block in group.blocks {
+                    frame.extend_from_slice(&block.to_be_bytes());
+                }
+            }
+            RdsFeedFormat::Bits => frame.extend_from_slice(&group_bits(&group.blocks)),
+        }
+    }
+    frame
+}
+
+enum Sink {
+    Udp { socket: UdpSocket, dest: SocketAddr },
+    Tcp { listener: TcpListener, clients: Vec<TcpStream> },
+}
+
+pub struct RdsFeed {
+    sink: Sink,
+    format: RdsFeedFormat,
+    seq: u32,
+}
+
+impl RdsFeed {
+    pub fn open(config: &RdsFeedConfig) -> Result<Self> {
+        let sink = match config.transport {
+            RdsFeedTransport::Udp => {
+                let dest = config
+                    .address
+                    .trim()
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| anyhow!("invalid RDS feed address"))?;
+                let bind = if dest.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" };
+                Sink::Udp {
+                    socket: UdpSocket::bind(bind)?,
+                    dest,
+                }
+            }
+            RdsFeedTransport::Tcp => {
+                let listener = TcpListener::bind(config.address.trim())?;
+                listener.set_nonblocking(true)?;
+                Sink::Tcp {
+                    listener,
+                    clients: Vec::new(),
+                }
+            }
+        };
+        Ok(RdsFeed {
+            sink,
+            format: config.format,
+            seq: 0,
+        })
+    }
+
+    // Clients that cannot keep up or have gone away are dropped.
+    pub fn send(&mut self, groups: &[GroupRecord]) {
+        if let Sink::Tcp { listener, clients } = &mut self.sink {
+            while let Ok((stream, _)) = listener.accept() {
+                let _ = stream.set_nonblocking(false);
+                let _ = stream.set_nodelay(true);
+                let _ = stream.set_write_timeout(Some(Duration::from_millis(100)));
+                clients.push(stream);
+            }
+        }
+        for chunk in groups.chunks(MAX_GROUPS_PER_FRAME) {
+            let frame = encode_frame(self.format, self.seq, chunk);
+            self.seq = self.seq.wrapping_add(chunk.len() as u32);
+            match &mut self.sink {
+                Sink::Udp { socket, dest } => {
+                    let _ = socket.send_to(&frame, *dest);
+                }
+                Sink::Tcp { clients, .. } => {
+                    clients.retain_mut(|client| match client.write_all(&frame) {
+                        Ok(()) => true,
+                        Err(e) => e.kind() == ErrorKind::Interrupted,
+                    });
+                }
+            }
+        }
+    }
+}
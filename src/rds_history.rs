@@ -0,0 +1,82 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RdsField {
+    Ps,
+    Rt,
+}
+
+impl std::fmt::Display for RdsField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RdsField::Ps => write!(f, "PS"),
+            RdsField::Rt => write!(f, "RT"),
+        }
+    }
+}
+
+// Remote is a value pushed to the status server, Sync a state mirrored from
+// the main encoder and Macro a clock or data provider value re-expanded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RdsChangeSource {
+    Manual,
+    Scroll,
+    Alternate,
+    Remote,
+    Sync,
+    Macro,
+}
+
+impl std::fmt::Display for RdsChangeSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RdsChangeSource::Manual => write!(f, "manual"),
+            RdsChangeSource::Scroll => write!(f, "scroll"),
+            RdsChangeSource::Alternate => write!(f, "alternate"),
+            RdsChangeSource::Remote => write!(f, "remote"),
+            RdsChangeSource::Sync => write!(f, "sync"),
+            RdsChangeSource::Macro => write!(f, "macro"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RdsHistoryEntry {
+    pub timestamp: DateTime<Local>,
+    // Output sample (228 kHz) the change went on air at, counted from the
+    // generator's start; locates it in a rendered file.
+    pub sample: usize,
+    pub field: RdsField,
+    pub source: RdsChangeSource,
+    pub text: String,
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') || value.contains('\r') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn write_history_csv(entries: &[RdsHistoryEntry], path: &str) -> Result<()> {
+    let mut out = BufWriter::new(File::create(Path::new(path))?);
+    writeln!(out, "timestamp,field,source,text")?;
+    for entry in entries {
+        writeln!(
+            out,
+            "{},{},{},{}",
+            entry.timestamp.to_rfc3339(),
+            entry.field,
+            entry.source,
+            csv_field(&entry.text)
+        )?;
+    }
+    out.flush()?;
+    Ok(())
+}
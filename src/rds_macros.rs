@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use chrono::Local;
+
+// PS/RT templates: `%name%` is replaced with a provider's value whenever the
+// text goes to the encoder, `%%` is a literal percent sign. A name no
+// provider knows expands to nothing, so an unset `%song%` leaves no residue
+// on air.
+pub trait ValueProvider {
+    fn value(&self, name: &str) -> Option<String>;
+}
+
+// `%time%` (HH:MM), `%date%` (DD.MM.YYYY) and `%day%` (Mon), local time.
+pub struct Clock;
+
+impl ValueProvider for Clock {
+    fn value(&self, name: &str) -> Option<String> {
+        let format = match name {
+            "time" => "%H:%M",
+            "date" => "%d.%m.%Y",
+            "day" => "%a",
+            _ => return None,
+        };
+        Some(Local::now().format(format).to_string())
+    }
+}
+
+// Values pushed from outside (playout automation, sensors), e.g. `song`
+// and `temp`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MacroValues {
+    values: BTreeMap<String, String>,
+}
+
+impl MacroValues {
+    pub fn set(&mut self, name: &str, value: &str) {
+        let name = name.trim().to_ascii_lowercase();
+        if value.is_empty() {
+            self.values.remove(&name);
+        } else {
+            self.values.insert(name, value.to_string());
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+impl ValueProvider for MacroValues {
+    fn value(&self, name: &str) -> Option<String> {
+        self.values.get(name).cloned()
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+// Earlier providers win.
+pub fn expand(template: &str, providers: &[&dyn ValueProvider]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if let Some(tail) = after.strip_prefix('%') {
+            out.push('%');
+            rest = tail;
+            continue;
+        }
+        match after.find('%') {
+            Some(end) if end > 0 && after[..end].chars().all(is_name_char) => {
+                let name = after[..end].to_ascii_lowercase();
+                if let Some(value) = providers.iter().find_map(|p| p.value(&name)) {
+                    out.push_str(&value);
+                }
+                rest = &after[end + 1..];
+            }
+            _ => {
+                out.push('%');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use crate::rds::{af_code, AF_MAX, AF_METHOD_B_PAIRS};
+
+// One relay of a network carrying the same programme. Regional variants
+// share the country and programme reference of the PI and differ only in
+// the area nibble (4-F, regional 1-12), e.g. 7201 and 7401.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transmitter {
+    pub name: String,
+    pub freq_mhz: String,
+    pub pi_hex: String,
+    // Comma-separated names of the sites a listener of this one can reach;
+    // empty means every other site of the network.
+    #[serde(default)]
+    pub neighbours: String,
+}
+
+impl Transmitter {
+    pub fn neighbour_names(&self) -> Vec<String> {
+        self.neighbours
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+// What one site transmits: its PI, its own frequency (the tuned frequency for
+// AF method B) and its AF list split into same-programme and regional
+// entries.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SitePlan {
+    pub name: String,
+    pub pi: u16,
+    pub tuned_mhz: f32,
+    pub af: Vec<f32>,
+    pub regional: Vec<f32>,
+}
+
+impl SitePlan {
+    // The `--af` syntax: "98.0,99.5,101.2R".
+    pub fn af_text(&self) -> String {
+        self.af
+            .iter()
+            .map(|f| format!("{:.1}", f))
+            .chain(self.regional.iter().map(|f| format!("{:.1}R", f)))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn cli_args(&self) -> Vec<String> {
+        vec![
+            "--pi".to_string(),
+            format!("{:04X}", self.pi),
+            "--af".to_string(),
+            self.af_text(),
+            "--af-tuned".to_string(),
+            format!("{:.1}", self.tuned_mhz),
+        ]
+    }
+}
+
+fn parse_pi(input: &str) -> Option<u16> {
+    let t = input.trim();
+    u16::from_str_radix(t.strip_prefix("0x").unwrap_or(t), 16).ok()
+}
+
+fn is_regional_area(pi: u16) -> bool {
+    (pi >> 8) & 0xF >= 4
+}
+
+// None when the two PIs belong to different programmes, Some(true) for
+// regional variants of one programme.
+fn relation(a: u16, b: u16) -> Option<bool> {
+    if a == b {
+        return Some(false);
+    }
+    let same_programme = a & 0xF0FF == b & 0xF0FF && is_regional_area(a) && is_regional_area(b);
+    same_programme.then_some(true)
+}
+
+// Builds every site's AF list from the network. Sites with an invalid PI or
+// frequency are left out; problems come back as warnings.
+pub fn plan_network(sites: &[Transmitter]) -> (Vec<SitePlan>, Vec<String>) {
+    let mut warnings = Vec::new();
+    let mut valid: Vec<(&Transmitter, &str, u16, f32)> = Vec::new();
+    for site in sites {
+        let name = site.name.trim();
+        if name.is_empty() {
+            warnings.push("A site has no name and was ignored.".to_string());
+            continue;
+        }
+        let Some(pi) = parse_pi(&site.pi_hex) else {
+            warnings.push(format!("{}: \"{}\" is not a PI code.", name, site.pi_hex.trim()));
+            continue;
+        };
+        let freq = site.freq_mhz.trim().parse::<f32>().ok().filter(|&f| af_code(f).is_some());
+        let Some(freq) = freq else {
+            warnings.push(format!("{}: \"{}\" is not a valid frequency (87.6–107.9 MHz).", name, site.freq_mhz.trim()));
+            continue;
+        };
+        if valid.iter().any(|(_, other, _, _)| *other == name) {
+            warnings.push(format!("{}: the name is used twice.", name));
+            continue;
+        }
+        valid.push((site, name, pi, freq));
+    }
+
+    let mut plans = Vec::new();
+    for &(site, name, pi, freq) in &valid {
+        let listed = site.neighbour_names();
+        for missing in listed.iter().filter(|n| !valid.iter().any(|(_, name, _, _)| name == n)) {
+            warnings.push(format!("{}: neighbour \"{}\" is not a valid site.", name, missing));
+        }
+        let mut plan = SitePlan {
+            name: name.to_string(),
+            pi,
+            tuned_mhz: freq,
+            af: Vec::new(),
+            regional: Vec::new(),
+        };
+        let mut codes = vec![af_code(freq)];
+        for &(_, other, other_pi, other_freq) in &valid {
+            if other == name || (!listed.is_empty() && !listed.iter().any(|n| n == other)) {
+                continue;
+            }
+            let Some(regional) = relation(pi, other_pi) else {
+                if !listed.is_empty() {
+                    warnings.push(format!("{}: {} carries another programme (PI {:04X}) and is not an AF.", name, other, other_pi));
+                }
+                continue;
+            };
+            let code = af_code(other_freq);
+            if codes.contains(&code) {
+                continue;
+            }
+            codes.push(code);
+            if regional {
+                plan.regional.push(other_freq);
+            } else {
+                plan.af.push(other_freq);
+            }
+        }
+        plan.af.sort_by(f32::total_cmp);
+        plan.regional.sort_by(f32::total_cmp);
+        let limit = if plan.regional.is_empty() { AF_MAX } else { AF_METHOD_B_PAIRS };
+        if plan.af.len() + plan.regional.len() > limit {
+            warnings.push(format!("{}: only the first {} AFs are transmitted.", name, limit));
+        }
+        plans.push(plan);
+    }
+    (plans, warnings)
+}
+
+// A batch job file (see `batch`) rendering one MPX file per site. Options
+// given next to --batch apply to every site.
+pub fn batch_file(plans: &[SitePlan]) -> String {
+    let jobs = plans
+        .iter()
+        .map(|plan| {
+            let mut job = Table::new();
+            job.insert("out".to_string(), Value::String(format!("{}.wav", file_stem(&plan.name))));
+            job.insert("pi".to_string(), Value::String(format!("{:04X}", plan.pi)));
+            job.insert("af".to_string(), Value::String(plan.af_text()));
+            job.insert("af-tuned".to_string(), Value::String(format!("{:.1}", plan.tuned_mhz)));
+            Value::Table(job)
+        })
+        .collect();
+    let mut file = Table::new();
+    file.insert("job".to_string(), Value::Array(jobs));
+    file.to_string()
+}
+
+pub fn file_stem(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
@@ -0,0 +1,191 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::rds_strings::rds_byte_to_char;
+
+// A group as sent, at the MPX sample its waveform starts on (228 kHz,
+// counted from the start of the render).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GroupRecord {
+    pub sample: usize,
+    pub blocks: [u16; 4],
+}
+
+const AF_FILLER: u16 = 0xCDCD;
+
+// The payload of the group types PulseFM sends, decoded from the blocks.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GroupPayload {
+    Ps {
+        segment: u8,
+        text: String,
+        tp: bool,
+        ta: bool,
+        ms: bool,
+        di: bool,
+        af: Option<[u8; 2]>,
+    },
+    Rt {
+        segment: u8,
+        text: String,
+        ab: bool,
+    },
+    Ct {
+        date: String,
+        utc: String,
+        offset_half_hours: i8,
+    },
+    SlowLabel {
+        variant: u8,
+        value: u16,
+    },
+    FastTuning {
+        segment: u8,
+        tp: bool,
+        ta: bool,
+        ms: bool,
+        di: bool,
+    },
+    Other,
+}
+
+impl GroupPayload {
+    pub fn decode(blocks: [u16; 4]) -> Self {
+        let [_, b, c, d] = blocks;
+        let version_b = b & 0x0800 != 0;
+        match (b >> 12, version_b) {
+            (0, _) => GroupPayload::Ps {
+                segment: (b & 0x3) as u8,
+                text: chars(&[d]),
+                tp: b & 0x0400 != 0,
+                ta: b & 0x0010 != 0,
+                ms: b & 0x0008 != 0,
+                di: b & 0x0004 != 0,
+                af: (!version_b && c != AF_FILLER).then(|| c.to_be_bytes()),
+            },
+            (2, false) => GroupPayload::Rt {
+                segment: (b & 0xF) as u8,
+                text: chars(&[c, d]),
+                ab: b & 0x0010 != 0,
+            },
+            (4, false) => {
+                let mjd = (((b & 0x3) as i64) << 15) | (c >> 1) as i64;
+                let date = NaiveDate::from_ymd_opt(1858, 11, 17)
+                    .and_then(|base| base.checked_add_signed(chrono::Duration::days(mjd)))
+                    .map(|date| date.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default();
+                let hour = ((c & 0x1) << 4) | (d >> 12);
+                let minute = (d >> 6) & 0x3F;
+                let half_hours = (d & 0x1F) as i8;
+                GroupPayload::Ct {
+                    date,
+                    utc: format!("{:02}:{:02}", hour, minute),
+                    offset_half_hours: if d & 0x20 != 0 { -half_hours } else { half_hours },
+                }
+            }
+            (1, false) => GroupPayload::SlowLabel {
+                variant: ((c >> 12) & 0x7) as u8,
+                value: c & 0x0FFF,
+            },
+            (15, true) => GroupPayload::FastTuning {
+                segment: (b & 0x3) as u8,
+                tp: b & 0x0400 != 0,
+                ta: b & 0x0010 != 0,
+                ms: b & 0x0008 != 0,
+                di: b & 0x0004 != 0,
+            },
+            _ => GroupPayload::Other,
+        }
+    }
+}
+
+fn chars(words: &[u16]) -> String {
+    words
+        .iter()
+        .flat_map(|w| w.to_be_bytes())
+        .map(rds_byte_to_char)
+        .collect()
+}
+
+impl fmt::Display for GroupPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupPayload::Ps { segment, text, tp, ta, ms, di, af } => {
+                write!(f, "PS {} \"{}\" TP={} TA={} MS={} DI={}", segment, text, *tp as u8, *ta as u8, *ms as u8, *di as u8)?;
+                if let Some([a, b]) = af {
+                    write!(f, " AF {} {}", a, b)?;
+                }
+                Ok(())
+            }
+            GroupPayload::Rt { segment, text, ab } => write!(f, "RT {} \"{}\" AB={}", segment, text, *ab as u8),
+            GroupPayload::Ct { date, utc, offset_half_hours } => {
+                write!(f, "CT {} {} UTC {:+}", date, utc, *offset_half_hours as f32 / 2.0)
+            }
+            GroupPayload::SlowLabel { variant, value } => write!(f, "1A variant {} 0x{:03X}", variant, value),
+            GroupPayload::FastTuning { segment, tp, ta, ms, di } => {
+                write!(f, "15B {} TP={} TA={} MS={} DI={}", segment, *tp as u8, *ta as u8, *ms as u8, *di as u8)
+            }
+            GroupPayload::Other => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ScheduleEntry {
+    sample: u64,
+    time_secs: f64,
+    group: String,
+    blocks: [String; 4],
+    payload: GroupPayload,
+}
+
+fn group_name(block_b: u16) -> String {
+    format!("{}{}", block_b >> 12, if block_b & 0x0800 != 0 { 'B' } else { 'A' })
+}
+
+// Writes the groups of a render as JSON (an array) or, for a `.csv` path,
+// CSV. Offsets are converted to the file's own rate.
+pub fn write_schedule(groups: &[GroupRecord], mpx_rate: u32, file_rate: u32, path: &str) -> Result<()> {
+    let entries = groups.iter().map(|group| {
+        let sample = (group.sample as f64 * file_rate as f64 / mpx_rate as f64) as u64;
+        ScheduleEntry {
+            sample,
+            time_secs: group.sample as f64 / mpx_rate as f64,
+            group: group_name(group.blocks[1]),
+            blocks: group.blocks.map(|b| format!("{:04X}", b)),
+            payload: GroupPayload::decode(group.blocks),
+        }
+    });
+
+    let mut out = BufWriter::new(File::create(Path::new(path))?);
+    if path.to_ascii_lowercase().ends_with(".csv") {
+        writeln!(out, "sample,time_secs,group,block_a,block_b,block_c,block_d,payload")?;
+        for entry in entries {
+            let [a, b, c, d] = &entry.blocks;
+            writeln!(
+                out,
+                "{},{:.6},{},{},{},{},{},\"{}\"",
+                entry.sample,
+                entry.time_secs,
+                entry.group,
+                a,
+                b,
+                c,
+                d,
+                entry.payload.to_string().replace('"', "\"\"")
+            )?;
+        }
+    } else {
+        serde_json::to_writer_pretty(&mut out, &entries.collect::<Vec<_>>())?;
+        writeln!(out)?;
+    }
+    out.flush()?;
+    Ok(())
+}
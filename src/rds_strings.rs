@@ -2,34 +2,122 @@ use std::collections::HashMap;
 use std::sync::OnceLock;
 
 static RDS_MAP: OnceLock<HashMap<u32, u8>> = OnceLock::new();
+static RDS_E2_MAP: OnceLock<HashMap<u32, u8>> = OnceLock::new();
+static RDS_E3_MAP: OnceLock<HashMap<u32, u8>> = OnceLock::new();
+static RDS_REVERSE_MAP: OnceLock<HashMap<u8, char>> = OnceLock::new();
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RdsCodeTable {
+    E1,
+    E2,
+    E3,
+}
+
+impl RdsCodeTable {
+    // Tables offered in the app and the CLI. E.2 and E.3 stay out until
+    // their upper halves are filled in; without them a designator costs
+    // PS/RT space and maps fewer characters than E.1.
+    pub const SELECTABLE: [RdsCodeTable; 1] = [RdsCodeTable::E1];
+
+    pub fn designator(&self) -> Option<[u8; 2]> {
+        match self {
+            RdsCodeTable::E1 => None,
+            RdsCodeTable::E2 => Some([0x0E, 0x0E]),
+            RdsCodeTable::E3 => Some([0x1B, 0x6E]),
+        }
+    }
+}
+
+impl std::fmt::Display for RdsCodeTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RdsCodeTable::E1 => write!(f, "E.1 (G0)"),
+            RdsCodeTable::E2 => write!(f, "E.2 (G1)"),
+            RdsCodeTable::E3 => write!(f, "E.3 (G2)"),
+        }
+    }
+}
+
+// Lines of "0xCODEPOINT 0xBYTE"; blank lines and `#` comments are skipped.
+fn parse_map(raw: &str) -> HashMap<u32, u8> {
+    let mut map = HashMap::new();
+    for line in raw.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let cp = parts
+            .next()
+            .and_then(|v| u32::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+            .expect("invalid codepoint in rds map");
+        let out = parts
+            .next()
+            .and_then(|v| u8::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+            .expect("invalid rds byte in rds map");
+        map.insert(cp, out);
+    }
+    map
+}
 
 fn rds_map() -> &'static HashMap<u32, u8> {
-    RDS_MAP.get_or_init(|| {
+    RDS_MAP.get_or_init(|| parse_map(include_str!("../assets/rds_unicode_map.txt")))
+}
+
+// E.2 and E.3 share columns 0-7 with E.1; columns 8-F come from each
+// table's own file.
+fn alternative_map(upper: &str) -> HashMap<u32, u8> {
+    let mut map: HashMap<u32, u8> = rds_map()
+        .iter()
+        .filter(|(_, &b)| b < 0x80)
+        .map(|(&cp, &b)| (cp, b))
+        .collect();
+    map.extend(parse_map(upper).into_iter().filter(|(_, b)| *b >= 0x80));
+    map
+}
+
+// E.1 byte back to a character; where several code points share a byte the
+// lowest one is used.
+pub fn rds_byte_to_char(byte: u8) -> char {
+    let map = RDS_REVERSE_MAP.get_or_init(|| {
         let mut map = HashMap::new();
-        let raw = include_str!("../assets/rds_unicode_map.txt");
-        for line in raw.lines() {
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
+        for (&cp, &b) in rds_map() {
+            if let Some(ch) = char::from_u32(cp) {
+                map.entry(b).and_modify(|c: &mut char| *c = (*c).min(ch)).or_insert(ch);
             }
-            let mut parts = line.split_whitespace();
-            let cp = parts
-                .next()
-                .and_then(|v| u32::from_str_radix(v.trim_start_matches("0x"), 16).ok())
-                .expect("invalid codepoint in rds map");
-            let out = parts
-                .next()
-                .and_then(|v| u8::from_str_radix(v.trim_start_matches("0x"), 16).ok())
-                .expect("invalid rds byte in rds map");
-            map.insert(cp, out);
         }
         map
-    })
+    });
+    map.get(&byte).copied().unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+fn table_map(table: RdsCodeTable) -> &'static HashMap<u32, u8> {
+    match table {
+        RdsCodeTable::E1 => rds_map(),
+        RdsCodeTable::E2 => {
+            RDS_E2_MAP.get_or_init(|| alternative_map(include_str!("../assets/rds_e2_upper_map.txt")))
+        }
+        RdsCodeTable::E3 => {
+            RDS_E3_MAP.get_or_init(|| alternative_map(include_str!("../assets/rds_e3_upper_map.txt")))
+        }
+    }
 }
 
 pub fn fill_rds_string(target: &mut [u8], input: &str) {
-    let map = rds_map();
+    fill_rds_string_with_table(target, input, RdsCodeTable::E1);
+}
+
+pub fn fill_rds_string_with_table(target: &mut [u8], input: &str, table: RdsCodeTable) {
+    let map = table_map(table);
     let mut out_index = 0;
+    if let Some(designator) = table.designator() {
+        for byte in designator {
+            if out_index < target.len() {
+                target[out_index] = byte;
+                out_index += 1;
+            }
+        }
+    }
     for ch in input.chars() {
         if out_index >= target.len() {
             break;
@@ -45,3 +133,39 @@ pub fn fill_rds_string(target: &mut [u8], input: &str) {
         out_index += 1;
     }
 }
+
+pub fn is_mappable(ch: char, table: RdsCodeTable) -> bool {
+    table_map(table).contains_key(&(ch as u32))
+}
+
+pub fn unmappable_chars(input: &str, table: RdsCodeTable) -> Vec<char> {
+    let mut out = Vec::new();
+    for ch in input.chars() {
+        if !is_mappable(ch, table) && !out.contains(&ch) {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn e1_round_trips_known_code_points() {
+        for (ch, byte) in [('A', 0x41), ('é', 0x82), ('ñ', 0x9A), ('€', 0xA9), ('Ä', 0xD1)] {
+            let mut out = [0u8; 1];
+            fill_rds_string_with_table(&mut out, &ch.to_string(), RdsCodeTable::E1);
+            assert_eq!(out[0], byte, "{}", ch);
+            assert_eq!(rds_byte_to_char(byte), ch);
+        }
+    }
+
+    #[test]
+    fn alternative_tables_lead_with_their_designator() {
+        let mut out = [0u8; 4];
+        fill_rds_string_with_table(&mut out, "AB", RdsCodeTable::E3);
+        assert_eq!(out, [0x1B, 0x6E, 0x41, 0x42]);
+    }
+}
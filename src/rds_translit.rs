@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{anyhow, Result};
+
+use crate::rds_strings::{is_mappable, RdsCodeTable};
+
+#[derive(Clone, Debug, Default)]
+pub struct TranslitTable {
+    map: BTreeMap<char, String>,
+}
+
+impl TranslitTable {
+    pub fn builtin() -> Self {
+        let mut table = TranslitTable::default();
+        table
+            .merge_str(include_str!("../assets/rds_translit_map.txt"))
+            .expect("invalid entry in transliteration map");
+        table
+    }
+
+    pub fn load(path: Option<&str>) -> Result<Self> {
+        let mut table = TranslitTable::builtin();
+        if let Some(path) = path.filter(|p| !p.trim().is_empty()) {
+            let raw = fs::read_to_string(path)?;
+            table.merge_str(&raw)?;
+        }
+        Ok(table)
+    }
+
+    // One entry per line: "<codepoint> [replacement]", e.g. "0x0416 Zh".
+    // A missing replacement drops the character; '#' starts a comment.
+    pub fn merge_str(&mut self, raw: &str) -> Result<()> {
+        for line in raw.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let cp_raw = parts.next().unwrap_or_default();
+            let ch = u32::from_str_radix(cp_raw.trim_start_matches("0x").trim_start_matches("U+"), 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| anyhow!("invalid codepoint in transliteration table: {}", cp_raw))?;
+            let replacement = parts.next().unwrap_or_default().to_string();
+            self.map.insert(ch, replacement);
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn transliterate(&self, input: &str, code_table: RdsCodeTable) -> String {
+        let mut out = String::with_capacity(input.len());
+        for ch in input.chars() {
+            if is_mappable(ch, code_table) {
+                out.push(ch);
+                continue;
+            }
+            match self.map.get(&ch) {
+                Some(replacement) => out.push_str(replacement),
+                None => out.push(ch),
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+// How often the scope and spectra are redrawn while the Meters tab is open.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphRate {
+    Fps10,
+    Fps15,
+    #[default]
+    Fps30,
+    Fps60,
+}
+
+impl GraphRate {
+    pub const ALL: [GraphRate; 4] = [GraphRate::Fps10, GraphRate::Fps15, GraphRate::Fps30, GraphRate::Fps60];
+
+    pub fn fps(self) -> u64 {
+        match self {
+            GraphRate::Fps10 => 10,
+            GraphRate::Fps15 => 15,
+            GraphRate::Fps30 => 30,
+            GraphRate::Fps60 => 60,
+        }
+    }
+
+    pub fn interval(self) -> Duration {
+        Duration::from_micros(1_000_000 / self.fps())
+    }
+}
+
+impl fmt::Display for GraphRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} fps", self.fps())
+    }
+}
+
+// How often readouts, alarms, automation and the status servers are updated.
+// Text gains nothing from more than a few updates a second.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextRate {
+    Hz2,
+    #[default]
+    Hz5,
+    Hz10,
+}
+
+impl TextRate {
+    pub const ALL: [TextRate; 3] = [TextRate::Hz2, TextRate::Hz5, TextRate::Hz10];
+
+    pub fn per_second(self) -> u64 {
+        match self {
+            TextRate::Hz2 => 2,
+            TextRate::Hz5 => 5,
+            TextRate::Hz10 => 10,
+        }
+    }
+
+    pub fn interval(self) -> Duration {
+        Duration::from_millis(1000 / self.per_second())
+    }
+}
+
+impl fmt::Display for TextRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} /s", self.per_second())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshSettings {
+    #[serde(default)]
+    pub graphs: GraphRate,
+    #[serde(default)]
+    pub text: TextRate,
+}
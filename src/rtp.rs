@@ -0,0 +1,191 @@
+use std::net::{Ipv4Addr, SocketAddrV4, UdpSocket};
+use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+
+const RTP_VERSION: u8 = 2;
+
+#[derive(Clone, Debug)]
+pub struct RtpConfig {
+    pub address: Ipv4Addr,
+    pub port: u16,
+    pub interface: Ipv4Addr,
+    pub sample_rate: u32,
+    pub channels: usize,
+    pub bits: u16,
+    pub buffer_ms: f32,
+}
+
+impl RtpConfig {
+    pub fn aes67(address: Ipv4Addr, port: u16) -> Self {
+        RtpConfig {
+            address,
+            port,
+            interface: Ipv4Addr::UNSPECIFIED,
+            sample_rate: 48_000,
+            channels: 2,
+            bits: 24,
+            buffer_ms: 20.0,
+        }
+    }
+}
+
+pub struct RtpPacket {
+    pub seq: u16,
+    pub timestamp: u32,
+    pub frames: Vec<(f32, f32)>,
+}
+
+pub fn parse_rtp_packet(data: &[u8], channels: usize, bits: u16) -> Option<RtpPacket> {
+    if data.len() < 12 || data[0] >> 6 != RTP_VERSION {
+        return None;
+    }
+    let padding = data[0] & 0x20 != 0;
+    let extension = data[0] & 0x10 != 0;
+    let csrc_count = (data[0] & 0x0F) as usize;
+    let seq = u16::from_be_bytes([data[2], data[3]]);
+    let timestamp = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+
+    let mut offset = 12 + csrc_count * 4;
+    if extension {
+        if data.len() < offset + 4 {
+            return None;
+        }
+        let words = u16::from_be_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        offset += 4 + words * 4;
+    }
+    let mut end = data.len();
+    if padding {
+        let pad = *data.last()? as usize;
+        end = end.checked_sub(pad)?;
+    }
+    if offset > end {
+        return None;
+    }
+
+    let bytes = match bits {
+        16 => 2,
+        24 => 3,
+        _ => return None,
+    };
+    let channels = channels.max(1);
+    let frame_bytes = bytes * channels;
+    let payload = &data[offset..end];
+    let mut frames = Vec::with_capacity(payload.len() / frame_bytes);
+    for frame in payload.chunks_exact(frame_bytes) {
+        let left = pcm_sample(&frame[..bytes]);
+        let right = if channels > 1 { pcm_sample(&frame[bytes..2 * bytes]) } else { left };
+        frames.push((left, right));
+    }
+    Some(RtpPacket { seq, timestamp, frames })
+}
+
+fn pcm_sample(bytes: &[u8]) -> f32 {
+    match bytes.len() {
+        2 => i16::from_be_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+        _ => {
+            let v = ((bytes[0] as i32) << 24 | (bytes[1] as i32) << 16 | (bytes[2] as i32) << 8) >> 8;
+            v as f32 / 8_388_608.0
+        }
+    }
+}
+
+pub fn open_rtp_socket(config: &RtpConfig) -> Result<UdpSocket> {
+    let socket = UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, config.port))?;
+    if config.address.is_multicast() {
+        socket.join_multicast_v4(&config.address, &config.interface)?;
+    } else if !config.address.is_unspecified() {
+        return Err(anyhow!("RTP address must be multicast or 0.0.0.0 for unicast"));
+    }
+    socket.set_read_timeout(Some(Duration::from_millis(100)))?;
+    Ok(socket)
+}
+
+pub struct ClockRecovery {
+    nominal_step: f64,
+    target_fill: f64,
+    correction: f64,
+    phase: f64,
+    prev: (f32, f32),
+    last_seq: Option<u16>,
+}
+
+impl ClockRecovery {
+    pub fn new(source_rate: u32, target_rate: u32, buffer_ms: f32) -> Self {
+        ClockRecovery {
+            nominal_step: source_rate as f64 / target_rate as f64,
+            target_fill: (buffer_ms.max(1.0) as f64 / 1000.0) * target_rate as f64,
+            correction: 0.0,
+            phase: 0.0,
+            prev: (0.0, 0.0),
+            last_seq: None,
+        }
+    }
+
+    pub fn accept(&mut self, seq: u16, stats: &RtpStats) -> bool {
+        if let Some(last) = self.last_seq {
+            let delta = seq.wrapping_sub(last);
+            if delta == 0 || delta > 0x8000 {
+                return false;
+            }
+            if delta > 1 {
+                stats.lost.fetch_add((delta - 1) as u32, Ordering::Relaxed);
+            }
+        }
+        self.last_seq = Some(seq);
+        stats.packets.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    // `fill` is the number of resampled frames waiting in the pipeline. The
+    // step is nudged so the buffer hovers around the target, following the
+    // sender's clock without PTP.
+    pub fn process(&mut self, frames: &[(f32, f32)], fill: usize, out: &mut Vec<(f32, f32)>) {
+        let error = (fill as f64 - self.target_fill) / self.target_fill;
+        let wanted = (error * 0.002).clamp(-0.005, 0.005);
+        self.correction += (wanted - self.correction) * 0.05;
+        let step = self.nominal_step * (1.0 + self.correction);
+
+        for &next in frames {
+            while self.phase < 1.0 {
+                let t = self.phase as f32;
+                out.push((
+                    self.prev.0 + (next.0 - self.prev.0) * t,
+                    self.prev.1 + (next.1 - self.prev.1) * t,
+                ));
+                self.phase += step;
+            }
+            self.phase -= 1.0;
+            self.prev = next;
+        }
+    }
+
+    pub fn ratio_ppm(&self) -> i32 {
+        (self.correction * 1_000_000.0).round() as i32
+    }
+}
+
+#[derive(Default)]
+pub struct RtpStats {
+    pub packets: AtomicU32,
+    pub lost: AtomicU32,
+    pub ppm: AtomicI32,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RtpStatus {
+    pub packets: u32,
+    pub lost: u32,
+    pub ppm: i32,
+}
+
+impl RtpStats {
+    pub fn status(&self) -> RtpStatus {
+        RtpStatus {
+            packets: self.packets.load(Ordering::Relaxed),
+            lost: self.lost.load(Ordering::Relaxed),
+            ppm: self.ppm.load(Ordering::Relaxed),
+        }
+    }
+}
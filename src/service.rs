@@ -0,0 +1,218 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, Result};
+use directories::BaseDirs;
+
+use crate::storage::CONFIG_DIR_ENV;
+
+// Starts the GUI with --autostart when the user logs in: a systemd user unit
+// on Linux, a LaunchAgent on macOS and a logon task on Windows (a Windows
+// service cannot show the window). Each pins the working directory, where
+// logs/ is written, and the settings folder through CONFIG_DIR_ENV.
+const SERVICE_NAME: &str = "pulsefm";
+const LAUNCHD_LABEL: &str = "com.pulsefm.encoder";
+const WINDOWS_TASK: &str = "PulseFM";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServiceSpec {
+    pub exe: PathBuf,
+    pub preset: Option<String>,
+    pub work_dir: PathBuf,
+    pub config_dir: PathBuf,
+}
+
+impl ServiceSpec {
+    fn args(&self) -> Vec<String> {
+        let mut args = vec!["--autostart".to_string()];
+        args.extend(self.preset.clone());
+        args
+    }
+}
+
+// Where the service definition lives on this platform.
+pub fn service_file() -> Result<PathBuf> {
+    let dirs = BaseDirs::new().ok_or_else(|| anyhow!("no home folder"))?;
+    match std::env::consts::OS {
+        "linux" => Ok(dirs.config_dir().join("systemd/user").join(format!("{}.service", SERVICE_NAME))),
+        "macos" => Ok(dirs.home_dir().join("Library/LaunchAgents").join(format!("{}.plist", LAUNCHD_LABEL))),
+        "windows" => Ok(dirs.data_dir().join("PulseFM").join("pulsefm-autostart.cmd")),
+        os => Err(anyhow!("service install is not supported on {}", os)),
+    }
+}
+
+pub fn is_installed() -> bool {
+    service_file().map(|path| path.is_file()).unwrap_or(false)
+}
+
+// Writes the service definition and registers it. Returns the file written.
+pub fn install(spec: &ServiceSpec) -> Result<PathBuf> {
+    if !spec.exe.is_file() {
+        return Err(anyhow!("{} not found; pass --exe with the path of the app", spec.exe.display()));
+    }
+    fs::create_dir_all(&spec.work_dir)?;
+    let path = service_file()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match std::env::consts::OS {
+        "linux" => {
+            fs::write(&path, systemd_unit(spec))?;
+            run("systemctl", &["--user", "daemon-reload"])?;
+            run("systemctl", &["--user", "enable", &format!("{}.service", SERVICE_NAME)])?;
+        }
+        "macos" => {
+            fs::write(&path, launchd_plist(spec))?;
+            run("launchctl", &["load", "-w", &path.to_string_lossy()])?;
+        }
+        _ => {
+            fs::write(&path, windows_launcher(spec))?;
+            let launcher = format!("\"{}\"", path.display());
+            run("schtasks", &["/Create", "/F", "/TN", WINDOWS_TASK, "/SC", "ONLOGON", "/TR", &launcher])?;
+        }
+    }
+    Ok(path)
+}
+
+// Unregisters and removes the service definition. Returns the file removed.
+pub fn uninstall() -> Result<PathBuf> {
+    let path = service_file()?;
+    if !path.is_file() {
+        return Err(anyhow!("not installed ({} does not exist)", path.display()));
+    }
+    match std::env::consts::OS {
+        "linux" => run("systemctl", &["--user", "disable", &format!("{}.service", SERVICE_NAME)])?,
+        "macos" => run("launchctl", &["unload", "-w", &path.to_string_lossy()])?,
+        _ => run("schtasks", &["/Delete", "/F", "/TN", WINDOWS_TASK])?,
+    }
+    fs::remove_file(&path)?;
+    if std::env::consts::OS == "linux" {
+        run("systemctl", &["--user", "daemon-reload"])?;
+    }
+    Ok(path)
+}
+
+fn run(program: &str, args: &[&str]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| anyhow!("could not run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+// Tied to the graphical session, since the app needs a display; restarted
+// if it exits with an error.
+pub fn systemd_unit(spec: &ServiceSpec) -> String {
+    // Specifiers (%) are expanded everywhere, variables ($) in ExecStart.
+    let quote = |s: &str| {
+        let s = s.replace('\\', "\\\\").replace('"', "\\\"").replace('%', "%%").replace('$', "$$");
+        format!("\"{}\"", s)
+    };
+    let exec = std::iter::once(spec.exe.to_string_lossy().into_owned())
+        .chain(spec.args())
+        .map(|arg| quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "[Unit]\n\
+         Description=PulseFM RDS encoder\n\
+         After=graphical-session.target sound.target\n\
+         PartOf=graphical-session.target\n\
+         \n\
+         [Service]\n\
+         ExecStart={}\n\
+         WorkingDirectory={}\n\
+         Environment={}\n\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         \n\
+         [Install]\n\
+         WantedBy=graphical-session.target\n",
+        exec,
+        spec.work_dir.to_string_lossy().replace('%', "%%"),
+        quote(&format!("{}={}", CONFIG_DIR_ENV, spec.config_dir.display())),
+    )
+}
+
+// Started at login and kept alive unless it quits normally.
+pub fn launchd_plist(spec: &ServiceSpec) -> String {
+    let escape = |s: &str| s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let args = std::iter::once(spec.exe.to_string_lossy().into_owned())
+        .chain(spec.args())
+        .map(|arg| format!("        <string>{}</string>\n", escape(&arg)))
+        .collect::<String>();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \x20   <key>Label</key>\n\
+         \x20   <string>{}</string>\n\
+         \x20   <key>ProgramArguments</key>\n\
+         \x20   <array>\n\
+         {}\
+         \x20   </array>\n\
+         \x20   <key>WorkingDirectory</key>\n\
+         \x20   <string>{}</string>\n\
+         \x20   <key>EnvironmentVariables</key>\n\
+         \x20   <dict>\n\
+         \x20       <key>{}</key>\n\
+         \x20       <string>{}</string>\n\
+         \x20   </dict>\n\
+         \x20   <key>RunAtLoad</key>\n\
+         \x20   <true/>\n\
+         \x20   <key>KeepAlive</key>\n\
+         \x20   <dict>\n\
+         \x20       <key>SuccessfulExit</key>\n\
+         \x20       <false/>\n\
+         \x20   </dict>\n\
+         </dict>\n\
+         </plist>\n",
+        LAUNCHD_LABEL,
+        args,
+        escape(&spec.work_dir.to_string_lossy()),
+        CONFIG_DIR_ENV,
+        escape(&spec.config_dir.to_string_lossy()),
+    )
+}
+
+// Task Scheduler cannot set a working directory or environment for a plain
+// command, so the logon task runs this launcher instead.
+pub fn windows_launcher(spec: &ServiceSpec) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('%', "%%").replace('"', ""));
+    let args = spec.args().iter().map(|arg| quote(arg)).collect::<Vec<_>>().join(" ");
+    format!(
+        "@echo off\r\n\
+         cd /d {}\r\n\
+         set \"{}={}\"\r\n\
+         start \"\" {} {}\r\n",
+        quote(&spec.work_dir.to_string_lossy()),
+        CONFIG_DIR_ENV,
+        spec.config_dir.to_string_lossy().replace('%', "%%"),
+        quote(&spec.exe.to_string_lossy()),
+        args,
+    )
+}
+
+// The GUI binary next to the running one (the CLI ships beside it).
+pub fn default_exe() -> Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    Ok(exe.with_file_name(format!("pulse-fm-rds-encoder{}", std::env::consts::EXE_SUFFIX)))
+}
+
+pub fn describe(path: &Path) -> String {
+    match std::env::consts::OS {
+        "linux" => format!("systemd user unit {}", path.display()),
+        "macos" => format!("LaunchAgent {}", path.display()),
+        _ => format!("logon task {} running {}", WINDOWS_TASK, path.display()),
+    }
+}
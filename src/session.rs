@@ -0,0 +1,110 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+#[derive(Clone, Debug)]
+pub struct SessionStats {
+    pub started: DateTime<Local>,
+    pub xruns: u32,
+    pub buffer_fill_min: f32,
+    pub buffer_fill_max: f32,
+    pub peak_level: f32,
+    pub dsp_load_worst: f32,
+    pub failover_switches: u32,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SessionReport {
+    pub started: String,
+    pub ended: String,
+    pub uptime_secs: i64,
+    pub xruns: u32,
+    pub buffer_fill_min: f32,
+    pub buffer_fill_max: f32,
+    pub peak_level: f32,
+    pub dsp_load_worst: f32,
+    pub failover_switches: u32,
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        SessionStats {
+            started: Local::now(),
+            xruns: 0,
+            buffer_fill_min: f32::MAX,
+            buffer_fill_max: 0.0,
+            peak_level: 0.0,
+            dsp_load_worst: 0.0,
+            failover_switches: 0,
+        }
+    }
+
+    pub fn record(&mut self, xruns: u32, buffer_fill: f32, peak: f32, dsp_load_worst: f32, failover_switches: u32) {
+        self.xruns = xruns;
+        self.buffer_fill_min = self.buffer_fill_min.min(buffer_fill);
+        self.buffer_fill_max = self.buffer_fill_max.max(buffer_fill);
+        self.peak_level = self.peak_level.max(peak);
+        self.dsp_load_worst = self.dsp_load_worst.max(dsp_load_worst);
+        self.failover_switches = failover_switches;
+    }
+
+    pub fn uptime_secs(&self) -> i64 {
+        (Local::now() - self.started).num_seconds()
+    }
+
+    pub fn report(&self) -> SessionReport {
+        SessionReport {
+            started: self.started.to_rfc3339(),
+            ended: Local::now().to_rfc3339(),
+            uptime_secs: self.uptime_secs(),
+            xruns: self.xruns,
+            buffer_fill_min: if self.buffer_fill_min == f32::MAX { 0.0 } else { self.buffer_fill_min },
+            buffer_fill_max: self.buffer_fill_max,
+            peak_level: self.peak_level,
+            dsp_load_worst: self.dsp_load_worst,
+            failover_switches: self.failover_switches,
+        }
+    }
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn write_session_report(report: &SessionReport, dir: &Path) -> Result<(PathBuf, PathBuf)> {
+    fs::create_dir_all(dir)?;
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    let json_path = dir.join(format!("session-{}.json", stamp));
+    fs::write(&json_path, serde_json::to_string_pretty(report)?)?;
+
+    let csv_path = dir.join("sessions.csv");
+    let new_file = !csv_path.exists();
+    let mut out = BufWriter::new(File::options().create(true).append(true).open(&csv_path)?);
+    if new_file {
+        writeln!(
+            out,
+            "started,ended,uptime_secs,xruns,buffer_fill_min,buffer_fill_max,peak_level,dsp_load_worst,failover_switches"
+        )?;
+    }
+    writeln!(
+        out,
+        "{},{},{},{},{:.3},{:.3},{:.4},{:.3},{}",
+        report.started,
+        report.ended,
+        report.uptime_secs,
+        report.xruns,
+        report.buffer_fill_min,
+        report.buffer_fill_max,
+        report.peak_level,
+        report.dsp_load_worst,
+        report.failover_switches
+    )?;
+    out.flush()?;
+    Ok((json_path, csv_path))
+}
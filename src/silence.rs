@@ -0,0 +1,104 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SilenceEvent {
+    Started,
+    Cleared,
+}
+
+pub struct SilenceDetector {
+    threshold: f32,
+    hold: Duration,
+    quiet_since: Option<Instant>,
+    alarmed: bool,
+}
+
+impl SilenceDetector {
+    pub fn new(threshold_db: f32, hold_secs: f32) -> Self {
+        SilenceDetector {
+            threshold: 10.0f32.powf(threshold_db / 20.0),
+            hold: Duration::from_secs_f32(hold_secs.max(0.5)),
+            quiet_since: None,
+            alarmed: false,
+        }
+    }
+
+    pub fn set_threshold(&mut self, threshold_db: f32, hold_secs: f32) {
+        self.threshold = 10.0f32.powf(threshold_db / 20.0);
+        self.hold = Duration::from_secs_f32(hold_secs.max(0.5));
+    }
+
+    pub fn is_alarmed(&self) -> bool {
+        self.alarmed
+    }
+
+    pub fn update(&mut self, level: f32, now: Instant) -> Option<SilenceEvent> {
+        if level >= self.threshold {
+            self.quiet_since = None;
+            if self.alarmed {
+                self.alarmed = false;
+                return Some(SilenceEvent::Cleared);
+            }
+            return None;
+        }
+        let since = *self.quiet_since.get_or_insert(now);
+        if !self.alarmed && now.duration_since(since) >= self.hold {
+            self.alarmed = true;
+            return Some(SilenceEvent::Started);
+        }
+        None
+    }
+
+    pub fn reset(&mut self) {
+        self.quiet_since = None;
+        self.alarmed = false;
+    }
+}
+
+// Plain HTTP only; the request runs on its own thread so a slow endpoint
+// never stalls the UI.
+pub fn post_webhook(url: &str, body: String) {
+    let url = url.to_string();
+    thread::spawn(move || {
+        if let Err(e) = post_json(&url, &body) {
+            eprintln!("webhook error: {}", e);
+        }
+    });
+}
+
+fn post_json(url: &str, body: &str) -> Result<()> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| anyhow!("only http:// webhook URLs are supported"))?;
+    let (host, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let addr = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:80", host)
+    };
+    let mut stream = TcpStream::connect(addr)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    )?;
+    let mut response = [0u8; 64];
+    let len = stream.read(&mut response)?;
+    let status = String::from_utf8_lossy(&response[..len]);
+    if !status.starts_with("HTTP/1.1 2") && !status.starts_with("HTTP/1.0 2") {
+        return Err(anyhow!("webhook returned {}", status.lines().next().unwrap_or("")));
+    }
+    Ok(())
+}
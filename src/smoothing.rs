@@ -0,0 +1,41 @@
+// Slider changes reach the DSP as step changes; ramping them over a few
+// tens of milliseconds removes the zipper noise they cause on air.
+pub const SMOOTHING_SECS: f32 = 0.05;
+
+#[derive(Clone, Copy, Debug)]
+pub struct SmoothedParam {
+    current: f32,
+    target: f32,
+    step: f32,
+    remaining: u32,
+    len: u32,
+}
+
+impl SmoothedParam {
+    pub fn new(value: f32, sample_rate: u32) -> Self {
+        SmoothedParam {
+            current: value,
+            target: value,
+            step: 0.0,
+            remaining: 0,
+            len: ((SMOOTHING_SECS * sample_rate as f32) as u32).max(1),
+        }
+    }
+
+    pub fn set_target(&mut self, target: f32) {
+        if target == self.target {
+            return;
+        }
+        self.target = target;
+        self.remaining = self.len;
+        self.step = (target - self.current) / self.len as f32;
+    }
+
+    pub fn step(&mut self) -> f32 {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            self.current = if self.remaining == 0 { self.target } else { self.current + self.step };
+        }
+        self.current
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::VecDeque;
+
+use crate::rds_macros::ValueProvider;
+
+pub const DEFAULT_SONG_HISTORY_LEN: usize = 5;
+pub const DEFAULT_PREVIOUSLY_RT: &str = "Previously: %prev1%";
+
+// Titles from the now-playing source (the `song` variable), newest first.
+// The first entry is the one on air; the rest were played before it.
+#[derive(Clone, Debug)]
+pub struct SongHistory {
+    titles: VecDeque<String>,
+    capacity: usize,
+}
+
+impl SongHistory {
+    // `capacity` counts the previously played titles kept.
+    pub fn new(capacity: usize) -> Self {
+        SongHistory {
+            titles: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.titles.truncate(self.capacity + 1);
+    }
+
+    // A repeat of the current title (the source re-sending its metadata) is
+    // not a new entry.
+    pub fn push(&mut self, title: &str) -> bool {
+        let title = title.trim();
+        if title.is_empty() || self.titles.front().map(String::as_str) == Some(title) {
+            return false;
+        }
+        self.titles.push_front(title.to_string());
+        self.titles.truncate(self.capacity + 1);
+        true
+    }
+
+    pub fn titles(&self) -> impl Iterator<Item = &str> {
+        self.titles.iter().map(String::as_str)
+    }
+
+    pub fn previous(&self) -> impl Iterator<Item = &str> {
+        self.titles().skip(1)
+    }
+}
+
+// `%prev1%` is the title before the current one, `%prev2%` the one before
+// that; `%previous%` joins them all with " / ".
+impl ValueProvider for SongHistory {
+    fn value(&self, name: &str) -> Option<String> {
+        if name == "previous" {
+            return Some(self.previous().collect::<Vec<_>>().join(" / "));
+        }
+        let n = name.strip_prefix("prev")?.parse::<usize>().ok()?;
+        self.previous().nth(n.checked_sub(1)?).map(str::to_string)
+    }
+}
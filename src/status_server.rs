@@ -0,0 +1,278 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+
+#[derive(Clone, Debug, Default)]
+pub struct EncoderStatus {
+    pub live: bool,
+    pub ta: bool,
+    pub tx_mute: bool,
+    pub ps: String,
+    pub rt: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Toggle {
+    On,
+    Off,
+    Flip,
+}
+
+impl Toggle {
+    pub fn apply(&self, current: bool) -> bool {
+        match self {
+            Toggle::On => true,
+            Toggle::Off => false,
+            Toggle::Flip => !current,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RemoteCommand {
+    Live(Toggle),
+    Ta(Toggle),
+    TxMute(Toggle),
+    // POST /value/<name>?text=... sets a PS/RT template variable; an empty
+    // text clears it.
+    SetValue { name: String, value: String },
+}
+
+pub struct StatusServer {
+    status: Arc<Mutex<EncoderStatus>>,
+    commands: Receiver<RemoteCommand>,
+    running: Arc<AtomicBool>,
+}
+
+impl StatusServer {
+    pub fn start(bind: &str) -> Result<Self> {
+        let listener = TcpListener::bind(bind)?;
+        listener.set_nonblocking(true)?;
+        let status = Arc::new(Mutex::new(EncoderStatus::default()));
+        let running = Arc::new(AtomicBool::new(true));
+        let (tx, rx) = channel();
+
+        let thread_status = status.clone();
+        let thread_running = running.clone();
+        thread::spawn(move || {
+            while thread_running.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let _ = handle_connection(stream, &thread_status, &tx);
+                    }
+                    Err(_) => thread::sleep(Duration::from_millis(50)),
+                }
+            }
+        });
+
+        Ok(StatusServer { status, commands: rx, running })
+    }
+
+    pub fn update(&self, status: EncoderStatus) {
+        if let Ok(mut current) = self.status.lock() {
+            *current = status;
+        }
+    }
+
+    pub fn take_commands(&self) -> Vec<RemoteCommand> {
+        self.commands.try_iter().collect()
+    }
+}
+
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    status: &Arc<Mutex<EncoderStatus>>,
+    commands: &Sender<RemoteCommand>,
+) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let current = status.lock().map(|s| s.clone()).unwrap_or_default();
+
+    match (method, path) {
+        ("GET", "/live") => respond_text(&mut stream, if current.live { "LIVE" } else { "OFF" }),
+        ("GET", "/ta") => respond_text(&mut stream, if current.ta { "TA" } else { "OFF" }),
+        ("GET", "/mute") => respond_text(&mut stream, if current.tx_mute { "MUTED" } else { "OFF" }),
+        ("GET", "/ps") => respond_text(&mut stream, current.ps.trim_end()),
+        ("GET", "/rt") => respond_text(&mut stream, current.rt.trim_end()),
+        ("GET", "/live.png") => respond_png(&mut stream, badge_color(current.live, [0x1F, 0xB8, 0x5A])),
+        ("GET", "/ta.png") => respond_png(&mut stream, badge_color(current.ta, [0xF2, 0xA1, 0x1F])),
+        ("GET", "/mute.png") => respond_png(&mut stream, badge_color(current.tx_mute, [0xD6, 0x3A, 0x3A])),
+        ("POST", path) => match parse_command(path, query) {
+            Some(command) => {
+                let _ = commands.send(command);
+                respond(&mut stream, "200 OK", "text/plain", b"OK")
+            }
+            None => respond(&mut stream, "404 Not Found", "text/plain", b"not found"),
+        },
+        _ => respond(&mut stream, "404 Not Found", "text/plain", b"not found"),
+    }
+}
+
+fn parse_command(path: &str, query: &str) -> Option<RemoteCommand> {
+    let mut parts = path.trim_matches('/').split('/');
+    let target = parts.next()?;
+    if target == "value" {
+        let name = parts.next().filter(|name| !name.is_empty())?;
+        let value = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "text")
+            .map(|(_, value)| percent_decode(value))
+            .unwrap_or_default();
+        return Some(RemoteCommand::SetValue { name: name.to_string(), value });
+    }
+    let toggle = match parts.next().unwrap_or("toggle") {
+        "on" | "start" => Toggle::On,
+        "off" | "stop" => Toggle::Off,
+        "toggle" => Toggle::Flip,
+        _ => return None,
+    };
+    match target {
+        "live" => Some(RemoteCommand::Live(toggle)),
+        "ta" => Some(RemoteCommand::Ta(toggle)),
+        "mute" => Some(RemoteCommand::TxMute(toggle)),
+        _ => None,
+    }
+}
+
+// Form encoding: `+` is a space, `%XX` a byte of UTF-8.
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|pair| std::str::from_utf8(pair).ok())
+            .and_then(|pair| u8::from_str_radix(pair, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => out.push(b' '),
+            (byte, _) => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn respond_text(stream: &mut TcpStream, body: &str) -> Result<()> {
+    respond(stream, "200 OK", "text/plain; charset=utf-8", body.as_bytes())
+}
+
+fn respond_png(stream: &mut TcpStream, rgb: [u8; 3]) -> Result<()> {
+    respond(stream, "200 OK", "image/png", &badge_png(rgb))
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8]) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()?;
+    Ok(())
+}
+
+fn badge_color(active: bool, on: [u8; 3]) -> [u8; 3] {
+    if active {
+        on
+    } else {
+        [0x2A, 0x2E, 0x3A]
+    }
+}
+
+const BADGE_SIZE: u32 = 72;
+
+// Solid-colour badge encoded as an uncompressed (stored deflate) RGB PNG.
+fn badge_png(rgb: [u8; 3]) -> Vec<u8> {
+    // Each scanline is filter type 0 followed by the pixels.
+    let mut row = vec![0];
+    row.extend(rgb.repeat(BADGE_SIZE as usize));
+    let raw = row.repeat(BADGE_SIZE as usize);
+
+    let mut zlib = vec![0x78, 0x01];
+    let chunks: Vec<&[u8]> = raw.chunks(0xFFFF).collect();
+    for (i, chunk) in chunks.iter().enumerate() {
+        zlib.push(if i + 1 == chunks.len() { 1 } else { 0 });
+        let len = chunk.len() as u16;
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(chunk);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&BADGE_SIZE.to_be_bytes());
+    ihdr.extend_from_slice(&BADGE_SIZE.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    png_chunk(&mut png, b"IHDR", &ihdr);
+    png_chunk(&mut png, b"IDAT", &zlib);
+    png_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let mut a = 1u32;
+    let mut b = 0u32;
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
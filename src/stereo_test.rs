@@ -0,0 +1,123 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+
+use crate::audio::AudioSource;
+use crate::phase_align::PILOT_HZ;
+
+pub const SEPARATION_TEST_SECS: f32 = 2.0;
+pub const MIN_SEPARATION_DB: f32 = 40.0;
+pub const MIN_PILOT_PROTECTION_DB: f32 = 40.0;
+
+pub const TEST_TONE_HZ: f64 = 1_000.0;
+// Inside the pilot notch band; its L-R sideband lands at 18.8 kHz.
+pub const PROTECTION_TONE_HZ: f64 = 19_200.0;
+const TONE_LEVEL: f32 = 0.316;
+const TONE_RATE: u32 = 48_000;
+// Filters, compressor and pre-emphasis settle before the analysis window.
+const SETTLE_SECS: f32 = 0.5;
+
+// Two seconds of a tone on the left channel only, at -10 dBFS.
+pub fn left_only_tone(freq: f64) -> AudioSource {
+    let frames = (SEPARATION_TEST_SECS * TONE_RATE as f32) as usize;
+    let mut samples = Vec::with_capacity(frames * 2);
+    for n in 0..frames {
+        let phase = 2.0 * PI * freq * n as f64 / TONE_RATE as f64;
+        samples.push(TONE_LEVEL * phase.sin() as f32);
+        samples.push(0.0);
+    }
+    AudioSource {
+        samples,
+        channels: 2,
+        sample_rate: TONE_RATE,
+    }
+}
+
+// Separation is the left/right level of the 1 kHz tone after decoding the
+// MPX the way a receiver does: M is the baseband, S the baseband after
+// multiplying by a 38 kHz carrier rebuilt from twice the received pilot
+// phase, L = (M + S) / 2 and R = (M - S) / 2. Pilot protection is how far
+// the tone near 19 kHz (and its 18.8 kHz L-R sideband) sits below the same
+// tone at 1 kHz.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SeparationReport {
+    pub separation_db: f32,
+    pub pilot_protection_db: f32,
+}
+
+impl SeparationReport {
+    pub fn measure(tone_mpx: &[f32], protection_mpx: &[f32], sample_rate: u32) -> Result<Self> {
+        let skip = (SETTLE_SECS * sample_rate as f32) as usize;
+        let tone_mpx = tone_mpx.get(skip..).unwrap_or_default();
+        let protection_mpx = protection_mpx.get(skip..).unwrap_or_default();
+        if tone_mpx.is_empty() || protection_mpx.is_empty() {
+            return Err(anyhow!("separation test render is too short"));
+        }
+
+        let pilot = tone(tone_mpx, PILOT_HZ, sample_rate);
+        if magnitude(pilot) < 1e-4 {
+            return Err(anyhow!("no pilot on the output; the stereo decoder needs one"));
+        }
+        // A sine of phase p correlates to an angle of p - 90 degrees.
+        let pilot_phase = pilot.1.atan2(pilot.0) + PI / 2.0;
+        let w38 = 2.0 * PI * 2.0 * PILOT_HZ / sample_rate as f64;
+        let demodulated = tone_mpx
+            .iter()
+            .enumerate()
+            .map(|(n, &x)| 2.0 * x * (w38 * n as f64 + 2.0 * pilot_phase).sin() as f32)
+            .collect::<Vec<_>>();
+
+        let mid = tone(tone_mpx, TEST_TONE_HZ, sample_rate);
+        let side = tone(&demodulated, TEST_TONE_HZ, sample_rate);
+        let left = magnitude((mid.0 + side.0, mid.1 + side.1)) / 2.0;
+        let right = magnitude((mid.0 - side.0, mid.1 - side.1)) / 2.0;
+
+        let leak = magnitude(tone(protection_mpx, PROTECTION_TONE_HZ, sample_rate))
+            .max(magnitude(tone(protection_mpx, 2.0 * PILOT_HZ - PROTECTION_TONE_HZ, sample_rate)));
+
+        Ok(SeparationReport {
+            separation_db: ratio_db(left, right),
+            pilot_protection_db: ratio_db(magnitude(mid), leak),
+        })
+    }
+
+    pub fn passed(&self) -> bool {
+        self.separation_db >= MIN_SEPARATION_DB && self.pilot_protection_db >= MIN_PILOT_PROTECTION_DB
+    }
+}
+
+impl fmt::Display for SeparationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: separation {:.1} dB (min {:.0}), pilot protection {:.1} dB (min {:.0})",
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.separation_db,
+            MIN_SEPARATION_DB,
+            self.pilot_protection_db,
+            MIN_PILOT_PROTECTION_DB
+        )
+    }
+}
+
+// Complex amplitude of one frequency over the whole slice.
+fn tone(samples: &[f32], freq: f64, sample_rate: u32) -> (f64, f64) {
+    let w = 2.0 * PI * freq / sample_rate as f64;
+    let (mut re, mut im) = (0.0f64, 0.0f64);
+    for (n, &x) in samples.iter().enumerate() {
+        let (s, c) = (w * n as f64).sin_cos();
+        re += x as f64 * c;
+        im -= x as f64 * s;
+    }
+    let scale = 2.0 / samples.len() as f64;
+    (re * scale, im * scale)
+}
+
+fn magnitude((re, im): (f64, f64)) -> f64 {
+    (re * re + im * im).sqrt()
+}
+
+fn ratio_db(signal: f64, leak: f64) -> f32 {
+    (20.0 * (signal / leak.max(1e-12)).log10()) as f32
+}
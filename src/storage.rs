@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use directories::ProjectDirs;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Takes precedence over the folder chosen in the app, e.g. for a service.
+pub const CONFIG_DIR_ENV: &str = "PULSEFM_CONFIG_DIR";
+
+// The settings files the app keeps, formerly in the working directory.
+pub const PRESETS_FILE: &str = "presets.json";
+pub const PRESET_REMOTE_FILE: &str = "preset_remote.json";
+pub const PROVIDERS_FILE: &str = "providers.json";
+pub const CONTACTS_FILE: &str = "contacts.json";
+pub const ALERTS_FILE: &str = "alerts.json";
+pub const METADATA_FILTER_FILE: &str = "metadata_filter.json";
+pub const AUTOMATION_FILE: &str = "automation.json";
+pub const PTY_SCHEDULE_FILE: &str = "pty_schedule.json";
+pub const NETWORK_FILE: &str = "network.json";
+pub const CALIBRATION_FILE: &str = "calibration.json";
+pub const LIMITS_FILE: &str = "limits.json";
+pub const REFRESH_FILE: &str = "refresh.json";
+pub const INSERTS_FILE: &str = "inserts.json";
+pub const AUTOSTART_FILE: &str = "autostart.json";
+pub const SUPERVISOR_FILE: &str = "supervisor.json";
+pub const COMPLIANCE_FILE: &str = "compliance.json";
+
+pub const CONFIG_FILES: [&str; 16] = [
+    PRESETS_FILE,
+    PRESET_REMOTE_FILE,
+    PROVIDERS_FILE,
+    CONTACTS_FILE,
+    ALERTS_FILE,
+    METADATA_FILTER_FILE,
+    AUTOMATION_FILE,
+    PTY_SCHEDULE_FILE,
+    NETWORK_FILE,
+    CALIBRATION_FILE,
+    LIMITS_FILE,
+    REFRESH_FILE,
+    INSERTS_FILE,
+    AUTOSTART_FILE,
+    SUPERVISOR_FILE,
+    COMPLIANCE_FILE,
+];
+
+// Single-file export of every settings file, for moving to another machine
+// or backing up a site install.
+pub const SETTINGS_EXPORT_FORMAT: &str = "pulsefm-settings";
+pub const SETTINGS_EXPORT_VERSION: u32 = 1;
+
+// Kept in the platform folder itself, since it says where everything else
+// is.
+const STORAGE_FILE: &str = "storage.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StorageSettings {
+    #[serde(default)]
+    config_dir: Option<String>,
+}
+
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+// ~/.config/PulseFM, %APPDATA%\PulseFM\config or
+// ~/Library/Application Support/PulseFM.
+pub fn platform_config_dir() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "PulseFM").map(|dirs| dirs.config_dir().to_path_buf())
+}
+
+pub fn override_dir() -> Option<PathBuf> {
+    let path = platform_config_dir()?.join(STORAGE_FILE);
+    let data = fs::read_to_string(path).ok()?;
+    let settings: StorageSettings = serde_json::from_str(&data).ok()?;
+    settings
+        .config_dir
+        .filter(|dir| !dir.trim().is_empty())
+        .map(|dir| PathBuf::from(dir.trim()))
+}
+
+// The folder in use, resolved on first call: the environment variable, then
+// the app's override, then the platform folder, then the working directory.
+pub fn config_dir() -> &'static Path {
+    CONFIG_DIR.get_or_init(|| {
+        let dir = std::env::var(CONFIG_DIR_ENV)
+            .ok()
+            .filter(|dir| !dir.trim().is_empty())
+            .map(|dir| PathBuf::from(dir.trim()))
+            .or_else(override_dir)
+            .or_else(platform_config_dir);
+        match dir {
+            Some(dir) if fs::create_dir_all(&dir).is_ok() => {
+                if let Ok(cwd) = std::env::current_dir() {
+                    let _ = migrate(&cwd, &dir);
+                }
+                dir
+            }
+            _ => std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        }
+    })
+}
+
+pub fn config_path(file_name: &str) -> PathBuf {
+    config_dir().join(file_name)
+}
+
+// Reads a settings file from the folder in use; a file that does not exist
+// yet gives the default.
+pub fn load_config<T: DeserializeOwned + Default>(file_name: &str) -> Result<T> {
+    let path = config_path(file_name);
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+pub fn save_config<T: Serialize + ?Sized>(file_name: &str, value: &T) -> Result<()> {
+    fs::write(config_path(file_name), serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+// Copies settings files that exist in `from` but not yet in `to`; files
+// already in `to` are never overwritten. Returns the names copied.
+pub fn migrate(from: &Path, to: &Path) -> Result<Vec<&'static str>> {
+    let mut copied = Vec::new();
+    if from == to {
+        return Ok(copied);
+    }
+    for name in CONFIG_FILES {
+        let (old, new) = (from.join(name), to.join(name));
+        if old.is_file() && !new.exists() {
+            fs::copy(&old, &new)?;
+            copied.push(name);
+        }
+    }
+    Ok(copied)
+}
+
+// Stores the override (None goes back to the platform folder) and copies the
+// current settings there. The new folder is used from the next start.
+pub fn set_override_dir(dir: Option<&str>) -> Result<Vec<&'static str>> {
+    let platform = platform_config_dir().ok_or_else(|| anyhow!("no platform config folder"))?;
+    fs::create_dir_all(&platform)?;
+    let dir = dir.map(str::trim).filter(|dir| !dir.is_empty());
+    let settings = StorageSettings {
+        config_dir: dir.map(str::to_string),
+    };
+    fs::write(platform.join(STORAGE_FILE), serde_json::to_string_pretty(&settings)?)?;
+    let target = dir.map(PathBuf::from).unwrap_or(platform);
+    fs::create_dir_all(&target)?;
+    migrate(config_dir(), &target)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SettingsExport {
+    pub format: String,
+    pub version: u32,
+    #[serde(default)]
+    pub app_version: String,
+    #[serde(default)]
+    pub exported: String,
+    // What was live at export time (current preset, audio devices), which is
+    // not necessarily saved in any file.
+    #[serde(default)]
+    pub current: Value,
+    // Each settings file that existed, by name, as parsed JSON.
+    #[serde(default)]
+    pub files: BTreeMap<String, Value>,
+}
+
+// Writes the settings files in use and `current` into one JSON file. Tokens
+// and passwords are kept, since the file is meant to restore a working
+// install. Returns the number of files included.
+pub fn export_settings(path: &Path, current: Value) -> Result<usize> {
+    let mut files = BTreeMap::new();
+    for name in CONFIG_FILES {
+        let file = config_dir().join(name);
+        if file.is_file() {
+            let value = serde_json::from_str(&fs::read_to_string(&file)?)
+                .map_err(|e| anyhow!("{}: {}", name, e))?;
+            files.insert(name.to_string(), value);
+        }
+    }
+    let export = SettingsExport {
+        format: SETTINGS_EXPORT_FORMAT.to_string(),
+        version: SETTINGS_EXPORT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        exported: Local::now().to_rfc3339(),
+        current,
+        files,
+    };
+    fs::write(path, serde_json::to_string_pretty(&export)?)?;
+    Ok(export.files.len())
+}
+
+pub fn read_settings_export(path: &Path) -> Result<SettingsExport> {
+    let export: SettingsExport = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if export.format != SETTINGS_EXPORT_FORMAT {
+        return Err(anyhow!("{} is not a PulseFM settings export", path.display()));
+    }
+    if export.version > SETTINGS_EXPORT_VERSION {
+        return Err(anyhow!(
+            "settings export version {} is newer than this build supports ({})",
+            export.version,
+            SETTINGS_EXPORT_VERSION
+        ));
+    }
+    Ok(export)
+}
+
+// Writes the files of an export into the folder in use. A file it replaces
+// is kept next to it as .bak; names this build does not know are skipped.
+// Returns the names written.
+pub fn import_settings(export: &SettingsExport) -> Result<Vec<&'static str>> {
+    let mut written = Vec::new();
+    for name in CONFIG_FILES {
+        let Some(value) = export.files.get(name) else {
+            continue;
+        };
+        let file = config_dir().join(name);
+        if file.is_file() {
+            fs::copy(&file, config_dir().join(format!("{}.bak", name)))?;
+        }
+        fs::write(&file, serde_json::to_string_pretty(value)?)?;
+        written.push(name);
+    }
+    Ok(written)
+}
@@ -0,0 +1,115 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+// First wait before a restart; each further attempt doubles it up to the cap.
+const FIRST_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+// No output callback for this long counts as a stalled engine.
+const STALL_TIMEOUT: Duration = Duration::from_secs(3);
+// Running this long without a fault clears the attempt count.
+const STABLE_AFTER: Duration = Duration::from_secs(600);
+pub const RECOVERY_LOG: &str = "recovery.log";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupervisorSettings {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+impl Default for SupervisorSettings {
+    fn default() -> Self {
+        SupervisorSettings {
+            enabled: default_enabled(),
+            max_restarts: default_max_restarts(),
+        }
+    }
+}
+
+// Restart bookkeeping for the live engine: spots a stalled output, spaces
+// restarts out with exponential backoff and gives up after the configured
+// number of attempts in a row.
+#[derive(Debug, Default)]
+pub struct Supervisor {
+    attempts: u32,
+    next_restart: Option<Instant>,
+    running_since: Option<Instant>,
+    last_callbacks: u64,
+    last_progress: Option<Instant>,
+}
+
+impl Supervisor {
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.next_restart.is_some()
+    }
+
+    // Called when the engine (re)starts.
+    pub fn started(&mut self, now: Instant) {
+        self.next_restart = None;
+        self.running_since = Some(now);
+        self.last_callbacks = 0;
+        self.last_progress = Some(now);
+    }
+
+    // Called on every tick while streaming with the engine's output callback
+    // count; true once the callbacks have stopped for STALL_TIMEOUT.
+    pub fn stalled(&mut self, callbacks: u64, now: Instant) -> bool {
+        if self.running_since.map(|t| now.duration_since(t) >= STABLE_AFTER).unwrap_or(false) {
+            self.attempts = 0;
+        }
+        if callbacks != self.last_callbacks {
+            self.last_callbacks = callbacks;
+            self.last_progress = Some(now);
+            return false;
+        }
+        self.last_progress.map(|t| now.duration_since(t) >= STALL_TIMEOUT).unwrap_or(false)
+    }
+
+    // Books the next attempt after a fault. Returns the wait, or None once
+    // `max_restarts` attempts in a row have been used up.
+    pub fn schedule(&mut self, max_restarts: u32, now: Instant) -> Option<Duration> {
+        self.running_since = None;
+        if self.attempts >= max_restarts {
+            self.next_restart = None;
+            return None;
+        }
+        let wait = FIRST_BACKOFF.saturating_mul(1u32 << self.attempts.min(8)).min(MAX_BACKOFF);
+        self.attempts += 1;
+        self.next_restart = Some(now + wait);
+        Some(wait)
+    }
+
+    pub fn due(&self, now: Instant) -> bool {
+        self.next_restart.map(|at| now >= at).unwrap_or(false)
+    }
+
+    // Forgets pending restarts and the attempt count, e.g. on a manual stop.
+    pub fn reset(&mut self) {
+        *self = Supervisor::default();
+    }
+}
+
+// Appends a timestamped line to the recovery log in `dir`.
+pub fn log_recovery(dir: &Path, line: &str) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(dir.join(RECOVERY_LOG))?;
+    writeln!(file, "{} {}", Local::now().format("%Y-%m-%d %H:%M:%S"), line)
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncRole {
+    Off,
+    Main,
+    Standby,
+}
+
+impl fmt::Display for SyncRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            SyncRole::Off => "Off",
+            SyncRole::Main => "Main",
+            SyncRole::Standby => "Standby",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncMessage {
+    State { seq: u64, on_air: bool, state: String },
+    Handover,
+    Takeover,
+    // Aligned start: both instances begin the MPX on the output sample that
+    // plays at this wall-clock time (microseconds since the Unix epoch).
+    Start { at_unix_us: u64 },
+}
+
+// Time for the trigger to reach the peer and both engines to open their
+// devices and prime their buffers.
+pub const START_LEAD: Duration = Duration::from_millis(1500);
+
+// Instances on one host (peer on 127.0.0.1) share the clock exactly; across
+// hosts the trigger is only as good as NTP/PTP between them.
+pub fn start_message(at: SystemTime) -> SyncMessage {
+    let at_unix_us = at.duration_since(UNIX_EPOCH).map(|d| d.as_micros() as u64).unwrap_or(0);
+    SyncMessage::Start { at_unix_us }
+}
+
+pub fn start_time(at_unix_us: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_micros(at_unix_us)
+}
+
+pub struct SyncLink {
+    socket: UdpSocket,
+    peer: SocketAddr,
+    seq: u64,
+    opened: Instant,
+    last_heard: Option<Instant>,
+}
+
+impl SyncLink {
+    pub fn open(bind_port: u16, peer: &str) -> Result<Self> {
+        let peer = peer
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow!("invalid peer address"))?;
+        let socket = UdpSocket::bind(("0.0.0.0", bind_port))?;
+        socket.set_nonblocking(true)?;
+        Ok(SyncLink {
+            socket,
+            peer,
+            seq: 0,
+            opened: Instant::now(),
+            last_heard: None,
+        })
+    }
+
+    pub fn send_state(&mut self, on_air: bool, state: &str) -> Result<()> {
+        self.seq += 1;
+        self.send(&SyncMessage::State {
+            seq: self.seq,
+            on_air,
+            state: state.to_string(),
+        })
+    }
+
+    pub fn send(&self, message: &SyncMessage) -> Result<()> {
+        let payload = serde_json::to_vec(message)?;
+        self.socket.send_to(&payload, self.peer)?;
+        Ok(())
+    }
+
+    pub fn poll(&mut self) -> Vec<SyncMessage> {
+        let mut messages = Vec::new();
+        let mut buf = vec![0u8; 65536];
+        while let Ok((len, from)) = self.socket.recv_from(&mut buf) {
+            if from.ip() != self.peer.ip() {
+                continue;
+            }
+            if let Ok(message) = serde_json::from_slice::<SyncMessage>(&buf[..len]) {
+                self.last_heard = Some(Instant::now());
+                messages.push(message);
+            }
+        }
+        messages
+    }
+
+    pub fn last_heard(&self) -> Option<Duration> {
+        self.last_heard.map(|t| t.elapsed())
+    }
+
+    pub fn heartbeat_lost(&self, timeout: Duration) -> bool {
+        self.last_heard.unwrap_or(self.opened).elapsed() > timeout
+    }
+}
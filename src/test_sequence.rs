@@ -0,0 +1,140 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+
+pub const TONE_HZ: f32 = 1000.0;
+// -6 dBFS per channel; L=R then gives the same mono level as a full-scale
+// single channel.
+pub const TONE_LEVEL: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestStep {
+    PilotOnly,
+    MonoTone,
+    LeftOnly,
+    RightOnly,
+    RdsOnly,
+    FullMpx,
+}
+
+impl TestStep {
+    pub const ALL: [TestStep; 6] = [
+        TestStep::PilotOnly,
+        TestStep::MonoTone,
+        TestStep::LeftOnly,
+        TestStep::RightOnly,
+        TestStep::RdsOnly,
+        TestStep::FullMpx,
+    ];
+
+    pub fn tone(self, sample: f32) -> (f32, f32) {
+        match self {
+            TestStep::PilotOnly | TestStep::RdsOnly => (0.0, 0.0),
+            TestStep::MonoTone | TestStep::FullMpx => (sample, sample),
+            TestStep::LeftOnly => (sample, 0.0),
+            TestStep::RightOnly => (0.0, sample),
+        }
+    }
+
+    pub fn pilot(self) -> bool {
+        !matches!(self, TestStep::RdsOnly)
+    }
+
+    pub fn rds(self) -> bool {
+        matches!(self, TestStep::RdsOnly | TestStep::FullMpx)
+    }
+}
+
+impl fmt::Display for TestStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            TestStep::PilotOnly => "Pilot only",
+            TestStep::MonoTone => "L=R 1 kHz",
+            TestStep::LeftOnly => "L only 1 kHz",
+            TestStep::RightOnly => "R only 1 kHz",
+            TestStep::RdsOnly => "RDS only",
+            TestStep::FullMpx => "Full MPX",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestSequence {
+    pub steps: Vec<(TestStep, f32)>,
+}
+
+impl TestSequence {
+    pub fn new(step_secs: f32) -> Self {
+        TestSequence {
+            steps: TestStep::ALL.iter().map(|&step| (step, step_secs)).collect(),
+        }
+    }
+
+    // Comma-separated durations in seconds, one per step in the default order.
+    pub fn parse_durations(value: &str) -> Result<Self> {
+        let durations = value
+            .split(',')
+            .map(|s| s.trim().parse::<f32>().map_err(|_| anyhow!("invalid step duration: {}", s.trim())))
+            .collect::<Result<Vec<_>>>()?;
+        if durations.len() != TestStep::ALL.len() {
+            return Err(anyhow!("expected {} step durations", TestStep::ALL.len()));
+        }
+        Ok(TestSequence {
+            steps: TestStep::ALL.iter().copied().zip(durations).collect(),
+        })
+    }
+
+    pub fn total_secs(&self) -> f32 {
+        self.steps.iter().map(|&(_, secs)| secs.max(0.0)).sum()
+    }
+}
+
+pub struct TestSequencer {
+    steps: Vec<(TestStep, u64)>,
+    index: usize,
+    pos: u64,
+    phase: f32,
+    phase_step: f32,
+}
+
+impl TestSequencer {
+    pub fn new(sequence: &TestSequence, sample_rate: u32) -> Self {
+        TestSequencer {
+            steps: sequence
+                .steps
+                .iter()
+                .map(|&(step, secs)| (step, (secs.max(0.0) * sample_rate as f32) as u64))
+                .collect(),
+            index: 0,
+            pos: 0,
+            phase: 0.0,
+            phase_step: TONE_HZ / sample_rate as f32,
+        }
+    }
+
+    // Current step and its left/right tone, or None once the sequence ends.
+    pub fn next_tone(&mut self) -> Option<(TestStep, f32, f32)> {
+        while let Some(&(_, len)) = self.steps.get(self.index) {
+            if self.pos < len {
+                break;
+            }
+            self.index += 1;
+            self.pos = 0;
+        }
+        let &(step, _) = self.steps.get(self.index)?;
+        self.pos += 1;
+        let sample = TONE_LEVEL * (2.0 * std::f32::consts::PI * self.phase).sin();
+        self.phase += self.phase_step;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+        let (left, right) = step.tone(sample);
+        Some((step, left, right))
+    }
+
+    pub fn status(&self, sample_rate: u32) -> Option<(TestStep, f32)> {
+        let &(step, len) = self.steps.get(self.index)?;
+        Some((step, len.saturating_sub(self.pos) as f32 / sample_rate as f32))
+    }
+}
@@ -0,0 +1,62 @@
+use crate::failover::BackupInput;
+
+// Program audio follows the TA flag: while TA is up the traffic input
+// replaces the program, with a crossfade each way. A WAV announcement starts
+// from its beginning every time TA goes up.
+#[derive(Clone, Debug)]
+pub struct TrafficRouting {
+    pub input: BackupInput,
+    pub crossfade_secs: f32,
+}
+
+impl TrafficRouting {
+    pub fn new(input: BackupInput) -> Self {
+        TrafficRouting {
+            input,
+            crossfade_secs: 0.5,
+        }
+    }
+}
+
+pub struct TrafficMixer {
+    fade_step: f32,
+    mix: f32,
+    ta: bool,
+}
+
+impl TrafficMixer {
+    pub fn new(routing: &TrafficRouting, sample_rate: u32) -> Self {
+        let fade_samples = (routing.crossfade_secs.max(0.0) * sample_rate as f32).max(1.0);
+        TrafficMixer {
+            fade_step: 1.0 / fade_samples,
+            mix: 0.0,
+            ta: false,
+        }
+    }
+
+    // True when TA has just gone up.
+    pub fn set_ta(&mut self, ta: bool) -> bool {
+        let raised = ta && !self.ta;
+        self.ta = ta;
+        raised
+    }
+
+    // The traffic source only needs to be read while it is (partly) on air.
+    pub fn audible(&self) -> bool {
+        self.ta || self.mix > 0.0
+    }
+
+    pub fn process(&mut self, program: (f32, f32), traffic: (f32, f32)) -> (f32, f32) {
+        if self.ta {
+            self.mix = (self.mix + self.fade_step).min(1.0);
+        } else {
+            self.mix = (self.mix - self.fade_step).max(0.0);
+        }
+        let (pl, pr) = program;
+        let (tl, tr) = traffic;
+        (
+            pl * (1.0 - self.mix) + tl * self.mix,
+            pr * (1.0 - self.mix) + tr * self.mix,
+        )
+    }
+}
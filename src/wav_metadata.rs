@@ -0,0 +1,109 @@
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
+
+// Broadcast Wave `bext` fields (EBU Tech 3285, version 1).
+#[derive(Clone, Debug)]
+pub struct BextInfo {
+    pub description: String,
+    pub originator: String,
+    pub originator_reference: String,
+    pub origination: DateTime<Local>,
+    pub coding_history: String,
+}
+
+// A labelled position in the file, in sample frames.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CueMarker {
+    pub sample: u64,
+    pub label: String,
+}
+
+// Appends `bext`, `cue ` and `LIST`/`adtl` label chunks to a finished WAV and
+// fixes the RIFF size. The chunks follow the audio data; BWF readers and
+// editors (BWF MetaEdit, Audacity, Reaper, Wavosaur) find them by ID.
+pub fn append_metadata(path: &str, bext: &BextInfo, cues: &[CueMarker]) -> Result<()> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    let mut header = [0u8; 12];
+    file.read_exact(&mut header)?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(anyhow!("{} is not a RIFF/WAVE file", path));
+    }
+
+    let mut chunks = Vec::new();
+    write_chunk(&mut chunks, b"bext", &bext_chunk(bext));
+    let cues = cues.iter().filter(|cue| cue.sample <= u32::MAX as u64).collect::<Vec<_>>();
+    if !cues.is_empty() {
+        let mut cue = Vec::with_capacity(4 + 24 * cues.len());
+        cue.extend((cues.len() as u32).to_le_bytes());
+        for (i, marker) in cues.iter().enumerate() {
+            let position = (marker.sample as u32).to_le_bytes();
+            cue.extend((i as u32 + 1).to_le_bytes());
+            cue.extend(position);
+            cue.extend(b"data");
+            cue.extend(0u32.to_le_bytes());
+            cue.extend(0u32.to_le_bytes());
+            cue.extend(position);
+        }
+        write_chunk(&mut chunks, b"cue ", &cue);
+
+        let mut adtl = b"adtl".to_vec();
+        for (i, marker) in cues.iter().enumerate() {
+            let mut label = (i as u32 + 1).to_le_bytes().to_vec();
+            label.extend(marker.label.as_bytes());
+            label.push(0);
+            write_chunk(&mut adtl, b"labl", &label);
+        }
+        write_chunk(&mut chunks, b"LIST", &adtl);
+    }
+
+    let end = file.seek(SeekFrom::End(0))?;
+    // Chunks start on even offsets.
+    if end % 2 == 1 {
+        file.write_all(&[0])?;
+    }
+    file.write_all(&chunks)?;
+    let riff_size = file.seek(SeekFrom::End(0))? - 8;
+    let riff_size = u32::try_from(riff_size).map_err(|_| anyhow!("{} is too large for RIFF metadata", path))?;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&riff_size.to_le_bytes())?;
+    Ok(())
+}
+
+fn write_chunk(out: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    out.extend(id);
+    out.extend((body.len() as u32).to_le_bytes());
+    out.extend(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+fn bext_chunk(bext: &BextInfo) -> Vec<u8> {
+    let mut out = Vec::with_capacity(602 + bext.coding_history.len());
+    fixed_text(&mut out, &bext.description, 256);
+    fixed_text(&mut out, &bext.originator, 32);
+    fixed_text(&mut out, &bext.originator_reference, 32);
+    fixed_text(&mut out, &bext.origination.format("%Y-%m-%d").to_string(), 10);
+    fixed_text(&mut out, &bext.origination.format("%H:%M:%S").to_string(), 8);
+    // TimeReference (low, high): the file starts at sample 0.
+    out.extend([0u8; 8]);
+    out.extend(1u16.to_le_bytes());
+    // UMID, the five loudness fields (unused in version 1) and reserved.
+    out.extend([0u8; 64 + 10 + 180]);
+    out.extend(bext.coding_history.as_bytes());
+    out
+}
+
+// ASCII, truncated or NUL-padded to the field width.
+fn fixed_text(out: &mut Vec<u8>, text: &str, width: usize) {
+    let mut bytes = text
+        .chars()
+        .map(|c| if c.is_ascii() && !c.is_ascii_control() { c as u8 } else { b'?' })
+        .take(width)
+        .collect::<Vec<_>>();
+    bytes.resize(width, 0);
+    out.extend(bytes);
+}
@@ -1,13 +1,32 @@
+use std::fmt;
 use std::path::Path;
+use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local};
 use hound::{SampleFormat, WavSpec, WavWriter};
 
-use crate::audio::load_wav;
+use crate::audio::{load_wav, AudioSource};
+use crate::checkpoint::{fingerprint, load_checkpoint, remove_checkpoint, save_checkpoint, write_sha256_sidecar};
 use crate::fm_mpx::FmMpx;
+use crate::impairment::{ImpairmentChain, Impairments};
+use crate::iq_export::{IqModulator, IqSettings};
+use crate::null_test::NullTestReport;
+use crate::phase_align::{pilot_table, PhaseReport, PHASE_TEST_SECS};
+use crate::processing_preset::ProcessingPreset;
+use crate::program_feed::{ProgramFeed, ProgramFeedWriter, PROGRAM_SAMPLE_RATE};
+use crate::rds::{FastTuning, PsScrollMode, RtOverflow};
+use crate::rds_history::RdsHistoryEntry;
+use crate::rds_schedule::{write_schedule, GroupRecord};
+use crate::rds_strings::RdsCodeTable;
+use crate::rds_translit::TranslitTable;
+use crate::stereo_test::{left_only_tone, SeparationReport, PROTECTION_TONE_HZ, SEPARATION_TEST_SECS, TEST_TONE_HZ};
+use crate::test_sequence::{TestSequence, TestSequencer};
+use crate::wav_metadata::{append_metadata, BextInfo, CueMarker};
 
 const MPX_SAMPLE_RATE: u32 = 228000;
 const SAMPLE_SCALE: f32 = 0.1;
+const RENDER_CHUNK_SECS: f32 = 5.0;
 
 #[derive(Clone, Debug)]
 pub struct GenerateConfig {
@@ -15,7 +34,11 @@ pub struct GenerateConfig {
     pub audio_path: Option<String>,
     pub ps: String,
     pub rt: String,
+    pub code_table: RdsCodeTable,
+    pub translit: Option<TranslitTable>,
     pub pi: u16,
+    pub ecc: Option<u8>,
+    pub lic: Option<u8>,
     pub tp: bool,
     pub ta: bool,
     pub pty: u8,
@@ -25,44 +48,59 @@ pub struct GenerateConfig {
     pub ab_auto: bool,
     pub ct_enabled: bool,
     pub af_list_mhz: Vec<f32>,
+    pub af_regional_mhz: Vec<f32>,
+    pub af_tuned_mhz: Option<f32>,
     pub ps_scroll_enabled: bool,
     pub ps_scroll_text: String,
     pub ps_scroll_cps: f32,
+    pub ps_scroll_mode: PsScrollMode,
+    pub ps_page_dwell_secs: f32,
+    pub ps_page_center: bool,
     pub rt_scroll_enabled: bool,
     pub rt_scroll_text: String,
     pub rt_scroll_cps: f32,
+    pub rt_overflow: RtOverflow,
+    pub rt_ellipsis: String,
+    pub rt_part_secs: f32,
+    pub rate_guard: bool,
     pub output_gain: f32,
     pub limiter_enabled: bool,
     pub limiter_threshold: f32,
     pub limiter_lookahead: usize,
     pub pilot_level: f32,
+    pub pilot_phase_deg: f32,
     pub rds_level: f32,
     pub stereo_separation: f32,
     pub preemphasis_tau: Option<f32>,
+    pub lpf_cutoff_hz: f32,
+    pub pilot_notch: bool,
     pub compressor_enabled: bool,
     pub comp_threshold_db: f32,
     pub comp_ratio: f32,
     pub comp_attack: f32,
     pub comp_release: f32,
+    pub comp_makeup_db: f32,
     pub group_0a: usize,
     pub group_2a: usize,
     pub group_4a: usize,
     pub ct_interval_groups: usize,
+    pub fast_tuning: FastTuning,
     pub ps_alt_list: Vec<String>,
     pub ps_alt_interval: usize,
+    pub impairments: Impairments,
+    pub program_feed: Option<ProgramFeed>,
+    // Sidecar listing every RDS group sent (.json, or .csv by extension).
+    pub group_schedule: Option<String>,
 }
 
-pub fn generate_mpx_wav<F>(config: &GenerateConfig, output_path: &str, mut progress: F) -> Result<()>
-where
-    F: FnMut(f32),
-{
-    let audio = match config.audio_path.as_ref() {
-        Some(path) => Some(load_wav(path)?),
-        None => None,
-    };
-
+fn configured_mpx(config: &GenerateConfig, audio: Option<AudioSource>) -> FmMpx {
     let mut mpx = FmMpx::new(audio);
+    mpx.set_rds_code_table(config.code_table);
+    mpx.set_rds_transliteration(config.translit.clone());
+    mpx.set_rds_rt_overflow(config.rt_overflow, &config.rt_ellipsis, config.rt_part_secs);
     mpx.set_rds_pi(config.pi);
+    mpx.set_rds_ecc(config.ecc);
+    mpx.set_rds_lic(config.lic);
     mpx.set_rds_ps(&config.ps);
     mpx.set_rds_rt(&config.rt);
     mpx.set_rds_tp(config.tp);
@@ -73,12 +111,17 @@ where
     mpx.set_rds_ab(config.ab);
     mpx.set_rds_ab_auto(config.ab_auto);
     mpx.set_rds_ct_enabled(config.ct_enabled);
-    mpx.set_rds_af_list(&config.af_list_mhz);
+    mpx.set_rds_af_entries(&config.af_list_mhz, &config.af_regional_mhz, config.af_tuned_mhz);
+    mpx.set_rds_rate_guard(config.rate_guard);
+    mpx.set_rds_ps_scroll_mode(config.ps_scroll_mode, config.ps_page_dwell_secs, config.ps_page_center);
     mpx.set_rds_ps_scroll(config.ps_scroll_enabled, &config.ps_scroll_text, config.ps_scroll_cps);
     mpx.set_rds_rt_scroll(config.rt_scroll_enabled, &config.rt_scroll_text, config.rt_scroll_cps);
     mpx.set_pilot_level(config.pilot_level);
+    mpx.set_pilot_phase(config.pilot_phase_deg);
     mpx.set_rds_level(config.rds_level);
     mpx.set_stereo_separation(config.stereo_separation);
+    mpx.set_lpf_cutoff(config.lpf_cutoff_hz);
+    mpx.set_pilot_notch(config.pilot_notch);
     mpx.set_preemphasis(config.preemphasis_tau);
     mpx.set_compressor(
         config.compressor_enabled,
@@ -86,47 +129,549 @@ where
         config.comp_ratio,
         config.comp_attack,
         config.comp_release,
+        config.comp_makeup_db,
     );
     mpx.set_rds_group_mix(config.group_0a, config.group_2a, config.group_4a);
     mpx.set_rds_ct_interval(config.ct_interval_groups);
+    mpx.set_rds_fast_tuning(config.fast_tuning);
     mpx.set_rds_ps_alternates(config.ps_alt_list.clone(), config.ps_alt_interval);
+    mpx
+}
 
-    let total_samples = (config.duration_secs * MPX_SAMPLE_RATE as f32) as usize;
-    let chunk_size = 2048usize;
-
-    let spec = WavSpec {
+fn mpx_spec() -> WavSpec {
+    WavSpec {
         channels: 1,
         sample_rate: MPX_SAMPLE_RATE,
         bits_per_sample: 32,
         sample_format: SampleFormat::Float,
+    }
+}
+
+fn scale_output(config: &GenerateConfig, sample: f32) -> f32 {
+    let out = sample * SAMPLE_SCALE * config.output_gain;
+    if config.limiter_enabled {
+        let threshold = config.limiter_threshold.max(0.1);
+        out.clamp(-threshold, threshold)
+    } else {
+        out
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderReport {
+    pub audio_secs: f32,
+    pub elapsed_secs: f32,
+    pub resumed_secs: f32,
+    pub sha256: String,
+}
+
+impl RenderReport {
+    pub fn speed(&self) -> f32 {
+        self.audio_secs / self.elapsed_secs.max(1e-3)
+    }
+}
+
+impl fmt::Display for RenderReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.1} s rendered in {:.1} s ({:.1}x realtime)",
+            self.audio_secs,
+            self.elapsed_secs,
+            self.speed()
+        )?;
+        if self.resumed_secs > 0.0 {
+            write!(f, ", resumed at {:.1} s", self.resumed_secs)?;
+        }
+        write!(f, ", sha256 {}", self.sha256)
+    }
+}
+
+struct RenderChunk {
+    mpx: Vec<f32>,
+    // Processed left/right program per MPX sample; empty unless captured.
+    program: Vec<[f32; 2]>,
+}
+
+// Renders samples `from..total` in batches of one 5 s chunk per CPU thread.
+// RDS for the batch is generated first, in order, on this thread; the program
+// part of each chunk is then added on its own worker from an encoder copy
+// warmed up just before the chunk (see ProgramEncoder::warmup_samples), and
+// the chunks are handed to `write_batch` in order with the index of the
+// first sample. PS/RT changes and groups sent go to `log`.
+fn render_batches<W>(
+    mpx: &mut FmMpx,
+    from: usize,
+    total: usize,
+    capture_program: bool,
+    log: &mut RdsLog,
+    mut write_batch: W,
+) -> Result<()>
+where
+    W: FnMut(&[RenderChunk], usize) -> Result<()>,
+{
+    let chunk_size = (RENDER_CHUNK_SECS * MPX_SAMPLE_RATE as f32) as usize;
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut generated = from;
+
+    while generated < total {
+        let mut chunks = Vec::with_capacity(workers);
+        let mut start = generated;
+        while chunks.len() < workers && start < total {
+            let len = (total - start).min(chunk_size);
+            let mut chunk = RenderChunk {
+                mpx: vec![0.0f32; len],
+                program: vec![[0.0f32; 2]; if capture_program { len } else { 0 }],
+            };
+            mpx.get_rds_samples(&mut chunk.mpx);
+            start += len;
+            chunks.push(chunk);
+        }
+        log.drain(mpx);
+        if let Some((audio, program)) = mpx.program() {
+            std::thread::scope(|scope| {
+                let mut chunk_start = generated;
+                for RenderChunk { mpx: chunk, program: feed } in chunks.iter_mut() {
+                    let at = chunk_start;
+                    chunk_start += chunk.len();
+                    let feed = (!feed.is_empty()).then_some(feed.as_mut_slice());
+                    scope.spawn(move || program.add_samples_at(audio, at, chunk, feed));
+                }
+            });
+        }
+
+        write_batch(&chunks, generated)?;
+        generated = start;
+    }
+    Ok(())
+}
+
+// What the RDS generator reports during a render, for the WAV cue markers
+// and the group schedule sidecar.
+#[derive(Default)]
+struct RdsLog {
+    history: Vec<RdsHistoryEntry>,
+    groups: Vec<GroupRecord>,
+}
+
+impl RdsLog {
+    fn drain(&mut self, mpx: &mut FmMpx) {
+        self.history.extend(mpx.rds.take_history(Local::now()));
+        self.groups.extend(mpx.rds.take_group_log());
+    }
+
+    fn write(
+        &self,
+        config: &GenerateConfig,
+        output_path: &str,
+        file_rate: u32,
+        coding: &str,
+        origination: DateTime<Local>,
+    ) -> Result<()> {
+        append_metadata(
+            output_path,
+            &bext_info(config, origination, coding),
+            &rds_cues(&self.history, file_rate),
+        )?;
+        if let Some(path) = config.group_schedule.as_ref() {
+            write_schedule(&self.groups, MPX_SAMPLE_RATE, file_rate, path)?;
+        }
+        Ok(())
+    }
+}
+
+// BWF origination data for an export: station PS as originator, PI as the
+// reference.
+fn bext_info(config: &GenerateConfig, started: DateTime<Local>, coding: &str) -> BextInfo {
+    BextInfo {
+        description: format!("PulseFM export, PS {}, PI {:04X}", config.ps.trim(), config.pi),
+        originator: config.ps.trim().to_string(),
+        originator_reference: format!("PI {:04X}", config.pi),
+        origination: started,
+        coding_history: format!("{},T=PulseFM {}\r\n", coding, env!("CARGO_PKG_VERSION")),
+    }
+}
+
+// One cue per PS/RT change, with positions scaled to the file's rate.
+fn rds_cues(history: &[RdsHistoryEntry], sample_rate: u32) -> Vec<CueMarker> {
+    history
+        .iter()
+        .map(|entry| CueMarker {
+            sample: (entry.sample as f64 * sample_rate as f64 / MPX_SAMPLE_RATE as f64) as u64,
+            label: format!("{} {}", entry.field, entry.text.trim_end()),
+        })
+        .collect()
+}
+
+// Writes the MPX as a 228 kHz float WAV. After each batch the WAV header is
+// flushed and a checkpoint saved next to the file. With `resume`, a render
+// interrupted with the same settings picks up from the last flushed batch:
+// the file is reopened for appending, RDS is replayed up to that point so the
+// group sequence lines up, and the program encoder seeks there directly. A
+// finished render leaves a `.sha256` sidecar.
+pub fn generate_mpx_wav<F>(
+    config: &GenerateConfig,
+    output_path: &str,
+    resume: bool,
+    mut progress: F,
+) -> Result<RenderReport>
+where
+    F: FnMut(f32),
+{
+    let started = Instant::now();
+    let origination = Local::now();
+    let audio = match config.audio_path.as_ref() {
+        Some(path) => Some(load_wav(path)?),
+        None => None,
     };
 
-    let mut writer = WavWriter::create(Path::new(output_path), spec)?;
-    let mut generated = 0usize;
+    let mut mpx = configured_mpx(config, audio);
 
-    while generated < total_samples {
-        let remaining = total_samples - generated;
-        let len = remaining.min(chunk_size);
-        let mut buffer = vec![0.0f32; len];
-        mpx.get_samples(&mut buffer)?;
+    let total_samples = (config.duration_secs * MPX_SAMPLE_RATE as f32) as usize;
+    let chunk_size = (RENDER_CHUNK_SECS * MPX_SAMPLE_RATE as f32) as usize;
+
+    // Everything in the config is Debug-printed in a stable order (the
+    // transliteration table is a BTreeMap), so equal settings hash equal.
+    let settings = fingerprint(&format!("{:?}", config));
+    let checkpoint = if resume {
+        if config.program_feed.is_some() {
+            return Err(anyhow!("exports with a program feed cannot be resumed"));
+        }
+        load_checkpoint(output_path, &settings)?
+    } else {
+        None
+    };
+    let (mut writer, resumed) = match checkpoint {
+        Some(samples) => {
+            let writer = WavWriter::append(Path::new(output_path))?;
+            // The header is flushed before the checkpoint is saved, so it can
+            // be one batch ahead but never behind.
+            let flushed = writer.len() as usize;
+            if flushed < samples || flushed > total_samples || writer.spec() != mpx_spec() {
+                return Err(anyhow!("{} does not match its checkpoint; render it again without resume", output_path));
+            }
+            (writer, flushed)
+        }
+        None => (WavWriter::create(Path::new(output_path), mpx_spec())?, 0),
+    };
+    let mut impairments = ImpairmentChain::new(&config.impairments, MPX_SAMPLE_RATE);
 
-        for sample in buffer {
-            let mut out = sample * SAMPLE_SCALE * config.output_gain;
-            if config.limiter_enabled {
-                let threshold = config.limiter_threshold.max(0.1);
-                if out > threshold {
-                    out = threshold;
-                } else if out < -threshold {
-                    out = -threshold;
+    // The last stretch before the resume point is rendered in full to bring
+    // the impairment filters back to their state.
+    let warmup = impairments.warmup_samples().min(resumed);
+    let mut skipped = 0usize;
+    let mut scratch = vec![0.0f32; chunk_size];
+    let mut log = RdsLog::default();
+    mpx.rds.set_group_log(config.group_schedule.is_some());
+    while skipped < resumed - warmup {
+        let len = (resumed - warmup - skipped).min(chunk_size);
+        mpx.get_rds_samples(&mut scratch[..len]);
+        log.drain(&mut mpx);
+        skipped += len;
+    }
+    if warmup > 0 {
+        let mut tail = vec![0.0f32; warmup];
+        mpx.get_rds_samples(&mut tail);
+        log.drain(&mut mpx);
+        if let Some((audio, program)) = mpx.program() {
+            program.add_samples_at(audio, skipped, &mut tail, None);
+        }
+        for (n, &sample) in tail.iter().enumerate() {
+            impairments.process(scale_output(config, sample), skipped + n);
+        }
+    }
+
+    let mut feed = match config.program_feed.as_ref() {
+        Some(feed) => Some(ProgramFeedWriter::create(
+            feed,
+            config.preemphasis_tau,
+            MPX_SAMPLE_RATE,
+            config.duration_secs,
+        )?),
+        None => None,
+    };
+
+    render_batches(&mut mpx, resumed, total_samples, feed.is_some(), &mut log, |chunks, mut generated| {
+        for chunk in chunks {
+            for (n, &sample) in chunk.mpx.iter().enumerate() {
+                writer.write_sample(impairments.process(scale_output(config, sample), generated + n))?;
+            }
+            if let Some(feed) = feed.as_mut() {
+                for &frame in &chunk.program {
+                    feed.push(frame)?;
                 }
             }
-            writer.write_sample(out)?;
+            generated += chunk.mpx.len();
         }
+        writer.flush()?;
+        save_checkpoint(output_path, &settings, generated)?;
+        progress(generated as f32 / total_samples as f32);
+        Ok(())
+    })?;
+
+    writer.finalize()?;
+    if let Some(feed) = feed {
+        feed.finalize()?;
+    }
+    log.write(
+        config,
+        output_path,
+        MPX_SAMPLE_RATE,
+        &format!("A=PCM,F={},W=32,M=mono", MPX_SAMPLE_RATE),
+        origination,
+    )?;
+    remove_checkpoint(output_path);
+    let sha256 = write_sha256_sidecar(output_path)?;
+    Ok(RenderReport {
+        audio_secs: (total_samples - resumed) as f32 / MPX_SAMPLE_RATE as f32,
+        elapsed_secs: started.elapsed().as_secs_f32(),
+        resumed_secs: resumed as f32 / MPX_SAMPLE_RATE as f32,
+        sha256,
+    })
+}
 
-        generated += len;
+// Runs the input through the audio processing alone (low-pass,
+// pre-emphasis, compressor and pilot notch) and writes it as a 48 kHz float
+// stereo WAV with the pre-emphasis undone, to audition on ordinary speakers.
+// The whole input is processed, or its first `duration_secs` if shorter;
+// there is no MPX, RDS, output scaling or resume.
+pub fn process_program_wav<F>(config: &GenerateConfig, output_path: &str, mut progress: F) -> Result<RenderReport>
+where
+    F: FnMut(f32),
+{
+    let started = Instant::now();
+    let path = config.audio_path.as_ref().ok_or_else(|| anyhow!("processing needs an input file"))?;
+    let audio = load_wav(path)?;
+    let frames = audio.samples.len() / audio.channels.max(1);
+    if frames == 0 || audio.sample_rate == 0 {
+        return Err(anyhow!("{} has no audio", path));
+    }
+    let secs = (frames as f32 / audio.sample_rate as f32).min(config.duration_secs);
+
+    let mut mpx = configured_mpx(config, Some(audio));
+    let total_samples = (secs * MPX_SAMPLE_RATE as f32) as usize;
+    let feed = ProgramFeed {
+        path: output_path.to_string(),
+        delay_ms: 0.0,
+    };
+    let mut writer = ProgramFeedWriter::create(&feed, config.preemphasis_tau, MPX_SAMPLE_RATE, secs)?;
+
+    render_batches(&mut mpx, 0, total_samples, true, &mut RdsLog::default(), |chunks, mut generated| {
+        for chunk in chunks {
+            for &frame in &chunk.program {
+                writer.push(frame)?;
+            }
+            generated += chunk.program.len();
+        }
+        progress(generated as f32 / total_samples.max(1) as f32);
+        Ok(())
+    })?;
+
+    writer.finalize()?;
+    let sha256 = write_sha256_sidecar(output_path)?;
+    Ok(RenderReport {
+        audio_secs: secs,
+        elapsed_secs: started.elapsed().as_secs_f32(),
+        resumed_secs: 0.0,
+        sha256,
+    })
+}
+
+// Processes the input once with each preset and writes the two renders next
+// to `output_path` (as -a.wav and -b.wav) with their difference, A minus B,
+// in `output_path` itself. Both renders go through the same filters, so they
+// stay sample-aligned and only the preset differences remain.
+pub fn run_null_test(
+    config: &GenerateConfig,
+    a: ProcessingPreset,
+    b: ProcessingPreset,
+    output_path: &str,
+) -> Result<NullTestReport> {
+    let stem = output_path.strip_suffix(".wav").unwrap_or(output_path);
+    let render = |preset: ProcessingPreset, path: &str| -> Result<AudioSource> {
+        let s = preset.settings();
+        let config = GenerateConfig {
+            compressor_enabled: s.compressor_enabled,
+            comp_threshold_db: s.comp_threshold_db,
+            comp_ratio: s.comp_ratio,
+            comp_attack: s.comp_attack,
+            comp_release: s.comp_release,
+            comp_makeup_db: s.comp_makeup_db,
+            preemphasis_tau: if s.preemphasis { config.preemphasis_tau.or(Some(50e-6)) } else { None },
+            ..config.clone()
+        };
+        process_program_wav(&config, path, |_| {})?;
+        load_wav(path)
+    };
+    let rendered_a = render(a, &format!("{}-a.wav", stem))?;
+    let rendered_b = render(b, &format!("{}-b.wav", stem))?;
+    let (report, difference) = NullTestReport::measure(&a.to_string(), &rendered_a, &b.to_string(), &rendered_b)?;
+
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: PROGRAM_SAMPLE_RATE,
+        bits_per_sample: 32,
+        sample_format: SampleFormat::Float,
+    };
+    let mut writer = WavWriter::create(Path::new(output_path), spec)?;
+    for &sample in &difference.samples {
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+    Ok(report)
+}
+
+// Writes the MPX frequency-modulated onto a 0 Hz carrier as a 16-bit stereo
+// WAV (I left, Q right) at the IQ rate, the capture format SDR# and GNU
+// Radio's WAV source read and transmit-capable SDRs can replay. Output scaling
+// and impairments apply as for the MPX export; there is no resume.
+pub fn generate_iq_wav<F>(
+    config: &GenerateConfig,
+    iq: &IqSettings,
+    output_path: &str,
+    mut progress: F,
+) -> Result<RenderReport>
+where
+    F: FnMut(f32),
+{
+    iq.validate(MPX_SAMPLE_RATE)?;
+    let started = Instant::now();
+    let origination = Local::now();
+    let audio = match config.audio_path.as_ref() {
+        Some(path) => Some(load_wav(path)?),
+        None => None,
+    };
+
+    let mut mpx = configured_mpx(config, audio);
+    let total_samples = (config.duration_secs * MPX_SAMPLE_RATE as f32) as usize;
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: iq.sample_rate,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let mut writer = WavWriter::create(Path::new(output_path), spec)?;
+    let mut impairments = ImpairmentChain::new(&config.impairments, MPX_SAMPLE_RATE);
+    let mut modulator = IqModulator::new(iq, MPX_SAMPLE_RATE);
+    let mut buffer = Vec::new();
+
+    let mut log = RdsLog::default();
+    mpx.rds.set_group_log(config.group_schedule.is_some());
+    render_batches(&mut mpx, 0, total_samples, false, &mut log, |chunks, mut generated| {
+        for chunk in chunks {
+            for (n, &sample) in chunk.mpx.iter().enumerate() {
+                modulator.process(impairments.process(scale_output(config, sample), generated + n), &mut buffer);
+                for &[i, q] in &buffer {
+                    writer.write_sample((i * i16::MAX as f32) as i16)?;
+                    writer.write_sample((q * i16::MAX as f32) as i16)?;
+                }
+                buffer.clear();
+            }
+            generated += chunk.mpx.len();
+        }
         progress(generated as f32 / total_samples as f32);
+        Ok(())
+    })?;
+
+    writer.finalize()?;
+    log.write(
+        config,
+        output_path,
+        iq.sample_rate,
+        &format!("A=PCM,F={},W=16,M=stereo", iq.sample_rate),
+        origination,
+    )?;
+    let sha256 = write_sha256_sidecar(output_path)?;
+    Ok(RenderReport {
+        audio_secs: total_samples as f32 / MPX_SAMPLE_RATE as f32,
+        elapsed_secs: started.elapsed().as_secs_f32(),
+        resumed_secs: 0.0,
+        sha256,
+    })
+}
+
+// Renders the test sequence with a clean synthesized tone; audio processing
+// from the config is not applied, only levels, RDS content, output gain and
+// export impairments.
+pub fn generate_test_sequence_wav<F>(
+    config: &GenerateConfig,
+    sequence: &TestSequence,
+    output_path: &str,
+    mut progress: F,
+) -> Result<()>
+where
+    F: FnMut(f32),
+{
+    let mut mpx = configured_mpx(config, None);
+    let mut sequencer = TestSequencer::new(sequence, MPX_SAMPLE_RATE);
+    let total_samples = (sequence.total_secs() * MPX_SAMPLE_RATE as f32) as usize;
+    let chunk_size = 2048usize;
+
+    let mut writer = WavWriter::create(Path::new(output_path), mpx_spec())?;
+    let mut generated = 0usize;
+    let mut buffer = vec![0.0f32; chunk_size];
+    let mut impairments = ImpairmentChain::new(&config.impairments, MPX_SAMPLE_RATE);
+
+    let pilot = pilot_table(config.pilot_phase_deg);
+    let mut frame = sequencer.next_tone();
+    while frame.is_some() {
+        mpx.get_samples(&mut buffer)?;
+        for rds in buffer.iter() {
+            let Some((step, left, right)) = frame else {
+                break;
+            };
+            // 19 kHz is exactly 12 samples at 228 kHz, as in the carrier tables.
+            let phase = 2.0 * std::f32::consts::PI * (generated % 12) as f32 / 12.0;
+            let mut sample = 4.05 * (left + right)
+                + 4.05 * config.stereo_separation * (2.0 * phase).sin() * (left - right);
+            if step.pilot() {
+                sample += config.pilot_level * pilot[generated % 12];
+            }
+            if step.rds() {
+                sample += rds;
+            }
+            writer.write_sample(impairments.process(scale_output(config, sample), generated))?;
+            generated += 1;
+            frame = sequencer.next_tone();
+        }
+        progress(generated as f32 / total_samples.max(1) as f32);
     }
 
     writer.finalize()?;
+    progress(1.0);
     Ok(())
 }
+
+// Pilot plus an unmodulated 38 kHz subcarrier at the same level, for lining
+// up external RDS encoders and SCA generators; returns the phases measured
+// on the rendered output.
+pub fn generate_phase_test_wav(config: &GenerateConfig, output_path: &str) -> Result<PhaseReport> {
+    let pilot = pilot_table(config.pilot_phase_deg);
+    let total_samples = (PHASE_TEST_SECS * MPX_SAMPLE_RATE as f32) as usize;
+    let samples = (0..total_samples)
+        .map(|n| {
+            let phase = 2.0 * std::f32::consts::PI * (n % 12) as f32 / 12.0;
+            scale_output(config, config.pilot_level * (pilot[n % 12] + (2.0 * phase).sin()))
+        })
+        .collect::<Vec<_>>();
+
+    let mut writer = WavWriter::create(Path::new(output_path), mpx_spec())?;
+    for &sample in &samples {
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+    Ok(PhaseReport::measure(&samples, MPX_SAMPLE_RATE))
+}
+
+// Runs an L-only tone through the configured chain (filters, pre-emphasis,
+// compressor, pilot notch, stereo encoder and output scaling), once at
+// 1 kHz and once inside the pilot notch band, and decodes the result.
+pub fn run_separation_test(config: &GenerateConfig) -> Result<SeparationReport> {
+    let render = |freq: f64| -> Result<Vec<f32>> {
+        let mut mpx = configured_mpx(config, Some(left_only_tone(freq)));
+        let mut samples = vec![0.0f32; (SEPARATION_TEST_SECS * MPX_SAMPLE_RATE as f32) as usize];
+        mpx.get_samples(&mut samples)?;
+        Ok(samples.into_iter().map(|sample| scale_output(config, sample)).collect())
+    };
+    SeparationReport::measure(&render(TEST_TONE_HZ)?, &render(PROTECTION_TONE_HZ)?, MPX_SAMPLE_RATE)
+}